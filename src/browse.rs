@@ -0,0 +1,378 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use axum::{
+    Router,
+    extract::{FromRef, Query, State},
+    http::StatusCode,
+    response::Html,
+    routing::get,
+};
+
+use crate::{
+    index::{SharedIndex, current_server},
+    models::ResourceInfo,
+    server::{DocsListResponse, DocumentServer, GetDocsListArgs},
+};
+
+/// Read-only HTML pages listing the indexed resources, for operators without an MCP client.
+pub fn router(index: SharedIndex) -> Router {
+    Router::new()
+        .route("/browse", get(list_page))
+        .route("/browse/resource", get(resource_page))
+        .with_state(index)
+}
+
+/// Each request renders the index current at that time, so pages follow config reloads.
+impl FromRef<SharedIndex> for DocumentServer {
+    fn from_ref(index: &SharedIndex) -> Self {
+        current_server(index)
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ResourceQuery {
+    uri: String,
+}
+
+type HtmlResult = Result<Html<String>, (StatusCode, Html<String>)>;
+
+async fn list_page(
+    State(server): State<DocumentServer>,
+    Query(mut args): Query<GetDocsListArgs>,
+) -> HtmlResult {
+    // Empty dropdown selections arrive as "", which would otherwise filter out everything.
+    for filter in [
+        &mut args.area,
+        &mut args.lang,
+        &mut args.category,
+        &mut args.project,
+    ] {
+        if filter.as_deref().is_some_and(|v| v.trim().is_empty()) {
+            *filter = None;
+        }
+    }
+
+    let listing = server
+        .list_documents(&args)
+        .map_err(|e| error_page(StatusCode::BAD_REQUEST, &e.message))?;
+
+    let mut body = String::new();
+    render_filters(&mut body, &server, &args);
+    render_table(&mut body, &listing);
+    render_pagination(&mut body, &args, &listing);
+    Ok(Html(layout("Indexed documents", &body)))
+}
+
+async fn resource_page(
+    State(server): State<DocumentServer>,
+    Query(ResourceQuery { uri }): Query<ResourceQuery>,
+) -> HtmlResult {
+    let info = server
+        .resource_info(&uri)
+        .ok_or_else(|| error_page(StatusCode::NOT_FOUND, "Resource not found"))?;
+
+    let mut body = String::new();
+    body.push_str("<p><a href=\"/browse\">&larr; All documents</a></p><table>");
+    for (name, value) in [
+        ("URI", info.uri.clone()),
+        ("File path", info.file_path.clone()),
+        ("Project", info.project.clone()),
+        ("Area", info.area.clone()),
+        ("Language", info.languages().collect::<Vec<_>>().join(", ")),
+        ("Category", info.category.join(", ")),
+        ("MIME type", info.mime_type.clone()),
+        ("Size", info.size.to_string()),
+        ("Description", info.description.clone()),
+    ] {
+        let _ = write!(
+            body,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            name,
+            escape_html(&value)
+        );
+    }
+    body.push_str("</table>");
+
+    if is_text_mime(&info.mime_type) {
+        if server.ensure_content_allowed(info).is_err() {
+            let _ = write!(
+                body,
+                "<p>Serving {} content is blocked by configuration.</p>",
+                escape_html(&info.mime_type)
+            );
+        } else {
+            let content = server
+                .read_file_by_path(&info.file_path)
+                .map_err(|e| error_page(StatusCode::INTERNAL_SERVER_ERROR, &e.message))?;
+            let _ = write!(body, "<pre>{}</pre>", escape_html(&content));
+        }
+    }
+
+    Ok(Html(layout(&info.uri, &body)))
+}
+
+fn render_filters(body: &mut String, server: &DocumentServer, args: &GetDocsListArgs) {
+    let mut projects = BTreeSet::new();
+    let mut areas = BTreeSet::new();
+    let mut categories = BTreeSet::new();
+    for info in server.resource_infos() {
+        projects.insert(info.project.as_str());
+        areas.insert(info.area.as_str());
+        categories.extend(info.category.iter().map(String::as_str));
+    }
+
+    body.push_str("<form method=\"get\" action=\"/browse\">");
+    for (name, values, selected) in [
+        ("project", &projects, args.project.as_deref()),
+        ("area", &areas, args.area.as_deref()),
+        ("category", &categories, args.category.as_deref()),
+    ] {
+        let _ = write!(
+            body,
+            "<label>{name} <select name=\"{name}\"><option value=\"\">any</option>"
+        );
+        for value in values.iter().filter(|v| !v.is_empty()) {
+            let marker = if Some(*value) == selected {
+                " selected"
+            } else {
+                ""
+            };
+            let escaped = escape_html(value);
+            let _ = write!(
+                body,
+                "<option value=\"{escaped}\"{marker}>{escaped}</option>"
+            );
+        }
+        body.push_str("</select></label> ");
+    }
+    body.push_str("<button type=\"submit\">Filter</button></form>");
+}
+
+fn render_table(body: &mut String, listing: &DocsListResponse) {
+    let _ = write!(
+        body,
+        "<p>{} documents</p><table><tr><th>URI</th><th>Project</th><th>Area</th>\
+         <th>Language</th><th>Category</th><th>Size</th></tr>",
+        listing.total_documents
+    );
+    for info in &listing.documents {
+        render_row(body, info);
+    }
+    body.push_str("</table>");
+}
+
+fn render_row(body: &mut String, info: &ResourceInfo) {
+    let href = serde_urlencoded::to_string([("uri", info.uri.as_str())]).unwrap_or_default();
+    let _ = write!(
+        body,
+        "<tr><td><a href=\"/browse/resource?{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td>\
+         <td>{}</td><td>{}</td></tr>",
+        escape_html(&href),
+        escape_html(&info.uri),
+        escape_html(&info.project),
+        escape_html(&info.area),
+        escape_html(&info.languages().collect::<Vec<_>>().join(", ")),
+        escape_html(&info.category.join(", ")),
+        info.size
+    );
+}
+
+fn render_pagination(body: &mut String, args: &GetDocsListArgs, listing: &DocsListResponse) {
+    let page_link = |page: u32| {
+        let query = serde_urlencoded::to_string(GetDocsListArgs {
+            page: Some(page),
+            ..args.clone()
+        })
+        .unwrap_or_default();
+        format!("/browse?{}", escape_html(&query))
+    };
+
+    body.push_str("<p>");
+    if listing.current_page > 1 {
+        let _ = write!(
+            body,
+            "<a href=\"{}\">&larr; Previous</a> ",
+            page_link(listing.current_page - 1)
+        );
+    }
+    let _ = write!(
+        body,
+        "Page {} of {}",
+        listing.current_page,
+        listing.total_pages.max(1)
+    );
+    if listing.current_page < listing.total_pages {
+        let _ = write!(
+            body,
+            " <a href=\"{}\">Next &rarr;</a>",
+            page_link(listing.current_page + 1)
+        );
+    }
+    body.push_str("</p>");
+}
+
+fn layout(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{}</title>\
+         <style>body{{font-family:sans-serif}}table{{border-collapse:collapse}}\
+         td,th{{border:1px solid #ccc;padding:2px 6px;text-align:left}}\
+         pre{{background:#f6f6f6;padding:8px;white-space:pre-wrap}}</style>\
+         </head><body><h1>{}</h1>{}</body></html>",
+        escape_html(title),
+        escape_html(title),
+        body
+    )
+}
+
+fn error_page(status: StatusCode, message: &str) -> (StatusCode, Html<String>) {
+    (
+        status,
+        Html(layout(
+            status.canonical_reason().unwrap_or("Error"),
+            &format!("<p>{}</p>", escape_html(message)),
+        )),
+    )
+}
+
+fn is_text_mime(mime_type: &str) -> bool {
+    mime_type.starts_with("text/") || mime_type.ends_with("yaml") || mime_type.ends_with("json")
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::{
+        config::ContentConfig, models::DocumentKey, server::ServerOptions,
+        utils::file_reader::FileReader,
+    };
+
+    fn resource(uri: &str, project: &str, area: &str) -> ResourceInfo {
+        ResourceInfo {
+            uri: uri.to_string(),
+            file_path: uri.trim_start_matches("docs://").to_string(),
+            area: area.to_string(),
+            lang: String::new(),
+            category: vec!["adr".to_string()],
+            project: project.to_string(),
+            mime_type: "text/markdown".to_string(),
+            size: 0,
+            description: "doc".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn server(temp_dir: &TempDir) -> DocumentServer {
+        let resources: BTreeMap<DocumentKey, ResourceInfo> = [
+            resource("docs://architecture/mpa/adr/001.mdx", "mpa", "architecture"),
+            resource("docs://architecture/crm/adr/001.mdx", "crm", "architecture"),
+        ]
+        .into_iter()
+        .map(|info| (DocumentKey::new(info.uri.clone()), info))
+        .collect();
+        DocumentServer::new_with_resources(
+            FileReader::new(temp_dir.path().to_string_lossy().to_string()).expect("file reader"),
+            resources,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_list_page_filters_rows_with_docs_list_filter() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let args = GetDocsListArgs {
+            project: Some("mpa".to_string()),
+            area: Some(String::new()),
+            ..Default::default()
+        };
+
+        let Html(html) = list_page(State(server(&temp_dir)), Query(args))
+            .await
+            .expect("page rendered");
+
+        assert!(html.contains("docs://architecture/mpa/adr/001.mdx"));
+        assert!(!html.contains(">docs://architecture/crm/adr/001.mdx<"));
+        assert!(html.contains("<option value=\"crm\">crm</option>"));
+        assert!(html.contains("<option value=\"mpa\" selected>mpa</option>"));
+        assert!(html.contains("<p>1 documents</p>"));
+    }
+
+    #[tokio::test]
+    async fn test_list_page_rejects_invalid_limit() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let args = GetDocsListArgs {
+            limit: Some(0),
+            ..Default::default()
+        };
+
+        let (status, _) = list_page(State(server(&temp_dir)), Query(args))
+            .await
+            .expect_err("invalid limit");
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_resource_page_shows_escaped_content() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let adr_dir = temp_dir.path().join("architecture/mpa/adr");
+        std::fs::create_dir_all(&adr_dir).expect("adr dir");
+        std::fs::write(adr_dir.join("001.mdx"), "# Decision <Callout/>\n").expect("write");
+
+        let Html(html) = resource_page(
+            State(server(&temp_dir)),
+            Query(ResourceQuery {
+                uri: "docs://architecture/mpa/adr/001.mdx".to_string(),
+            }),
+        )
+        .await
+        .expect("page rendered");
+
+        assert!(html.contains("<pre># Decision &lt;Callout/&gt;\n</pre>"));
+        assert!(html.contains("<th>Project</th><td>mpa</td>"));
+    }
+
+    #[tokio::test]
+    async fn test_resource_page_hides_content_blocked_by_the_content_policy() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let adr_dir = temp_dir.path().join("architecture/mpa/adr");
+        std::fs::create_dir_all(&adr_dir).expect("adr dir");
+        std::fs::write(adr_dir.join("001.mdx"), "# Secret decision\n").expect("write");
+        let docs = server(&temp_dir).with_options(ServerOptions {
+            content: ContentConfig {
+                denied_mime_types: vec!["text/markdown".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let Html(html) = resource_page(
+            State(docs),
+            Query(ResourceQuery {
+                uri: "docs://architecture/mpa/adr/001.mdx".to_string(),
+            }),
+        )
+        .await
+        .expect("page rendered");
+
+        assert!(!html.contains("Secret decision"));
+        assert!(html.contains("Serving text/markdown content is blocked by configuration."));
+        assert!(html.contains("<th>Project</th><td>mpa</td>"));
+    }
+}
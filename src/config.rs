@@ -1,7 +1,17 @@
-use std::{fs, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
 
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use serde::Deserialize;
 
+use crate::{
+    models::{AGREEMENT_EXTENSIONS, DOCUMENT_TYPE_NAMES},
+    utils::openapi::MultiDocumentMode,
+};
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
@@ -14,13 +24,449 @@ pub struct Config {
     #[serde(default = "default_agreements")]
     pub agreements: Vec<String>,
 
+    /// Extensions of agreement documents, e.g. add `yaml` for schema contracts.
+    #[serde(default = "default_agreement_extensions")]
+    pub agreement_extensions: Vec<String>,
+
+    /// Area -> language of agreements without a language directory (e.g. quality-assurance),
+    /// or `all` for agreements that apply to every language and match any language filter.
+    #[serde(default)]
+    pub agreements_defaults: BTreeMap<String, String>,
+
     #[serde(default = "default_guide_extensions")]
     pub guide_extensions: Vec<String>,
 
     #[serde(default)]
     pub guides: Vec<GuideConfig>,
 
+    /// Directories of API style guides, indexed as `style-guide` documents under
+    /// `docs://style-guides/<area>/...` with `agreement_extensions`. Agreements under these
+    /// paths are not indexed as agreements.
+    #[serde(default)]
+    pub style_guides: Vec<String>,
+
+    #[serde(default)]
+    pub preferred_extensions: PreferredExtensions,
+
+    /// OpenAPI access levels (e.g. "internal") whose specs are never indexed.
+    #[serde(default)]
+    pub openapi_excluded_access_levels: Vec<String>,
+
+    /// Structurally validate OpenAPI specs while scanning and flag invalid ones.
+    #[serde(default)]
+    pub validate_specs_on_scan: bool,
+
+    /// How specs holding several `---`-separated YAML documents are parsed: use the first
+    /// document declaring `openapi`/`swagger`, or reject the spec.
+    #[serde(default)]
+    pub openapi_multi_document: MultiDocumentMode,
+
+    /// Walk a directory that several document types of one project list (e.g. one `c4/`
+    /// under c1, c2 and c3) once, offering each file to every type, instead of once per type.
+    #[serde(default = "default_walk_shared_targets_once")]
+    pub walk_shared_targets_once: bool,
+
+    /// Open every indexed file at the end of a scan and report unreadable ones in the scan
+    /// status.
+    #[serde(default)]
+    pub verify_readability: bool,
+
+    /// JSON file per-document read counts (get_usage_stats) are loaded from at startup and
+    /// saved to on shutdown. Counts are kept in memory only when unset.
+    #[serde(default)]
+    pub usage_stats_file: Option<String>,
+
+    /// Directory of the index cache: the scanned index with file fingerprints, so a restart
+    /// rescans only targets that changed. Disabled when unset.
+    #[serde(default)]
+    pub index_cache_dir: Option<String>,
+
+    /// After a scan, walk the docs root for files with a scanned extension that lie outside
+    /// every scan target and report them (get_scan_status, get_orphaned_documents).
+    #[serde(default)]
+    pub detect_orphans: bool,
+
+    /// Files the orphan pass examines before it stops, so huge roots stay cheap.
+    #[serde(default = "default_orphan_scan_max_files")]
+    pub orphan_scan_max_files: usize,
+
+    /// Files export_search_index reads at once when it includes document content.
+    #[serde(default = "default_export_read_concurrency")]
+    pub export_read_concurrency: usize,
+
+    /// Decode documents that are not UTF-8 from a detected encoding (BOM, UTF-16, Latin-1 and
+    /// other legacy encodings) instead of failing to read them. Read once at startup.
+    #[serde(default)]
+    pub detect_encoding: bool,
+
+    /// Glob patterns of document URIs to hide (`docs://architecture/mpa/adr/*-secret.md`,
+    /// `docs://**/internal/**`). Matching documents are left out of the index, so they are
+    /// neither listed nor readable.
+    #[serde(default)]
+    pub hidden_uris: Vec<String>,
+
+    /// Allow the refresh_resource tool to re-read single documents into the served index.
+    #[serde(default)]
+    pub enable_reload: bool,
+
+    #[serde(default)]
+    pub language_variants: LanguageVariantsConfig,
+
+    /// Section headings every ADR must contain (checked by lint_adr).
+    #[serde(default = "default_adr_required_sections")]
+    pub adr_required_sections: Vec<String>,
+
+    /// Alternative headings accepted for a required ADR section, keyed by section name.
+    #[serde(default)]
+    pub adr_section_synonyms: BTreeMap<String, Vec<String>>,
+
+    /// Let get_resource_content take docs-root relative file paths besides docs:// URIs.
+    #[serde(default)]
+    pub accept_file_paths: bool,
+
+    /// Reuse parsed OpenAPI specs and frontmatter across tool calls until a file's content
+    /// changes.
+    #[serde(default = "default_cache_parsed_documents")]
+    pub cache_parsed_documents: bool,
+
+    /// Re-check at read time that a document's file still has an extension its document type
+    /// is scanned with, rejecting reads that would serve something else through a stale index.
+    #[serde(default = "default_verify_extensions_on_read")]
+    pub verify_extensions_on_read: bool,
+
+    /// Document types that are scanned and served (agreements, c1, c2, c3, c4, erd, adr,
+    /// openapi, guide, style-guide). Default: all.
+    #[serde(default = "default_enabled_document_types")]
+    pub enabled_document_types: Vec<String>,
+
+    /// Categories get_docs_list `sort_by = "priority"` lists first, in this order; documents
+    /// in none of them come last.
+    #[serde(default)]
+    pub type_priority: Vec<String>,
+
+    /// File name of the per-project metadata manifest served by get_project_manifest. Such
+    /// files are never indexed as documents.
+    #[serde(default = "default_manifest_file_name")]
+    pub manifest_file_name: String,
+
+    /// File stems recognized as a project's overview document, in priority order.
+    #[serde(default = "default_readme_names")]
+    pub readme_names: Vec<String>,
+
+    /// Upper bound for serialized JSON tool responses; larger ones are trimmed and marked
+    /// `response_truncated`. Unlimited when unset.
+    #[serde(default)]
+    pub max_response_bytes: Option<usize>,
+
+    #[serde(default)]
+    pub uri_templates: UriTemplates,
+
+    /// Shared ADR locations; ADRs found there get `adr_scope = "global"`.
+    #[serde(default)]
+    pub global_adr_paths: Vec<String>,
+
+    /// Project assigned to documents outside every configured project (e.g. agreements).
+    /// Unset keeps their project empty.
+    #[serde(default)]
+    pub default_project: Option<String>,
+
+    #[serde(default)]
+    pub expectations: ExpectationsConfig,
+
+    #[serde(default)]
+    pub staleness: StalenessConfig,
+
+    #[serde(default)]
+    pub content: ContentConfig,
+
+    /// Old URI -> new URI for renamed documents. Keys ending in `/` rewrite every URI under
+    /// that prefix; other keys match one URI exactly.
+    #[serde(default)]
+    pub uri_aliases: BTreeMap<String, String>,
+
+    /// Advertised title and description replacements keyed by tool name; the tools behave the
+    /// same.
+    #[serde(default)]
+    pub tool_overrides: BTreeMap<String, ToolOverride>,
+
+    /// Documents whose generated URI is longer than this many characters are skipped.
+    #[serde(default = "default_max_uri_length")]
+    pub max_uri_length: usize,
+
+    /// Generated resource descriptions longer than this many characters are cut and end
+    /// with an ellipsis. Unlimited when unset.
+    #[serde(default)]
+    pub max_description_length: Option<usize>,
+
+    /// Character cap for the project/area/language catalog appended to the server
+    /// instructions.
+    #[serde(default = "default_instructions_max_chars")]
+    pub instructions_max_chars: usize,
+
+    /// Files larger than this many bytes get no word count or token estimate.
+    #[serde(default = "default_max_file_size")]
+    pub max_file_size: u64,
+
+    /// Characters per token for the `token_estimate` of indexed documents.
+    #[serde(default = "default_chars_per_token")]
+    pub chars_per_token: f64,
+
+    /// Serve read-only HTML pages of the index under `/browse`.
+    #[serde(default)]
+    pub browse: bool,
+
     pub projects: Vec<ProjectConfig>,
+
+    /// Command-line scan restrictions, kept so reloads apply them to the new config.
+    #[serde(skip)]
+    pub scan_filter: ScanFilter,
+}
+
+/// Restricts what a config feeds into the scanner without editing it (`--only-project`,
+/// `--skip-type`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanFilter {
+    /// Projects to scan; empty scans all. Agreements, global ADRs and guides are kept.
+    pub only_projects: Vec<String>,
+    /// Document type names not to scan
+    pub skip_types: Vec<String>,
+}
+
+impl ScanFilter {
+    /// Drops the filtered projects and types from `cfg` and remembers the filter there.
+    /// Fails on a project `cfg` does not define, listing the defined ones.
+    pub fn apply(&self, cfg: &mut Config) -> Result<(), String> {
+        if let Some(unknown) = self
+            .only_projects
+            .iter()
+            .find(|name| !cfg.projects.iter().any(|project| project.name == **name))
+        {
+            let known: Vec<&str> = cfg.projects.iter().map(|p| p.name.as_str()).collect();
+            return Err(format!(
+                "unknown project '{}' (expected one of {})",
+                unknown,
+                known.join(", ")
+            ));
+        }
+
+        if !self.only_projects.is_empty() {
+            cfg.projects.retain(|project| {
+                let keep = self.only_projects.contains(&project.name);
+                if !keep {
+                    tracing::info!("Skipping project '{}' (--only-project)", project.name);
+                }
+                keep
+            });
+        }
+        cfg.enabled_document_types.retain(|type_name| {
+            let keep = !self.skip_types.contains(type_name);
+            if !keep {
+                tracing::info!("Skipping document type '{}' (--skip-type)", type_name);
+            }
+            keep
+        });
+        cfg.scan_filter = self.clone();
+        Ok(())
+    }
+}
+
+/// Extension priority per document group, used when one logical document exists in several
+/// formats (e.g. `c1.mdx` and `c1.puml`). Empty lists register every variant.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PreferredExtensions {
+    #[serde(default)]
+    pub diagram: Vec<String>,
+    #[serde(default)]
+    pub openapi: Vec<String>,
+    #[serde(default)]
+    pub guide: Vec<String>,
+    #[serde(default)]
+    pub agreements: Vec<String>,
+}
+
+/// URI shape per document type for the well-known architecture/OpenAPI layouts. `{name}`
+/// placeholders are filled from path segments; a segment holding only an empty placeholder
+/// (e.g. OpenAPI `{sub_category}`) is dropped.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UriTemplates {
+    /// C1-C3 diagrams (`architecture/{project}/c4/c1.mdx`)
+    #[serde(default = "default_c4_system_template")]
+    pub c4_system: String,
+    /// C4 service diagrams (`architecture/{project}/c4/services/...`)
+    #[serde(default = "default_c4_service_template")]
+    pub c4_service: String,
+    #[serde(default = "default_erd_template")]
+    pub erd: String,
+    #[serde(default = "default_adr_template")]
+    pub adr: String,
+    /// OpenAPI specs (`openapi-spec/{project}/{service}/{version}/{access_level}/...`)
+    #[serde(default = "default_openapi_template")]
+    pub openapi: String,
+}
+
+const DIAGRAM_SEGMENTS: &[&str] = &["project", "filename"];
+const OPENAPI_SEGMENTS: &[&str] = &[
+    "project",
+    "service",
+    "version",
+    "access_level",
+    "sub_category",
+    "filename",
+];
+
+impl UriTemplates {
+    /// Rejects templates that are not `docs://` URIs, omit `{filename}` (which would make
+    /// distinct files collide) or use placeholders unknown for their document type.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, template, allowed) in [
+            ("c4_system", &self.c4_system, DIAGRAM_SEGMENTS),
+            ("c4_service", &self.c4_service, DIAGRAM_SEGMENTS),
+            ("erd", &self.erd, DIAGRAM_SEGMENTS),
+            ("adr", &self.adr, DIAGRAM_SEGMENTS),
+            ("openapi", &self.openapi, OPENAPI_SEGMENTS),
+        ] {
+            if !template.starts_with("docs://") {
+                return Err(format!("uri_templates.{} must start with docs://", name));
+            }
+            if !template.contains("{filename}") {
+                return Err(format!("uri_templates.{} must contain {{filename}}", name));
+            }
+            let mut rest = template.as_str();
+            while let Some(open) = rest.find('{') {
+                let Some(close) = rest[open..].find('}') else {
+                    return Err(format!(
+                        "uri_templates.{} has an unclosed placeholder",
+                        name
+                    ));
+                };
+                let placeholder = &rest[open + 1..open + close];
+                if !allowed.contains(&placeholder) {
+                    return Err(format!(
+                        "uri_templates.{} uses unknown placeholder {{{}}}; allowed: {}",
+                        name,
+                        placeholder,
+                        allowed.join(", ")
+                    ));
+                }
+                rest = &rest[open + close + 1..];
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for UriTemplates {
+    fn default() -> Self {
+        Self {
+            c4_system: default_c4_system_template(),
+            c4_service: default_c4_service_template(),
+            erd: default_erd_template(),
+            adr: default_adr_template(),
+            openapi: default_openapi_template(),
+        }
+    }
+}
+
+/// Replacement metadata advertised for one tool in `tools/list`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolOverride {
+    #[serde(default)]
+    pub title: Option<String>,
+
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Documentation set every project is expected to have, reported as `completeness` by
+/// get_project_overview and get_documentation_coverage. Entries are document categories
+/// (`c1`, `c2`, `c3`, `c4`, `erd`, `adr`, `openapi`); nothing is checked while empty.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectationsConfig {
+    #[serde(default)]
+    pub required: Vec<String>,
+
+    /// Per-project lists replacing `required`
+    #[serde(default)]
+    pub projects: BTreeMap<String, Vec<String>>,
+}
+
+impl ExpectationsConfig {
+    pub fn required_for(&self, project: &str) -> &[String] {
+        self.projects.get(project).unwrap_or(&self.required)
+    }
+}
+
+/// Age thresholds of get_stale_documents and `--check`: a document whose file was last
+/// modified more than its type's threshold ago is stale. Types without a threshold are not
+/// checked.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StalenessConfig {
+    /// Document type (`adr`, `openapi`, ...) -> maximum age in days
+    #[serde(default)]
+    pub max_age_days: BTreeMap<String, u32>,
+
+    /// Threshold of types missing from `max_age_days`
+    #[serde(default)]
+    pub default_max_age_days: Option<u32>,
+
+    /// Stale documents `--check` tolerates before failing
+    #[serde(default)]
+    pub budget: u32,
+}
+
+/// MIME types whose content is never served, even when indexed. `type/*` matches a whole
+/// top-level type; an empty allow list allows everything not denied.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContentConfig {
+    #[serde(default)]
+    pub denied_mime_types: Vec<String>,
+
+    #[serde(default)]
+    pub allowed_mime_types: Vec<String>,
+}
+
+impl ContentConfig {
+    /// Whether content of `mime_type` may be served; the deny list wins over the allow list.
+    pub fn permits(&self, mime_type: &str) -> bool {
+        let mime_type = mime_type.to_ascii_lowercase();
+        let matches = |pattern: &String| match pattern.strip_suffix("/*") {
+            Some(top_level) => mime_type
+                .split_once('/')
+                .is_some_and(|(kind, _)| kind == top_level),
+            None => *pattern == mime_type,
+        };
+        !self.denied_mime_types.iter().any(matches)
+            && (self.allowed_mime_types.is_empty() || self.allowed_mime_types.iter().any(matches))
+    }
+}
+
+/// Language variants of one document (`api.md`, `api.ru.md`). Detection is disabled while
+/// `languages` is empty.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LanguageVariantsConfig {
+    /// Language suffixes recognized before the extension, in preference order
+    #[serde(default)]
+    pub languages: Vec<String>,
+
+    /// Language of a primary document without suffix
+    #[serde(default = "default_content_language")]
+    pub default_language: String,
+}
+
+impl Default for LanguageVariantsConfig {
+    fn default() -> Self {
+        Self {
+            languages: Vec::new(),
+            default_language: default_content_language(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -37,6 +483,11 @@ pub struct GuideConfig {
 pub struct ProjectConfig {
     pub name: String,
 
+    /// Other names for this project in diagrams (e.g. "OAuth Platform"), used by
+    /// get_project_dependencies.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+
     #[serde(default)]
     pub c4: C4Config,
 
@@ -63,15 +514,48 @@ pub struct C4Config {
     pub services: Vec<String>,
 }
 
+impl ProjectConfig {
+    /// Directories that may hold the project's manifest, shallowest first: every configured
+    /// path and its ancestors down to the one named after the project
+    /// (`docs/architecture/mpa/adr` -> `docs/architecture/mpa`, `docs/architecture/mpa/adr`).
+    pub fn manifest_dirs(&self) -> Vec<String> {
+        let paths = [
+            &self.c4.c1,
+            &self.c4.c2,
+            &self.c4.c3,
+            &self.c4.services,
+            &self.erd,
+            &self.adr,
+            &self.openapi,
+        ];
+        let mut dirs = BTreeSet::new();
+        for path in paths.into_iter().flatten() {
+            let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+            let start = segments
+                .iter()
+                .position(|segment| *segment == self.name)
+                .map_or(segments.len(), |index| index + 1);
+            for end in start.max(1)..=segments.len() {
+                dirs.insert(segments[..end].join("/"));
+            }
+        }
+        let mut dirs: Vec<String> = dirs.into_iter().collect();
+        dirs.sort_by_key(|dir| dir.matches('/').count());
+        dirs
+    }
+}
+
 impl Config {
-    pub fn load(explicit_config: Option<&Path>) -> anyhow::Result<Self> {
-        let config_path = match explicit_config {
+    /// Config file location: the explicit path, or `arch-mcp.toml` in the working directory.
+    pub fn resolve_path(explicit_config: Option<&Path>) -> anyhow::Result<PathBuf> {
+        Ok(match explicit_config {
             Some(path) => path.to_path_buf(),
-            None => {
-                // Default: look for arch-mcp.toml in current working directory
-                std::env::current_dir()?.join("arch-mcp.toml")
-            }
-        };
+            None => std::env::current_dir()?.join("arch-mcp.toml"),
+        })
+    }
+
+    pub fn load(explicit_config: Option<&Path>) -> anyhow::Result<Self> {
+        let config_path = Self::resolve_path(explicit_config)?;
 
         let content = fs::read_to_string(&config_path).map_err(|e| {
             anyhow::anyhow!(
@@ -92,7 +576,156 @@ impl Config {
         normalize_extensions(&mut cfg.diagram_extensions);
         normalize_extensions(&mut cfg.openapi_extensions);
         normalize_paths(&mut cfg.agreements);
+        normalize_paths(&mut cfg.style_guides);
+        cfg.agreements_defaults = std::mem::take(&mut cfg.agreements_defaults)
+            .into_iter()
+            .map(|(area, lang)| (area.trim().to_string(), lang.trim().to_ascii_lowercase()))
+            .filter(|(area, lang)| !area.is_empty() && !lang.is_empty())
+            .collect();
+        normalize_extensions(&mut cfg.agreement_extensions);
         normalize_extensions(&mut cfg.guide_extensions);
+        normalize_extension_order(&mut cfg.preferred_extensions.diagram);
+        normalize_extension_order(&mut cfg.preferred_extensions.openapi);
+        normalize_extension_order(&mut cfg.preferred_extensions.guide);
+        normalize_extension_order(&mut cfg.preferred_extensions.agreements);
+        normalize_extension_order(&mut cfg.readme_names);
+        normalize_extension_order(&mut cfg.language_variants.languages);
+        cfg.language_variants.default_language = cfg
+            .language_variants
+            .default_language
+            .trim()
+            .to_ascii_lowercase();
+        cfg.default_project = cfg
+            .default_project
+            .take()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        normalize_extension_order(&mut cfg.expectations.required);
+        for required in cfg.expectations.projects.values_mut() {
+            normalize_extension_order(required);
+        }
+        normalize_paths(&mut cfg.global_adr_paths);
+        normalize_paths(&mut cfg.adr_required_sections);
+        cfg.adr_section_synonyms = std::mem::take(&mut cfg.adr_section_synonyms)
+            .into_iter()
+            .map(|(section, mut synonyms)| {
+                normalize_paths(&mut synonyms);
+                (section.trim().to_string(), synonyms)
+            })
+            .filter(|(section, _)| !section.is_empty())
+            .collect();
+        for mime_types in [
+            &mut cfg.content.denied_mime_types,
+            &mut cfg.content.allowed_mime_types,
+        ] {
+            normalize_paths(mime_types);
+            mime_types.iter_mut().for_each(|m| m.make_ascii_lowercase());
+        }
+        normalize_paths(&mut cfg.openapi_excluded_access_levels);
+        for level in &mut cfg.openapi_excluded_access_levels {
+            level.make_ascii_lowercase();
+        }
+
+        cfg.uri_aliases = std::mem::take(&mut cfg.uri_aliases)
+            .into_iter()
+            .map(|(from, to)| (from.trim().to_string(), to.trim().to_string()))
+            .collect();
+        if let Some((from, to)) = cfg
+            .uri_aliases
+            .iter()
+            .find(|(from, to)| !from.starts_with("docs://") || !to.starts_with("docs://"))
+        {
+            anyhow::bail!(
+                "Invalid config file '{}': uri_aliases entry '{}' = '{}' must map docs:// URIs",
+                config_path.display(),
+                from,
+                to
+            );
+        }
+
+        normalize_paths(&mut cfg.hidden_uris);
+        if let Some(pattern) = cfg
+            .hidden_uris
+            .iter()
+            .find(|pattern| !pattern.starts_with("docs://"))
+        {
+            anyhow::bail!(
+                "Invalid config file '{}': hidden_uris pattern '{}' must start with docs://",
+                config_path.display(),
+                pattern
+            );
+        }
+        cfg.hidden_uri_matcher().map_err(|e| {
+            anyhow::anyhow!(
+                "Invalid config file '{}': invalid hidden_uris pattern: {}",
+                config_path.display(),
+                e
+            )
+        })?;
+
+        normalize_paths(&mut cfg.type_priority);
+        normalize_paths(&mut cfg.enabled_document_types);
+        for name in &mut cfg.enabled_document_types {
+            name.make_ascii_lowercase();
+        }
+        if let Some(unknown) = cfg
+            .enabled_document_types
+            .iter()
+            .find(|name| !DOCUMENT_TYPE_NAMES.contains(&name.as_str()))
+        {
+            anyhow::bail!(
+                "Invalid config file '{}': unknown document type '{}' in enabled_document_types \
+                 (expected one of {})",
+                config_path.display(),
+                unknown,
+                DOCUMENT_TYPE_NAMES.join(", ")
+            );
+        }
+
+        cfg.staleness.max_age_days = std::mem::take(&mut cfg.staleness.max_age_days)
+            .into_iter()
+            .map(|(name, days)| (name.trim().to_ascii_lowercase(), days))
+            .collect();
+        if let Some(unknown) = cfg
+            .staleness
+            .max_age_days
+            .keys()
+            .find(|name| !DOCUMENT_TYPE_NAMES.contains(&name.as_str()))
+        {
+            anyhow::bail!(
+                "Invalid config file '{}': unknown document type '{}' in staleness.max_age_days \
+                 (expected one of {})",
+                config_path.display(),
+                unknown,
+                DOCUMENT_TYPE_NAMES.join(", ")
+            );
+        }
+
+        if !(cfg.chars_per_token.is_finite() && cfg.chars_per_token > 0.0) {
+            anyhow::bail!(
+                "Invalid config file '{}': chars_per_token must be a positive number, got {}",
+                config_path.display(),
+                cfg.chars_per_token
+            );
+        }
+
+        let tool_names = crate::server::tool_names();
+        if let Some(unknown) = cfg
+            .tool_overrides
+            .keys()
+            .find(|name| !tool_names.contains(name))
+        {
+            anyhow::bail!(
+                "Invalid config file '{}': unknown tool '{}' in tool_overrides (expected one of {})",
+                config_path.display(),
+                unknown,
+                tool_names.join(", ")
+            );
+        }
+
+        cfg.uri_templates.validate().map_err(|e| {
+            anyhow::anyhow!("Invalid config file '{}': {}", config_path.display(), e)
+        })?;
 
         for guide in &mut cfg.guides {
             normalize_paths(&mut guide.paths);
@@ -106,12 +739,92 @@ impl Config {
             normalize_paths(&mut project.erd);
             normalize_paths(&mut project.adr);
             normalize_paths(&mut project.openapi);
+            normalize_paths(&mut project.aliases);
         }
 
         Ok(cfg)
     }
 }
 
+/// Extension filter the scanner applies to one document type.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, schemars::JsonSchema)]
+pub struct EffectiveExtensions {
+    /// Accepted extensions (lowercase, without dot)
+    pub extensions: Vec<String>,
+    /// Format priority when one document exists with several extensions; empty keeps all
+    pub preferred: Vec<String>,
+    /// File stem the type additionally requires (`c1` for C1 diagrams)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_stem: Option<String>,
+}
+
+impl Config {
+    /// Extension filters per document type, as the scanner resolves them from this
+    /// (already normalized) config.
+    /// Whether documents of `type_name` (a `DocumentType::type_name`) are scanned and served.
+    pub fn document_type_enabled(&self, type_name: &str) -> bool {
+        self.enabled_document_types
+            .iter()
+            .any(|enabled| enabled == type_name)
+    }
+
+    /// `hidden_uris` compiled; `*` stays within one path segment, `**` spans segments.
+    pub fn hidden_uri_matcher(&self) -> Result<GlobSet, globset::Error> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &self.hidden_uris {
+            builder.add(GlobBuilder::new(pattern).literal_separator(true).build()?);
+        }
+        builder.build()
+    }
+
+    /// Extension filters per document type, as the scanner resolves them from this
+    /// (already normalized) config.
+    pub fn effective_extensions(&self) -> BTreeMap<String, EffectiveExtensions> {
+        let preferred = &self.preferred_extensions;
+        let entry = |extensions: &[String], preferred: &[String], file_stem: Option<&str>| {
+            EffectiveExtensions {
+                extensions: extensions.to_vec(),
+                preferred: preferred.to_vec(),
+                file_stem: file_stem.map(ToString::to_string),
+            }
+        };
+        let diagrams = &self.diagram_extensions;
+        BTreeMap::from([
+            (
+                "agreements".to_string(),
+                entry(&self.agreement_extensions, &preferred.agreements, None),
+            ),
+            (
+                "c1".to_string(),
+                entry(diagrams, &preferred.diagram, Some("c1")),
+            ),
+            (
+                "c2".to_string(),
+                entry(diagrams, &preferred.diagram, Some("c2")),
+            ),
+            (
+                "c3".to_string(),
+                entry(diagrams, &preferred.diagram, Some("c3")),
+            ),
+            ("c4".to_string(), entry(diagrams, &preferred.diagram, None)),
+            ("erd".to_string(), entry(diagrams, &preferred.diagram, None)),
+            ("adr".to_string(), entry(diagrams, &preferred.diagram, None)),
+            (
+                "openapi".to_string(),
+                entry(&self.openapi_extensions, &preferred.openapi, None),
+            ),
+            (
+                "guide".to_string(),
+                entry(&self.guide_extensions, &preferred.guide, None),
+            ),
+            (
+                "style-guide".to_string(),
+                entry(&self.agreement_extensions, &preferred.agreements, None),
+            ),
+        ])
+    }
+}
+
 fn default_diagram_extensions() -> Vec<String> {
     vec!["mdx".to_string(), "puml".to_string(), "dot".to_string()]
 }
@@ -124,10 +837,106 @@ fn default_agreements() -> Vec<String> {
     vec!["content/docs/backend".to_string()]
 }
 
+fn default_walk_shared_targets_once() -> bool {
+    true
+}
+
+fn default_cache_parsed_documents() -> bool {
+    true
+}
+
+fn default_verify_extensions_on_read() -> bool {
+    true
+}
+
+fn default_enabled_document_types() -> Vec<String> {
+    DOCUMENT_TYPE_NAMES
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn default_agreement_extensions() -> Vec<String> {
+    AGREEMENT_EXTENSIONS
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
 fn default_guide_extensions() -> Vec<String> {
     vec!["rst".to_string()]
 }
 
+fn default_content_language() -> String {
+    "en".to_string()
+}
+
+fn default_c4_system_template() -> String {
+    "docs://architecture/{project}/{filename}".to_string()
+}
+
+fn default_c4_service_template() -> String {
+    "docs://architecture/{project}/c4/{filename}".to_string()
+}
+
+fn default_erd_template() -> String {
+    "docs://architecture/erd/{project}/{filename}".to_string()
+}
+
+fn default_adr_template() -> String {
+    "docs://architecture/{project}/adr/{filename}".to_string()
+}
+
+fn default_openapi_template() -> String {
+    "docs://openapi/{project}/{service}/{version}/{access_level}/{sub_category}/{filename}"
+        .to_string()
+}
+
+pub fn default_adr_required_sections() -> Vec<String> {
+    vec![
+        "Status".to_string(),
+        "Context".to_string(),
+        "Decision".to_string(),
+        "Consequences".to_string(),
+    ]
+}
+
+fn default_orphan_scan_max_files() -> usize {
+    100_000
+}
+
+pub(crate) fn default_export_read_concurrency() -> usize {
+    8
+}
+
+fn default_max_uri_length() -> usize {
+    2048
+}
+
+pub fn default_instructions_max_chars() -> usize {
+    2000
+}
+
+fn default_max_file_size() -> u64 {
+    1024 * 1024
+}
+
+fn default_chars_per_token() -> f64 {
+    4.0
+}
+
+pub fn default_manifest_file_name() -> String {
+    "manifest.json".to_string()
+}
+
+pub fn default_readme_names() -> Vec<String> {
+    vec![
+        "readme".to_string(),
+        "overview".to_string(),
+        "index".to_string(),
+    ]
+}
+
 fn normalize_extensions(exts: &mut Vec<String>) {
     for ext in exts.iter_mut() {
         let e = ext.trim().trim_start_matches('.').to_ascii_lowercase();
@@ -138,6 +947,18 @@ fn normalize_extensions(exts: &mut Vec<String>) {
     exts.dedup();
 }
 
+/// Like `normalize_extensions`, but keeps the configured order (first occurrence wins).
+fn normalize_extension_order(exts: &mut Vec<String>) {
+    let mut seen = Vec::with_capacity(exts.len());
+    for ext in exts.drain(..) {
+        let e = ext.trim().trim_start_matches('.').to_ascii_lowercase();
+        if !e.is_empty() && !seen.contains(&e) {
+            seen.push(e);
+        }
+    }
+    *exts = seen;
+}
+
 fn normalize_paths(paths: &mut Vec<String>) {
     for p in paths.iter_mut() {
         let trimmed = p.trim().to_string();
@@ -171,17 +992,98 @@ projects = [
         assert_eq!(cfg.agreements, vec!["content/docs/backend"]);
         assert_eq!(cfg.guide_extensions, vec!["rst"]);
         assert!(cfg.guides.is_empty());
+        assert!(cfg.style_guides.is_empty());
+        assert!(!cfg.browse);
+        assert!(!cfg.validate_specs_on_scan);
+        assert_eq!(cfg.max_response_bytes, None);
+        assert_eq!(cfg.max_description_length, None);
+        assert_eq!(cfg.index_cache_dir, None);
+        assert_eq!(cfg.usage_stats_file, None);
+        assert!(!cfg.detect_orphans);
+        assert!(!cfg.detect_encoding);
+        assert!(!cfg.enable_reload);
+        assert!(cfg.hidden_uris.is_empty());
+        assert!(cfg.agreements_defaults.is_empty());
+        assert!(cfg.staleness.max_age_days.is_empty());
+        assert_eq!(cfg.staleness.default_max_age_days, None);
+        assert_eq!(cfg.staleness.budget, 0);
+        assert_eq!(cfg.max_file_size, 1024 * 1024);
+        assert!((cfg.chars_per_token - 4.0).abs() < f64::EPSILON);
+        assert_eq!(cfg.orphan_scan_max_files, 100_000);
+        assert_eq!(cfg.export_read_concurrency, 8);
+        assert_eq!(cfg.openapi_multi_document, MultiDocumentMode::FirstOpenapi);
+        assert_eq!(cfg.default_project, None);
+        assert!(cfg.global_adr_paths.is_empty());
+        assert!(cfg.language_variants.languages.is_empty());
+        assert_eq!(
+            cfg.adr_required_sections,
+            vec!["Status", "Context", "Decision", "Consequences"]
+        );
+        assert_eq!(cfg.language_variants.default_language, "en");
+        assert_eq!(cfg.readme_names, vec!["readme", "overview", "index"]);
+        assert!(cfg.preferred_extensions.diagram.is_empty());
+    }
+
+    #[test]
+    fn parse_preferred_extensions_keeps_order() {
+        let toml_str = r#"
+[preferred_extensions]
+diagram = [".PUML", "mdx", "puml"]
+
+[[projects]]
+name = "example-project"
+"#;
+
+        let mut cfg: Config = toml::from_str(toml_str).expect("parse config");
+        normalize_extension_order(&mut cfg.preferred_extensions.diagram);
+
+        assert_eq!(cfg.preferred_extensions.diagram, vec!["puml", "mdx"]);
+        assert!(cfg.preferred_extensions.openapi.is_empty());
+    }
+
+    #[test]
+    fn parse_uri_templates_with_validation() {
+        let toml_str = r#"
+[uri_templates]
+adr = "docs://adr/{project}/{filename}"
+
+[[projects]]
+name = "example-project"
+"#;
+
+        let mut cfg: Config = toml::from_str(toml_str).expect("parse config");
+        assert_eq!(cfg.uri_templates.adr, "docs://adr/{project}/{filename}");
+        assert_eq!(
+            cfg.uri_templates.erd,
+            "docs://architecture/erd/{project}/{filename}"
+        );
+        assert!(cfg.uri_templates.validate().is_ok());
+
+        cfg.uri_templates.erd = "docs://erd/{project}".to_string();
+        assert!(cfg.uri_templates.validate().is_err());
+        cfg.uri_templates.erd = "docs://erd/{service}/{filename}".to_string();
+        let error = cfg
+            .uri_templates
+            .validate()
+            .expect_err("unknown placeholder");
+        assert!(error.contains("{service}"));
     }
 
     #[test]
     fn parse_full_config_shape() {
         let toml_str = r#"
+browse = true
+default_project = "shared"
+global_adr_paths = ["arch/shared/adr"]
+validate_specs_on_scan = true
+openapi_excluded_access_levels = ["internal"]
 diagram_extensions = ["puml", ".dot"]
 openapi_extensions = ["YAML", "yml"]
 agreements = ["content/docs/backend", "content/docs/frontend"]
 
 [[projects]]
 name = "example-project"
+aliases = ["Example Platform"]
 erd = ["arch/erd"]
 adr = ["arch/adr"]
 openapi = ["openapi-spec"]
@@ -206,8 +1108,15 @@ services = ["arch/c4/services"]
             vec!["content/docs/backend", "content/docs/frontend"]
         );
 
+        assert!(cfg.browse);
+        assert_eq!(cfg.default_project.as_deref(), Some("shared"));
+        assert_eq!(cfg.global_adr_paths, vec!["arch/shared/adr"]);
+        assert!(cfg.validate_specs_on_scan);
+        assert_eq!(cfg.openapi_excluded_access_levels, vec!["internal"]);
+
         let p = &cfg.projects[0];
         assert_eq!(p.name, "example-project");
+        assert_eq!(p.aliases, vec!["Example Platform"]);
         assert_eq!(p.c4.c1, vec!["arch/c4"]);
         assert_eq!(p.c4.services, vec!["arch/c4/services"]);
         assert_eq!(p.erd, vec!["arch/erd"]);
@@ -215,6 +1124,196 @@ services = ["arch/c4/services"]
         assert_eq!(p.openapi, vec!["openapi-spec"]);
     }
 
+    #[test]
+    fn parse_expectations_with_project_override() {
+        let toml_str = r#"
+[expectations]
+required = ["c1", "c2", "erd", "adr", "openapi"]
+
+[expectations.projects]
+docs-site = ["c1", "adr"]
+
+[[projects]]
+name = "billing"
+"#;
+
+        let cfg: Config = toml::from_str(toml_str).expect("parse config");
+        assert_eq!(
+            cfg.expectations.required_for("billing"),
+            ["c1", "c2", "erd", "adr", "openapi"]
+        );
+        assert_eq!(cfg.expectations.required_for("docs-site"), ["c1", "adr"]);
+
+        let cfg: Config = toml::from_str(
+            "projects = []
+",
+        )
+        .expect("parse config");
+        assert!(cfg.expectations.required_for("billing").is_empty());
+    }
+
+    #[test]
+    fn effective_extensions_reflect_defaults_and_overrides() {
+        let temp_dir = tempfile::TempDir::new().expect("temp dir");
+        let path = temp_dir.path().join("arch-mcp.toml");
+        fs::write(&path, "projects = []\n").expect("write config");
+        let cfg = Config::load(Some(&path)).expect("load config");
+        let effective = cfg.effective_extensions();
+
+        assert_eq!(effective["c1"].extensions, vec!["dot", "mdx", "puml"]);
+        assert_eq!(effective["c1"].file_stem.as_deref(), Some("c1"));
+        assert_eq!(effective["openapi"].extensions, vec!["yaml", "yml"]);
+        assert_eq!(effective["guide"].extensions, vec!["rst"]);
+        assert_eq!(effective["agreements"].extensions, vec!["md", "mdx", "txt"]);
+        assert!(effective["adr"].preferred.is_empty());
+
+        fs::write(
+            &path,
+            "openapi_extensions = [\".JSON\", \" yaml\"]\n\
+             agreement_extensions = [\"md\", \"YAML\"]\n\
+             projects = []\n\
+             [preferred_extensions]\n\
+             diagram = [\"PUML\", \".mdx\"]\n",
+        )
+        .expect("write config");
+        let cfg = Config::load(Some(&path)).expect("load config");
+        let effective = cfg.effective_extensions();
+
+        assert_eq!(effective["openapi"].extensions, vec!["json", "yaml"]);
+        assert_eq!(effective["agreements"].extensions, vec!["md", "yaml"]);
+        assert_eq!(effective["style-guide"].extensions, vec!["md", "yaml"]);
+        assert_eq!(effective["erd"].preferred, vec!["puml", "mdx"]);
+        assert!(effective["openapi"].preferred.is_empty());
+    }
+
+    #[test]
+    fn enabled_document_types_default_to_all_and_reject_unknown_names() {
+        let temp_dir = tempfile::TempDir::new().expect("temp dir");
+        let path = temp_dir.path().join("arch-mcp.toml");
+        fs::write(&path, "projects = []\n").expect("write config");
+        let cfg = Config::load(Some(&path)).expect("load config");
+        assert!(
+            DOCUMENT_TYPE_NAMES
+                .iter()
+                .all(|name| cfg.document_type_enabled(name))
+        );
+
+        fs::write(
+            &path,
+            "enabled_document_types = [\" ADR\", \"openapi\"]\nprojects = []\n",
+        )
+        .expect("write config");
+        let cfg = Config::load(Some(&path)).expect("load config");
+        assert_eq!(cfg.enabled_document_types, vec!["adr", "openapi"]);
+        assert!(!cfg.document_type_enabled("guide"));
+
+        fs::write(
+            &path,
+            "enabled_document_types = [\"adrs\"]\nprojects = []\n",
+        )
+        .expect("write config");
+        let error = Config::load(Some(&path)).expect_err("unknown type");
+        assert!(error.to_string().contains("unknown document type 'adrs'"));
+    }
+
+    #[test]
+    fn hidden_uris_must_be_valid_docs_globs() {
+        let temp_dir = tempfile::TempDir::new().expect("temp dir");
+        let path = temp_dir.path().join("arch-mcp.toml");
+        fs::write(
+            &path,
+            "hidden_uris = [\" docs://architecture/*/adr/*-secret.md\"]\nprojects = []\n",
+        )
+        .expect("write config");
+        let cfg = Config::load(Some(&path)).expect("load config");
+        let hidden = cfg.hidden_uri_matcher().expect("matcher");
+        assert!(hidden.is_match("docs://architecture/mpa/adr/007-secret.md"));
+        assert!(!hidden.is_match("docs://architecture/mpa/adr/nested/007-secret.md"));
+
+        for invalid in ["architecture/**", "docs://architecture/[mpa"] {
+            fs::write(
+                &path,
+                format!("hidden_uris = [\"{}\"]\nprojects = []\n", invalid),
+            )
+            .expect("write config");
+            let error = Config::load(Some(&path)).expect_err("invalid pattern");
+            assert!(error.to_string().contains("hidden_uris"));
+        }
+    }
+
+    #[test]
+    fn tool_overrides_reject_unknown_tools() {
+        let temp_dir = tempfile::TempDir::new().expect("temp dir");
+        let path = temp_dir.path().join("arch-mcp.toml");
+        fs::write(
+            &path,
+            "projects = []\n[tool_overrides.get_docs_list]\ndescription = \"List documents\"\n",
+        )
+        .expect("write config");
+        let cfg = Config::load(Some(&path)).expect("load config");
+        assert_eq!(
+            cfg.tool_overrides["get_docs_list"].description.as_deref(),
+            Some("List documents")
+        );
+
+        fs::write(
+            &path,
+            "projects = []\n[tool_overrides.get_doc_list]\ntitle = \"Docs\"\n",
+        )
+        .expect("write config");
+        let error = Config::load(Some(&path)).expect_err("unknown tool");
+        assert!(
+            error
+                .to_string()
+                .contains("unknown tool 'get_doc_list' in tool_overrides")
+        );
+    }
+
+    #[test]
+    fn manifest_dirs_stop_at_the_project_directory() {
+        let project = ProjectConfig {
+            name: "mpa".to_string(),
+            aliases: Vec::new(),
+            c4: C4Config {
+                c1: vec!["docs/architecture/mpa/c4".to_string()],
+                services: vec!["docs/architecture/mpa/c4/services/".to_string()],
+                ..Default::default()
+            },
+            erd: Vec::new(),
+            adr: vec!["docs/architecture/mpa/adr".to_string()],
+            openapi: vec!["openapi-spec/billing".to_string()],
+        };
+
+        assert_eq!(
+            project.manifest_dirs(),
+            vec![
+                "openapi-spec/billing",
+                "docs/architecture/mpa",
+                "docs/architecture/mpa/adr",
+                "docs/architecture/mpa/c4",
+                "docs/architecture/mpa/c4/services",
+            ]
+        );
+    }
+
+    #[test]
+    fn content_config_deny_wins_over_allow() {
+        let toml_str = r#"
+projects = []
+
+[content]
+allowed_mime_types = ["text/*", "application/x-yaml"]
+denied_mime_types = ["text/x-env"]
+"#;
+        let cfg: Config = toml::from_str(toml_str).expect("parse config");
+
+        assert!(cfg.content.permits("text/markdown"));
+        assert!(cfg.content.permits("application/x-yaml"));
+        assert!(!cfg.content.permits("text/x-env"));
+        assert!(!cfg.content.permits("application/octet-stream"));
+        assert!(ContentConfig::default().permits("application/octet-stream"));
+    }
+
     #[test]
     fn parse_config_with_guides() {
         let toml_str = r#"
@@ -245,4 +1344,19 @@ name = "example-project"
         assert_eq!(cfg.guides[1].name, "psrt");
         assert_eq!(cfg.guides[1].paths, vec!["psrt", "psrt/extra"]);
     }
+
+    #[test]
+    fn style_guide_paths_are_normalized_and_enable_the_type() {
+        let temp_dir = tempfile::TempDir::new().expect("temp dir");
+        let path = temp_dir.path().join("arch-mcp.toml");
+        fs::write(
+            &path,
+            "style_guides = [\" docs/backend/php/api-documentation \", \"\"]\nprojects = []\n",
+        )
+        .expect("write config");
+        let cfg = Config::load(Some(&path)).expect("load config");
+
+        assert_eq!(cfg.style_guides, vec!["docs/backend/php/api-documentation"]);
+        assert!(cfg.document_type_enabled("style-guide"));
+    }
 }
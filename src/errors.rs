@@ -0,0 +1,260 @@
+//! Tool and resource errors. The error message is a stable snake_case code; `data` is one of
+//! the payloads below, always with a human-readable `error`. Changing a code or a payload
+//! field changes the wire contract.
+//!
+//! There is no rate-limited family yet: the only server limit, `--max-sessions`, rejects a
+//! session at the HTTP layer before any JSON-RPC exchange. Add its constructor, payload and
+//! table-test row with the first limit enforced within a session.
+
+use std::fmt::Display;
+
+use rmcp::ErrorData as McpError;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// `data` of invalid-argument errors (`-32602`)
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct InvalidArgumentData {
+    pub error: String,
+    /// The offending arguments and what they accept, e.g. `provided_limit`, `valid_fields`
+    #[serde(flatten)]
+    pub context: Map<String, Value>,
+}
+
+/// `data` of not-found errors (`-32002`)
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct NotFoundData {
+    pub error: String,
+    /// Close matches worth retrying with; omitted when there are none
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+    /// What was looked up, e.g. `uri`, `project`, `file_path`
+    #[serde(flatten)]
+    pub context: Map<String, Value>,
+}
+
+/// `data` of errors reading an indexed file (`-32603`): `file_read_error`,
+/// `permission_denied` and `backing_store_unavailable`
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ReadFailureData {
+    /// Path relative to the docs root
+    pub file_path: String,
+    pub error: String,
+}
+
+/// `data` of other internal errors (`-32603`)
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct InternalData {
+    pub error: String,
+    #[serde(flatten)]
+    pub context: Map<String, Value>,
+}
+
+/// Successful response standing in for one that did not fit `max_response_bytes`
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct TruncationNotice {
+    /// Always true
+    pub response_truncated: bool,
+    pub max_response_bytes: usize,
+    /// How to get a response that fits
+    pub guidance: String,
+}
+
+fn payload(data: impl Serialize) -> Option<Value> {
+    serde_json::to_value(data).ok()
+}
+
+/// Context fields of an error payload, written like a `json!` object: `context!({"uri": uri})`.
+/// Only an object literal is accepted, so no context is lost.
+macro_rules! context {
+    ({ $($fields:tt)* }) => {
+        match serde_json::json!({ $($fields)* }) {
+            serde_json::Value::Object(fields) => fields,
+            _ => unreachable!("json! builds an object from braces"),
+        }
+    };
+}
+pub(crate) use context;
+
+pub fn invalid_argument(
+    code: &'static str,
+    error: impl Into<String>,
+    context: Map<String, Value>,
+) -> McpError {
+    McpError::invalid_params(
+        code,
+        payload(InvalidArgumentData {
+            error: error.into(),
+            context,
+        }),
+    )
+}
+
+pub fn not_found(
+    code: &'static str,
+    error: impl Into<String>,
+    context: Map<String, Value>,
+) -> McpError {
+    not_found_with_suggestions(code, error, Vec::<String>::new(), context)
+}
+
+pub fn not_found_with_suggestions(
+    code: &'static str,
+    error: impl Into<String>,
+    suggestions: impl IntoIterator<Item = impl Into<String>>,
+    context: Map<String, Value>,
+) -> McpError {
+    McpError::resource_not_found(
+        code,
+        payload(NotFoundData {
+            error: error.into(),
+            suggestions: suggestions.into_iter().map(Into::into).collect(),
+            context,
+        }),
+    )
+}
+
+fn read_failure(code: &'static str, file_path: &str, error: String) -> McpError {
+    McpError::internal_error(
+        code,
+        payload(ReadFailureData {
+            file_path: file_path.to_string(),
+            error,
+        }),
+    )
+}
+
+pub fn read_failed(file_path: &str, error: impl Display) -> McpError {
+    read_failure(
+        "file_read_error",
+        file_path,
+        format!("Failed to read file: {}", error),
+    )
+}
+
+pub fn permission_denied(file_path: &str, error: impl Display) -> McpError {
+    read_failure(
+        "permission_denied",
+        file_path,
+        format!("The server may not read this file: {}", error),
+    )
+}
+
+pub fn backing_store_unavailable(file_path: &str) -> McpError {
+    read_failure(
+        "backing_store_unavailable",
+        file_path,
+        "The docs root is unavailable; metadata is still served and content returns once it is \
+         reachable again"
+            .to_string(),
+    )
+}
+
+pub fn internal(
+    code: &'static str,
+    error: impl Into<String>,
+    context: Map<String, Value>,
+) -> McpError {
+    McpError::internal_error(
+        code,
+        payload(InternalData {
+            error: error.into(),
+            context,
+        }),
+    )
+}
+
+pub fn truncation_notice(max_response_bytes: usize, guidance: &str) -> TruncationNotice {
+    TruncationNotice {
+        response_truncated: true,
+        max_response_bytes,
+        guidance: guidance.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_keys(error: &McpError) -> Vec<String> {
+        let mut keys: Vec<String> = error
+            .data
+            .as_ref()
+            .and_then(Value::as_object)
+            .map(|data| data.keys().cloned().collect())
+            .unwrap_or_default();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_constructors_keep_codes_and_data_keys() {
+        let cases: Vec<(McpError, i32, &str, &[&str])> = vec![
+            (
+                invalid_argument(
+                    "invalid_limit",
+                    "too big",
+                    context!({"provided_limit": 500}),
+                ),
+                -32602,
+                "invalid_limit",
+                &["error", "provided_limit"],
+            ),
+            (
+                not_found("project_not_found", "no docs", context!({"project": "mpa"})),
+                -32002,
+                "project_not_found",
+                &["error", "project"],
+            ),
+            (
+                not_found_with_suggestions(
+                    "resource_not_found",
+                    "ambiguous",
+                    ["docs://a.md"],
+                    context!({"uri": "docs://A.md"}),
+                ),
+                -32002,
+                "resource_not_found",
+                &["error", "suggestions", "uri"],
+            ),
+            (
+                read_failed("a.md", "gone"),
+                -32603,
+                "file_read_error",
+                &["error", "file_path"],
+            ),
+            (
+                permission_denied("a.md", "denied"),
+                -32603,
+                "permission_denied",
+                &["error", "file_path"],
+            ),
+            (
+                backing_store_unavailable("a.md"),
+                -32603,
+                "backing_store_unavailable",
+                &["error", "file_path"],
+            ),
+            (
+                internal("serialization_error", "broken", context!({})),
+                -32603,
+                "serialization_error",
+                &["error"],
+            ),
+        ];
+
+        for (error, code, message, keys) in cases {
+            assert_eq!(error.code.0, code, "{}", message);
+            assert_eq!(error.message, message);
+            assert_eq!(data_keys(&error), keys, "{}", message);
+        }
+
+        let notice = serde_json::to_value(truncation_notice(100, "narrow")).expect("notice");
+        let mut keys: Vec<&String> = notice.as_object().expect("object").keys().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            ["guidance", "max_response_bytes", "response_truncated"]
+        );
+    }
+}
@@ -0,0 +1,1617 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+    sync::{Arc, PoisonError, RwLock},
+    time::Duration,
+};
+
+use tracing::{info, warn};
+
+use crate::{
+    config::Config,
+    index_cache::{self, CachedWalk, IndexCache},
+    models::{
+        self, ContentCounting, DocumentKey, DocumentScanner, DocumentType, ResourceInfo,
+        ScanProgress, TargetScan,
+    },
+    server::{DocumentServer, ServerOptions, TermCache},
+    utils::file_reader::FileReader,
+};
+
+/// The config currently in effect and the server state built from it.
+pub struct ServedIndex {
+    pub config: Config,
+    /// Config file text `config` was loaded from, for diffs against rejected edits
+    config_text: String,
+    /// Template cloned into every MCP session and browse request
+    pub server: DocumentServer,
+}
+
+pub type SharedIndex = Arc<RwLock<ServedIndex>>;
+
+impl ServedIndex {
+    pub fn build(config: Config, config_text: String, file_reader: &FileReader) -> Self {
+        Self::build_tracked(config, config_text, file_reader, &ScanTracker::default())
+    }
+
+    /// Like `build`, reporting progress to `tracker`, which the built server keeps reporting.
+    pub fn build_tracked(
+        config: Config,
+        config_text: String,
+        file_reader: &FileReader,
+        tracker: &ScanTracker,
+    ) -> Self {
+        let (resources, options) = scan(&config, file_reader, tracker);
+        tracker.update(|status| {
+            status.status = ScanPhase::Ready;
+            status.documents_found = resources.len().try_into().unwrap_or(u32::MAX);
+        });
+        let server = DocumentServer::new_with_resources(file_reader.clone(), resources)
+            .with_options(options)
+            .with_term_cache(TermCache::default())
+            .with_scan_tracker(tracker.clone());
+        Self {
+            config,
+            config_text,
+            server,
+        }
+    }
+
+    /// Empty index served while the initial scan runs; its server reports the returned
+    /// tracker's progress.
+    pub fn pending(
+        config: Config,
+        config_text: String,
+        file_reader: &FileReader,
+    ) -> (Self, ScanTracker) {
+        let tracker = ScanTracker::default();
+        tracker.update(|status| status.status = ScanPhase::Scanning);
+        let server = DocumentServer::new_with_resources(file_reader.clone(), BTreeMap::new())
+            .with_options(server_options(&config))
+            .with_scan_tracker(tracker.clone());
+        let index = Self {
+            config,
+            config_text,
+            server,
+        };
+        (index, tracker)
+    }
+}
+
+/// Phase of the index build
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanPhase {
+    Scanning,
+    #[default]
+    Ready,
+    Failed,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, schemars::JsonSchema)]
+pub struct ScanStatus {
+    pub status: ScanPhase,
+    /// Document sources (agreements, each project, global ADRs, each guide, style guides)
+    /// scanned so far
+    pub sources_scanned: u32,
+    pub sources_total: u32,
+    /// Documents found so far; the final count once ready
+    pub documents_found: u32,
+    /// Files walked so far, indexed or not; a directory shared by several document types
+    /// counts once with `walk_shared_targets_once`
+    pub files_visited: u32,
+    /// Files each scanner path pattern classified in the last completed scan, served by
+    /// get_pattern_stats
+    #[serde(skip)]
+    pub pattern_matches: BTreeMap<String, u32>,
+    /// When the last completed scan finished (RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_scan_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_scan_duration_ms: Option<u64>,
+    /// Why the scan failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Indexed files that could not be opened, checked with `verify_readability`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unreadable_files: Vec<UnreadableFile>,
+    /// Files outside every scan target, found with `detect_orphans`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orphaned_files: Option<u32>,
+    /// True when the orphan pass stopped at `orphan_scan_max_files`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub orphan_scan_truncated: bool,
+    /// Paths of the orphaned files, served by get_orphaned_documents
+    #[serde(skip)]
+    pub orphans: Vec<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+pub struct UnreadableFile {
+    pub uri: String,
+    pub file_path: String,
+    pub error: String,
+}
+
+/// Progress of an index build, shared between the scan and the servers reporting it.
+#[derive(Debug, Clone, Default)]
+pub struct ScanTracker(Arc<RwLock<ScanStatus>>);
+
+impl ScanTracker {
+    pub fn status(&self) -> ScanStatus {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub(crate) fn update(&self, change: impl FnOnce(&mut ScanStatus)) {
+        change(&mut self.0.write().unwrap_or_else(PoisonError::into_inner));
+    }
+
+    fn is(&self, other: &ScanTracker) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Runs `build` on a blocking thread and swaps its result into `index`, which keeps serving
+/// the pending index meanwhile. A reload that finished first wins; a failed build leaves the
+/// empty index serving with the tracker marked failed.
+pub async fn finish_initial_scan(
+    index: SharedIndex,
+    tracker: ScanTracker,
+    build: impl FnOnce(&ScanTracker) -> ServedIndex + Send + 'static,
+) {
+    let scan_tracker = tracker.clone();
+    match tokio::task::spawn_blocking(move || build(&scan_tracker)).await {
+        Ok(built) => {
+            let mut current = index.write().unwrap_or_else(PoisonError::into_inner);
+            if current.server.scan_tracker().is(&tracker) {
+                *current = built;
+            }
+        }
+        Err(error) => {
+            warn!("Initial scan failed: {}", error);
+            tracker.update(|status| {
+                status.status = ScanPhase::Failed;
+                status.error = Some(error.to_string());
+            });
+        }
+    }
+}
+
+/// Rescans of an initial scan that indexed nothing, e.g. while the docs volume is still
+/// being mounted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmptyScanRetry {
+    /// Rescans before giving up; 0 disables retrying
+    pub retries: u32,
+    pub delay: Duration,
+}
+
+/// Runs `build` until it indexes a document or `retry.retries` rescans, `retry.delay` apart,
+/// found none. Blocks, so call it from a blocking thread.
+pub fn build_retrying_empty(
+    retry: EmptyScanRetry,
+    mut build: impl FnMut() -> ServedIndex,
+) -> ServedIndex {
+    let mut built = build();
+    for attempt in 1..=retry.retries {
+        if built.server.resource_count() > 0 {
+            return built;
+        }
+        warn!(
+            "Initial scan found no documents, rescanning in {:?} (attempt {} of {})",
+            retry.delay, attempt, retry.retries
+        );
+        std::thread::sleep(retry.delay);
+        built = build();
+    }
+    if retry.retries > 0 && built.server.resource_count() == 0 {
+        warn!(
+            "Initial scan still found no documents after {} rescans, serving an empty index",
+            retry.retries
+        );
+    }
+    built
+}
+
+/// Clone of the current server template; a reload in progress never holds the lock while
+/// scanning, so readers only wait for the swap itself.
+pub fn current_server(index: &SharedIndex) -> DocumentServer {
+    index
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .server
+        .clone()
+}
+
+/// Re-reads the config file and, if it loads and validates, re-scans with it and swaps config
+/// and resources in one step. On failure the previous index keeps serving and the error is
+/// returned together with a diff of the rejected edit. Returns the new document count.
+pub fn reload(
+    index: &SharedIndex,
+    config_path: &Path,
+    file_reader: &FileReader,
+) -> Result<usize, String> {
+    let new_text = std::fs::read_to_string(config_path).unwrap_or_default();
+    let (old_text, scan_filter) = {
+        let current = index.read().unwrap_or_else(PoisonError::into_inner);
+        (
+            current.config_text.clone(),
+            current.config.scan_filter.clone(),
+        )
+    };
+    let config = Config::load(Some(config_path))
+        .map_err(|e| e.to_string())
+        .and_then(|mut config| {
+            scan_filter.apply(&mut config)?;
+            Ok(config)
+        })
+        .map_err(|e| format!("{}\n{}", e, config_diff(&old_text, &new_text)))?;
+
+    let rebuilt = ServedIndex::build(config, new_text, file_reader);
+    let documents = rebuilt.server.resource_count();
+    let mut current = index.write().unwrap_or_else(PoisonError::into_inner);
+    if current.config.browse != rebuilt.config.browse {
+        warn!("Changing browse takes effect after a restart");
+    }
+    *current = rebuilt;
+    Ok(documents)
+}
+
+/// Re-scans with the config in effect and swaps the result in, e.g. after the docs root came
+/// back from an outage. Returns the document count served afterwards.
+pub fn rescan(index: &SharedIndex, file_reader: &FileReader) -> usize {
+    let (config, config_text) = {
+        let current = index.read().unwrap_or_else(PoisonError::into_inner);
+        (current.config.clone(), current.config_text.clone())
+    };
+    let rebuilt = ServedIndex::build(config, config_text, file_reader);
+    swap_rescanned(index, rebuilt)
+}
+
+/// Swaps in a rescan unless a reload replaced the config it was built with meanwhile; the
+/// reload's index is newer and stays.
+fn swap_rescanned(index: &SharedIndex, rebuilt: ServedIndex) -> usize {
+    let mut current = index.write().unwrap_or_else(PoisonError::into_inner);
+    if current.config_text != rebuilt.config_text {
+        info!("Config was reloaded during the rescan, keeping the reloaded index");
+        return current.server.resource_count();
+    }
+    *current = rebuilt;
+    current.server.resource_count()
+}
+
+/// Re-stats and re-reads one indexed document, re-derives its content metadata with the
+/// passes a scan runs, and swaps the result into the served index. Sessions opened afterwards
+/// see it. Fails when the file can no longer be read.
+pub fn refresh_resource(
+    index: &SharedIndex,
+    file_reader: &FileReader,
+    info: &ResourceInfo,
+) -> std::io::Result<ResourceInfo> {
+    let metadata = file_reader.metadata(&info.file_path)?;
+    file_reader.check_readable(&info.file_path)?;
+    let cfg = index
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .config
+        .clone();
+
+    let refreshed = ResourceInfo {
+        size: metadata.len().try_into().unwrap_or(u32::MAX),
+        lang: path_lang(&cfg, info).unwrap_or_else(|| info.lang.clone()),
+        last_modified: models::last_modified(&metadata),
+        langs: Vec::new(),
+        applies_to: Vec::new(),
+        valid: None,
+        validation_error: None,
+        summary: None,
+        word_count: None,
+        token_estimate: None,
+        adr_date: None,
+        parse_error: None,
+        ..info.clone()
+    };
+    let key = DocumentKey::new(refreshed.uri.clone());
+    let mut resources = BTreeMap::from([(key.clone(), refreshed)]);
+    if cfg.validate_specs_on_scan && info.parsed_format() == Some("openapi") {
+        DocumentScanner::validate_specs(&mut resources, file_reader, cfg.openapi_multi_document);
+    }
+    DocumentScanner::apply_agreement_defaults(&mut resources, &cfg.agreements_defaults);
+    derive_content_metadata(&cfg, &mut resources, file_reader);
+    let refreshed = resources.remove(&key).unwrap_or_else(|| info.clone());
+
+    let mut current = index.write().unwrap_or_else(PoisonError::into_inner);
+    if current.server.replace_resource(refreshed.clone()) {
+        info!(uri = %refreshed.uri, "Refreshed document");
+    }
+    Ok(refreshed)
+}
+
+/// Language the scan that indexed `info` derives from its path
+fn path_lang(cfg: &Config, info: &ResourceInfo) -> Option<String> {
+    scan_sources(cfg)
+        .into_iter()
+        .flatten()
+        .filter(|call| info.type_name() == Some(call.document_type.type_name()))
+        .find_map(|call| {
+            call.targets.iter().find_map(|target| {
+                let target = target.trim_end_matches('/');
+                let covered = info
+                    .file_path
+                    .strip_prefix(target)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+                covered
+                    .then(|| {
+                        DocumentScanner::path_lang(
+                            &call.document_type,
+                            &cfg.uri_templates,
+                            target,
+                            info,
+                        )
+                    })
+                    .flatten()
+            })
+        })
+}
+
+/// Passes deriving metadata from document content, shared by scans and `refresh_resource`.
+/// Returns the number of documents that failed to parse.
+fn derive_content_metadata(
+    cfg: &Config,
+    resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+    file_reader: &FileReader,
+) -> usize {
+    DocumentScanner::extract_summaries(
+        resources,
+        file_reader,
+        ContentCounting {
+            max_file_size: cfg.max_file_size,
+            chars_per_token: cfg.chars_per_token,
+        },
+    );
+    DocumentScanner::extract_langs(resources, file_reader);
+    DocumentScanner::extract_applies_to(resources, file_reader);
+    DocumentScanner::extract_adr_dates(resources, file_reader);
+    DocumentScanner::detect_parse_failures(resources, file_reader, cfg.openapi_multi_document)
+}
+
+/// Line diff of the changed region: `-` lines of the old text, `+` lines of the new one.
+fn config_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut diff = format!("@@ line {} @@", prefix + 1);
+    for line in &old[prefix..old.len() - suffix] {
+        diff.push_str("\n- ");
+        diff.push_str(line);
+    }
+    for line in &new[prefix..new.len() - suffix] {
+        diff.push_str("\n+ ");
+        diff.push_str(line);
+    }
+    diff
+}
+
+/// Scans every configured document source and derives the tool settings. With
+/// `index_cache_dir`, an unchanged tree is served from the cache and only targets that
+/// changed are walked again.
+fn scan(
+    cfg: &Config,
+    file_reader: &FileReader,
+    tracker: &ScanTracker,
+) -> (BTreeMap<DocumentKey, ResourceInfo>, ServerOptions) {
+    let scan_start = std::time::Instant::now();
+    tracker.update(|status| {
+        *status = ScanStatus {
+            status: ScanPhase::Scanning,
+            sources_total: scan_sources(cfg).len().try_into().unwrap_or(u32::MAX),
+            ..Default::default()
+        };
+    });
+    let progress = ScanProgress::default();
+
+    let cache_dir = cfg.index_cache_dir.as_deref().map(Path::new);
+    let cached = cache_dir.and_then(|dir| IndexCache::load(dir, cfg, file_reader.docs_root()));
+    let mut resources = match cached
+        .as_ref()
+        .and_then(|cache| cached_index(cfg, file_reader, cache))
+    {
+        Some(resources) => {
+            info!("Index cache is current, serving its documents");
+            tracker.update(|status| {
+                status.sources_scanned = status.sources_total;
+                status.documents_found = resources.len().try_into().unwrap_or(u32::MAX);
+            });
+            resources
+        }
+        None => {
+            let mut cache = IndexCache::new(cfg, file_reader.docs_root());
+            let resources = scan_documents(
+                cfg,
+                file_reader,
+                tracker,
+                &progress,
+                cached.as_ref(),
+                &mut cache.walks,
+            );
+            if let Some(dir) = cache_dir {
+                cache.resources = resources.values().cloned().collect();
+                cache.save(dir);
+            }
+            resources
+        }
+    };
+
+    if cfg.verify_readability {
+        let unreadable = unreadable_files(&resources, file_reader);
+        if !unreadable.is_empty() {
+            warn!(
+                "{} indexed files are unreadable (see get_scan_status)",
+                unreadable.len()
+            );
+        }
+        tracker.update(|status| status.unreadable_files = unreadable);
+    }
+
+    if cfg.detect_orphans {
+        let (orphans, truncated) = orphaned_files(cfg, &resources, file_reader);
+        if !orphans.is_empty() {
+            warn!(
+                "{} files lie outside every scan target and are not indexed (see \
+                 get_orphaned_documents)",
+                orphans.len()
+            );
+        }
+        if truncated {
+            warn!(
+                "Orphan detection stopped after {} files (orphan_scan_max_files)",
+                cfg.orphan_scan_max_files
+            );
+        }
+        tracker.update(|status| {
+            status.orphaned_files = Some(orphans.len().try_into().unwrap_or(u32::MAX));
+            status.orphan_scan_truncated = truncated;
+            status.orphans = orphans;
+        });
+    }
+
+    let hidden_file_paths = match cfg.hidden_uri_matcher() {
+        Ok(hidden_uris) => DocumentScanner::exclude_hidden(&mut resources, &hidden_uris),
+        Err(error) => {
+            warn!("Ignoring invalid hidden_uris: {}", error);
+            BTreeSet::new()
+        }
+    };
+    if !hidden_file_paths.is_empty() {
+        info!(
+            "{} documents hidden by hidden_uris",
+            hidden_file_paths.len()
+        );
+    }
+
+    let scan_duration = scan_start.elapsed();
+    tracker.update(|status| {
+        status.files_visited = progress.files().try_into().unwrap_or(u32::MAX);
+        status.pattern_matches = progress
+            .pattern_counts()
+            .into_iter()
+            .map(|(pattern, files)| (pattern.to_string(), files.try_into().unwrap_or(u32::MAX)))
+            .collect();
+        status.last_scan_at = Some(chrono::Utc::now().to_rfc3339());
+        status.last_scan_duration_ms =
+            Some(scan_duration.as_millis().try_into().unwrap_or(u64::MAX));
+    });
+    info!(
+        "Scanned {} documents ({} files visited) in {:?}",
+        resources.len(),
+        progress.files(),
+        scan_duration
+    );
+
+    let options = ServerOptions {
+        hidden_file_paths,
+        ..server_options(cfg)
+    };
+    (resources, options)
+}
+
+/// The cached index when every target walk the config makes is cached and unchanged.
+fn cached_index(
+    cfg: &Config,
+    file_reader: &FileReader,
+    cache: &IndexCache,
+) -> Option<BTreeMap<DocumentKey, ResourceInfo>> {
+    let docs_root = Path::new(file_reader.docs_root());
+    let mut walks = 0;
+    for (source, calls) in enabled_sources(cfg).iter().enumerate() {
+        for (target, indices) in target_walks(calls, cfg.walk_shared_targets_once) {
+            cache.unchanged_walk(docs_root, source, target, &indices)?;
+            walks += 1;
+        }
+    }
+    (walks == cache.walks.len()).then(|| keyed(&cache.resources))
+}
+
+/// Walks the configured targets, reusing the walks `cached` still matches, and runs the
+/// post-processing passes over the documents found. Every walk made or reused is pushed
+/// to `walks`.
+#[allow(clippy::too_many_lines)]
+fn scan_documents(
+    cfg: &Config,
+    file_reader: &FileReader,
+    tracker: &ScanTracker,
+    progress: &ScanProgress,
+    cached: Option<&IndexCache>,
+    walks: &mut Vec<CachedWalk>,
+) -> BTreeMap<DocumentKey, ResourceInfo> {
+    let mut resources: BTreeMap<DocumentKey, ResourceInfo> = BTreeMap::new();
+    let source_scanned = || tracker.update(|status| status.sources_scanned += 1);
+    let docs_root = Path::new(file_reader.docs_root());
+    let mut invalid_specs = 0;
+    for (source, calls) in enabled_sources(cfg).into_iter().enumerate() {
+        let mut scanned: Vec<BTreeMap<DocumentKey, ResourceInfo>> =
+            calls.iter().map(|_| BTreeMap::new()).collect();
+        for (target, indices) in target_walks(&calls, cfg.walk_shared_targets_once) {
+            if let Some(walk) =
+                cached.and_then(|cache| cache.unchanged_walk(docs_root, source, target, &indices))
+            {
+                for (&index, documents) in &walk.documents {
+                    scanned[index].extend(keyed(documents));
+                }
+                walks.push(walk.clone());
+                continue;
+            }
+
+            // Fingerprinted before walking, so a change made meanwhile invalidates the cache
+            let fingerprints = index_cache::fingerprint_target(docs_root, target);
+            let mut walked: Vec<BTreeMap<DocumentKey, ResourceInfo>> =
+                indices.iter().map(|_| BTreeMap::new()).collect();
+            let mut scans: Vec<TargetScan> = indices
+                .iter()
+                .zip(walked.iter_mut())
+                .map(|(&index, resources)| TargetScan {
+                    document_type: &calls[index].document_type,
+                    allowed_extensions: calls[index].extensions,
+                    resources,
+                })
+                .collect();
+            if let Err(e) = DocumentScanner::scan_target(
+                &cfg.uri_templates,
+                target,
+                &mut scans,
+                file_reader,
+                progress,
+            ) {
+                warn!("Failed to scan target '{}': {}", target, e);
+            }
+            let mut documents = BTreeMap::new();
+            for (&index, walked) in indices.iter().zip(walked) {
+                documents.insert(index, walked.values().cloned().collect());
+                scanned[index].extend(walked);
+            }
+            walks.push(CachedWalk {
+                source,
+                target: target.to_string(),
+                documents,
+                fingerprints,
+            });
+        }
+
+        for (call, mut scanned) in calls.iter().zip(scanned) {
+            let is_spec = matches!(call.document_type, DocumentType::OpenApiSpec(_));
+            if is_spec {
+                DocumentScanner::exclude_access_levels(
+                    &mut scanned,
+                    &cfg.openapi_excluded_access_levels,
+                );
+            }
+            if is_spec && cfg.validate_specs_on_scan {
+                invalid_specs += DocumentScanner::validate_specs(
+                    &mut scanned,
+                    file_reader,
+                    cfg.openapi_multi_document,
+                );
+            }
+            DocumentScanner::apply_preferred_extensions(&mut scanned, call.preferred);
+            let found: u32 = scanned.len().try_into().unwrap_or(u32::MAX);
+            tracker.update(|status| status.documents_found += found);
+            resources.extend(scanned);
+        }
+        source_scanned();
+    }
+
+    // Only a style-guide scan picks up what agreements leave under style_guides paths
+    let claimed = if cfg.document_type_enabled(DocumentType::ApiStyleGuide.type_name()) {
+        DocumentScanner::exclude_claimed_agreements(&mut resources, &cfg.style_guides)
+    } else {
+        0
+    };
+    if claimed > 0 {
+        let claimed_count: u32 = claimed.try_into().unwrap_or(u32::MAX);
+        tracker.update(|status| {
+            status.documents_found = status.documents_found.saturating_sub(claimed_count);
+        });
+        info!(
+            "{} agreements skipped for lying under style_guides paths",
+            claimed
+        );
+    }
+
+    // Manifests are project metadata served by get_project_manifest, not documents.
+    resources.retain(|_, info| {
+        info.file_path.rsplit('/').next() != Some(cfg.manifest_file_name.as_str())
+    });
+
+    let long_uris = DocumentScanner::skip_long_uris(&mut resources, cfg.max_uri_length);
+    if long_uris > 0 {
+        warn!(
+            "{} documents skipped for URIs longer than {} characters",
+            long_uris, cfg.max_uri_length
+        );
+    }
+
+    if let Some(max_length) = cfg.max_description_length {
+        DocumentScanner::truncate_descriptions(&mut resources, max_length);
+    }
+
+    DocumentScanner::detect_language_variants(
+        &mut resources,
+        &cfg.language_variants.languages,
+        &cfg.language_variants.default_language,
+    );
+
+    DocumentScanner::assign_adr_scope(&mut resources, &cfg.global_adr_paths);
+    DocumentScanner::apply_agreement_defaults(&mut resources, &cfg.agreements_defaults);
+    if let Some(default_project) = &cfg.default_project {
+        DocumentScanner::assign_default_project(&mut resources, default_project);
+    }
+
+    let parse_failures = derive_content_metadata(cfg, &mut resources, file_reader);
+
+    if parse_failures > 0 {
+        warn!(
+            "{} documents failed to parse (see get_parse_failures)",
+            parse_failures
+        );
+    }
+    if invalid_specs > 0 {
+        warn!(
+            "{} API specifications failed validation (see get_docs_list only_invalid)",
+            invalid_specs
+        );
+    }
+
+    resources
+}
+
+fn keyed(documents: &[ResourceInfo]) -> BTreeMap<DocumentKey, ResourceInfo> {
+    documents
+        .iter()
+        .map(|info| (DocumentKey::new(info.uri.clone()), info.clone()))
+        .collect()
+}
+
+fn unreadable_files(
+    resources: &BTreeMap<DocumentKey, ResourceInfo>,
+    file_reader: &FileReader,
+) -> Vec<UnreadableFile> {
+    resources
+        .values()
+        .filter_map(|info| {
+            let error = file_reader.check_readable(&info.file_path).err()?;
+            warn!(uri = %info.uri, %error, "Indexed file is unreadable");
+            Some(UnreadableFile {
+                uri: info.uri.clone(),
+                file_path: info.file_path.clone(),
+                error: error.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Files under the docs root with an extension some document type is scanned with that are
+/// neither indexed nor inside a configured target, sorted, and whether the walk stopped at
+/// `orphan_scan_max_files`. Hidden files and directories are skipped.
+fn orphaned_files(
+    cfg: &Config,
+    resources: &BTreeMap<DocumentKey, ResourceInfo>,
+    file_reader: &FileReader,
+) -> (Vec<String>, bool) {
+    let indexed: BTreeSet<&str> = resources
+        .values()
+        .map(|info| info.file_path.as_str())
+        .collect();
+    let effective = cfg.effective_extensions();
+    let extensions: BTreeSet<&str> = effective
+        .values()
+        .flat_map(|entry| entry.extensions.iter().map(String::as_str))
+        .collect();
+    let targets: Vec<String> = scan_plan(cfg)
+        .into_iter()
+        .map(|step| step.target.trim_end_matches('/').to_string())
+        .collect();
+    let covered = |path: &str| {
+        indexed.contains(path)
+            || targets.iter().any(|target| {
+                path.strip_prefix(target.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+    };
+
+    let docs_root = Path::new(file_reader.docs_root());
+    let mut orphans = Vec::new();
+    let mut examined = 0;
+    let mut pending = vec![String::new()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(docs_root.join(&dir)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            let path = if dir.is_empty() {
+                name
+            } else {
+                format!("{}/{}", dir, name)
+            };
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            examined += 1;
+            if examined > cfg.orphan_scan_max_files {
+                orphans.sort();
+                return (orphans, true);
+            }
+            let extension = path
+                .rsplit_once('.')
+                .map(|(_, extension)| extension.to_ascii_lowercase())
+                .unwrap_or_default();
+            if extensions.contains(extension.as_str()) && !covered(&path) {
+                orphans.push(path);
+            }
+        }
+    }
+    orphans.sort();
+    (orphans, false)
+}
+
+/// Targets to walk with the indices of the calls listing them, in first-listed order. With
+/// `walk_once`, a target listed by several calls (or twice by one) is walked a single time.
+fn target_walks<'a>(calls: &[ScanCall<'a>], walk_once: bool) -> Vec<(&'a str, Vec<usize>)> {
+    let mut walks: Vec<(&'a str, Vec<usize>)> = Vec::new();
+    for (index, call) in calls.iter().enumerate() {
+        for target in call.targets {
+            let shared = walks.iter_mut().find(|(walked, _)| {
+                walk_once && walked.trim_end_matches('/') == target.trim_end_matches('/')
+            });
+            match shared {
+                Some((_, indices)) if indices.contains(&index) => {}
+                Some((_, indices)) => indices.push(index),
+                None => walks.push((target, vec![index])),
+            }
+        }
+    }
+    walks
+}
+
+/// One scanner call: a document type scanned in its targets.
+struct ScanCall<'a> {
+    document_type: DocumentType,
+    targets: &'a [String],
+    extensions: &'a [String],
+    preferred: &'a [String],
+}
+
+/// Scanner calls the config asks for, grouped by progress source: agreements, each project,
+/// global ADRs, each guide, style guides. Calls of disabled document types are included.
+fn scan_sources(cfg: &Config) -> Vec<Vec<ScanCall<'_>>> {
+    let preferred = &cfg.preferred_extensions;
+    let call = |document_type, targets, extensions, preferred| ScanCall {
+        document_type,
+        targets,
+        extensions,
+        preferred,
+    };
+    let diagrams = (&cfg.diagram_extensions[..], &preferred.diagram[..]);
+
+    let mut sources = vec![vec![call(
+        DocumentType::Agreements,
+        &cfg.agreements,
+        &cfg.agreement_extensions,
+        &preferred.agreements,
+    )]];
+    for project in &cfg.projects {
+        let name = || project.name.clone();
+        sources.push(vec![
+            call(
+                DocumentType::C1Diagram(name()),
+                &project.c4.c1,
+                diagrams.0,
+                diagrams.1,
+            ),
+            call(
+                DocumentType::C2Diagram(name()),
+                &project.c4.c2,
+                diagrams.0,
+                diagrams.1,
+            ),
+            call(
+                DocumentType::C3Diagram(name()),
+                &project.c4.c3,
+                diagrams.0,
+                diagrams.1,
+            ),
+            call(
+                DocumentType::C4Diagram(name()),
+                &project.c4.services,
+                diagrams.0,
+                diagrams.1,
+            ),
+            call(
+                DocumentType::ErdDiagram(name()),
+                &project.erd,
+                diagrams.0,
+                diagrams.1,
+            ),
+            call(
+                DocumentType::AdrDocument(name()),
+                &project.adr,
+                diagrams.0,
+                diagrams.1,
+            ),
+            call(
+                DocumentType::OpenApiSpec(name()),
+                &project.openapi,
+                &cfg.openapi_extensions,
+                &preferred.openapi,
+            ),
+        ]);
+    }
+    // ADRs shared across projects; files outside the known layout are registered under
+    // default_project, or "global" when it is unset.
+    let global_owner = cfg.default_project.as_deref().unwrap_or("global");
+    sources.push(vec![call(
+        DocumentType::AdrDocument(global_owner.to_string()),
+        &cfg.global_adr_paths,
+        diagrams.0,
+        diagrams.1,
+    )]);
+    for guide in &cfg.guides {
+        sources.push(vec![call(
+            DocumentType::GuideDoc(guide.name.clone()),
+            &guide.paths,
+            &cfg.guide_extensions,
+            &preferred.guide,
+        )]);
+    }
+    if !cfg.style_guides.is_empty() {
+        sources.push(vec![call(
+            DocumentType::ApiStyleGuide,
+            &cfg.style_guides,
+            &cfg.agreement_extensions,
+            &preferred.agreements,
+        )]);
+    }
+    sources
+}
+
+/// `scan_sources` without the calls of disabled document types.
+fn enabled_sources(cfg: &Config) -> Vec<Vec<ScanCall<'_>>> {
+    scan_sources(cfg)
+        .into_iter()
+        .map(|source| {
+            source
+                .into_iter()
+                .filter(|call| cfg.document_type_enabled(call.document_type.type_name()))
+                .collect()
+        })
+        .collect()
+}
+
+/// One (document type, target, extensions) scan the config derives, in scan order.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
+pub struct ScanPlanStep {
+    pub document_type: String,
+    /// Project, or guide product, the documents are registered under; absent for agreements
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Directory or file relative to the docs root
+    pub target: String,
+    /// Extensions picked up; empty means any
+    pub extensions: Vec<String>,
+    /// Extensions winning when one document exists in several
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub preferred_extensions: Vec<String>,
+    /// False when the type is not in `enabled_document_types`; the step is skipped
+    pub enabled: bool,
+}
+
+/// The scans `cfg` makes, one step per target.
+pub fn scan_plan(cfg: &Config) -> Vec<ScanPlanStep> {
+    scan_sources(cfg)
+        .into_iter()
+        .flatten()
+        .flat_map(|call| {
+            call.targets.iter().map(move |target| ScanPlanStep {
+                document_type: call.document_type.type_name().to_string(),
+                owner: call.document_type.owner().map(ToString::to_string),
+                target: target.clone(),
+                extensions: call.extensions.to_vec(),
+                preferred_extensions: call.preferred.to_vec(),
+                enabled: cfg.document_type_enabled(call.document_type.type_name()),
+            })
+        })
+        .collect()
+}
+
+/// Tool settings derived from the config.
+fn server_options(cfg: &Config) -> ServerOptions {
+    ServerOptions {
+        readme_names: cfg.readme_names.clone(),
+        max_response_bytes: cfg.max_response_bytes,
+        adr_required_sections: cfg.adr_required_sections.clone(),
+        adr_section_synonyms: cfg.adr_section_synonyms.clone(),
+        accept_file_paths: cfg.accept_file_paths,
+        cache_parsed_documents: cfg.cache_parsed_documents,
+        openapi_multi_document: cfg.openapi_multi_document,
+        verify_extensions_on_read: cfg.verify_extensions_on_read,
+        enabled_document_types: cfg.enabled_document_types.clone(),
+        type_priority: cfg.type_priority.clone(),
+        variant_languages: cfg.language_variants.languages.clone(),
+        project_aliases: cfg
+            .projects
+            .iter()
+            .map(|project| (project.name.clone(), project.aliases.clone()))
+            .collect(),
+        manifest_file_name: cfg.manifest_file_name.clone(),
+        project_manifest_dirs: cfg
+            .projects
+            .iter()
+            .map(|project| (project.name.clone(), project.manifest_dirs()))
+            .collect(),
+        expectations: cfg.expectations.clone(),
+        effective_extensions: cfg.effective_extensions(),
+        instructions_max_chars: cfg.instructions_max_chars,
+        content: cfg.content.clone(),
+        uri_aliases: cfg.uri_aliases.clone(),
+        tool_overrides: cfg.tool_overrides.clone(),
+        scan_plan: scan_plan(cfg),
+        detect_orphans: cfg.detect_orphans,
+        export_read_concurrency: cfg.export_read_concurrency,
+        enable_reload: cfg.enable_reload,
+        // Known only once a scan applied hidden_uris
+        hidden_file_paths: BTreeSet::new(),
+        staleness: cfg.staleness.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::config::ScanFilter;
+
+    const PROJECT_A: &str =
+        "agreements = []\n\n[[projects]]\nname = \"proj-a\"\nadr = [\"architecture/proj-a/adr\"]\n";
+    const PROJECT_B: &str =
+        "\n[[projects]]\nname = \"proj-b\"\nadr = [\"architecture/proj-b/adr\"]\n";
+
+    fn setup() -> (TempDir, PathBuf, FileReader, SharedIndex) {
+        let temp_dir = TempDir::new().expect("temp dir");
+        for adr in [
+            "architecture/proj-a/adr/001-outbox.mdx",
+            "architecture/proj-b/adr/002-queues.mdx",
+        ] {
+            let path = temp_dir.path().join(adr);
+            fs::create_dir_all(path.parent().expect("parent")).expect("dir");
+            fs::write(path, "# ADR\n").expect("write adr");
+        }
+        let config_path = temp_dir.path().join("arch-mcp.toml");
+        fs::write(&config_path, PROJECT_A).expect("write config");
+
+        let file_reader =
+            FileReader::new(temp_dir.path().to_string_lossy().to_string()).expect("reader");
+        let config = Config::load(Some(&config_path)).expect("config");
+        let index = Arc::new(RwLock::new(ServedIndex::build(
+            config,
+            PROJECT_A.to_string(),
+            &file_reader,
+        )));
+        (temp_dir, config_path, file_reader, index)
+    }
+
+    #[test]
+    fn test_reload_swaps_config_and_resources() {
+        let (_temp_dir, config_path, file_reader, index) = setup();
+        assert_eq!(current_server(&index).resource_count(), 1);
+
+        fs::write(&config_path, format!("{}{}", PROJECT_A, PROJECT_B)).expect("rewrite");
+        assert_eq!(reload(&index, &config_path, &file_reader), Ok(2));
+
+        let served = index.read().expect("index");
+        assert_eq!(served.config.projects.len(), 2);
+        assert_eq!(served.server.resource_count(), 2);
+    }
+
+    #[test]
+    fn test_disabled_document_types_are_not_indexed() {
+        let (temp_dir, config_path, file_reader, _) = setup();
+        let guide = temp_dir.path().join("guides/eva4/intro.rst");
+        fs::create_dir_all(guide.parent().expect("parent")).expect("dir");
+        fs::write(guide, "# Intro\n").expect("write guide");
+        let guides = "\n[[guides]]\nname = \"eva4\"\npaths = [\"guides/eva4\"]\n";
+
+        let text = format!("{}{}", PROJECT_A, guides);
+        fs::write(&config_path, &text).expect("rewrite");
+        let all = ServedIndex::build(
+            Config::load(Some(&config_path)).expect("config"),
+            text,
+            &file_reader,
+        );
+        assert_eq!(all.server.resource_count(), 2);
+
+        let text = format!(
+            "enabled_document_types = [\"guide\"]\n{}{}",
+            PROJECT_A, guides
+        );
+        fs::write(&config_path, &text).expect("rewrite");
+        let guides_only = ServedIndex::build(
+            Config::load(Some(&config_path)).expect("config"),
+            text,
+            &file_reader,
+        );
+        let uris: Vec<&str> = guides_only
+            .server
+            .resource_infos()
+            .map(|info| info.uri.as_str())
+            .collect();
+        assert_eq!(uris, vec!["docs://guides/eva4/intro.rst"]);
+    }
+
+    #[test]
+    fn test_scan_plan_lists_each_configured_scan() {
+        let (_temp_dir, config_path, _, _) = setup();
+        fs::write(
+            &config_path,
+            format!(
+                "enabled_document_types = [\"adr\", \"guide\"]\nglobal_adr_paths = \
+                 [\"architecture/shared/adr\"]\n{}\n[[guides]]\nname = \"eva4\"\npaths = \
+                 [\"guides/eva4\"]\n",
+                PROJECT_A.replace("agreements = []", "agreements = [\"agreements/backend\"]")
+            ),
+        )
+        .expect("rewrite");
+        let cfg = Config::load(Some(&config_path)).expect("config");
+
+        let summary: Vec<(String, Option<String>, String, bool)> = scan_plan(&cfg)
+            .into_iter()
+            .map(|step| (step.document_type, step.owner, step.target, step.enabled))
+            .collect();
+        let step = |document_type: &str, owner: Option<&str>, target: &str, enabled| {
+            (
+                document_type.to_string(),
+                owner.map(ToString::to_string),
+                target.to_string(),
+                enabled,
+            )
+        };
+        assert_eq!(
+            summary,
+            vec![
+                step("agreements", None, "agreements/backend", false),
+                step("adr", Some("proj-a"), "architecture/proj-a/adr", true),
+                step("adr", Some("global"), "architecture/shared/adr", true),
+                step("guide", Some("eva4"), "guides/eva4", true),
+            ]
+        );
+        let plan = scan_plan(&cfg);
+        assert_eq!(plan[1].extensions, cfg.diagram_extensions);
+        assert_eq!(plan[3].extensions, vec!["rst"]);
+    }
+
+    #[test]
+    fn test_scan_filter_excludes_projects_and_types() {
+        let (_temp_dir, config_path, file_reader, _) = setup();
+        let text = format!("{}{}", PROJECT_A, PROJECT_B);
+        fs::write(&config_path, &text).expect("rewrite");
+        let scan_with = |filter: ScanFilter| {
+            let mut config = Config::load(Some(&config_path)).expect("config");
+            filter.apply(&mut config).map(|()| {
+                ServedIndex::build(config, text.clone(), &file_reader)
+                    .server
+                    .resource_infos()
+                    .map(|info| info.uri.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        let only_b = scan_with(ScanFilter {
+            only_projects: vec!["proj-b".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(
+            only_b,
+            Ok(vec![
+                "docs://architecture/proj-b/adr/002-queues.mdx".to_string()
+            ])
+        );
+        let without_adrs = scan_with(ScanFilter {
+            skip_types: vec!["adr".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(without_adrs, Ok(Vec::new()));
+        let unknown = scan_with(ScanFilter {
+            only_projects: vec!["proj-c".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(
+            unknown,
+            Err("unknown project 'proj-c' (expected one of proj-a, proj-b)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_manifest_files_are_not_indexed() {
+        let (temp_dir, config_path, file_reader, _) = setup();
+        let guides_dir = temp_dir.path().join("guides/eva4");
+        fs::create_dir_all(&guides_dir).expect("dir");
+        fs::write(guides_dir.join("intro.json"), "{}").expect("write guide");
+        fs::write(guides_dir.join("manifest.json"), "{}").expect("write manifest");
+
+        let text = format!(
+            "guide_extensions = [\"json\"]\n{}\n[[guides]]\nname = \"eva4\"\npaths = [\"guides/eva4\"]\n",
+            PROJECT_A
+        );
+        fs::write(&config_path, &text).expect("rewrite");
+        let served = ServedIndex::build(
+            Config::load(Some(&config_path)).expect("config"),
+            text,
+            &file_reader,
+        );
+        let uris: Vec<&str> = served
+            .server
+            .resource_infos()
+            .map(|info| info.uri.as_str())
+            .filter(|uri| uri.starts_with("docs://guides"))
+            .collect();
+        assert_eq!(uris, vec!["docs://guides/eva4/intro.json"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_verify_readability_reports_unreadable_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (temp_dir, config_path, file_reader, _) = setup();
+        let adr = temp_dir
+            .path()
+            .join("architecture/proj-a/adr/001-outbox.mdx");
+        fs::set_permissions(&adr, fs::Permissions::from_mode(0o000)).expect("chmod");
+        if fs::read(&adr).is_ok() {
+            // Privileged users read files regardless of their mode
+            return;
+        }
+
+        let text = format!("verify_readability = true\n{}", PROJECT_A);
+        fs::write(&config_path, &text).expect("rewrite");
+        let served = ServedIndex::build(
+            Config::load(Some(&config_path)).expect("config"),
+            text,
+            &file_reader,
+        );
+        let status = served.server.scan_tracker().status();
+        assert_eq!(served.server.resource_count(), 1);
+        assert_eq!(status.unreadable_files.len(), 1);
+        assert_eq!(
+            status.unreadable_files[0].file_path,
+            "architecture/proj-a/adr/001-outbox.mdx"
+        );
+    }
+
+    #[test]
+    fn test_shared_c4_directory_is_walked_once() {
+        let (temp_dir, config_path, file_reader, _) = setup();
+        let c4 = temp_dir.path().join("architecture/proj-a/c4");
+        fs::create_dir_all(&c4).expect("dir");
+        for level in ["c1", "c2", "c3"] {
+            fs::write(c4.join(format!("{}.mdx", level)), "# Diagram\n").expect("write");
+        }
+        let c4_config = "\n[projects.c4]\nc1 = [\"architecture/proj-a/c4\"]\n\
+                         c2 = [\"architecture/proj-a/c4\"]\nc3 = [\"architecture/proj-a/c4/\"]\n";
+
+        let scan = |walk_once: &str| {
+            let text = format!(
+                "walk_shared_targets_once = {}\n{}{}",
+                walk_once, PROJECT_A, c4_config
+            );
+            fs::write(&config_path, &text).expect("rewrite");
+            let tracker = ScanTracker::default();
+            let served = ServedIndex::build_tracked(
+                Config::load(Some(&config_path)).expect("config"),
+                text,
+                &file_reader,
+                &tracker,
+            );
+            (
+                served.server.resource_count(),
+                tracker.status().files_visited,
+            )
+        };
+
+        // 3 diagrams walked once, plus the ADR directory
+        assert_eq!(scan("true"), (4, 4));
+        assert_eq!(scan("false"), (4, 10));
+    }
+
+    #[test]
+    fn test_index_cache_skips_unchanged_targets() {
+        let (temp_dir, config_path, file_reader, _) = setup();
+        let cache_dir = TempDir::new().expect("cache dir");
+        let text = format!(
+            "index_cache_dir = {:?}\n{}{}",
+            cache_dir.path().to_string_lossy(),
+            PROJECT_A,
+            PROJECT_B
+        );
+        fs::write(&config_path, &text).expect("rewrite");
+        let scan = || {
+            let tracker = ScanTracker::default();
+            let served = ServedIndex::build_tracked(
+                Config::load(Some(&config_path)).expect("config"),
+                text.clone(),
+                &file_reader,
+                &tracker,
+            );
+            (
+                served.server.resource_count(),
+                tracker.status().files_visited,
+            )
+        };
+
+        assert_eq!(scan(), (2, 2));
+        // Hit: nothing is walked
+        assert_eq!(scan(), (2, 0));
+
+        // Partial invalidation: only proj-b's ADR directory is walked again
+        fs::write(
+            temp_dir
+                .path()
+                .join("architecture/proj-b/adr/003-retries.mdx"),
+            "# ADR\n",
+        )
+        .expect("write adr");
+        assert_eq!(scan(), (3, 2));
+        assert_eq!(scan(), (3, 0));
+
+        // A corrupt cache falls back to a full scan and is rewritten
+        fs::write(
+            cache_dir.path().join(index_cache::CACHE_FILE_NAME),
+            "{ not json",
+        )
+        .expect("corrupt");
+        assert_eq!(scan(), (3, 3));
+        assert_eq!(scan(), (3, 0));
+    }
+
+    #[test]
+    fn test_index_cache_is_discarded_when_config_changes() {
+        let (_temp_dir, config_path, file_reader, _) = setup();
+        let cache_dir = TempDir::new().expect("cache dir");
+        let scan = |config: &str| {
+            let text = format!(
+                "index_cache_dir = {:?}\n{}",
+                cache_dir.path().to_string_lossy(),
+                config
+            );
+            fs::write(&config_path, &text).expect("rewrite");
+            let tracker = ScanTracker::default();
+            let served = ServedIndex::build_tracked(
+                Config::load(Some(&config_path)).expect("config"),
+                text,
+                &file_reader,
+                &tracker,
+            );
+            (
+                served.server.resource_count(),
+                tracker.status().files_visited,
+            )
+        };
+
+        assert_eq!(scan(PROJECT_A), (1, 1));
+        assert_eq!(scan(PROJECT_A), (1, 0));
+        let both = format!("{}{}", PROJECT_A, PROJECT_B);
+        assert_eq!(scan(&both), (2, 2));
+    }
+
+    #[test]
+    fn test_empty_initial_scan_is_retried_until_documents_appear() {
+        let (temp_dir, config_path, file_reader, _) = setup();
+        let adr_dir = temp_dir.path().join("architecture/proj-a/adr");
+        fs::remove_dir_all(&adr_dir).expect("unmount");
+        let mut attempts = 0;
+        let mut build = || {
+            attempts += 1;
+            let built = ServedIndex::build(
+                Config::load(Some(&config_path)).expect("config"),
+                PROJECT_A.to_string(),
+                &file_reader,
+            );
+            // The volume finishes mounting after the first scan
+            fs::create_dir_all(&adr_dir).expect("dir");
+            fs::write(adr_dir.join("001-outbox.mdx"), "# ADR\n").expect("write adr");
+            built
+        };
+        let retry = EmptyScanRetry {
+            retries: 3,
+            delay: Duration::ZERO,
+        };
+
+        let built = build_retrying_empty(retry, &mut build);
+        assert_eq!(built.server.resource_count(), 1);
+        assert_eq!(attempts, 2);
+
+        fs::remove_dir_all(&adr_dir).expect("unmount");
+        let disabled = EmptyScanRetry {
+            retries: 0,
+            delay: Duration::ZERO,
+        };
+        let built = build_retrying_empty(disabled, || {
+            ServedIndex::build(
+                Config::load(Some(&config_path)).expect("config"),
+                PROJECT_A.to_string(),
+                &file_reader,
+            )
+        });
+        assert_eq!(built.server.resource_count(), 0);
+    }
+
+    #[test]
+    fn test_detect_orphans_reports_files_outside_every_target() {
+        let (temp_dir, config_path, file_reader, _) = setup();
+        for (path, content) in [(".git/notes.mdx", "# Hidden\n"), ("notes.bin", "Notes\n")] {
+            let path = temp_dir.path().join(path);
+            fs::create_dir_all(path.parent().expect("parent")).expect("dir");
+            fs::write(path, content).expect("write");
+        }
+        let scan = |settings: &str| {
+            let text = format!("{}{}", settings, PROJECT_A);
+            fs::write(&config_path, &text).expect("rewrite");
+            let tracker = ScanTracker::default();
+            ServedIndex::build_tracked(
+                Config::load(Some(&config_path)).expect("config"),
+                text,
+                &file_reader,
+                &tracker,
+            );
+            tracker.status()
+        };
+
+        let status = scan("detect_orphans = true\n");
+        // proj-a's ADR is covered; proj-b is not configured
+        assert_eq!(
+            status.orphans,
+            vec!["architecture/proj-b/adr/002-queues.mdx"]
+        );
+        assert_eq!(status.orphaned_files, Some(1));
+        assert!(!status.orphan_scan_truncated);
+
+        let status = scan("detect_orphans = true\norphan_scan_max_files = 1\n");
+        assert!(status.orphan_scan_truncated);
+
+        assert_eq!(scan("").orphaned_files, None);
+    }
+
+    #[test]
+    fn test_style_guides_claim_their_paths_from_agreements() {
+        let (temp_dir, config_path, file_reader, _) = setup();
+        for path in [
+            "docs/backend/php/http/errors.md",
+            "docs/backend/php/api-documentation/rest.md",
+        ] {
+            let path = temp_dir.path().join(path);
+            fs::create_dir_all(path.parent().expect("parent")).expect("dir");
+            fs::write(path, "# Guide\n").expect("write");
+        }
+        let text = "agreements = [\"docs/backend\"]\n\
+                    style_guides = [\"docs/backend/php/api-documentation\"]\n\
+                    projects = []\n";
+        fs::write(&config_path, text).expect("rewrite");
+        let built = ServedIndex::build(
+            Config::load(Some(&config_path)).expect("config"),
+            text.to_string(),
+            &file_reader,
+        );
+
+        let uris: Vec<&str> = built
+            .server
+            .resource_infos()
+            .map(|info| info.uri.as_str())
+            .collect();
+        assert_eq!(
+            uris,
+            vec![
+                "docs://agreements/backend/php/http/errors.md",
+                "docs://style-guides/backend/php/api-documentation/rest.md",
+            ]
+        );
+        assert_eq!(built.server.scan_status().scan.documents_found, 2);
+
+        let text = format!("enabled_document_types = [\"agreements\"]\n{}", text);
+        fs::write(&config_path, &text).expect("rewrite");
+        let agreements_only = ServedIndex::build(
+            Config::load(Some(&config_path)).expect("config"),
+            text,
+            &file_reader,
+        );
+        let uris: Vec<&str> = agreements_only
+            .server
+            .resource_infos()
+            .map(|info| info.uri.as_str())
+            .collect();
+        assert_eq!(
+            uris,
+            vec![
+                "docs://agreements/backend/php/api-documentation/rest.md",
+                "docs://agreements/backend/php/http/errors.md",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rescan_picks_up_documents_with_current_config() {
+        let (temp_dir, _config_path, file_reader, index) = setup();
+        fs::write(
+            temp_dir
+                .path()
+                .join("architecture/proj-a/adr/003-retries.mdx"),
+            "# ADR\n",
+        )
+        .expect("write adr");
+
+        assert_eq!(rescan(&index, &file_reader), 2);
+        assert_eq!(current_server(&index).resource_count(), 2);
+    }
+
+    #[test]
+    fn test_refresh_resource_rederives_the_path_language() {
+        let (temp_dir, config_path, file_reader, _) = setup();
+        let agreement = temp_dir.path().join("docs/backend/php/errors.md");
+        fs::create_dir_all(agreement.parent().expect("parent")).expect("dir");
+        fs::write(&agreement, "---\nlangs: [go]\n---\n# Errors\n").expect("write");
+        let text = "agreements = [\"docs/backend\"]\nprojects = []\n";
+        fs::write(&config_path, text).expect("rewrite");
+        let index = Arc::new(RwLock::new(ServedIndex::build(
+            Config::load(Some(&config_path)).expect("config"),
+            text.to_string(),
+            &file_reader,
+        )));
+        let uri = "docs://agreements/backend/php/errors.md";
+        let info = current_server(&index)
+            .resource_infos()
+            .find(|info| info.uri == uri)
+            .cloned()
+            .expect("agreement");
+        assert_eq!(info.lang, "go");
+
+        fs::write(&agreement, "# Errors\n").expect("drop frontmatter");
+        let refreshed = refresh_resource(&index, &file_reader, &info).expect("refresh");
+
+        assert_eq!(refreshed.lang, "php");
+        assert_eq!(refreshed.langs, vec!["php"]);
+    }
+
+    #[test]
+    fn test_rescan_does_not_revert_a_concurrent_reload() {
+        let (_temp_dir, config_path, file_reader, index) = setup();
+        let (config, config_text) = {
+            let current = index.read().expect("index");
+            (current.config.clone(), current.config_text.clone())
+        };
+        let rescanned = ServedIndex::build(config, config_text, &file_reader);
+
+        fs::write(&config_path, format!("{}{}", PROJECT_A, PROJECT_B)).expect("rewrite");
+        assert_eq!(reload(&index, &config_path, &file_reader), Ok(2));
+        assert_eq!(swap_rescanned(&index, rescanned), 2);
+
+        let served = index.read().expect("index");
+        assert_eq!(served.config.projects.len(), 2);
+        assert_eq!(served.server.resource_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_initial_scan_serves_pending_index_until_swapped() {
+        let (_temp_dir, config_path, file_reader, _) = setup();
+        let config = Config::load(Some(&config_path)).expect("config");
+        let (pending, tracker) =
+            ServedIndex::pending(config.clone(), PROJECT_A.to_string(), &file_reader);
+        let index = Arc::new(RwLock::new(pending));
+
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        let scan = tokio::spawn(finish_initial_scan(
+            index.clone(),
+            tracker,
+            move |tracker| {
+                released.recv().expect("release");
+                ServedIndex::build_tracked(config, PROJECT_A.to_string(), &file_reader, tracker)
+            },
+        ));
+
+        let waiting = current_server(&index);
+        assert_eq!(waiting.resource_count(), 0);
+        assert_eq!(waiting.scan_tracker().status().status, ScanPhase::Scanning);
+
+        release.send(()).expect("send");
+        scan.await.expect("scan task");
+        let status = waiting.scan_tracker().status();
+        assert_eq!(status.status, ScanPhase::Ready);
+        assert_eq!((status.sources_scanned, status.sources_total), (3, 3));
+        assert_eq!(status.documents_found, 1);
+        assert_eq!(current_server(&index).resource_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_initial_scan_keeps_pending_index() {
+        let (_temp_dir, config_path, file_reader, _) = setup();
+        let config = Config::load(Some(&config_path)).expect("config");
+        let (pending, tracker) = ServedIndex::pending(config, String::new(), &file_reader);
+        let index = Arc::new(RwLock::new(pending));
+
+        finish_initial_scan(index.clone(), tracker, |_| panic!("docs root vanished")).await;
+
+        let status = current_server(&index).scan_tracker().status();
+        assert_eq!(status.status, ScanPhase::Failed);
+        assert!(
+            status
+                .error
+                .is_some_and(|error| error.contains("docs root vanished"))
+        );
+        assert_eq!(current_server(&index).resource_count(), 0);
+    }
+
+    #[test]
+    fn test_reload_rejects_invalid_config_and_keeps_serving() {
+        let (_temp_dir, config_path, file_reader, index) = setup();
+
+        let broken = format!("{}{}unknown_key = true\n", PROJECT_A, PROJECT_B);
+        fs::write(&config_path, broken).expect("rewrite");
+        let error = reload(&index, &config_path, &file_reader).expect_err("rejected");
+
+        assert!(error.contains("Failed to parse config file"));
+        assert!(error.ends_with(
+            "@@ line 6 @@\n+ \n+ [[projects]]\n+ name = \"proj-b\"\n\
+             + adr = [\"architecture/proj-b/adr\"]\n+ unknown_key = true"
+        ));
+        let served = index.read().expect("index");
+        assert_eq!(served.config.projects.len(), 1);
+        assert_eq!(served.server.resource_count(), 1);
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::BTreeMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use tracing::{debug, info, warn};
+
+use crate::{config::Config, models::ResourceInfo};
+
+/// File the index cache is written to inside `index_cache_dir`
+pub const CACHE_FILE_NAME: &str = "index-cache.json";
+
+/// Scanned index written after a scan and checked on the next one. A cache written by another
+/// server version or for another config (or docs root) is discarded.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct IndexCache {
+    pub crate_version: String,
+    pub config_hash: String,
+    /// Raw documents of every target walk, reused for walks whose fingerprints still match
+    pub walks: Vec<CachedWalk>,
+    /// The finished index, served as is when every walk matches
+    pub resources: Vec<ResourceInfo>,
+}
+
+/// One target walk of one scan source, before per-type post-processing.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CachedWalk {
+    /// Position of the source (agreements, a project, global ADRs, a guide) in scan order
+    pub source: usize,
+    pub target: String,
+    /// Index of the source's scanner call -> documents it found in the target
+    pub documents: BTreeMap<usize, Vec<ResourceInfo>>,
+    /// The target and everything under it when it was walked
+    pub fingerprints: Vec<Fingerprint>,
+}
+
+/// Size and modification time of a path relative to the docs root; a directory's
+/// modification time changes when entries are added or removed.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Fingerprint {
+    pub path: String,
+    /// None when the path did not exist
+    pub stat: Option<FileStat>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FileStat {
+    pub size: u64,
+    pub modified_ns: u64,
+}
+
+impl IndexCache {
+    pub fn new(cfg: &Config, docs_root: &str) -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            config_hash: config_hash(cfg, docs_root),
+            walks: Vec::new(),
+            resources: Vec::new(),
+        }
+    }
+
+    /// The cache in `dir` when it was written by this version for `cfg`. A missing, stale or
+    /// unreadable cache yields None, falling back to a full scan.
+    pub fn load(dir: &Path, cfg: &Config, docs_root: &str) -> Option<Self> {
+        let path = dir.join(CACHE_FILE_NAME);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) => {
+                debug!("No index cache at {}: {}", path.display(), error);
+                return None;
+            }
+        };
+        let cache: Self = match serde_json::from_str(&text) {
+            Ok(cache) => cache,
+            Err(error) => {
+                warn!("Ignoring corrupt index cache {}: {}", path.display(), error);
+                return None;
+            }
+        };
+        let expected = Self::new(cfg, docs_root);
+        if cache.crate_version != expected.crate_version
+            || cache.config_hash != expected.config_hash
+        {
+            info!("Discarding index cache written for another version or config");
+            return None;
+        }
+        Some(cache)
+    }
+
+    /// Writes the cache through a temporary file, so a crash never leaves a partial one.
+    pub fn save(&self, dir: &Path) {
+        let path = dir.join(CACHE_FILE_NAME);
+        let partial = dir.join(format!("{}.tmp", CACHE_FILE_NAME));
+        let written = std::fs::create_dir_all(dir)
+            .and_then(|()| serde_json::to_vec(self).map_err(std::io::Error::other))
+            .and_then(|bytes| std::fs::write(&partial, bytes))
+            .and_then(|()| std::fs::rename(&partial, &path));
+        if let Err(error) = written {
+            warn!("Failed to write index cache {}: {}", path.display(), error);
+        }
+    }
+
+    /// The cached walk of `target` by `source` covering exactly the scanner calls `calls`,
+    /// when nothing under the target changed since.
+    pub fn unchanged_walk(
+        &self,
+        docs_root: &Path,
+        source: usize,
+        target: &str,
+        calls: &[usize],
+    ) -> Option<&CachedWalk> {
+        self.walks
+            .iter()
+            .find(|walk| {
+                walk.source == source
+                    && walk.target == target
+                    && walk.documents.keys().copied().eq(calls.iter().copied())
+            })
+            .filter(|walk| walk.is_unchanged(docs_root))
+    }
+}
+
+impl CachedWalk {
+    fn is_unchanged(&self, docs_root: &Path) -> bool {
+        self.fingerprints
+            .iter()
+            .all(|fingerprint| stat(&docs_root.join(&fingerprint.path)) == fingerprint.stat)
+    }
+}
+
+/// Fingerprints of `target` and every directory and file under it.
+pub fn fingerprint_target(docs_root: &Path, target: &str) -> Vec<Fingerprint> {
+    let mut fingerprints = Vec::new();
+    let mut pending = vec![PathBuf::from(target)];
+    while let Some(relative) = pending.pop() {
+        let full_path = docs_root.join(&relative);
+        let stat = stat(&full_path);
+        if stat.is_some()
+            && full_path.is_dir()
+            && let Ok(entries) = std::fs::read_dir(&full_path)
+        {
+            pending.extend(
+                entries
+                    .flatten()
+                    .map(|entry| relative.join(entry.file_name())),
+            );
+        }
+        fingerprints.push(Fingerprint {
+            path: relative.to_string_lossy().to_string(),
+            stat,
+        });
+    }
+    fingerprints
+}
+
+fn stat(path: &Path) -> Option<FileStat> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified_ns = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_nanos().try_into().unwrap_or(u64::MAX));
+    Some(FileStat {
+        size: metadata.len(),
+        modified_ns,
+    })
+}
+
+/// Hash of everything a scan depends on besides the files: the effective config (including
+/// command-line scan filters) and the docs root.
+fn config_hash(cfg: &Config, docs_root: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", cfg).hash(&mut hasher);
+    docs_root.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
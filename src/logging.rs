@@ -0,0 +1,240 @@
+use std::sync::{
+    Arc, Mutex, Weak,
+    atomic::{AtomicU64, Ordering},
+};
+
+use rmcp::{
+    Peer, RoleServer,
+    model::{LoggingLevel, LoggingMessageNotificationParam},
+};
+use serde_json::{Map, Value, json};
+use tokio::sync::mpsc;
+use tracing::{Event, Span, Subscriber, field::Field, span};
+use tracing_subscriber::{Layer, layer::Context, registry::LookupSpan};
+
+/// Per-session queue depth; events beyond it are dropped so a slow client never blocks tracing.
+const SESSION_QUEUE_CAPACITY: usize = 256;
+
+/// Name of the span [`session_span`] opens.
+const SESSION_SPAN: &str = "mcp_session";
+
+/// Span for work done on behalf of one MCP session. Events inside it are only forwarded to
+/// that session; events outside every session span go to all subscribed sessions.
+pub fn session_span(session: u64) -> Span {
+    tracing::debug_span!("mcp_session", session)
+}
+
+/// Registry of MCP sessions that asked for log notifications via `logging/setLevel`.
+#[derive(Debug, Clone, Default)]
+pub struct LogBridge {
+    sessions: Arc<Mutex<Vec<Weak<SessionLog>>>>,
+    next_session: Arc<AtomicU64>,
+}
+
+/// Log subscription of a single MCP session.
+#[derive(Debug)]
+pub struct SessionLog {
+    session: u64,
+    level: Mutex<LoggingLevel>,
+    sender: mpsc::Sender<LoggingMessageNotificationParam>,
+}
+
+/// Session of a [`session_span`], kept in the span's extensions.
+struct SessionTag(u64);
+
+impl SessionLog {
+    pub fn set_level(&self, level: LoggingLevel) {
+        if let Ok(mut current) = self.level.lock() {
+            *current = level;
+        }
+    }
+
+    fn accepts(&self, level: LoggingLevel) -> bool {
+        self.level
+            .lock()
+            .is_ok_and(|current| severity(level) >= severity(*current))
+    }
+}
+
+impl LogBridge {
+    /// Allocates the identifier a session tags its span and subscription with.
+    pub fn session_id(&self) -> u64 {
+        self.next_session.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Registers a session and spawns the task forwarding its queued events to the peer.
+    ///
+    /// The returned handle must be kept alive by the session; once dropped, the session
+    /// is unregistered and the forwarding task ends.
+    pub fn register(
+        &self,
+        peer: Peer<RoleServer>,
+        session: u64,
+        level: LoggingLevel,
+    ) -> Arc<SessionLog> {
+        let (sender, mut receiver) = mpsc::channel(SESSION_QUEUE_CAPACITY);
+        tokio::spawn(async move {
+            while let Some(param) = receiver.recv().await {
+                if peer.notify_logging_message(param).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let session = Arc::new(SessionLog {
+            session,
+            level: Mutex::new(level),
+            sender,
+        });
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.retain(|s| s.strong_count() > 0);
+            sessions.push(Arc::downgrade(&session));
+        }
+        session
+    }
+
+    /// Tracing layer feeding this bridge.
+    pub fn layer(&self) -> McpLogLayer {
+        McpLogLayer {
+            bridge: self.clone(),
+        }
+    }
+
+    /// Queues `param` for the session it was logged in, or for every session when None.
+    fn dispatch(
+        &self,
+        origin: Option<u64>,
+        level: LoggingLevel,
+        param: &LoggingMessageNotificationParam,
+    ) {
+        let Ok(sessions) = self.sessions.lock() else {
+            return;
+        };
+        for session in sessions.iter().filter_map(Weak::upgrade) {
+            if origin.is_none_or(|origin| origin == session.session) && session.accepts(level) {
+                // Full queue means the client is not keeping up: drop instead of blocking.
+                let _ = session.sender.try_send(param.clone());
+            }
+        }
+    }
+
+    fn has_sessions(&self) -> bool {
+        self.sessions
+            .lock()
+            .is_ok_and(|sessions| sessions.iter().any(|s| s.strong_count() > 0))
+    }
+}
+
+/// Tracing layer forwarding events as MCP `notifications/message`.
+pub struct McpLogLayer {
+    bridge: LogBridge,
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for McpLogLayer {
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != SESSION_SPAN {
+            return;
+        }
+        let mut fields = FieldCollector::default();
+        attrs.record(&mut fields);
+        if let (Some(session), Some(span)) = (
+            fields.0.get("session").and_then(Value::as_u64),
+            ctx.span(id),
+        ) {
+            span.extensions_mut().insert(SessionTag(session));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        // rmcp logs while delivering notifications; forwarding those would feed back into itself.
+        if metadata.target().starts_with("rmcp") || !self.bridge.has_sessions() {
+            return;
+        }
+
+        let origin = ctx.event_scope(event).and_then(|mut scope| {
+            scope.find_map(|span| span.extensions().get::<SessionTag>().map(|tag| tag.0))
+        });
+        let level = to_logging_level(*metadata.level());
+        let mut fields = FieldCollector::default();
+        event.record(&mut fields);
+
+        let param = LoggingMessageNotificationParam {
+            level,
+            logger: Some(metadata.target().to_string()),
+            data: Value::Object(fields.0),
+        };
+        self.bridge.dispatch(origin, level, &param);
+    }
+}
+
+#[derive(Default)]
+struct FieldCollector(Map<String, Value>);
+
+impl tracing::field::Visit for FieldCollector {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), json!(format!("{:?}", value)));
+    }
+}
+
+fn to_logging_level(level: tracing::Level) -> LoggingLevel {
+    match level {
+        tracing::Level::ERROR => LoggingLevel::Error,
+        tracing::Level::WARN => LoggingLevel::Warning,
+        tracing::Level::INFO => LoggingLevel::Info,
+        tracing::Level::DEBUG | tracing::Level::TRACE => LoggingLevel::Debug,
+    }
+}
+
+fn severity(level: LoggingLevel) -> u8 {
+    match level {
+        LoggingLevel::Debug => 0,
+        LoggingLevel::Info => 1,
+        LoggingLevel::Notice => 2,
+        LoggingLevel::Warning => 3,
+        LoggingLevel::Error => 4,
+        LoggingLevel::Critical => 5,
+        LoggingLevel::Alert => 6,
+        LoggingLevel::Emergency => 7,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity_ordering() {
+        assert!(severity(LoggingLevel::Error) > severity(LoggingLevel::Warning));
+        assert!(severity(LoggingLevel::Warning) > severity(LoggingLevel::Info));
+        assert!(severity(LoggingLevel::Info) > severity(LoggingLevel::Debug));
+    }
+
+    #[test]
+    fn test_tracing_level_mapping() {
+        assert_eq!(to_logging_level(tracing::Level::ERROR), LoggingLevel::Error);
+        assert_eq!(
+            to_logging_level(tracing::Level::WARN),
+            LoggingLevel::Warning
+        );
+        assert_eq!(to_logging_level(tracing::Level::INFO), LoggingLevel::Info);
+        assert_eq!(to_logging_level(tracing::Level::TRACE), LoggingLevel::Debug);
+    }
+}
@@ -1,22 +1,37 @@
-use std::{collections::BTreeMap, path::PathBuf, time::Duration};
-
-use clap::Parser;
-use rmcp::transport::streamable_http_server::{
-    StreamableHttpService, session::local::LocalSessionManager,
+use std::{
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::Duration,
 };
-use tracing::{info, warn};
+
+use clap::{CommandFactory, Parser};
+use rmcp::transport::streamable_http_server::StreamableHttpService;
+use tracing::{Level, info, warn};
 use tracing_subscriber::{
+    Layer,
+    filter::Targets,
     layer::SubscriberExt,
     util::SubscriberInitExt,
     {self},
 };
+mod browse;
 mod config;
+mod errors;
+mod index;
+mod index_cache;
+mod logging;
+mod manifest;
 mod models;
 mod server;
+mod sessions;
+mod store;
+mod subscriptions;
+mod usage;
 mod utils;
-use config::Config;
-use models::{DocumentKey, DocumentScanner, DocumentType, ResourceInfo};
-use server::DocumentServer;
+use config::{Config, ScanFilter};
+use index::{EmptyScanRetry, ServedIndex, SharedIndex, current_server};
+use logging::LogBridge;
+use sessions::SessionLimit;
 
 use crate::utils::file_reader::FileReader;
 
@@ -39,6 +54,51 @@ struct Cli {
     /// RUST_LOG-style level when RUST_LOG env is unset.
     #[arg(long, value_name = "LEVEL", default_value = "info")]
     rust_log: String,
+
+    /// Print the scans the config derives as JSON and exit without serving.
+    #[arg(long)]
+    print_scan_plan: bool,
+
+    /// Scan, print the stale documents report ([staleness] thresholds) as JSON and exit; fails
+    /// when more documents are stale than staleness.budget allows. For CI.
+    #[arg(long)]
+    check: bool,
+
+    /// Scan only this project (repeatable). Agreements, global ADRs and guides are kept.
+    #[arg(long = "only-project", value_name = "NAME")]
+    only_projects: Vec<String>,
+
+    /// Do not scan this document type (repeatable).
+    #[arg(
+        long = "skip-type",
+        value_name = "TYPE",
+        value_parser = clap::builder::PossibleValuesParser::new(models::DOCUMENT_TYPE_NAMES)
+    )]
+    skip_types: Vec<String>,
+
+    /// Maximum concurrent MCP sessions; further sessions are rejected until one closes.
+    /// Sessions idle for 30 minutes are closed while the limit applies. Default: unlimited.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    max_sessions: Option<u32>,
+
+    /// Oldest response schema version clients must understand. Clients that declare an older
+    /// one (experimental capability schema_version) are logged as incompatible when they
+    /// initialize; clients that declare none are not checked. Default: no check.
+    #[arg(
+        long,
+        value_name = "VERSION",
+        value_parser = clap::value_parser!(u32).range(1..=i64::from(server::SCHEMA_VERSION))
+    )]
+    min_client_schema: Option<u32>,
+
+    /// Rescan this many times when the initial scan indexes no documents, e.g. while the docs
+    /// volume is still being mounted. Default: no rescans.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    retry_empty_scan: u32,
+
+    /// Seconds to wait before each rescan of --retry-empty-scan.
+    #[arg(long, value_name = "SECS", default_value_t = 5)]
+    retry_empty_scan_delay: u64,
 }
 
 impl Cli {
@@ -54,10 +114,69 @@ impl Cli {
     fn rust_log(&self) -> &str {
         &self.rust_log
     }
+    fn print_scan_plan(&self) -> bool {
+        self.print_scan_plan
+    }
+    fn check(&self) -> bool {
+        self.check
+    }
+    fn max_sessions(&self) -> Option<usize> {
+        self.max_sessions.and_then(|max| usize::try_from(max).ok())
+    }
+    fn min_client_schema(&self) -> Option<u32> {
+        self.min_client_schema
+    }
+    fn empty_scan_retry(&self) -> EmptyScanRetry {
+        EmptyScanRetry {
+            retries: self.retry_empty_scan,
+            delay: Duration::from_secs(self.retry_empty_scan_delay),
+        }
+    }
+    fn scan_filter(&self) -> ScanFilter {
+        ScanFilter {
+            only_projects: self.only_projects.clone(),
+            skip_types: self.skip_types.clone(),
+        }
+    }
+}
+
+/// Saves the read counters when the config sets `usage_stats_file`.
+fn save_usage_stats(usage_stats: Option<&(PathBuf, usage::UsageCounters)>) {
+    let Some((path, usage)) = usage_stats else {
+        return;
+    };
+    match usage.save(path) {
+        Ok(()) => info!("Usage stats saved to {}", path.display()),
+        Err(error) => warn!(
+            "Failed to save usage stats to {}: {}",
+            path.display(),
+            error
+        ),
+    }
+}
+
+/// Waits for `signal`, then saves the read counters. Open streaming sessions can keep the
+/// server running until the forced exit, so the counters are saved before it is armed.
+async fn shutdown_on(
+    signal: impl Future<Output = ()>,
+    usage_stats: Option<&(PathBuf, usage::UsageCounters)>,
+) {
+    signal.await;
+    save_usage_stats(usage_stats);
+}
+
+async fn setup_graceful_shutdown(usage_stats: Option<(PathBuf, usage::UsageCounters)>) {
+    shutdown_on(shutdown_signal(), usage_stats.as_ref()).await;
+
+    tokio::spawn(async {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        warn!("Graceful shutdown timeout reached, forcing exit...");
+        std::process::exit(0);
+    });
 }
 
 #[allow(clippy::ignored_unit_patterns)]
-async fn setup_graceful_shutdown() {
+async fn shutdown_signal() {
     tokio::select! {
         _ = tokio::signal::ctrl_c() => {
             info!("Received ctrl+c, shutting down gracefully...");
@@ -85,124 +204,188 @@ async fn setup_graceful_shutdown() {
             }
         } => {}
     }
+}
 
-    tokio::spawn(async {
-        tokio::time::sleep(Duration::from_secs(5)).await;
-        warn!("Graceful shutdown timeout reached, forcing exit...");
-        std::process::exit(0);
+/// Re-applies the config file on SIGHUP. A rejected config is logged and the previous one
+/// keeps serving.
+fn spawn_reload_on_sighup(index: SharedIndex, config_path: PathBuf, file_reader: FileReader) {
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{SignalKind, signal};
+        let Ok(mut sighup) = signal(SignalKind::hangup()) else {
+            warn!("Failed to create SIGHUP handler, config reload disabled");
+            return;
+        };
+        while sighup.recv().await.is_some() {
+            let (index, config_path, file_reader) =
+                (index.clone(), config_path.clone(), file_reader.clone());
+            let outcome = tokio::task::spawn_blocking(move || {
+                index::reload(&index, &config_path, &file_reader)
+            })
+            .await;
+            match outcome {
+                Ok(Ok(documents)) => info!("Config reloaded, {} documents indexed", documents),
+                Ok(Err(error)) => {
+                    warn!("Config reload rejected, keeping previous config: {}", error)
+                }
+                Err(error) => warn!("Config reload failed: {}", error),
+            }
+        }
     });
+    #[cfg(not(unix))]
+    let _ = (index, config_path, file_reader);
 }
 
-#[tokio::main]
-#[allow(clippy::too_many_lines)]
-async fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+/// How often the docs root is checked for disappearing and coming back
+const ROOT_PROBE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Probes the docs root periodically. Once it is reachable again after an outage, re-scans so
+/// documents changed in the meantime are picked up.
+fn spawn_backing_store_probe(index: SharedIndex, file_reader: FileReader) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ROOT_PROBE_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let reader = file_reader.clone();
+            let recovered = tokio::task::spawn_blocking(move || {
+                reader.probe();
+                reader.take_recovery()
+            })
+            .await
+            .unwrap_or(false);
+            if !recovered {
+                continue;
+            }
+            let (index, reader) = (index.clone(), file_reader.clone());
+            match tokio::task::spawn_blocking(move || index::rescan(&index, &reader)).await {
+                Ok(documents) => info!("Docs root is back, {} documents indexed", documents),
+                Err(error) => warn!("Rescan after docs root recovery failed: {}", error),
+            }
+        }
+    });
+}
 
+/// Console output filtered by `env_filter`, plus the bridge to sessions that asked for log
+/// notifications. The filter only applies to the console: sessions pick their own level via
+/// logging/setLevel, down to debug for this crate's events.
+fn log_subscriber(
+    env_filter: tracing_subscriber::EnvFilter,
+    log_bridge: &LogBridge,
+) -> impl tracing::Subscriber + Send + Sync {
     tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(env_filter))
         .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| cli.rust_log().to_string().into()),
+            log_bridge
+                .layer()
+                .with_filter(Targets::new().with_target(env!("CARGO_CRATE_NAME"), Level::DEBUG)),
         )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-    let file_reader = FileReader::new(cli.docs_root().to_string_lossy().to_string())?;
-    let cfg = Config::load(cli.config().map(std::path::PathBuf::as_path))?;
-    let mut resources: BTreeMap<DocumentKey, ResourceInfo> = BTreeMap::new();
-
-    let scan_start = std::time::Instant::now();
-
-    // Scan agreements
-    let area_paths = cfg.agreements.clone();
-    DocumentScanner::scan_documents(
-        DocumentType::Agreements,
-        area_paths,
-        &file_reader,
-        &mut resources,
-    );
+}
 
-    for project in &cfg.projects {
-        let diagram_exts = cfg.diagram_extensions.clone();
-        let openapi_exts = cfg.openapi_extensions.clone();
-
-        let mut scan_type =
-            |document_type: DocumentType, targets: Vec<String>, exts: Vec<String>| {
-                DocumentScanner::scan_documents_with_extensions(
-                    document_type,
-                    targets,
-                    &exts,
-                    &file_reader,
-                    &mut resources,
-                );
-            };
-
-        scan_type(
-            DocumentType::C1Diagram(project.name.clone()),
-            project.c4.c1.clone(),
-            diagram_exts.clone(),
-        );
-        scan_type(
-            DocumentType::C2Diagram(project.name.clone()),
-            project.c4.c2.clone(),
-            diagram_exts.clone(),
-        );
-        scan_type(
-            DocumentType::C3Diagram(project.name.clone()),
-            project.c4.c3.clone(),
-            diagram_exts.clone(),
-        );
-        scan_type(
-            DocumentType::C4Diagram(project.name.clone()),
-            project.c4.services.clone(),
-            diagram_exts.clone(),
-        );
-        scan_type(
-            DocumentType::ErdDiagram(project.name.clone()),
-            project.erd.clone(),
-            diagram_exts.clone(),
-        );
-        scan_type(
-            DocumentType::AdrDocument(project.name.clone()),
-            project.adr.clone(),
-            diagram_exts.clone(),
-        );
-        scan_type(
-            DocumentType::OpenApiSpec(project.name.clone()),
-            project.openapi.clone(),
-            openapi_exts.clone(),
-        );
-    }
+#[tokio::main]
+#[allow(clippy::too_many_lines)]
+async fn main() -> anyhow::Result<()> {
+    server::mark_started();
+    let cli = Cli::parse();
 
-    let guide_exts = cfg.guide_extensions.clone();
-    for guide in &cfg.guides {
-        DocumentScanner::scan_documents_with_extensions(
-            DocumentType::GuideDoc(guide.name.clone()),
-            guide.paths.clone(),
-            &guide_exts,
-            &file_reader,
-            &mut resources,
-        );
+    let log_bridge = LogBridge::default();
+    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| cli.rust_log().to_string().into());
+    log_subscriber(env_filter, &log_bridge).init();
+    let mut cfg = Config::load(cli.config().map(PathBuf::as_path))?;
+    if let Err(error) = cli.scan_filter().apply(&mut cfg) {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::InvalidValue,
+                format!("--only-project: {}", error),
+            )
+            .exit();
     }
+    if cli.print_scan_plan() {
+        println!("{}", serde_json::to_string_pretty(&index::scan_plan(&cfg))?);
+        return Ok(());
+    }
+    let file_reader = FileReader::new(cli.docs_root().to_string_lossy().to_string())?
+        .with_encoding_detection(cfg.detect_encoding);
+    let config_path = Config::resolve_path(cli.config().map(PathBuf::as_path))?;
+    let config_text = std::fs::read_to_string(&config_path).unwrap_or_default();
+    if cli.check() {
+        let budget = cfg.staleness.budget;
+        let built = ServedIndex::build(cfg, config_text, &file_reader);
+        let report = built
+            .server
+            .stale_documents(&Default::default(), None, chrono::Utc::now());
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if report.total_stale > budget {
+            anyhow::bail!(
+                "{} documents are stale, more than the staleness budget of {}",
+                report.total_stale,
+                budget
+            );
+        }
+        return Ok(());
+    }
+    let browse = cfg.browse;
+    let usage_file = cfg.usage_stats_file.as_ref().map(PathBuf::from);
+    let (pending, tracker) = ServedIndex::pending(cfg.clone(), config_text.clone(), &file_reader);
+    let index: SharedIndex = Arc::new(RwLock::new(pending));
+    let scan_reader = file_reader.clone();
+    let empty_scan_retry = cli.empty_scan_retry();
+    tokio::spawn(index::finish_initial_scan(
+        index.clone(),
+        tracker,
+        move |tracker| {
+            index::build_retrying_empty(empty_scan_retry, || {
+                ServedIndex::build_tracked(cfg.clone(), config_text.clone(), &scan_reader, tracker)
+            })
+        },
+    ));
+    spawn_reload_on_sighup(index.clone(), config_path, file_reader.clone());
+    spawn_backing_store_probe(index.clone(), file_reader.clone());
 
-    let scan_duration = scan_start.elapsed();
-    info!(
-        "Scanned {} documents in {:?}",
-        resources.len(),
-        scan_duration
-    );
-
-    let server_file_reader = file_reader.clone();
+    let session_index = index.clone();
+    let session_manager = Arc::new(sessions::local_session_manager(cli.max_sessions()));
+    let sessions = session_manager.clone();
+    let subscriptions = subscriptions::SubscriptionRegistry::default();
+    let usage = match &usage_file {
+        Some(path) => usage::UsageCounters::load(path).unwrap_or_else(|error| {
+            warn!("Ignoring usage stats file {}: {}", path.display(), error);
+            usage::UsageCounters::default()
+        }),
+        None => usage::UsageCounters::default(),
+    };
+    let session_usage = usage.clone();
+    let min_client_schema = cli.min_client_schema();
     let service = StreamableHttpService::new(
         move || {
-            Ok(DocumentServer::new_with_resources(
-                server_file_reader.clone(),
-                resources.clone(),
-            ))
+            Ok(current_server(&session_index)
+                .with_log_bridge(log_bridge.clone())
+                .with_session_manager(sessions.clone())
+                .with_subscriptions(subscriptions.clone())
+                .with_usage(session_usage.clone())
+                .with_min_client_schema(min_client_schema)
+                .with_index(session_index.clone()))
         },
-        LocalSessionManager::default().into(),
+        Arc::new(SessionLimit::new(session_manager, cli.max_sessions())),
         rmcp::transport::streamable_http_server::StreamableHttpServerConfig::default(),
     );
 
-    let router = axum::Router::new().nest_service("/mcp", service);
+    let health_index = index.clone();
+    let manifest_index = index.clone();
+    let mut router = axum::Router::new()
+        .nest_service(manifest::MCP_ENDPOINT, service)
+        .merge(manifest::router(manifest_index))
+        .route(
+            "/healthz",
+            axum::routing::get(move || {
+                let status = current_server(&health_index).scan_status();
+                async move { axum::Json(status) }
+            }),
+        );
+    if browse {
+        router = router.merge(browse::router(index));
+        info!("HTML browsing enabled at /browse");
+    }
     let tcp_listener = tokio::net::TcpListener::bind(cli.bind_address()).await?;
     info!(
         "MCP server started on {}, docs_root: {}, RUST_LOG: {}",
@@ -210,8 +393,155 @@ async fn main() -> anyhow::Result<()> {
         file_reader.docs_root(),
         cli.rust_log()
     );
+    let usage_stats = usage_file.map(|path| (path, usage));
     let _ = axum::serve(tcp_listener, router)
-        .with_graceful_shutdown(setup_graceful_shutdown())
+        .with_graceful_shutdown(setup_graceful_shutdown(usage_stats.clone()))
         .await;
+    // Reads served while the last requests drained
+    save_usage_stats(usage_stats.as_ref());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::error::ErrorKind;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_saves_usage_stats_once_signalled() {
+        let temp_dir = tempfile::TempDir::new().expect("temp dir");
+        let path = temp_dir.path().join("usage.json");
+        let usage = usage::UsageCounters::default();
+        usage.record_read("docs://a.md");
+        let usage_stats = (path.clone(), usage.clone());
+        let (signal, signalled) = tokio::sync::oneshot::channel::<()>();
+        let shutdown = tokio::spawn(async move {
+            shutdown_on(
+                async {
+                    let _ = signalled.await;
+                },
+                Some(&usage_stats),
+            )
+            .await;
+        });
+
+        usage.record_read("docs://a.md");
+        tokio::task::yield_now().await;
+        assert!(!path.exists());
+        signal.send(()).expect("signal");
+        shutdown.await.expect("shutdown");
+
+        let saved = usage::UsageCounters::load(&path).expect("saved stats");
+        assert_eq!(saved.counts()["docs://a.md"], 2);
+    }
+
+    #[test]
+    fn test_console_level_does_not_filter_session_logs() {
+        let subscriber = log_subscriber("info".into(), &LogBridge::default());
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(tracing::enabled!(target: "arch_mcp_server::server", Level::DEBUG));
+            assert!(!tracing::enabled!(target: "hyper", Level::DEBUG));
+            assert!(tracing::enabled!(target: "hyper", Level::INFO));
+        });
+    }
+
+    #[test]
+    fn test_scan_filter_flags_are_repeatable_and_type_checked() {
+        let cli = Cli::try_parse_from([
+            "arch-mcp-server",
+            "--docs-root",
+            "/docs",
+            "--only-project",
+            "mpa",
+            "--only-project",
+            "crm",
+            "--skip-type",
+            "openapi",
+        ])
+        .expect("parsed");
+        assert_eq!(
+            cli.scan_filter(),
+            ScanFilter {
+                only_projects: vec!["mpa".to_string(), "crm".to_string()],
+                skip_types: vec!["openapi".to_string()],
+            }
+        );
+
+        let error = Cli::try_parse_from([
+            "arch-mcp-server",
+            "--docs-root",
+            "/docs",
+            "--skip-type",
+            "adrs",
+        ])
+        .expect_err("unknown type");
+        assert_eq!(error.kind(), ErrorKind::InvalidValue);
+        assert!(error.to_string().contains("agreements, c1, c2"));
+    }
+
+    #[test]
+    fn test_max_sessions_must_be_positive() {
+        let parse = |value: &str| {
+            Cli::try_parse_from([
+                "arch-mcp-server",
+                "--docs-root",
+                "/docs",
+                "--max-sessions",
+                value,
+            ])
+        };
+        assert_eq!(parse("8").expect("parsed").max_sessions(), Some(8));
+        assert!(parse("0").is_err());
+    }
+
+    #[test]
+    fn test_min_client_schema_is_bounded_by_the_server_schema() {
+        let parse = |value: &str| {
+            Cli::try_parse_from([
+                "arch-mcp-server",
+                "--docs-root",
+                "/docs",
+                "--min-client-schema",
+                value,
+            ])
+        };
+        let current = server::SCHEMA_VERSION.to_string();
+        assert_eq!(
+            parse(&current).expect("parsed").min_client_schema(),
+            Some(server::SCHEMA_VERSION)
+        );
+        assert!(parse("0").is_err());
+        assert!(parse(&(server::SCHEMA_VERSION + 1).to_string()).is_err());
+        let defaults =
+            Cli::try_parse_from(["arch-mcp-server", "--docs-root", "/docs"]).expect("parsed");
+        assert_eq!(defaults.min_client_schema(), None);
+    }
+
+    #[test]
+    fn test_retry_empty_scan_is_off_by_default() {
+        let parse = |args: &[&str]| {
+            Cli::try_parse_from(
+                ["arch-mcp-server", "--docs-root", "/docs"]
+                    .iter()
+                    .chain(args),
+            )
+            .expect("parsed")
+            .empty_scan_retry()
+        };
+        assert_eq!(
+            parse(&[]),
+            EmptyScanRetry {
+                retries: 0,
+                delay: Duration::from_secs(5),
+            }
+        );
+        assert_eq!(
+            parse(&["--retry-empty-scan", "3", "--retry-empty-scan-delay", "10"]),
+            EmptyScanRetry {
+                retries: 3,
+                delay: Duration::from_secs(10),
+            }
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use std::{collections::BTreeMap, sync::PoisonError};
+
+use axum::{Json, Router, extract::State, routing::get};
+
+use crate::{
+    index::{ScanPhase, ServedIndex, SharedIndex},
+    server::tool_names,
+};
+
+/// Path the MCP service is mounted at
+pub const MCP_ENDPOINT: &str = "/mcp";
+
+/// Deployment description for developer portals that register MCP servers.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct DeploymentManifest {
+    pub name: String,
+    pub version: String,
+    pub mcp_endpoint: String,
+    /// Configured project names
+    pub projects: Vec<String>,
+    pub total_documents: u32,
+    /// Document type (see get_effective_extensions) -> indexed documents
+    pub documents_by_type: BTreeMap<String, u32>,
+    pub scan_status: ScanPhase,
+    /// When the last completed scan finished (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_scan_at: Option<String>,
+    /// Names of the tools the server registers
+    pub tools: Vec<String>,
+}
+
+/// `/manifest.json`, built per request from the index in effect, so it follows rescans and
+/// config reloads.
+pub fn router(index: SharedIndex) -> Router {
+    Router::new()
+        .route("/manifest.json", get(manifest_json))
+        .with_state(index)
+}
+
+async fn manifest_json(State(index): State<SharedIndex>) -> Json<DeploymentManifest> {
+    let index = index.read().unwrap_or_else(PoisonError::into_inner);
+    Json(manifest(&index))
+}
+
+fn manifest(index: &ServedIndex) -> DeploymentManifest {
+    let mut documents_by_type: BTreeMap<String, u32> = BTreeMap::new();
+    for info in index.server.resource_infos() {
+        if let Some(type_name) = info.type_name() {
+            *documents_by_type.entry(type_name.to_string()).or_default() += 1;
+        }
+    }
+    let scan = index.server.scan_status();
+    DeploymentManifest {
+        name: env!("CARGO_PKG_NAME").to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        mcp_endpoint: MCP_ENDPOINT.to_string(),
+        projects: index
+            .config
+            .projects
+            .iter()
+            .map(|project| project.name.clone())
+            .collect(),
+        total_documents: scan.documents_served,
+        documents_by_type,
+        scan_status: scan.scan.status,
+        last_scan_at: scan.scan.last_scan_at,
+        tools: tool_names(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, RwLock};
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::{config::Config, utils::file_reader::FileReader};
+
+    #[tokio::test]
+    async fn test_manifest_route_describes_the_served_index() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        for adr in [
+            "architecture/mpa/adr/001-outbox.mdx",
+            "architecture/mpa/adr/002-queues.mdx",
+        ] {
+            let path = temp_dir.path().join(adr);
+            std::fs::create_dir_all(path.parent().expect("parent")).expect("dir");
+            std::fs::write(path, "# ADR\n").expect("write adr");
+        }
+        let config_path = temp_dir.path().join("arch-mcp.toml");
+        std::fs::write(
+            &config_path,
+            "agreements = []\n\n[[projects]]\nname = \"mpa\"\nadr = [\"architecture/mpa/adr\"]\n",
+        )
+        .expect("write config");
+        let file_reader =
+            FileReader::new(temp_dir.path().to_string_lossy().to_string()).expect("reader");
+        let config = Config::load(Some(&config_path)).expect("config");
+        let index: SharedIndex = Arc::new(RwLock::new(ServedIndex::build(
+            config,
+            String::new(),
+            &file_reader,
+        )));
+
+        let Json(served) = manifest_json(State(index)).await;
+        let body = serde_json::to_string(&served).expect("serialized");
+        let manifest: DeploymentManifest = serde_json::from_str(&body).expect("manifest");
+
+        assert_eq!(manifest.name, env!("CARGO_PKG_NAME"));
+        assert_eq!(manifest.mcp_endpoint, "/mcp");
+        assert_eq!(manifest.projects, vec!["mpa"]);
+        assert_eq!(manifest.total_documents, 2);
+        assert_eq!(
+            manifest.documents_by_type,
+            BTreeMap::from([("adr".to_string(), 2)])
+        );
+        assert_eq!(manifest.scan_status, ScanPhase::Ready);
+        assert!(manifest.last_scan_at.is_some());
+        assert!(manifest.tools.iter().any(|tool| tool == "get_docs_list"));
+    }
+}
@@ -1,6 +1,37 @@
-use std::{collections::BTreeMap, path::Path};
-
-use crate::utils::file_reader::FileReader;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use globset::GlobSet;
+
+use crate::{
+    config::UriTemplates,
+    utils::{
+        date,
+        file_reader::FileReader,
+        markdown,
+        openapi::{self, MultiDocumentMode},
+    },
+};
+
+/// Default extensions of agreement documents (`agreement_extensions` in the config).
+pub const AGREEMENT_EXTENSIONS: &[&str] = &["md", "mdx", "txt"];
+
+/// Every `DocumentType::type_name`, the default of `enabled_document_types`.
+pub const DOCUMENT_TYPE_NAMES: &[&str] = &[
+    "agreements",
+    "c1",
+    "c2",
+    "c3",
+    "c4",
+    "erd",
+    "adr",
+    "openapi",
+    "guide",
+    "style-guide",
+];
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct DocumentKey(String); // resource URI
@@ -11,18 +42,128 @@ impl DocumentKey {
     }
 }
 
+/// Structured form of the positional `category` entries, for document types whose path
+/// layout names them.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Classification {
+    Openapi {
+        service: String,
+        version: String,
+        access_level: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sub_category: Option<String>,
+    },
+    Adr {
+        /// Number from the filename prefix (`001-outbox.mdx` -> "001")
+        number: String,
+    },
+    C4 {
+        /// "c1", "c2", "c3" or "c4"
+        level: String,
+        /// Service name of a per-service C4 diagram
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        service: Option<String>,
+    },
+    Agreement {
+        area: String,
+        lang: String,
+        /// Directories between the language and the file
+        category_path: Vec<String>,
+    },
+}
+
 /// Document resource metadata
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct ResourceInfo {
     pub uri: String,
     pub file_path: String,
     pub area: String,
     pub lang: String,
+    /// Every programming language the document applies to, from frontmatter `langs:` or the
+    /// path; `lang` is the first entry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub langs: Vec<String>,
+    /// Projects an agreement is scoped to, from frontmatter `applies_to:`; empty for agreements
+    /// that apply to every project
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub applies_to: Vec<String>,
     pub category: Vec<String>,
     pub project: String,
     pub mime_type: String,
     pub size: u32,
     pub description: String,
+    /// Structural validation result for API specs; absent when not validated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid: Option<bool>,
+    /// Summary of why validation failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation_error: Option<String>,
+    /// URI of the primary document this is a language variant of (`api.ru.md` -> `api.md`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant_of: Option<String>,
+    /// Natural language of the content, set for documents that have language variants
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_lang: Option<String>,
+    /// Language -> URI of the variants folded into this entry by `collapse_variants`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub other_languages: BTreeMap<String, String>,
+    /// File modification time (RFC 3339, UTC); absent when the filesystem does not report one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    /// First paragraph of a markdown document, truncated for listings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Words in the content; absent for binary files and files over `max_file_size`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_count: Option<u32>,
+    /// Rough token count of the content (characters / `chars_per_token`, rounded up), for
+    /// budgeting context before reading; absent when `word_count` is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_estimate: Option<u32>,
+    /// ADR decision date (ISO `YYYY-MM-DD`) from frontmatter `date:` or a `Date:` line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adr_date: Option<String>,
+    /// "global" for ADRs from a configured shared location, "project" for other ADRs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adr_scope: Option<String>,
+    /// Why the format-specific parse (spec YAML/JSON, markdown frontmatter) failed at scan time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parse_error: Option<String>,
+    /// Named parts of the path (service/version/access level, ADR number, C4 level, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classification: Option<Classification>,
+}
+
+impl ResourceInfo {
+    /// Format checked by `DocumentScanner::detect_parse_failures`, if any.
+    pub fn parsed_format(&self) -> Option<&'static str> {
+        if self.category.first().is_some_and(|c| c == "openapi") {
+            Some("openapi")
+        } else if self.mime_type == "text/markdown" {
+            Some("frontmatter")
+        } else {
+            None
+        }
+    }
+
+    /// Config name of the document type the document was scanned as (see
+    /// `DocumentType::type_name`).
+    pub fn type_name(&self) -> Option<&str> {
+        match self.category.first()?.as_str() {
+            "guides" => Some("guide"),
+            category => Some(category),
+        }
+    }
+
+    /// Languages to match language filters against: `langs`, or `lang` when `langs` is unset.
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        let single = self.langs.is_empty() && !self.lang.is_empty();
+        self.langs
+            .iter()
+            .map(String::as_str)
+            .chain(single.then_some(self.lang.as_str()))
+    }
 }
 
 /// Document types with extensibility
@@ -38,6 +179,7 @@ pub enum DocumentType {
     AdrDocument(String),
     OpenApiSpec(String),
     GuideDoc(String),
+    ApiStyleGuide,
 }
 
 impl DocumentType {
@@ -53,6 +195,38 @@ impl DocumentType {
             DocumentType::AdrDocument(project) => format!("docs://architecture/{}/adr/", project),
             DocumentType::OpenApiSpec(project) => format!("docs://openapi/{}/", project),
             DocumentType::GuideDoc(product) => format!("docs://guides/{}/", product),
+            DocumentType::ApiStyleGuide => "docs://style-guides/".to_string(),
+        }
+    }
+
+    /// Project, or guide product, documents of this type are registered under
+    pub fn owner(&self) -> Option<&str> {
+        match self {
+            DocumentType::Agreements | DocumentType::ApiStyleGuide => None,
+            DocumentType::C1Diagram(owner)
+            | DocumentType::C2Diagram(owner)
+            | DocumentType::C3Diagram(owner)
+            | DocumentType::C4Diagram(owner)
+            | DocumentType::ErdDiagram(owner)
+            | DocumentType::AdrDocument(owner)
+            | DocumentType::OpenApiSpec(owner)
+            | DocumentType::GuideDoc(owner) => Some(owner),
+        }
+    }
+
+    /// Name used for this type in the config (`enabled_document_types`) and diagnostics.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            DocumentType::Agreements => "agreements",
+            DocumentType::C1Diagram(_) => "c1",
+            DocumentType::C2Diagram(_) => "c2",
+            DocumentType::C3Diagram(_) => "c3",
+            DocumentType::C4Diagram(_) => "c4",
+            DocumentType::ErdDiagram(_) => "erd",
+            DocumentType::AdrDocument(_) => "adr",
+            DocumentType::OpenApiSpec(_) => "openapi",
+            DocumentType::GuideDoc(_) => "guide",
+            DocumentType::ApiStyleGuide => "style-guide",
         }
     }
 
@@ -69,6 +243,10 @@ impl DocumentType {
                 let category_str = categories.join(", ");
                 format!("Agreement document: {} - {} ({})", category_str, area, lang)
             }
+            DocumentType::ApiStyleGuide => {
+                let category_str = categories.join(", ");
+                format!("API style guide: {} - {} ({})", category_str, area, lang)
+            }
             DocumentType::ErdDiagram(project) => {
                 // Use filename as the diagram name, remove .mdx extension
                 let diagram_name = _filename.trim_end_matches(".mdx");
@@ -119,93 +297,506 @@ impl DocumentType {
     }
 }
 
+/// (uri, area, lang, categories, project, classification) derived from a file path
+type PathEntry = (
+    String,
+    String,
+    String,
+    Vec<String>,
+    String,
+    Option<Classification>,
+);
+
+/// Limits of the word and token counts taken with the summaries.
+#[derive(Debug, Clone, Copy)]
+pub struct ContentCounting {
+    /// Larger files are not counted
+    pub max_file_size: u64,
+    pub chars_per_token: f64,
+}
+
+impl ContentCounting {
+    fn token_estimate(&self, content: &str) -> u32 {
+        // Float to int casts saturate, so huge counts end at u32::MAX
+        (content.chars().count() as f64 / self.chars_per_token).ceil() as u32
+    }
+}
+
+/// Files visited between progress log lines of a scan.
+pub const SCAN_PROGRESS_LOG_INTERVAL: usize = 5_000;
+
+/// Counts the files a scan visits and logs the running total every `log_every` files, so a
+/// scan of a huge tree shows progress instead of looking hung.
+#[derive(Debug)]
+pub struct ScanProgress {
+    files: Cell<usize>,
+    log_every: usize,
+    /// Path pattern arm -> files it classified, once per document type accepting the file
+    patterns: RefCell<BTreeMap<&'static str, usize>>,
+}
+
+impl Default for ScanProgress {
+    fn default() -> Self {
+        Self::new(SCAN_PROGRESS_LOG_INTERVAL)
+    }
+}
+
+impl ScanProgress {
+    pub fn new(log_every: usize) -> Self {
+        Self {
+            files: Cell::new(0),
+            log_every: log_every.max(1),
+            patterns: RefCell::default(),
+        }
+    }
+
+    /// Files visited so far, indexed or not
+    pub fn files(&self) -> usize {
+        self.files.get()
+    }
+
+    /// Files each path pattern arm classified, e.g. `c4_system`, `openapi_endpoints` or
+    /// `scan_target` for files outside the known layouts
+    pub fn pattern_counts(&self) -> BTreeMap<&'static str, usize> {
+        self.patterns.borrow().clone()
+    }
+
+    fn pattern_matched(&self, pattern: &'static str) {
+        *self.patterns.borrow_mut().entry(pattern).or_default() += 1;
+    }
+
+    fn file_visited(&self, target: &str) {
+        let files = self.files.get() + 1;
+        self.files.set(files);
+        if files % self.log_every == 0 {
+            tracing::info!(
+                "Scanning: {} files visited, current target '{}'",
+                files,
+                target
+            );
+        }
+    }
+}
+
+/// One document type's share of a target walk: files passing its filter land in `resources`.
+pub struct TargetScan<'a> {
+    pub document_type: &'a DocumentType,
+    pub allowed_extensions: &'a [String],
+    pub resources: &'a mut BTreeMap<DocumentKey, ResourceInfo>,
+}
+
 /// Document scanner for populating BTreeMap
 pub struct DocumentScanner;
 
 impl DocumentScanner {
-    /// Scans documents and populates BTreeMap
-    pub fn scan_documents(
-        document_type: DocumentType,
-        area_paths: Vec<String>,
-        file_reader: &FileReader,
+    /// Keeps a single file per logical document (URI without extension) when several
+    /// extensions compete, picking the one listed first in `preferred`. Extensions missing
+    /// from `preferred` neither shadow nor get shadowed.
+    pub fn apply_preferred_extensions(
         resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+        preferred: &[String],
     ) {
-        if matches!(document_type, DocumentType::Agreements) {
-            for target in area_paths {
-                if let Err(e) = Self::scan_target_with_extensions(
-                    &document_type,
-                    &target,
-                    &[],
-                    file_reader,
-                    resources,
-                ) {
-                    tracing::warn!("Failed to scan target '{}': {}", target, e);
+        if preferred.is_empty() {
+            return;
+        }
+
+        let rank = |uri: &str| {
+            let (stem, extension) = uri.rsplit_once('.')?;
+            let extension = extension.to_ascii_lowercase();
+            let position = preferred.iter().position(|e| *e == extension)?;
+            Some((stem.to_string(), position))
+        };
+
+        let mut winners: BTreeMap<String, (usize, String)> = BTreeMap::new();
+        for info in resources.values() {
+            if let Some((stem, position)) = rank(&info.uri) {
+                let winner = winners
+                    .entry(stem)
+                    .or_insert_with(|| (position, info.uri.clone()));
+                if position < winner.0 {
+                    *winner = (position, info.uri.clone());
                 }
             }
+        }
+
+        resources.retain(|_, info| {
+            let Some((stem, _)) = rank(&info.uri) else {
+                return true;
+            };
+            let preferred_uri = &winners[&stem].1;
+            if *preferred_uri == info.uri {
+                return true;
+            }
+            tracing::info!(
+                shadowed = %info.uri,
+                preferred = %preferred_uri,
+                "Skipping document shadowed by a preferred extension"
+            );
+            false
+        });
+    }
+
+    /// Drops agreements lying under a style guide path, so a directory claimed by
+    /// `style_guides` is indexed once, as style guides. Like shadowed extensions, each dropped
+    /// document is logged. Returns how many were dropped.
+    pub fn exclude_claimed_agreements(
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+        style_guide_paths: &[String],
+    ) -> usize {
+        let claimed_by = |file_path: &str| {
+            style_guide_paths.iter().find(|path| {
+                let path = path.trim_end_matches('/');
+                file_path
+                    .strip_prefix(path)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+        };
+        let before = resources.len();
+        resources.retain(|_, info| {
+            if info.category.first().is_none_or(|c| c != "agreements") {
+                return true;
+            }
+            let Some(claimed) = claimed_by(&info.file_path) else {
+                return true;
+            };
+            tracing::info!(
+                shadowed = %info.uri,
+                style_guides = %claimed,
+                "Skipping agreement claimed by a style guide path"
+            );
+            false
+        });
+        before - resources.len()
+    }
+
+    /// Links language variants (`name.<lang>.ext`, with `lang` from `languages`) to their
+    /// primary document `name.ext`. Variants get `content_lang` and `variant_of`; a primary
+    /// with variants gets `default_language`.
+    pub fn detect_language_variants(
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+        languages: &[String],
+        default_language: &str,
+    ) {
+        if languages.is_empty() {
             return;
         }
 
-        for area_path in area_paths {
-            if let Err(e) = Self::scan_area(&document_type, &area_path, file_reader, resources) {
-                tracing::warn!("Failed to scan area '{}': {}", area_path, e);
+        let mut primaries = Vec::new();
+        for info in resources.values_mut() {
+            let Some((primary_uri, lang)) = split_language_variant(&info.uri, languages) else {
+                continue;
+            };
+            info.content_lang = Some(lang);
+            info.variant_of = Some(primary_uri.clone());
+            primaries.push(primary_uri);
+        }
+
+        for primary_uri in primaries {
+            if let Some(primary) = resources.get_mut(&DocumentKey::new(primary_uri))
+                && primary.content_lang.is_none()
+            {
+                primary.content_lang = Some(default_language.to_string());
             }
         }
     }
 
-    pub fn scan_documents_with_extensions(
-        document_type: DocumentType,
-        scan_targets: Vec<String>,
-        allowed_extensions: &[String],
+    /// Sets `summary` on markdown documents from their first paragraph after frontmatter, and
+    /// `word_count` and `token_estimate` on text documents within `counting.max_file_size`,
+    /// from the same read.
+    pub fn extract_summaries(
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
         file_reader: &FileReader,
+        counting: ContentCounting,
+    ) {
+        for info in resources.values_mut() {
+            let is_markdown = info.mime_type == "text/markdown";
+            let countable = u64::from(info.size) <= counting.max_file_size;
+            if !is_markdown && !countable {
+                continue;
+            }
+            // Binary content fails to decode and gets no counts
+            let Ok(content) = file_reader.read_file_content(&info.file_path) else {
+                continue;
+            };
+            if is_markdown {
+                let body = markdown::split_frontmatter(&content)
+                    .map_or(content.as_str(), |(_, body)| body);
+                info.summary = markdown::summary(body);
+            }
+            if countable && !content.contains('\0') {
+                info.word_count = Some(
+                    markdown::word_count(&content)
+                        .try_into()
+                        .unwrap_or(u32::MAX),
+                );
+                info.token_estimate = Some(counting.token_estimate(&content));
+            }
+        }
+    }
+
+    /// Sets `langs` from a markdown frontmatter `langs: [go, php]` list, or to the single
+    /// path-derived `lang`, and makes `lang` the first entry.
+    pub fn extract_langs(
         resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+        file_reader: &FileReader,
     ) {
-        for target in scan_targets {
-            if let Err(e) = Self::scan_target_with_extensions(
-                &document_type,
-                &target,
-                allowed_extensions,
-                file_reader,
-                resources,
-            ) {
-                tracing::warn!("Failed to scan target '{}': {}", target, e);
+        for info in resources.values_mut() {
+            let declared = if info.mime_type == "text/markdown" {
+                file_reader
+                    .read_file_content(&info.file_path)
+                    .ok()
+                    .map(|content| frontmatter_langs(&content))
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+            if let Some(first) = declared.first() {
+                info.lang = first.clone();
+                info.langs = declared;
+            } else if !info.lang.is_empty() {
+                info.langs = vec![info.lang.clone()];
             }
         }
     }
 
-    /// Scans one area folder recursively
-    fn scan_area(
-        document_type: &DocumentType,
-        area_path: &str,
+    /// Sets `applies_to` on markdown agreements from a frontmatter `applies_to: [crm, mpa]`
+    /// list (or one project name).
+    pub fn extract_applies_to(
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
         file_reader: &FileReader,
+    ) {
+        for info in resources.values_mut().filter(|info| {
+            info.mime_type == "text/markdown" && info.category.iter().any(|c| c == "agreements")
+        }) {
+            if let Ok(content) = file_reader.read_file_content(&info.file_path) {
+                info.applies_to = frontmatter_applies_to(&content);
+            }
+        }
+    }
+
+    /// Sets `adr_date` on ADRs from frontmatter `date:`, falling back to a `Date:` line in
+    /// the body (`Date: 2024-01-15`, `**Date:** 15.01.2024`).
+    pub fn extract_adr_dates(
         resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let full_path = Path::new(file_reader.docs_root()).join(area_path);
+        file_reader: &FileReader,
+    ) {
+        for info in resources.values_mut() {
+            if !info.category.iter().any(|cat| cat == "adr") {
+                continue;
+            }
+            if let Ok(content) = file_reader.read_file_content(&info.file_path) {
+                info.adr_date = adr_date(&content);
+            }
+        }
+    }
 
-        if !full_path.exists() {
-            return Err(format!("Area path does not exist: {}", area_path).into());
+    /// Sets `adr_scope` on ADRs: "global" when the file lies under one of `global_paths`,
+    /// "project" otherwise.
+    pub fn assign_adr_scope(
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+        global_paths: &[String],
+    ) {
+        for info in resources.values_mut() {
+            if !info.category.iter().any(|cat| cat == "adr") {
+                continue;
+            }
+            let is_global = global_paths.iter().any(|path| {
+                let path = path.trim_end_matches('/');
+                info.file_path
+                    .strip_prefix(path)
+                    .is_some_and(|rest| rest.starts_with('/'))
+            });
+            info.adr_scope = Some(if is_global { "global" } else { "project" }.to_string());
         }
+    }
 
-        if !full_path.is_dir() {
-            return Err(format!("Area path is not a directory: {}", area_path).into());
+    /// Sets `lang` on agreements that have none from the default of their area
+    /// (`agreements_defaults`), which may be `all`.
+    pub fn apply_agreement_defaults(
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+        defaults: &BTreeMap<String, String>,
+    ) {
+        if defaults.is_empty() {
+            return;
         }
+        for info in resources.values_mut().filter(|info| {
+            info.lang.is_empty() && info.category.first().is_some_and(|c| c == "agreements")
+        }) {
+            if let Some(lang) = defaults.get(&info.area) {
+                info.lang = lang.clone();
+            }
+        }
+    }
 
-        Self::scan_directory_recursive(
-            document_type,
-            &full_path,
-            area_path,
-            file_reader,
-            resources,
-        )?;
+    /// Assigns `default_project` to documents that no configured project claimed (agreements,
+    /// standard-structure docs) so project tools can find them.
+    pub fn assign_default_project(
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+        default_project: &str,
+    ) {
+        for info in resources.values_mut() {
+            if info.project.is_empty() {
+                info.project = default_project.to_string();
+            }
+        }
+    }
 
-        Ok(())
+    /// Removes documents whose URI matches `hidden_uris`. Returns the file paths of the removed
+    /// documents, which content reads must not reach through includes either.
+    pub fn exclude_hidden(
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+        hidden_uris: &GlobSet,
+    ) -> BTreeSet<String> {
+        let mut hidden = BTreeSet::new();
+        if hidden_uris.is_empty() {
+            return hidden;
+        }
+        resources.retain(|_, info| {
+            let is_hidden = hidden_uris.is_match(&info.uri);
+            if is_hidden {
+                tracing::debug!(uri = %info.uri, "Hiding document matched by hidden_uris");
+                hidden.insert(info.file_path.clone());
+            }
+            !is_hidden
+        });
+        hidden
     }
 
-    fn scan_target_with_extensions(
-        document_type: &DocumentType,
-        target: &str,
-        allowed_extensions: &[String],
+    /// Removes OpenAPI specs whose access level (the fourth category of specs in the
+    /// `openapi-spec/{project}/{service}/{version}/{access_level}/...` layout) is excluded.
+    /// Returns the number of removed specs.
+    pub fn exclude_access_levels(
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+        excluded: &[String],
+    ) -> usize {
+        if excluded.is_empty() {
+            return 0;
+        }
+
+        let before = resources.len();
+        resources.retain(|_, info| {
+            let is_excluded = match info.category.as_slice() {
+                [kind, _service, _version, access_level, ..] if kind == "openapi" => excluded
+                    .iter()
+                    .any(|level| level.eq_ignore_ascii_case(access_level)),
+                _ => false,
+            };
+            if is_excluded {
+                tracing::info!(uri = %info.uri, "Skipping OpenAPI spec with excluded access level");
+            }
+            !is_excluded
+        });
+        before - resources.len()
+    }
+
+    /// Removes resources whose URI is longer than `max_length` characters, which some clients
+    /// reject. Returns the number of removed resources.
+    pub fn skip_long_uris(
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+        max_length: usize,
+    ) -> usize {
+        let before = resources.len();
+        resources.retain(|_, info| {
+            let too_long = info.uri.chars().count() > max_length;
+            if too_long {
+                tracing::warn!(
+                    file = %info.file_path,
+                    length = info.uri.chars().count(),
+                    max_length,
+                    "Skipping document whose URI exceeds max_uri_length"
+                );
+            }
+            !too_long
+        });
+        before - resources.len()
+    }
+
+    /// Cuts descriptions longer than `max_length` characters so that, ellipsis included, they
+    /// are exactly `max_length` long.
+    pub fn truncate_descriptions(
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+        max_length: usize,
+    ) {
+        for info in resources.values_mut() {
+            if info.description.chars().count() > max_length {
+                let mut cut: String = info
+                    .description
+                    .chars()
+                    .take(max_length.saturating_sub(1))
+                    .collect();
+                cut.push('…');
+                info.description = cut;
+            }
+        }
+    }
+
+    /// Marks each spec as valid or invalid with a cheap structural check (parses as YAML/JSON,
+    /// declares `openapi`/`swagger`/`asyncapi` and `info`). Invalid specs stay indexed.
+    /// Multi-document specs are checked on the document `multi_document` selects. Returns the
+    /// number of invalid specs.
+    pub fn validate_specs(
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
         file_reader: &FileReader,
+        multi_document: MultiDocumentMode,
+    ) -> usize {
+        let mut invalid = 0;
+        for info in resources.values_mut() {
+            let result = file_reader
+                .read_file_content(&info.file_path)
+                .map_err(|e| format!("Failed to read file: {}", e))
+                .and_then(|content| validate_spec_structure(&content, multi_document));
+
+            info.valid = Some(result.is_ok());
+            if let Err(error) = result {
+                tracing::warn!(uri = %info.uri, %error, "Invalid API specification");
+                info.validation_error = Some(error);
+                invalid += 1;
+            }
+        }
+        invalid
+    }
+
+    /// Parses API specs as YAML/JSON and markdown frontmatter, recording failures in
+    /// `parse_error`; such documents are still served as raw text. Unreadable files are
+    /// skipped. Multi-document specs are parsed as `multi_document` directs. Returns the
+    /// number of failures.
+    pub fn detect_parse_failures(
         resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+        file_reader: &FileReader,
+        multi_document: MultiDocumentMode,
+    ) -> usize {
+        let mut failures = 0;
+        for info in resources.values_mut() {
+            let Some(format) = info.parsed_format() else {
+                continue;
+            };
+            let Ok(content) = file_reader.read_file_content(&info.file_path) else {
+                continue;
+            };
+
+            let result = if format == "openapi" {
+                openapi::parse_spec_documents(&content, multi_document).map(|_| ())
+            } else {
+                markdown::parse_frontmatter(&content).map(|_| ())
+            };
+            info.parse_error = result.err();
+            if let Some(error) = &info.parse_error {
+                tracing::warn!(uri = %info.uri, %error, "Failed to parse {}", format);
+                failures += 1;
+            }
+        }
+        failures
+    }
+
+    /// Walks `target` once and offers every file to each of `scans`, which applies its own
+    /// document type and extension filter. Several types listing one directory (c1, c2 and
+    /// c3 in a shared `c4/`) thereby share a single walk.
+    pub fn scan_target(
+        uri_templates: &UriTemplates,
+        target: &str,
+        scans: &mut [TargetScan<'_>],
+        file_reader: &FileReader,
+        progress: &ScanProgress,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let full_path = Path::new(file_reader.docs_root()).join(target);
 
@@ -215,14 +806,19 @@ impl DocumentScanner {
         }
 
         if full_path.is_file() {
-            Self::process_file_universal(
-                document_type,
-                &full_path,
-                target,
-                allowed_extensions,
-                file_reader,
-                resources,
-            )?;
+            progress.file_visited(target);
+            for scan in scans.iter_mut() {
+                Self::process_file_universal(
+                    scan.document_type,
+                    uri_templates,
+                    &full_path,
+                    target,
+                    scan.allowed_extensions,
+                    file_reader,
+                    progress,
+                    scan.resources,
+                )?;
+            }
             return Ok(());
         }
 
@@ -232,99 +828,24 @@ impl DocumentScanner {
         }
 
         Self::scan_directory_recursive_universal(
-            document_type,
+            uri_templates,
             &full_path,
             target,
-            allowed_extensions,
+            scans,
             file_reader,
-            resources,
+            progress,
         )?;
 
         Ok(())
     }
 
-    /// Recursive directory scanning
-    fn scan_directory_recursive(
-        document_type: &DocumentType,
-        dir_path: &Path,
-        area_path: &str,
-        file_reader: &FileReader,
-        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let entries = std::fs::read_dir(dir_path)?;
-
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
-
-            if path.is_dir() {
-                // For C4 diagrams, don't scan subdirectories - only scan the top-level c4/ directory
-                // For C4 service diagrams, scan the services/ subdirectory
-                let is_c4_diagram = matches!(
-                    document_type,
-                    DocumentType::C1Diagram(_)
-                        | DocumentType::C2Diagram(_)
-                        | DocumentType::C3Diagram(_)
-                );
-                let is_c4_service_diagram = matches!(document_type, DocumentType::C4Diagram(_));
-                let is_erd_diagram = matches!(document_type, DocumentType::ErdDiagram(_));
-                let is_adr_document = matches!(document_type, DocumentType::AdrDocument(_));
-
-                if !is_c4_diagram {
-                    // For C4 service diagrams, only scan services/ subdirectory
-                    if is_c4_service_diagram {
-                        if path.file_name().and_then(|n| n.to_str()) == Some("services") {
-                            Self::scan_directory_recursive(
-                                document_type,
-                                &path,
-                                area_path,
-                                file_reader,
-                                resources,
-                            )?;
-                        }
-                    } else if is_erd_diagram {
-                        // For ERD diagrams, scan recursively
-                        Self::scan_directory_recursive(
-                            document_type,
-                            &path,
-                            area_path,
-                            file_reader,
-                            resources,
-                        )?;
-                    } else if is_adr_document {
-                        // For ADR documents, scan recursively
-                        Self::scan_directory_recursive(
-                            document_type,
-                            &path,
-                            area_path,
-                            file_reader,
-                            resources,
-                        )?;
-                    } else {
-                        Self::scan_directory_recursive(
-                            document_type,
-                            &path,
-                            area_path,
-                            file_reader,
-                            resources,
-                        )?;
-                    }
-                }
-            } else if path.is_file() {
-                Self::process_file(document_type, &path, area_path, file_reader, resources)?;
-            }
-        }
-
-        Ok(())
-    }
-
     fn scan_directory_recursive_universal(
-        document_type: &DocumentType,
+        uri_templates: &UriTemplates,
         dir_path: &Path,
         scan_root: &str,
-        allowed_extensions: &[String],
+        scans: &mut [TargetScan<'_>],
         file_reader: &FileReader,
-        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+        progress: &ScanProgress,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let entries = std::fs::read_dir(dir_path)?;
 
@@ -334,434 +855,184 @@ impl DocumentScanner {
 
             if path.is_dir() {
                 Self::scan_directory_recursive_universal(
-                    document_type,
+                    uri_templates,
                     &path,
                     scan_root,
-                    allowed_extensions,
+                    scans,
                     file_reader,
-                    resources,
+                    progress,
                 )?;
             } else if path.is_file() {
-                Self::process_file_universal(
-                    document_type,
-                    &path,
-                    scan_root,
-                    allowed_extensions,
-                    file_reader,
-                    resources,
-                )?;
+                progress.file_visited(scan_root);
+                for scan in scans.iter_mut() {
+                    Self::process_file_universal(
+                        scan.document_type,
+                        uri_templates,
+                        &path,
+                        scan_root,
+                        scan.allowed_extensions,
+                        file_reader,
+                        progress,
+                        scan.resources,
+                    )?;
+                }
             }
         }
 
         Ok(())
     }
 
-    /// Processes a single file and adds to resources
-    #[allow(clippy::too_many_lines)]
-    fn process_file(
+    /// Derives URI and metadata from the well-known architecture and OpenAPI layouts.
+    ///
+    /// Consulted before the target-relative layout so a file in a known layout gets the same
+    /// URI whichever target registered it. The leading `content/docs` segment is optional.
+    /// Also returns the name of the matching pattern arm, counted in `ScanProgress`.
+    fn match_known_layout(
         document_type: &DocumentType,
-        file_path: &Path,
-        _area_path: &str,
-        file_reader: &FileReader,
-        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let filename = file_path
-            .file_name()
-            .ok_or("Invalid file name")?
-            .to_string_lossy()
-            .to_string();
-
-        // Check if file should be processed based on document type
-        if !Self::should_process_file(document_type, &filename) {
-            return Ok(());
-        }
-
-        // Get relative path from docs_root to understand the structure
-        let relative_path = file_path
-            .strip_prefix(file_reader.docs_root())
-            .map_err(|e| format!("Failed to get relative path: {}", e))?
-            .to_string_lossy()
-            .to_string();
-
-        // Parse the relative path to extract area, lang, category, and project
-        // Expected structure: content/docs/architecture/project/c4/filename.mdx
-        // or: content/docs/architecture/project/c4/services/service.mdx
-        // or: content/docs/backend/lang/category/filename.mdx
-        let path_parts: Vec<&str> = relative_path.split('/').collect();
-        let (uri, area, lang, categories, project) = match path_parts.as_slice() {
-            ["content", "docs", "architecture", project, "c4", filename] => {
-                // C4 diagram structure: content/docs/architecture/project/c4/filename.mdx
-                let uri = format!("docs://architecture/{}/{}", project, filename);
+        uri_templates: &UriTemplates,
+        relative_path: &str,
+    ) -> Option<(&'static str, PathEntry)> {
+        let layout_path = relative_path
+            .strip_prefix("content/docs/")
+            .unwrap_or(relative_path);
+        let path_parts: Vec<&str> = layout_path.split('/').collect();
+
+        let (pattern, template, segments, area, categories, classification) = match path_parts
+            .as_slice()
+        {
+            ["architecture", project, "c4", filename] => {
+                // C4 diagram structure: architecture/project/c4/filename.mdx
                 let category = match document_type {
-                    DocumentType::C1Diagram(_) => "c1".to_string(),
-                    DocumentType::C2Diagram(_) => "c2".to_string(),
-                    DocumentType::C3Diagram(_) => "c3".to_string(),
-                    _ => "c4".to_string(),
+                    DocumentType::C1Diagram(_) => "c1",
+                    DocumentType::C2Diagram(_) => "c2",
+                    DocumentType::C3Diagram(_) => "c3",
+                    _ => "c4",
                 };
                 (
-                    uri,
-                    "architecture".to_string(),
-                    String::new(),
-                    vec![category],
-                    (*project).to_string(),
+                    "c4_system",
+                    &uri_templates.c4_system,
+                    vec![("project", *project), ("filename", *filename)],
+                    "architecture",
+                    vec![category.to_string()],
+                    Some(Classification::C4 {
+                        level: category.to_string(),
+                        service: None,
+                    }),
                 )
             }
-            [
-                "content",
-                "docs",
+            ["architecture", project, "c4", "services", filename] => (
+                // C4 service diagram structure: architecture/project/c4/services/service.mdx
+                "c4_service",
+                &uri_templates.c4_service,
+                vec![("project", *project), ("filename", *filename)],
                 "architecture",
-                project,
-                "c4",
-                "services",
-                filename,
-            ] => {
-                // C4 service diagram structure: content/docs/architecture/project/c4/services/service.mdx
-                let uri = format!("docs://architecture/{}/c4/{}", project, filename);
+                vec!["c4".to_string()],
+                Some(Classification::C4 {
+                    level: "c4".to_string(),
+                    service: Some(file_stem(filename).to_string()),
+                }),
+            ),
+            ["architecture", project, "erd", "services", filename]
+            | ["architecture", project, "erd", filename] => (
+                // ERD diagram structure: architecture/project/erd/[services/]filename.mdx
+                "erd",
+                &uri_templates.erd,
+                vec![("project", *project), ("filename", *filename)],
+                "architecture",
+                vec!["erd".to_string()],
+                None,
+            ),
+            ["architecture", project, "adr", filename] => {
+                // ADR document structure: architecture/project/adr/filename.mdx
+                let adr_number = filename
+                    .split('-')
+                    .next()
+                    .unwrap_or("unknown")
+                    .trim_end_matches(".mdx");
                 (
-                    uri,
-                    "architecture".to_string(),
-                    String::new(),
-                    vec!["c4".to_string()],
-                    (*project).to_string(),
+                    "adr",
+                    &uri_templates.adr,
+                    vec![("project", *project), ("filename", *filename)],
+                    "architecture",
+                    vec!["adr".to_string(), format!("ADR-{}", adr_number)],
+                    Some(Classification::Adr {
+                        number: adr_number.to_string(),
+                    }),
                 )
             }
             [
-                "content",
-                "docs",
-                "architecture",
-                project,
-                "erd",
-                "services",
-                filename,
-            ] => {
-                // ERD diagram structure: content/docs/architecture/project/erd/services/filename.mdx
-                let uri = format!("docs://architecture/erd/{}/{}", project, filename);
-                (
-                    uri,
-                    "architecture".to_string(),
-                    String::new(),
-                    vec!["erd".to_string()],
-                    (*project).to_string(),
-                )
-            }
-            ["content", "docs", "architecture", project, "erd", filename] => {
-                // ERD diagram structure: content/docs/architecture/project/erd/filename.mdx
-                let uri = format!("docs://architecture/erd/{}/{}", project, filename);
-                (
-                    uri,
-                    "architecture".to_string(),
-                    String::new(),
-                    vec!["erd".to_string()],
-                    (*project).to_string(),
-                )
-            }
-            ["content", "docs", "architecture", project, "adr", filename] => {
-                // ADR document structure: content/docs/architecture/project/adr/filename.mdx
-                let uri = format!("docs://architecture/{}/adr/{}", project, filename);
-                // Extract ADR number from filename for category
-                let adr_number = filename
-                    .split('-')
-                    .next()
-                    .unwrap_or("unknown")
-                    .trim_end_matches(".mdx");
-                let category = format!("ADR-{}", adr_number);
-                (
-                    uri,
-                    "architecture".to_string(),
-                    String::new(),
-                    vec!["adr".to_string(), category],
-                    (*project).to_string(),
-                )
-            }
-            [
-                "content",
-                "docs",
-                "openapi-spec",
-                project,
-                service,
-                version,
-                access_level,
-                "endpoints",
-                filename,
-            ] => {
-                // OpenAPI spec structure: content/docs/openapi-spec/project/service/version/access_level/endpoints/filename.yaml
-                let uri = format!(
-                    "docs://openapi/{}/{}/{}/{}/{}",
-                    project, service, version, access_level, filename
-                );
-                (
-                    uri,
-                    "openapi".to_string(),
-                    String::new(),
-                    vec![
-                        "openapi".to_string(),
-                        (*service).to_string(),
-                        (*version).to_string(),
-                        (*access_level).to_string(),
-                    ],
-                    (*project).to_string(),
-                )
-            }
-            [
-                "content",
-                "docs",
-                "openapi-spec",
-                project,
-                service,
-                version,
-                access_level,
-                filename,
-            ] => {
-                // OpenAPI spec structure without endpoints subfolder: content/docs/openapi-spec/project/service/version/access_level/filename.yaml
-                let uri = format!(
-                    "docs://openapi/{}/{}/{}/{}/{}",
-                    project, service, version, access_level, filename
-                );
-                (
-                    uri,
-                    "openapi".to_string(),
-                    String::new(),
-                    vec![
-                        "openapi".to_string(),
-                        (*service).to_string(),
-                        (*version).to_string(),
-                        (*access_level).to_string(),
-                    ],
-                    (*project).to_string(),
-                )
-            }
-            [
-                "content",
-                "docs",
-                "openapi-spec",
-                project,
-                service,
-                version,
-                access_level,
-                sub_category,
-                "endpoints",
-                filename,
-            ] => {
-                // OpenAPI spec structure with subcategory: content/docs/openapi-spec/project/service/version/access_level/sub_category/endpoints/filename.yaml
-                let uri = format!(
-                    "docs://openapi/{}/{}/{}/{}/{}/{}",
-                    project, service, version, access_level, sub_category, filename
-                );
-                (
-                    uri,
-                    "openapi".to_string(),
-                    String::new(),
-                    vec![
-                        "openapi".to_string(),
-                        (*service).to_string(),
-                        (*version).to_string(),
-                        (*access_level).to_string(),
-                        (*sub_category).to_string(),
-                    ],
-                    (*project).to_string(),
-                )
-            }
-            [
-                "content",
-                "docs",
-                "openapi-spec",
-                project,
-                service,
-                version,
-                access_level,
-                sub_category,
-                filename,
-            ] => {
-                // OpenAPI spec structure with subcategory without endpoints: content/docs/openapi-spec/project/service/version/access_level/sub_category/filename.yaml
-                let uri = format!(
-                    "docs://openapi/{}/{}/{}/{}/{}/{}",
-                    project, service, version, access_level, sub_category, filename
-                );
-                (
-                    uri,
-                    "openapi".to_string(),
-                    String::new(),
-                    vec![
-                        "openapi".to_string(),
-                        (*service).to_string(),
-                        (*version).to_string(),
-                        (*access_level).to_string(),
-                        (*sub_category).to_string(),
-                    ],
-                    (*project).to_string(),
-                )
-            }
-            [
-                "openapi-spec",
-                project,
-                service,
-                version,
-                access_level,
-                "endpoints",
-                filename,
-            ] => {
-                // OpenAPI spec structure: openapi-spec/project/service/version/access_level/endpoints/filename.yaml
-                let uri = format!(
-                    "docs://openapi/{}/{}/{}/{}/{}",
-                    project, service, version, access_level, filename
-                );
-                (
-                    uri,
-                    "openapi".to_string(),
-                    String::new(),
-                    vec![
-                        "openapi".to_string(),
-                        (*service).to_string(),
-                        (*version).to_string(),
-                        (*access_level).to_string(),
-                    ],
-                    (*project).to_string(),
-                )
-            }
-            [
-                "openapi-spec",
-                project,
-                service,
-                version,
-                access_level,
-                filename,
-            ] => {
-                // OpenAPI spec structure without endpoints subfolder: openapi-spec/project/service/version/access_level/filename.yaml
-                let uri = format!(
-                    "docs://openapi/{}/{}/{}/{}/{}",
-                    project, service, version, access_level, filename
-                );
-                (
-                    uri,
-                    "openapi".to_string(),
-                    String::new(),
-                    vec![
-                        "openapi".to_string(),
-                        (*service).to_string(),
-                        (*version).to_string(),
-                        (*access_level).to_string(),
-                    ],
-                    (*project).to_string(),
-                )
-            }
-            [
-                "openapi-spec",
+                "openapi-spec",
                 project,
                 service,
                 version,
                 access_level,
-                sub_category,
-                "endpoints",
-                filename,
+                rest @ ..,
             ] => {
-                // OpenAPI spec structure with subcategory: openapi-spec/project/service/version/access_level/sub_category/endpoints/filename.yaml
-                let uri = format!(
-                    "docs://openapi/{}/{}/{}/{}/{}/{}",
-                    project, service, version, access_level, sub_category, filename
-                );
-                (
-                    uri,
+                // OpenAPI spec structure: openapi-spec/project/service/version/access_level/[sub_category/][endpoints/]filename.yaml
+                let (pattern, sub_category, filename) = match rest {
+                    [filename] => ("openapi", "", *filename),
+                    ["endpoints", filename] => ("openapi_endpoints", "", *filename),
+                    [sub_category, filename] => ("openapi_sub_category", *sub_category, *filename),
+                    [sub_category, "endpoints", filename] => {
+                        ("openapi_sub_category_endpoints", *sub_category, *filename)
+                    }
+                    _ => return None,
+                };
+                let mut categories = vec![
                     "openapi".to_string(),
-                    String::new(),
-                    vec![
-                        "openapi".to_string(),
-                        (*service).to_string(),
-                        (*version).to_string(),
-                        (*access_level).to_string(),
-                        (*sub_category).to_string(),
-                    ],
-                    (*project).to_string(),
-                )
-            }
-            [
-                "openapi-spec",
-                project,
-                service,
-                version,
-                access_level,
-                sub_category,
-                filename,
-            ] => {
-                // OpenAPI spec structure with subcategory without endpoints: openapi-spec/project/service/version/access_level/sub_category/filename.yaml
-                let uri = format!(
-                    "docs://openapi/{}/{}/{}/{}/{}/{}",
-                    project, service, version, access_level, sub_category, filename
-                );
+                    (*service).to_string(),
+                    (*version).to_string(),
+                    (*access_level).to_string(),
+                ];
+                if !sub_category.is_empty() {
+                    categories.push(sub_category.to_string());
+                }
                 (
-                    uri,
-                    "openapi".to_string(),
-                    String::new(),
+                    pattern,
+                    &uri_templates.openapi,
                     vec![
-                        "openapi".to_string(),
-                        (*service).to_string(),
-                        (*version).to_string(),
-                        (*access_level).to_string(),
-                        (*sub_category).to_string(),
+                        ("project", *project),
+                        ("service", *service),
+                        ("version", *version),
+                        ("access_level", *access_level),
+                        ("sub_category", sub_category),
+                        ("filename", filename),
                     ],
-                    (*project).to_string(),
-                )
-            }
-            ["content", "docs", area, lang, category, ..] => {
-                // Standard structure: content/docs/backend/lang/category/filename.mdx
-                let uri = format!(
-                    "{}{}/{}/{}/{}",
-                    document_type.get_uri_prefix(),
-                    area,
-                    lang,
-                    category,
-                    filename
-                );
-                let categories = if matches!(document_type, DocumentType::Agreements) {
-                    vec!["agreements".to_string(), (*category).to_string()]
-                } else {
-                    vec![(*category).to_string()]
-                };
-                (
-                    uri,
-                    (*area).to_string(),
-                    (*lang).to_string(),
+                    "openapi",
                     categories,
-                    String::new(),
+                    Some(Classification::Openapi {
+                        service: (*service).to_string(),
+                        version: (*version).to_string(),
+                        access_level: (*access_level).to_string(),
+                        sub_category: (!sub_category.is_empty()).then(|| sub_category.to_string()),
+                    }),
                 )
             }
-            ["content", "docs", _area, ..] => {
-                // Skip files directly in area - they should not be processed
-                return Ok(());
-            }
-            _ => return Err(format!("Invalid path structure: {}", relative_path).into()),
-        };
-
-        let mime_type = Self::get_mime_type(&filename);
-
-        let metadata = std::fs::metadata(file_path)?;
-        let size = metadata.len().try_into().unwrap_or(u32::MAX);
-
-        let key = DocumentKey::new(uri.clone());
-        let description = document_type.generate_description(&area, &lang, &categories, &filename);
-
-        let resource_info = ResourceInfo {
-            uri,
-            file_path: relative_path.clone(),
-            area,
-            lang,
-            category: categories,
-            project,
-            mime_type,
-            size,
-            description,
+            _ => return None,
         };
 
-        resources.insert(key, resource_info);
-        Ok(())
+        let project = segments[0].1.to_string();
+        Some((
+            pattern,
+            (
+                render_uri_template(template, &segments),
+                area.to_string(),
+                String::new(),
+                categories,
+                project,
+                classification,
+            ),
+        ))
     }
 
-    #[allow(clippy::too_many_lines)]
     fn process_file_universal(
         document_type: &DocumentType,
+        uri_templates: &UriTemplates,
         file_path: &Path,
         scan_root: &str,
         allowed_extensions: &[String],
         file_reader: &FileReader,
+        progress: &ScanProgress,
         resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let filename = file_path
@@ -781,7 +1052,46 @@ impl DocumentScanner {
             .to_string_lossy()
             .to_string();
 
-        let subpath = relative_under_target(&relative_path, scan_root);
+        let (pattern, entry) =
+            Self::path_entry(document_type, uri_templates, &relative_path, scan_root);
+        progress.pattern_matched(pattern);
+        Self::insert_resource(
+            document_type,
+            file_path,
+            relative_path,
+            &filename,
+            entry,
+            resources,
+        )
+    }
+
+    /// Language a scan of `scan_root` derives from the path of `info`, before frontmatter or
+    /// `agreements_defaults` apply. None when that scan would map the file to another URI.
+    pub fn path_lang(
+        document_type: &DocumentType,
+        uri_templates: &UriTemplates,
+        scan_root: &str,
+        info: &ResourceInfo,
+    ) -> Option<String> {
+        let (_, (uri, _, lang, ..)) =
+            Self::path_entry(document_type, uri_templates, &info.file_path, scan_root);
+        (uri == info.uri).then_some(lang)
+    }
+
+    /// URI and metadata a scan of `scan_root` derives for the file at `relative_path`, with the
+    /// name of the pattern that matched (see `ScanProgress`). Reads nothing.
+    fn path_entry(
+        document_type: &DocumentType,
+        uri_templates: &UriTemplates,
+        relative_path: &str,
+        scan_root: &str,
+    ) -> (&'static str, PathEntry) {
+        let filename = relative_path.rsplit('/').next().unwrap_or(relative_path);
+        if let Some(known) = Self::match_known_layout(document_type, uri_templates, relative_path) {
+            return known;
+        }
+
+        let subpath = relative_under_target(relative_path, scan_root);
         let uri = match document_type {
             DocumentType::Agreements => {
                 let area = guess_agreements_area(scan_root);
@@ -792,39 +1102,65 @@ impl DocumentScanner {
                 };
                 format!("{}{}", document_type.get_uri_prefix(), uri_subpath)
             }
+            DocumentType::ApiStyleGuide => {
+                let (area, area_subpath) = style_guide_subpath(relative_path, scan_root);
+                let uri_subpath = if area.is_empty() {
+                    area_subpath
+                } else {
+                    format!("{}/{}", area, area_subpath)
+                };
+                format!("{}{}", document_type.get_uri_prefix(), uri_subpath)
+            }
             _ => format!("{}{}", document_type.get_uri_prefix(), subpath),
         };
 
-        let (area, lang, categories, project) = match document_type {
+        let (area, lang, categories, project, classification) = match document_type {
             DocumentType::C1Diagram(project) => (
                 "architecture".to_string(),
                 String::new(),
                 vec!["c1".to_string()],
                 project.clone(),
+                Some(Classification::C4 {
+                    level: "c1".to_string(),
+                    service: None,
+                }),
             ),
             DocumentType::C2Diagram(project) => (
                 "architecture".to_string(),
                 String::new(),
                 vec!["c2".to_string()],
                 project.clone(),
+                Some(Classification::C4 {
+                    level: "c2".to_string(),
+                    service: None,
+                }),
             ),
             DocumentType::C3Diagram(project) => (
                 "architecture".to_string(),
                 String::new(),
                 vec!["c3".to_string()],
                 project.clone(),
+                Some(Classification::C4 {
+                    level: "c3".to_string(),
+                    service: None,
+                }),
             ),
             DocumentType::C4Diagram(project) => (
                 "architecture".to_string(),
                 String::new(),
                 vec!["c4".to_string()],
                 project.clone(),
+                Some(Classification::C4 {
+                    level: "c4".to_string(),
+                    service: Some(file_stem(filename).to_string()),
+                }),
             ),
             DocumentType::ErdDiagram(project) => (
                 "architecture".to_string(),
                 String::new(),
                 vec!["erd".to_string()],
                 project.clone(),
+                None,
             ),
             DocumentType::AdrDocument(project) => {
                 let adr_number = filename
@@ -837,6 +1173,9 @@ impl DocumentScanner {
                     String::new(),
                     vec!["adr".to_string(), format!("ADR-{}", adr_number)],
                     project.clone(),
+                    Some(Classification::Adr {
+                        number: adr_number.to_string(),
+                    }),
                 )
             }
             DocumentType::OpenApiSpec(project) => (
@@ -844,38 +1183,68 @@ impl DocumentScanner {
                 String::new(),
                 vec!["openapi".to_string()],
                 project.clone(),
+                None,
             ),
             DocumentType::GuideDoc(product) => (
                 "guides".to_string(),
                 String::new(),
                 vec!["guides".to_string()],
                 product.clone(),
+                None,
             ),
             DocumentType::Agreements => {
                 let area = guess_agreements_area(scan_root);
                 let mut categories: Vec<String> = vec!["agreements".to_string()];
 
                 let (lang, extra_categories) = parse_agreements_subpath(&subpath, &area);
-                categories.extend(extra_categories);
+                categories.extend(extra_categories.iter().cloned());
+                let area = if area.is_empty() {
+                    "agreements".to_string()
+                } else {
+                    area
+                };
+                let classification = Classification::Agreement {
+                    area: area.clone(),
+                    lang: lang.clone(),
+                    category_path: extra_categories,
+                };
 
-                (
-                    if area.is_empty() {
-                        "agreements".to_string()
-                    } else {
-                        area
-                    },
-                    lang,
-                    categories,
-                    String::new(),
-                )
+                (area, lang, categories, String::new(), Some(classification))
+            }
+            DocumentType::ApiStyleGuide => {
+                let (area, area_subpath) = style_guide_subpath(relative_path, scan_root);
+                let (lang, extra_categories) = parse_agreements_subpath(&area_subpath, &area);
+                let mut categories = vec!["style-guide".to_string()];
+                categories.extend(extra_categories);
+                let area = if area.is_empty() {
+                    "style-guides".to_string()
+                } else {
+                    area
+                };
+                (area, lang, categories, String::new(), None)
             }
         };
 
-        let mime_type = Self::get_mime_type(&filename);
+        (
+            "scan_target",
+            (uri, area, lang, categories, project, classification),
+        )
+    }
+
+    fn insert_resource(
+        document_type: &DocumentType,
+        file_path: &Path,
+        relative_path: String,
+        filename: &str,
+        (uri, area, lang, categories, project, classification): PathEntry,
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mime_type = Self::get_mime_type(filename);
         let metadata = std::fs::metadata(file_path)?;
         let size = metadata.len().try_into().unwrap_or(u32::MAX);
+        let last_modified = last_modified(&metadata);
         let key = DocumentKey::new(uri.clone());
-        let description = document_type.generate_description(&area, &lang, &categories, &filename);
+        let description = document_type.generate_description(&area, &lang, &categories, filename);
 
         let resource_info = ResourceInfo {
             uri,
@@ -887,6 +1256,9 @@ impl DocumentScanner {
             mime_type,
             size,
             description,
+            last_modified,
+            classification,
+            ..Default::default()
         };
 
         resources.insert(key, resource_info);
@@ -923,7 +1295,9 @@ impl DocumentScanner {
             | DocumentType::ErdDiagram(_)
             | DocumentType::AdrDocument(_)
             | DocumentType::OpenApiSpec(_)
-            | DocumentType::GuideDoc(_) => is_allowed_ext,
+            | DocumentType::GuideDoc(_)
+            | DocumentType::ApiStyleGuide => is_allowed_ext,
+            DocumentType::Agreements if !allowed_extensions.is_empty() => is_allowed_ext,
             DocumentType::Agreements => Self::should_process_file(document_type, filename),
         }
     }
@@ -950,12 +1324,12 @@ impl DocumentScanner {
 
             // Agreements: process all supported files
             DocumentType::Agreements => Path::new(filename).extension().is_some_and(|ext| {
-                ext.eq_ignore_ascii_case("md")
-                    || ext.eq_ignore_ascii_case("mdx")
-                    || ext.eq_ignore_ascii_case("txt")
+                AGREEMENT_EXTENSIONS
+                    .iter()
+                    .any(|supported| ext.eq_ignore_ascii_case(supported))
             }),
-            // GuideDoc uses extension-based scanning only (process_file_universal)
-            DocumentType::GuideDoc(_) => false,
+            // Guides and style guides use extension-based scanning only (process_file_universal)
+            DocumentType::GuideDoc(_) | DocumentType::ApiStyleGuide => false,
         }
     }
 
@@ -976,12 +1350,141 @@ impl DocumentScanner {
     }
 }
 
-fn relative_under_target(relative_path_from_docs_root: &str, scan_root: &str) -> String {
-    let scan_root = scan_root.trim_end_matches('/');
-    let prefix = format!("{}/", scan_root);
-    let rest = relative_path_from_docs_root
-        .strip_prefix(&prefix)
-        .unwrap_or(relative_path_from_docs_root);
+/// Modification time of a file as RFC 3339 in UTC, for `ResourceInfo::last_modified`.
+pub fn last_modified(metadata: &std::fs::Metadata) -> Option<String> {
+    let modified = metadata.modified().ok()?;
+    Some(chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339())
+}
+
+/// Lowercased, de-duplicated languages from frontmatter `langs:` (a list or one string).
+fn frontmatter_langs(content: &str) -> Vec<String> {
+    let Ok(frontmatter) = markdown::parse_frontmatter(content) else {
+        return Vec::new();
+    };
+    let values: Vec<&str> = match frontmatter.metadata.get("langs") {
+        Some(serde_json::Value::Array(items)) => {
+            items.iter().filter_map(serde_json::Value::as_str).collect()
+        }
+        Some(serde_json::Value::String(lang)) => vec![lang.as_str()],
+        _ => Vec::new(),
+    };
+    let mut langs: Vec<String> = Vec::new();
+    for lang in values {
+        let lang = lang.trim().to_ascii_lowercase();
+        if !lang.is_empty() && !langs.contains(&lang) {
+            langs.push(lang);
+        }
+    }
+    langs
+}
+
+/// De-duplicated project names from frontmatter `applies_to:` (a list or one string).
+fn frontmatter_applies_to(content: &str) -> Vec<String> {
+    let Ok(frontmatter) = markdown::parse_frontmatter(content) else {
+        return Vec::new();
+    };
+    let values: Vec<&str> = match frontmatter.metadata.get("applies_to") {
+        Some(serde_json::Value::Array(items)) => {
+            items.iter().filter_map(serde_json::Value::as_str).collect()
+        }
+        Some(serde_json::Value::String(project)) => vec![project.as_str()],
+        _ => Vec::new(),
+    };
+    let mut projects: Vec<String> = Vec::new();
+    for project in values.into_iter().map(str::trim) {
+        if !project.is_empty() && !projects.iter().any(|p| p == project) {
+            projects.push(project.to_string());
+        }
+    }
+    projects
+}
+
+fn adr_date(content: &str) -> Option<String> {
+    let frontmatter = markdown::parse_frontmatter(content).ok();
+    if let Some(date) = frontmatter
+        .as_ref()
+        .and_then(|f| f.get_str("date"))
+        .and_then(date::normalize_date)
+    {
+        return Some(date);
+    }
+
+    let body = frontmatter.map_or(content, |f| f.body);
+    body.lines().find_map(|line| {
+        let line = line.trim_start_matches(['#', '-', '*', ' ', '\t']);
+        let (label, value) = line.split_once(':')?;
+        if !label
+            .trim_end_matches('*')
+            .trim()
+            .eq_ignore_ascii_case("date")
+        {
+            return None;
+        }
+        date::normalize_date(value.trim().trim_matches('*'))
+    })
+}
+
+/// File name without its last extension (`billing.mdx` -> "billing").
+pub(crate) fn file_stem(filename: &str) -> &str {
+    filename.rsplit_once('.').map_or(filename, |(stem, _)| stem)
+}
+
+/// Expands `{name}` placeholders. A path segment holding only a placeholder whose value is
+/// empty is dropped, so optional segments leave no `//` behind.
+fn render_uri_template(template: &str, segments: &[(&str, &str)]) -> String {
+    template
+        .split('/')
+        .filter_map(|part| {
+            let mut rendered = part.to_string();
+            for (name, value) in segments {
+                let placeholder = format!("{{{}}}", name);
+                if part == placeholder && value.is_empty() {
+                    return None;
+                }
+                rendered = rendered.replace(&placeholder, value);
+            }
+            Some(rendered)
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `docs://x/api.ru.md` -> (`docs://x/api.md`, "ru") when "ru" is a known language.
+fn split_language_variant(uri: &str, languages: &[String]) -> Option<(String, String)> {
+    let (dir, filename) = uri.rsplit_once('/')?;
+    let (rest, extension) = filename.rsplit_once('.')?;
+    let (stem, lang) = rest.rsplit_once('.')?;
+    if stem.is_empty() {
+        return None;
+    }
+    let lang = languages.iter().find(|l| l.eq_ignore_ascii_case(lang))?;
+    Some((format!("{}/{}.{}", dir, stem, extension), lang.clone()))
+}
+
+fn validate_spec_structure(content: &str, multi_document: MultiDocumentMode) -> Result<(), String> {
+    let document = openapi::parse_spec_documents(content, multi_document)?;
+    let mapping = document
+        .as_object()
+        .ok_or_else(|| "Specification root must be a mapping".to_string())?;
+
+    if !["openapi", "swagger", "asyncapi"]
+        .iter()
+        .any(|key| mapping.contains_key(*key))
+    {
+        return Err("Missing 'openapi', 'swagger' or 'asyncapi' version key".to_string());
+    }
+    if !mapping.contains_key("info") {
+        return Err("Missing 'info' section".to_string());
+    }
+    Ok(())
+}
+
+fn relative_under_target(relative_path_from_docs_root: &str, scan_root: &str) -> String {
+    let scan_root = scan_root.trim_end_matches('/');
+    let prefix = format!("{}/", scan_root);
+    let rest = relative_path_from_docs_root
+        .strip_prefix(&prefix)
+        .unwrap_or(relative_path_from_docs_root);
     rest.trim_start_matches('/').to_string()
 }
 
@@ -1015,6 +1518,22 @@ fn guess_agreements_area(scan_root: &str) -> String {
     }
 }
 
+/// Area of a style guide file (as for agreements, from the target path) and its path below
+/// the area directory, so `docs/backend/php/api-documentation/rest.md` keeps its language
+/// and category segments whichever directory the target names.
+fn style_guide_subpath(relative_path: &str, scan_root: &str) -> (String, String) {
+    let area = guess_agreements_area(scan_root);
+    let parts: Vec<&str> = relative_path.split('/').collect();
+    let subpath = match parts
+        .iter()
+        .position(|part| part.eq_ignore_ascii_case(&area))
+    {
+        Some(position) if !area.is_empty() => parts[position + 1..].join("/"),
+        _ => relative_under_target(relative_path, scan_root),
+    };
+    (area, subpath)
+}
+
 fn parse_agreements_subpath(subpath: &str, area: &str) -> (String, Vec<String>) {
     let parts: Vec<&str> = subpath.split('/').filter(|p| !p.is_empty()).collect();
     if (area == "backend" || area == "frontend") && parts.len() >= 2 {
@@ -1152,6 +1671,7 @@ mod tests {
             mime_type: "text/markdown".to_string(),
             size: 1024,
             description: "Test document".to_string(),
+            ..Default::default()
         };
 
         assert_eq!(resource_info.uri, "docs://test/uri");
@@ -1396,7 +1916,7 @@ mod tests {
         let c3_doc = DocumentType::C3Diagram("proj-a".to_string());
         let c4_doc = DocumentType::C4Diagram("proj-b".to_string());
 
-        // These should match the category logic in process_file
+        // These should match the category logic in match_known_layout
         assert!(matches!(c1_doc, DocumentType::C1Diagram(_)));
         assert!(matches!(c2_doc, DocumentType::C2Diagram(_)));
         assert!(matches!(c3_doc, DocumentType::C3Diagram(_)));
@@ -1933,27 +2453,33 @@ mod tests {
         let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
         let mut resources: BTreeMap<DocumentKey, ResourceInfo> = BTreeMap::new();
 
-        DocumentScanner::scan_documents_with_extensions(
+        scan_targets(
             DocumentType::C1Diagram("proj-a".to_string()),
-            vec!["arch/c4".to_string(), "missing/path".to_string()],
+            &UriTemplates::default(),
+            &["arch/c4".to_string(), "missing/path".to_string()],
             &["puml".to_string(), "dot".to_string(), "mdx".to_string()],
             &file_reader,
+            &ScanProgress::default(),
             &mut resources,
         );
 
-        DocumentScanner::scan_documents_with_extensions(
+        scan_targets(
             DocumentType::OpenApiSpec("proj-a".to_string()),
-            vec!["openapi".to_string()],
+            &UriTemplates::default(),
+            &["openapi".to_string()],
             &["yaml".to_string(), "yml".to_string()],
             &file_reader,
+            &ScanProgress::default(),
             &mut resources,
         );
 
-        DocumentScanner::scan_documents_with_extensions(
+        scan_targets(
             DocumentType::GuideDoc("eva4".to_string()),
-            vec!["eva4".to_string()],
+            &UriTemplates::default(),
+            &["eva4".to_string()],
             &["rst".to_string()],
             &file_reader,
+            &ScanProgress::default(),
             &mut resources,
         );
 
@@ -1967,4 +2493,1285 @@ mod tests {
             "docs://guides/eva4/svc/eva-repl.rst".to_string()
         )));
     }
+
+    /// Scans each target through `scan_target` like the index build does for a type that
+    /// shares none of its targets.
+    fn scan_targets(
+        document_type: DocumentType,
+        uri_templates: &UriTemplates,
+        targets: &[String],
+        allowed_extensions: &[String],
+        file_reader: &FileReader,
+        progress: &ScanProgress,
+        resources: &mut BTreeMap<DocumentKey, ResourceInfo>,
+    ) {
+        for target in targets {
+            DocumentScanner::scan_target(
+                uri_templates,
+                target,
+                &mut [TargetScan {
+                    document_type: &document_type,
+                    allowed_extensions,
+                    resources,
+                }],
+                file_reader,
+                progress,
+            )
+            .expect("scan target");
+        }
+    }
+
+    fn write_file(root: &Path, relative: &str, content: &str) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().expect("parent dir")).expect("create dirs");
+        fs::write(path, content).expect("write file");
+    }
+
+    fn sorted_uris(resources: &BTreeMap<DocumentKey, ResourceInfo>) -> Vec<String> {
+        resources.values().map(|info| info.uri.clone()).collect()
+    }
+
+    #[test]
+    fn test_validate_specs_flags_broken_spec_without_dropping_it() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(
+            docs_root,
+            "openapi-spec/billing/v1/public/invoices.yaml",
+            "openapi: 3.0.0\ninfo:\n  title: Invoices\n  version: v1\npaths: {}\n",
+        );
+        write_file(
+            docs_root,
+            "openapi-spec/billing/v1/public/payments.yaml",
+            "openapi: 3.0.0\npaths: [unclosed\n",
+        );
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::OpenApiSpec("billing".to_string()),
+            &UriTemplates::default(),
+            &["openapi-spec/billing".to_string()],
+            &["yaml".to_string()],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+        let invalid = DocumentScanner::validate_specs(
+            &mut resources,
+            &file_reader,
+            MultiDocumentMode::default(),
+        );
+
+        assert_eq!(invalid, 1);
+        assert_eq!(resources.len(), 2);
+        let by_file = |name: &str| {
+            resources
+                .values()
+                .find(|info| info.file_path.ends_with(name))
+                .expect("indexed")
+        };
+        assert_eq!(by_file("invoices.yaml").valid, Some(true));
+        assert_eq!(by_file("payments.yaml").valid, Some(false));
+        assert!(
+            by_file("payments.yaml")
+                .validation_error
+                .as_deref()
+                .is_some_and(|e| e.starts_with("Invalid YAML/JSON"))
+        );
+    }
+
+    #[test]
+    fn test_detect_language_variants_links_suffixed_files() {
+        let mut resources = BTreeMap::new();
+        for uri in [
+            "docs://agreements/backend/go/api.md",
+            "docs://agreements/backend/go/api.ru.md",
+            "docs://agreements/backend/go/errors.md",
+            "docs://agreements/backend/go/v1.2.md",
+        ] {
+            resources.insert(
+                DocumentKey::new(uri.to_string()),
+                ResourceInfo {
+                    uri: uri.to_string(),
+                    ..Default::default()
+                },
+            );
+        }
+
+        DocumentScanner::detect_language_variants(
+            &mut resources,
+            &["en".to_string(), "ru".to_string()],
+            "en",
+        );
+
+        let get = |uri: &str| &resources[&DocumentKey::new(uri.to_string())];
+        let variant = get("docs://agreements/backend/go/api.ru.md");
+        assert_eq!(variant.content_lang.as_deref(), Some("ru"));
+        assert_eq!(
+            variant.variant_of.as_deref(),
+            Some("docs://agreements/backend/go/api.md")
+        );
+        let primary = get("docs://agreements/backend/go/api.md");
+        assert_eq!(primary.content_lang.as_deref(), Some("en"));
+        assert_eq!(primary.variant_of, None);
+
+        for uri in [
+            "docs://agreements/backend/go/errors.md",
+            "docs://agreements/backend/go/v1.2.md",
+        ] {
+            assert_eq!(get(uri).content_lang, None);
+            assert_eq!(get(uri).variant_of, None);
+        }
+    }
+
+    #[test]
+    fn test_excluded_access_levels_drop_internal_specs() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(
+            docs_root,
+            "openapi-spec/billing/invoices/v1/public/invoices.yaml",
+            "openapi: 3.0.0",
+        );
+        write_file(
+            docs_root,
+            "openapi-spec/billing/invoices/v1/internal/admin.yaml",
+            "openapi: 3.0.0",
+        );
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::OpenApiSpec("billing".to_string()),
+            &UriTemplates::default(),
+            &["openapi-spec/billing".to_string()],
+            &["yaml".to_string()],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+        let removed =
+            DocumentScanner::exclude_access_levels(&mut resources, &["internal".to_string()]);
+
+        assert_eq!(removed, 1);
+        assert_eq!(
+            sorted_uris(&resources),
+            vec!["docs://openapi/billing/invoices/v1/public/invoices.yaml"]
+        );
+    }
+
+    #[test]
+    fn test_detect_parse_failures_flags_malformed_spec_and_frontmatter() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(
+            docs_root,
+            "openapi-spec/billing/invoices/v1/public/broken.yaml",
+            "openapi: 3.0.0\ninfo: [unclosed\n",
+        );
+        write_file(
+            docs_root,
+            "openapi-spec/billing/invoices/v1/public/ok.yaml",
+            "openapi: 3.0.0\ninfo: {title: ok}\n",
+        );
+        write_file(
+            docs_root,
+            "architecture/billing/adr/001-outbox.mdx",
+            "---\ntitle: [unclosed\n---\n# Outbox\n",
+        );
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::OpenApiSpec("billing".to_string()),
+            &UriTemplates::default(),
+            &["openapi-spec/billing".to_string()],
+            &["yaml".to_string()],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+        scan_targets(
+            DocumentType::AdrDocument("billing".to_string()),
+            &UriTemplates::default(),
+            &["architecture/billing/adr".to_string()],
+            &["mdx".to_string()],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+
+        assert_eq!(
+            DocumentScanner::detect_parse_failures(
+                &mut resources,
+                &file_reader,
+                MultiDocumentMode::default()
+            ),
+            2
+        );
+        let broken = &resources[&DocumentKey::new(
+            "docs://openapi/billing/invoices/v1/public/broken.yaml".to_string(),
+        )];
+        assert!(
+            broken
+                .parse_error
+                .as_deref()
+                .is_some_and(|e| e.starts_with("Invalid YAML/JSON"))
+        );
+        let adr = &resources
+            [&DocumentKey::new("docs://architecture/billing/adr/001-outbox.mdx".to_string())];
+        assert!(adr.parse_error.is_some());
+        let ok = &resources
+            [&DocumentKey::new("docs://openapi/billing/invoices/v1/public/ok.yaml".to_string())];
+        assert_eq!(ok.parse_error, None);
+    }
+
+    #[test]
+    fn test_extract_summaries_reads_first_paragraph() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(
+            docs_root,
+            "architecture/mpa/adr/001-outbox.mdx",
+            "---\ntitle: Outbox\n---\n# ADR-001 Outbox\n\nWe publish events through an outbox.\n\n## Status\n",
+        );
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::AdrDocument("mpa".to_string()),
+            &UriTemplates::default(),
+            &["architecture/mpa/adr".to_string()],
+            &[],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+        DocumentScanner::extract_summaries(&mut resources, &file_reader, COUNTING);
+
+        let info = resources.values().next().expect("scanned ADR");
+        assert_eq!(
+            info.summary.as_deref(),
+            Some("We publish events through an outbox.")
+        );
+    }
+
+    const COUNTING: ContentCounting = ContentCounting {
+        max_file_size: 1024,
+        chars_per_token: 4.0,
+    };
+
+    #[test]
+    fn test_extract_summaries_counts_words_and_tokens_of_text_documents() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        // 8 words in 56 characters
+        let spec = "openapi: 3.0.0\ninfo:\n  title: Billing API\n  version: v1\n";
+        assert_eq!(spec.chars().count(), 56);
+        write_file(docs_root, "openapi/billing/api.yaml", spec);
+        write_file(docs_root, "openapi/billing/big.yaml", &"word ".repeat(300));
+        write_file(docs_root, "openapi/billing/binary.yaml", "key: \0\0\0\n");
+        fs::write(
+            docs_root.join("openapi/billing/latin1.yaml"),
+            b"caf\xe9: 1\n",
+        )
+        .expect("write");
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::OpenApiSpec("billing".to_string()),
+            &UriTemplates::default(),
+            &["openapi/billing".to_string()],
+            &["yaml".to_string()],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+
+        DocumentScanner::extract_summaries(&mut resources, &file_reader, COUNTING);
+
+        let counts = |name: &str| {
+            let info = resources
+                .values()
+                .find(|info| info.file_path.ends_with(name))
+                .expect("scanned");
+            (info.word_count, info.token_estimate)
+        };
+        assert_eq!(counts("api.yaml"), (Some(8), Some(14)));
+        assert_eq!(counts("big.yaml"), (None, None));
+        assert_eq!(counts("binary.yaml"), (None, None));
+        assert_eq!(counts("latin1.yaml"), (None, None));
+
+        let mut resources = BTreeMap::from([(
+            DocumentKey::new("docs://api.yaml".to_string()),
+            ResourceInfo {
+                file_path: "openapi/billing/api.yaml".to_string(),
+                size: 56,
+                ..Default::default()
+            },
+        )]);
+        let coarse = ContentCounting {
+            chars_per_token: 2.5,
+            ..COUNTING
+        };
+        DocumentScanner::extract_summaries(&mut resources, &file_reader, coarse);
+        let info = resources.values().next().expect("document");
+        assert_eq!((info.word_count, info.token_estimate), (Some(8), Some(23)));
+    }
+
+    #[test]
+    fn test_extract_langs_reads_frontmatter_list_or_keeps_path_language() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(
+            docs_root,
+            "content/docs/backend/shared/errors.md",
+            "---\nlangs: [PHP, go, php]\n---\n# Errors\n",
+        );
+        write_file(docs_root, "content/docs/backend/go/style.md", "# Style\n");
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::Agreements,
+            &UriTemplates::default(),
+            &["content/docs/backend".to_string()],
+            &[],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+        DocumentScanner::extract_langs(&mut resources, &file_reader);
+
+        let langs: Vec<(&str, &str, Vec<String>)> = resources
+            .values()
+            .map(|info| {
+                (
+                    info.file_path.as_str(),
+                    info.lang.as_str(),
+                    info.langs.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            langs,
+            vec![
+                (
+                    "content/docs/backend/go/style.md",
+                    "go",
+                    vec!["go".to_string()]
+                ),
+                (
+                    "content/docs/backend/shared/errors.md",
+                    "php",
+                    vec!["php".to_string(), "go".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_agreement_defaults_fills_empty_languages_per_area() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(
+            docs_root,
+            "docs/quality-assurance/testing/e2e.md",
+            "# E2E\n",
+        );
+        write_file(docs_root, "docs/devops/deploy/release.md", "# Release\n");
+        write_file(docs_root, "docs/backend/php/style.md", "# Style\n");
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::Agreements,
+            &UriTemplates::default(),
+            &[
+                "docs/quality-assurance".to_string(),
+                "docs/devops".to_string(),
+                "docs/backend".to_string(),
+            ],
+            &[],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+        let defaults = BTreeMap::from([
+            ("quality-assurance".to_string(), "all".to_string()),
+            ("backend".to_string(), "go".to_string()),
+        ]);
+        DocumentScanner::apply_agreement_defaults(&mut resources, &defaults);
+        DocumentScanner::extract_langs(&mut resources, &file_reader);
+
+        let langs: BTreeMap<&str, (&str, Vec<String>)> = resources
+            .values()
+            .map(|info| {
+                (
+                    info.file_path.as_str(),
+                    (info.lang.as_str(), info.langs.clone()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            langs["docs/quality-assurance/testing/e2e.md"],
+            ("all", vec!["all".to_string()])
+        );
+        assert_eq!(langs["docs/devops/deploy/release.md"], ("", vec![]));
+        assert_eq!(
+            langs["docs/backend/php/style.md"],
+            ("php", vec!["php".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_extract_applies_to_reads_frontmatter_projects_of_agreements() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(
+            docs_root,
+            "content/docs/backend/go/errors.md",
+            "---\napplies_to: [crm, mpa, crm]\n---\n# Errors\n",
+        );
+        write_file(
+            docs_root,
+            "content/docs/backend/go/naming.md",
+            "---\napplies_to: billing\n---\n# Naming\n",
+        );
+        write_file(docs_root, "content/docs/backend/go/style.md", "# Style\n");
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::Agreements,
+            &UriTemplates::default(),
+            &["content/docs/backend".to_string()],
+            &[],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+        DocumentScanner::extract_applies_to(&mut resources, &file_reader);
+
+        let applies_to: Vec<(&str, Vec<String>)> = resources
+            .values()
+            .map(|info| (info.file_path.as_str(), info.applies_to.clone()))
+            .collect();
+        assert_eq!(
+            applies_to,
+            vec![
+                (
+                    "content/docs/backend/go/errors.md",
+                    vec!["crm".to_string(), "mpa".to_string()]
+                ),
+                (
+                    "content/docs/backend/go/naming.md",
+                    vec!["billing".to_string()]
+                ),
+                ("content/docs/backend/go/style.md", Vec::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_adr_date_from_frontmatter_or_date_line() {
+        assert_eq!(
+            adr_date("---\ndate: 2024-03-01\n---\n# ADR\n").as_deref(),
+            Some("2024-03-01")
+        );
+        assert_eq!(
+            adr_date("# ADR-004\n\n**Date:** 15.01.2024\n\n## Status\n").as_deref(),
+            Some("2024-01-15")
+        );
+        assert_eq!(
+            adr_date("---\ntitle: x\n---\n- Date: March 5, 2023\n").as_deref(),
+            Some("2023-03-05")
+        );
+        assert_eq!(adr_date("# ADR\nUpdate: soon\n"), None);
+    }
+
+    #[test]
+    fn test_assign_adr_scope_distinguishes_global_adrs() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(docs_root, "architecture/mpa/adr/001-outbox.mdx", "# ADR");
+        write_file(
+            docs_root,
+            "architecture/shared/adr/001-logging.mdx",
+            "# ADR",
+        );
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+
+        let mut resources = BTreeMap::new();
+        for (project, path) in [
+            ("mpa", "architecture/mpa/adr"),
+            ("shared", "architecture/shared/adr"),
+        ] {
+            scan_targets(
+                DocumentType::AdrDocument(project.to_string()),
+                &UriTemplates::default(),
+                &[path.to_string()],
+                &[],
+                &file_reader,
+                &ScanProgress::default(),
+                &mut resources,
+            );
+        }
+
+        DocumentScanner::assign_adr_scope(&mut resources, &["architecture/shared/".to_string()]);
+
+        let scopes: Vec<(&str, Option<&str>)> = resources
+            .values()
+            .map(|info| (info.uri.as_str(), info.adr_scope.as_deref()))
+            .collect();
+        assert_eq!(
+            scopes,
+            vec![
+                (
+                    "docs://architecture/mpa/adr/001-outbox.mdx",
+                    Some("project")
+                ),
+                (
+                    "docs://architecture/shared/adr/001-logging.mdx",
+                    Some("global")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_assign_default_project_folds_agreements() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(
+            docs_root,
+            "content/docs/backend/rust/errors/handling.mdx",
+            "# Errors",
+        );
+        write_file(docs_root, "architecture/mpa/adr/001-outbox.mdx", "# ADR");
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::Agreements,
+            &UriTemplates::default(),
+            &["content/docs/backend".to_string()],
+            &[],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+        scan_targets(
+            DocumentType::AdrDocument("mpa".to_string()),
+            &UriTemplates::default(),
+            &["architecture/mpa/adr".to_string()],
+            &[],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+        assert!(resources.values().any(|info| info.project.is_empty()));
+
+        DocumentScanner::assign_default_project(&mut resources, "shared");
+
+        let projects: Vec<(&str, &str)> = resources
+            .values()
+            .map(|info| (info.category[0].as_str(), info.project.as_str()))
+            .collect();
+        assert_eq!(projects, vec![("agreements", "shared"), ("adr", "mpa")]);
+    }
+
+    #[test]
+    fn test_validate_spec_structure_requires_version_and_info() {
+        assert!(
+            validate_spec_structure(
+                "swagger: '2.0'\ninfo: {title: x}\n",
+                MultiDocumentMode::default()
+            )
+            .is_ok()
+        );
+        assert!(
+            validate_spec_structure(
+                "{\"asyncapi\": \"2.6.0\", \"info\": {}}",
+                MultiDocumentMode::default()
+            )
+            .is_ok()
+        );
+        assert!(
+            validate_spec_structure("openapi: 3.0.0\npaths: {}\n", MultiDocumentMode::default())
+                .is_err()
+        );
+        assert!(validate_spec_structure("info: {}\n", MultiDocumentMode::default()).is_err());
+        assert!(validate_spec_structure("- a\n- b\n", MultiDocumentMode::default()).is_err());
+    }
+
+    #[test]
+    fn test_validate_spec_structure_follows_the_multi_document_mode() {
+        let content = "kind: Config\n---\nopenapi: 3.0.0\ninfo: {title: x}\npaths: {}\n";
+
+        assert!(validate_spec_structure(content, MultiDocumentMode::FirstOpenapi).is_ok());
+        assert!(validate_spec_structure(content, MultiDocumentMode::Reject).is_err());
+    }
+
+    #[test]
+    fn test_preferred_extension_shadows_competing_file() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(docs_root, "arch/proj-a/services/billing.puml", "@startuml");
+        write_file(docs_root, "arch/proj-a/services/billing.mdx", "# C1");
+        write_file(docs_root, "arch/proj-a/services/payments.dot", "digraph {}");
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::C4Diagram("proj-a".to_string()),
+            &UriTemplates::default(),
+            &["arch/proj-a/services".to_string()],
+            &["dot".to_string(), "mdx".to_string(), "puml".to_string()],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+        assert_eq!(resources.len(), 3);
+
+        DocumentScanner::apply_preferred_extensions(
+            &mut resources,
+            &["puml".to_string(), "mdx".to_string()],
+        );
+
+        let uris = sorted_uris(&resources);
+        assert_eq!(uris.len(), 2);
+        assert!(uris.iter().any(|uri| uri.ends_with("/billing.puml")));
+        assert!(!uris.iter().any(|uri| uri.ends_with("/billing.mdx")));
+        assert!(uris.iter().any(|uri| uri.ends_with("/payments.dot")));
+    }
+
+    #[test]
+    fn test_layout_uris_do_not_depend_on_the_content_docs_prefix() {
+        let files = [
+            "architecture/proj-a/c4/c1.mdx",
+            "architecture/proj-a/c4/services/billing.mdx",
+            "architecture/proj-a/erd/services/users.mdx",
+            "architecture/proj-a/adr/001-outbox.mdx",
+            "openapi-spec/proj-a/activation/v2/public/endpoints/get-info.yaml",
+            "openapi-spec/proj-a/oauth/v2/internal/activation/get-token.yaml",
+        ];
+        let targets = [
+            (
+                DocumentType::C1Diagram("proj-a".to_string()),
+                "architecture/proj-a/c4",
+            ),
+            (
+                DocumentType::C4Diagram("proj-a".to_string()),
+                "architecture/proj-a/c4/services",
+            ),
+            (
+                DocumentType::ErdDiagram("proj-a".to_string()),
+                "architecture/proj-a/erd",
+            ),
+            (
+                DocumentType::AdrDocument("proj-a".to_string()),
+                "architecture/proj-a/adr",
+            ),
+            (
+                DocumentType::OpenApiSpec("proj-a".to_string()),
+                "openapi-spec/proj-a",
+            ),
+        ];
+        let scan = |prefix: &str| {
+            let temp_dir = TempDir::new().expect("temp dir");
+            for file in files {
+                write_file(temp_dir.path(), &format!("{}{}", prefix, file), "content");
+            }
+            let file_reader =
+                FileReader::new(temp_dir.path().to_string_lossy().to_string()).expect("reader");
+            let mut resources = BTreeMap::new();
+            for (document_type, target) in &targets {
+                scan_targets(
+                    document_type.clone(),
+                    &UriTemplates::default(),
+                    &[format!("{}{}", prefix, target)],
+                    &[],
+                    &file_reader,
+                    &ScanProgress::default(),
+                    &mut resources,
+                );
+            }
+            resources
+        };
+
+        let prefixed = scan("content/docs/");
+        let bare = scan("");
+        assert_eq!(prefixed.len(), 6);
+        assert_eq!(sorted_uris(&prefixed), sorted_uris(&bare));
+        assert!(bare.contains_key(&DocumentKey::new(
+            "docs://openapi/proj-a/activation/v2/public/get-info.yaml".to_string()
+        )));
+        assert!(bare.contains_key(&DocumentKey::new(
+            "docs://architecture/erd/proj-a/users.mdx".to_string()
+        )));
+        for (key, info) in &prefixed {
+            assert_eq!(bare[key].category, info.category);
+            assert_eq!(bare[key].project, info.project);
+        }
+    }
+
+    #[test]
+    fn test_match_known_layout_classifies_path_segments() {
+        let templates = UriTemplates::default();
+        let project = || "proj-a".to_string();
+        let classify = |document_type: DocumentType, path: &str| {
+            DocumentScanner::match_known_layout(&document_type, &templates, path)
+                .and_then(|(_, (.., classification))| classification)
+                .map(|classification| serde_json::to_value(classification).expect("serialize"))
+        };
+
+        assert_eq!(
+            classify(
+                DocumentType::OpenApiSpec(project()),
+                "openapi-spec/proj-a/oauth/v2/internal/tokens/endpoints/issue.yaml"
+            ),
+            Some(serde_json::json!({
+                "type": "openapi",
+                "service": "oauth",
+                "version": "v2",
+                "access_level": "internal",
+                "sub_category": "tokens"
+            }))
+        );
+        assert_eq!(
+            classify(
+                DocumentType::AdrDocument(project()),
+                "architecture/proj-a/adr/007-outbox.mdx"
+            ),
+            Some(serde_json::json!({"type": "adr", "number": "007"}))
+        );
+        assert_eq!(
+            classify(
+                DocumentType::C2Diagram(project()),
+                "architecture/proj-a/c4/c2.mdx"
+            ),
+            Some(serde_json::json!({"type": "c4", "level": "c2"}))
+        );
+        assert_eq!(
+            classify(
+                DocumentType::C4Diagram(project()),
+                "architecture/proj-a/c4/services/billing.mdx"
+            ),
+            Some(serde_json::json!({"type": "c4", "level": "c4", "service": "billing"}))
+        );
+        assert_eq!(
+            classify(
+                DocumentType::ErdDiagram(project()),
+                "architecture/proj-a/erd/orders.mdx"
+            ),
+            None
+        );
+
+        let schema = serde_json::to_value(schemars::schema_for!(ResourceInfo)).expect("schema");
+        assert!(schema["properties"].get("classification").is_some());
+    }
+
+    #[test]
+    fn test_scan_agreements_sets_agreement_classification() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(
+            docs_root,
+            "content/docs/backend/go/http/errors.md",
+            "# Errors",
+        );
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::Agreements,
+            &UriTemplates::default(),
+            &["content/docs/backend".to_string()],
+            &[],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+
+        let info = resources.values().next().expect("agreement");
+        assert_eq!(info.category, vec!["agreements", "http"]);
+        assert_eq!(
+            info.classification,
+            Some(Classification::Agreement {
+                area: "backend".to_string(),
+                lang: "go".to_string(),
+                category_path: vec!["http".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_standard_structure_keeps_directories_below_category() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(
+            docs_root,
+            "content/docs/backend/go/http/client/retries/errors.md",
+            "# Client errors",
+        );
+        write_file(
+            docs_root,
+            "content/docs/backend/go/http/server/retries/errors.md",
+            "# Server errors",
+        );
+        write_file(
+            docs_root,
+            "content/docs/backend/go/http/errors.md",
+            "# Errors",
+        );
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::Agreements,
+            &UriTemplates::default(),
+            &["content/docs/backend".to_string()],
+            &[],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+
+        let uris: Vec<&str> = resources.values().map(|info| info.uri.as_str()).collect();
+        assert_eq!(
+            uris,
+            vec![
+                "docs://agreements/backend/go/http/client/retries/errors.md",
+                "docs://agreements/backend/go/http/errors.md",
+                "docs://agreements/backend/go/http/server/retries/errors.md",
+            ]
+        );
+        let nested = resources
+            .values()
+            .find(|info| info.file_path == "content/docs/backend/go/http/client/retries/errors.md")
+            .expect("nested agreement");
+        assert_eq!(
+            nested.category,
+            vec!["agreements", "http", "client", "retries"]
+        );
+        assert_eq!(
+            nested.classification,
+            Some(Classification::Agreement {
+                area: "backend".to_string(),
+                lang: "go".to_string(),
+                category_path: vec![
+                    "http".to_string(),
+                    "client".to_string(),
+                    "retries".to_string()
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_default_uri_templates_reproduce_layout_uris() {
+        let templates = UriTemplates::default();
+        let project = || "proj-a".to_string();
+        let cases = [
+            (
+                DocumentType::C2Diagram(project()),
+                "architecture/proj-a/c4/c2.mdx",
+                "docs://architecture/proj-a/c2.mdx",
+            ),
+            (
+                DocumentType::C4Diagram(project()),
+                "content/docs/architecture/proj-a/c4/services/billing.mdx",
+                "docs://architecture/proj-a/c4/billing.mdx",
+            ),
+            (
+                DocumentType::ErdDiagram(project()),
+                "architecture/proj-a/erd/services/users.mdx",
+                "docs://architecture/erd/proj-a/users.mdx",
+            ),
+            (
+                DocumentType::ErdDiagram(project()),
+                "architecture/proj-a/erd/orders.mdx",
+                "docs://architecture/erd/proj-a/orders.mdx",
+            ),
+            (
+                DocumentType::AdrDocument(project()),
+                "architecture/proj-a/adr/001-outbox.mdx",
+                "docs://architecture/proj-a/adr/001-outbox.mdx",
+            ),
+            (
+                DocumentType::OpenApiSpec(project()),
+                "openapi-spec/proj-a/activation/v2/public/endpoints/get-info.yaml",
+                "docs://openapi/proj-a/activation/v2/public/get-info.yaml",
+            ),
+            (
+                DocumentType::OpenApiSpec(project()),
+                "openapi-spec/proj-a/oauth/v2/internal/activation/endpoints/get-token.yaml",
+                "docs://openapi/proj-a/oauth/v2/internal/activation/get-token.yaml",
+            ),
+        ];
+
+        for (document_type, path, expected) in cases {
+            let (_, (uri, ..)) =
+                DocumentScanner::match_known_layout(&document_type, &templates, path)
+                    .expect("known layout");
+            assert_eq!(uri, expected);
+        }
+    }
+
+    #[test]
+    fn test_custom_uri_template_reshapes_layout_uris() {
+        let templates = UriTemplates {
+            openapi: "docs://api/{service}/{access_level}/{sub_category}/{filename}".to_string(),
+            ..Default::default()
+        };
+        let document_type = DocumentType::OpenApiSpec("proj-a".to_string());
+
+        let (_, (uri, area, _, categories, project, _)) = DocumentScanner::match_known_layout(
+            &document_type,
+            &templates,
+            "openapi-spec/proj-a/activation/v2/public/endpoints/get-info.yaml",
+        )
+        .expect("known layout");
+        assert_eq!(uri, "docs://api/activation/public/get-info.yaml");
+        assert_eq!(area, "openapi");
+        assert_eq!(categories, vec!["openapi", "activation", "v2", "public"]);
+        assert_eq!(project, "proj-a");
+    }
+
+    #[test]
+    fn test_openapi_version_segment_is_free_form() {
+        let templates = UriTemplates::default();
+        let document_type = DocumentType::OpenApiSpec("proj-a".to_string());
+
+        for version in ["2024-01", "1.0.0", "beta"] {
+            let path = format!(
+                "openapi-spec/proj-a/billing/{}/public/invoices.yaml",
+                version
+            );
+            let (_, (uri, _, _, categories, ..)) =
+                DocumentScanner::match_known_layout(&document_type, &templates, &path)
+                    .expect("known layout");
+            assert_eq!(
+                uri,
+                format!(
+                    "docs://openapi/proj-a/billing/{}/public/invoices.yaml",
+                    version
+                )
+            );
+            assert_eq!(categories, vec!["openapi", "billing", version, "public"]);
+        }
+    }
+
+    #[test]
+    fn test_style_guides_keep_area_language_and_category_of_their_path() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(
+            docs_root,
+            "docs/backend/php/api-documentation/rest/pagination.md",
+            "# Pagination\n",
+        );
+        write_file(docs_root, "docs/style/go/naming.md", "# Naming\n");
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+        let mut resources = BTreeMap::new();
+
+        scan_targets(
+            DocumentType::ApiStyleGuide,
+            &UriTemplates::default(),
+            &[
+                "docs/backend/php/api-documentation".to_string(),
+                "docs/style".to_string(),
+            ],
+            &["md".to_string()],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+
+        let guides: Vec<&ResourceInfo> = resources.values().collect();
+        assert_eq!(
+            guides[0].uri,
+            "docs://style-guides/backend/php/api-documentation/rest/pagination.md"
+        );
+        assert_eq!(
+            (guides[0].area.as_str(), guides[0].lang.as_str()),
+            ("backend", "php")
+        );
+        assert_eq!(
+            guides[0].category,
+            vec!["style-guide", "api-documentation", "rest"]
+        );
+        assert_eq!(guides[0].type_name(), Some("style-guide"));
+        assert_eq!(guides[1].uri, "docs://style-guides/style/go/naming.md");
+        assert_eq!(
+            (guides[1].area.as_str(), guides[1].lang.as_str()),
+            ("style", "")
+        );
+        assert_eq!(guides[1].category, vec!["style-guide", "go"]);
+
+        let mut agreement = guides[0].clone();
+        agreement.uri = "docs://agreements/backend/php/api-documentation/rest.md".to_string();
+        agreement.category = vec!["agreements".to_string()];
+        let mut mixed: BTreeMap<DocumentKey, ResourceInfo> = [agreement, guides[1].clone()]
+            .into_iter()
+            .map(|info| (DocumentKey::new(info.uri.clone()), info))
+            .collect();
+        assert_eq!(
+            DocumentScanner::exclude_claimed_agreements(
+                &mut mixed,
+                &["docs/backend/php".to_string()]
+            ),
+            1
+        );
+        assert_eq!(mixed.len(), 1);
+    }
+
+    #[test]
+    fn test_scan_of_ten_thousand_files_counts_every_visit() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        for section in 0..100 {
+            let dir = temp_dir.path().join(format!("eva4/section-{:03}", section));
+            fs::create_dir_all(&dir).expect("dir");
+            for page in 0..100 {
+                let extension = if page % 10 == 0 { "txt" } else { "rst" };
+                fs::write(
+                    dir.join(format!("page-{:03}.{}", page, extension)),
+                    "Page\n",
+                )
+                .expect("write");
+            }
+        }
+        let file_reader =
+            FileReader::new(temp_dir.path().to_string_lossy().to_string()).expect("reader");
+        let progress = ScanProgress::new(1_000);
+        let mut resources = BTreeMap::new();
+
+        scan_targets(
+            DocumentType::GuideDoc("eva4".to_string()),
+            &UriTemplates::default(),
+            &["eva4".to_string()],
+            &["rst".to_string()],
+            &file_reader,
+            &progress,
+            &mut resources,
+        );
+
+        assert_eq!(progress.files(), 10_000);
+        assert_eq!(resources.len(), 9_000);
+        assert!(resources.contains_key(&DocumentKey::new(
+            "docs://guides/eva4/section-099/page-099.rst".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_scan_counts_files_per_path_pattern() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        for path in [
+            "architecture/proj-a/c4/c1.mdx",
+            "architecture/proj-a/c4/services/billing.mdx",
+            "architecture/proj-a/erd/orders.mdx",
+            "architecture/proj-a/erd/services/billing.mdx",
+            "architecture/proj-a/adr/001-use-rust.mdx",
+            "architecture/proj-a/adr/002-use-axum.mdx",
+            "openapi-spec/proj-a/billing/v1/public/openapi.yaml",
+            "openapi-spec/proj-a/billing/v1/public/endpoints/invoices.yaml",
+            "openapi-spec/proj-a/billing/v1/internal/admin/refunds.yaml",
+            "openapi-spec/proj-a/billing/v1/internal/admin/endpoints/audit.yaml",
+            "openapi-spec/proj-a/billing/v1/internal/admin/too/deep.yaml",
+            "guides/eva4/install.rst",
+        ] {
+            write_file(docs_root, path, "Doc");
+        }
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+        let progress = ScanProgress::default();
+        let mut resources = BTreeMap::new();
+        for (document_type, target) in [
+            (
+                DocumentType::C1Diagram("proj-a".to_string()),
+                "architecture/proj-a/c4",
+            ),
+            (
+                DocumentType::C4Diagram("proj-a".to_string()),
+                "architecture/proj-a/c4/services",
+            ),
+            (
+                DocumentType::ErdDiagram("proj-a".to_string()),
+                "architecture/proj-a/erd",
+            ),
+            (
+                DocumentType::AdrDocument("proj-a".to_string()),
+                "architecture/proj-a/adr",
+            ),
+            (
+                DocumentType::OpenApiSpec("proj-a".to_string()),
+                "openapi-spec/proj-a",
+            ),
+            (DocumentType::GuideDoc("eva4".to_string()), "guides/eva4"),
+        ] {
+            scan_targets(
+                document_type,
+                &UriTemplates::default(),
+                &[target.to_string()],
+                &[],
+                &file_reader,
+                &progress,
+                &mut resources,
+            );
+        }
+
+        assert_eq!(
+            progress.pattern_counts(),
+            BTreeMap::from([
+                ("adr", 2),
+                ("c4_service", 1),
+                ("c4_system", 1),
+                ("erd", 2),
+                ("openapi", 1),
+                ("openapi_endpoints", 1),
+                ("openapi_sub_category", 1),
+                ("openapi_sub_category_endpoints", 1),
+                ("scan_target", 2),
+            ])
+        );
+        assert_eq!(progress.files(), 13);
+    }
+
+    #[test]
+    fn test_skip_long_uris_drops_deeply_nested_documents() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        let nested = format!("eva4/{}install.rst", "very-deep-directory/".repeat(6));
+        write_file(docs_root, &nested, "Install");
+        write_file(docs_root, "eva4/setup.rst", "Setup");
+
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::GuideDoc("eva4".to_string()),
+            &UriTemplates::default(),
+            &["eva4".to_string()],
+            &["rst".to_string()],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+        assert_eq!(resources.len(), 2);
+
+        assert_eq!(DocumentScanner::skip_long_uris(&mut resources, 100), 1);
+        assert!(
+            resources
+                .values()
+                .all(|info| info.file_path == "eva4/setup.rst")
+        );
+        assert_eq!(DocumentScanner::skip_long_uris(&mut resources, 2048), 0);
+    }
+
+    #[test]
+    fn test_truncate_descriptions_cuts_at_configured_length() {
+        let description = DocumentType::Agreements.generate_description(
+            "backend",
+            "php",
+            &[
+                "agreements".to_string(),
+                "api".to_string(),
+                "versioning".to_string(),
+                "deprecation".to_string(),
+            ],
+            "api-versioning.md",
+        );
+        let length = description.chars().count();
+        let key = DocumentKey::new("docs://agreements/backend/php/api-versioning.md".to_string());
+        let mut resources = BTreeMap::from([(
+            key.clone(),
+            ResourceInfo {
+                description: description.clone(),
+                ..Default::default()
+            },
+        )]);
+
+        DocumentScanner::truncate_descriptions(&mut resources, length);
+        assert_eq!(resources[&key].description, description);
+
+        DocumentScanner::truncate_descriptions(&mut resources, length - 1);
+        let truncated = &resources[&key].description;
+        assert_eq!(truncated.chars().count(), length - 1);
+        assert!(truncated.ends_with('…'));
+        assert!(description.starts_with(truncated.trim_end_matches('…')));
+    }
+
+    #[test]
+    fn test_scan_agreements_with_configured_extensions() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(
+            docs_root,
+            "content/docs/backend/go/contract.yaml",
+            "type: object",
+        );
+        write_file(docs_root, "content/docs/backend/go/style.md", "# Style");
+        write_file(docs_root, "content/docs/backend/go/notes.txt", "notes");
+
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+        let scan = |extensions: &[String]| {
+            let mut resources = BTreeMap::new();
+            scan_targets(
+                DocumentType::Agreements,
+                &UriTemplates::default(),
+                &["content/docs/backend".to_string()],
+                extensions,
+                &file_reader,
+                &ScanProgress::default(),
+                &mut resources,
+            );
+            let mut files: Vec<String> =
+                resources.into_values().map(|info| info.file_path).collect();
+            files.sort();
+            files
+        };
+
+        assert_eq!(
+            scan(&["md".to_string(), "yaml".to_string()]),
+            vec![
+                "content/docs/backend/go/contract.yaml",
+                "content/docs/backend/go/style.md"
+            ]
+        );
+        assert_eq!(
+            scan(&[]),
+            vec![
+                "content/docs/backend/go/notes.txt",
+                "content/docs/backend/go/style.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_accepts_layout_without_content_docs_prefix() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path();
+        write_file(docs_root, "architecture/proj-b/adr/002-queues.mdx", "adr");
+
+        let file_reader = FileReader::new(docs_root.to_string_lossy().to_string()).expect("reader");
+        let mut resources = BTreeMap::new();
+        scan_targets(
+            DocumentType::AdrDocument("proj-b".to_string()),
+            &UriTemplates::default(),
+            &["architecture/proj-b/adr".to_string()],
+            &[],
+            &file_reader,
+            &ScanProgress::default(),
+            &mut resources,
+        );
+
+        let info = resources
+            .get(&DocumentKey::new(
+                "docs://architecture/proj-b/adr/002-queues.mdx".to_string(),
+            ))
+            .expect("adr registered");
+        assert_eq!(info.category, vec!["adr", "ADR-002"]);
+        assert_eq!(info.project, "proj-b");
+    }
 }
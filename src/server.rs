@@ -1,1027 +1,10056 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, LazyLock, Mutex},
+    time::Instant,
+};
 
+use futures::StreamExt;
 use rmcp::{
     ErrorData as McpError, RoleServer, ServerHandler,
     handler::server::{
         router::{prompt::PromptRouter, tool::ToolRouter},
+        tool::ToolCallContext,
         wrapper::Parameters,
     },
     model::{
-        AnnotateAble, CallToolResult, Content, GetPromptRequestParams, GetPromptResult,
-        Implementation, InitializeRequestParams, InitializeResult, ListPromptsResult,
-        ListResourceTemplatesResult, ListResourcesResult, PaginatedRequestParams, ProtocolVersion,
-        RawResource, ReadResourceRequestParams, ReadResourceResult, Resource, ResourceContents,
-        ServerCapabilities, ServerInfo, SubscribeRequestParams, UnsubscribeRequestParams,
+        AnnotateAble, CallToolRequestParams, CallToolResult, ClientCapabilities, Content,
+        ExperimentalCapabilities, GetPromptRequestParams, GetPromptResult, Implementation,
+        InitializeRequestParams, InitializeResult, ListPromptsResult, ListResourceTemplatesResult,
+        ListResourcesResult, ListToolsResult, Meta, PaginatedRequestParams, ProtocolVersion,
+        RawResource, RawResourceTemplate, ReadResourceRequestParams, ReadResourceResult, Resource,
+        ResourceContents, ServerCapabilities, ServerInfo, SetLevelRequestParams,
+        SubscribeRequestParams, UnsubscribeRequestParams,
     },
     prompt_handler, prompt_router, schemars,
     service::RequestContext,
-    tool, tool_handler, tool_router,
+    tool, tool_router,
+    transport::{
+        common::http_header::HEADER_SESSION_ID,
+        streamable_http_server::session::local::LocalSessionManager,
+    },
 };
 use serde_json::json;
+use tracing::Instrument;
 
 use crate::{
-    models::{DocumentKey, ResourceInfo},
-    utils::file_reader::FileReader,
+    config::{
+        ContentConfig, EffectiveExtensions, ExpectationsConfig, StalenessConfig, ToolOverride,
+    },
+    errors,
+    index::{self, ScanPhase, ScanPlanStep, ScanStatus, ScanTracker, SharedIndex},
+    logging::{self, LogBridge, SessionLog},
+    models::{DocumentKey, DocumentScanner, ResourceInfo, file_stem},
+    store::{FilterSet, OPENAPI_URI_SCHEME, ResourceStore, matches_filter, matches_lang_filter},
+    subscriptions::{SessionSubscriptions, SubscriptionRegistry},
+    usage::UsageCounters,
+    utils::{
+        c4_graph, date, erd,
+        file_reader::{
+            FileReader, content_revision, normalize_relative_path, resolve_relative_link,
+        },
+        markdown, normalize,
+        openapi::{self, MultiDocumentMode},
+        parse_cache::{CacheStats, ParseCache},
+        search::{self, TermCounts},
+        transclusion,
+    },
 };
 
-#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct GetResourceContentArgs {
+    /// Resource path in format docs://path/to/file, or a docs-root relative file path when the
+    /// server accepts file paths
+    pub path: String,
+    /// Revision from a previous read; when it still matches, only `not_modified: true` is returned
+    pub if_revision_not: Option<String>,
+    /// Remove JSX/HTML-like component tags from .mdx files, keeping their inner text (default: false)
+    pub strip_jsx: Option<bool>,
+    /// Replace markdown partial includes (MDX imports of .md/.mdx files, `<!-- include: path -->`)
+    /// with the partial's content, recursively (default: false)
+    pub inline_includes: Option<bool>,
+    /// Soft-wrap markdown prose longer than this many characters for display; code blocks,
+    /// tables and non-markdown files are left intact (default: off)
+    pub wrap_column: Option<u32>,
+    /// With `wrap_column`, also break lines of YAML/JSON and other non-markdown files at the
+    /// column. Display only: the result is no longer valid YAML/JSON (default: false)
+    pub wrap_code: Option<bool>,
+    /// Return one JSON object with the parsed markdown frontmatter and the body after it
+    /// instead of the raw text (default: false)
+    pub split_frontmatter: Option<bool>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ResourceRevision {
+    /// Canonical resource URI
+    pub uri: String,
+    /// Requested path when it was not the canonical URI (a `uri_aliases` rewrite, a tolerated
+    /// formatting difference or a file path); clients should switch to `uri`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aliased_from: Option<String>,
+    /// Content revision; changes whenever the file content changes
+    pub revision: String,
+    /// True when the content is unchanged since `if_revision_not` and was omitted
+    pub not_modified: bool,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct SplitContentResponse {
+    #[serde(flatten)]
+    pub revision: ResourceRevision,
+    /// Frontmatter fields; empty when the document has none or is not markdown
+    pub frontmatter: serde_json::Map<String, serde_json::Value>,
+    /// Content after the frontmatter
+    pub body: String,
+}
+
+/// Maximum number of URIs accepted by `get_metadata_batch` in one call
+const MAX_METADATA_BATCH_SIZE: usize = 200;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetNormalizedContentArgs {
     /// Resource path in format docs://path/to/file
     pub path: String,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
-pub struct GetDocsListArgs {
-    /// Area filter (e.g., "architecture", "backend", "frontend") - supports OR with | separator
-    pub area: Option<String>,
-    /// Language filter (e.g.,"php", "go", "ts", "js", "py", "rust") - supports OR with | separator  
-    pub lang: Option<String>,
-    /// Category filter (e.g., "c1", "c2", "c3", "c4", "api-documentation") - supports OR with | separator
-    pub category: Option<String>,
+pub struct NormalizedContentResponse {
+    /// Resource URI
+    pub uri: String,
+    /// Applied normalization: "yaml" or "json" (keys sorted) or "text"
+    pub normalization: String,
+    /// Normalized content
+    pub content: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetMetadataBatchArgs {
+    /// Resource URIs in format docs://path/to/file (max 200)
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct MetadataBatchResponse {
+    /// Metadata of found resources, in request order
+    pub documents: Vec<ResourceInfo>,
+    /// Requested URIs that are not present in scanned documents
+    pub not_found: Vec<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct RefreshResourceArgs {
+    /// Resource URI in format docs://path/to/file
+    pub path: String,
+}
+
+/// Maximum number of suggestions returned when a lookup misses
+const MAX_SUGGESTIONS: usize = 5;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ResolvePathArgs {
+    /// File path relative to the docs root (e.g., "content/docs/architecture/mpa/adr/007-outbox.mdx")
+    pub file_path: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ResolvePathResponse {
+    /// Resolved docs:// URI
+    pub uri: String,
+    /// How the path was matched: "exact" or "suffix"
+    pub match_type: String,
+    /// Metadata of the resolved resource
+    pub resource: ResourceInfo,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ResolveLinksArgs {
+    /// Document URI in format docs://path/to/file
+    pub uri: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ResolvedLink {
+    /// Link text as written in the document
+    pub text: String,
+    /// Link target as written in the document
+    pub target: String,
+    /// Indexed docs:// URI the target points to; null when the file is not indexed
+    pub uri: Option<String>,
+    /// Fragment of the target without the leading '#'
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ResolveLinksResponse {
+    /// URI of the document the links were extracted from
+    pub uri: String,
+    /// Relative links in document order
+    pub links: Vec<ResolvedLink>,
+}
+
+/// Default and maximum page size of get_uri_map
+const DEFAULT_URI_MAP_LIMIT: u32 = 1000;
+const MAX_URI_MAP_LIMIT: u32 = 5000;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetUriMapArgs {
+    /// Optional project filter - supports OR with | separator
+    pub project: Option<String>,
     /// Page number for pagination (default: 1)
     pub page: Option<u32>,
-    /// Number of items per page (default: 50, max: 200)
+    /// Number of entries per page (default: 1000, max: 5000)
     pub limit: Option<u32>,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
-pub struct DocsListResponse {
-    /// List of matching documents
-    pub documents: Vec<ResourceInfo>,
-    /// Total number of pages
-    pub total_pages: u32,
+pub struct UriMapResponse {
+    /// File path (relative to docs root) -> docs:// URI
+    pub path_to_uri: BTreeMap<String, String>,
+    /// docs:// URI -> file path, for the same entries
+    pub uri_to_path: BTreeMap<String, String>,
+    /// Total number of entries matching the filter
+    pub total_entries: u32,
     /// Current page number
     pub current_page: u32,
-    /// Number of items per page
-    pub limit: u32,
-    /// Total number of matching documents
-    pub total_documents: u32,
+    /// Total number of pages
+    pub total_pages: u32,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
-pub struct AdrListResponse {
-    /// List of ADR documents sorted by ADR number
-    pub adr_documents: Vec<ResourceInfo>,
-    /// Total number of ADR documents
-    pub total_adr_documents: u32,
+pub struct LintAdrArgs {
+    /// ADR URI in format docs://path/to/file; mutually exclusive with project
+    pub uri: Option<String>,
+    /// Lint all ADRs of this project; mutually exclusive with uri
+    pub project: Option<String>,
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
-pub struct GetAllAdrDocumentsArgs {}
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct LintFinding {
+    /// Machine-readable finding code (e.g., "missing_section")
+    pub code: String,
+    /// "error" or "warning"
+    pub severity: String,
+    /// Human-readable description
+    pub message: String,
+}
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
-pub struct GetProjectOverviewArgs {
-    /// Project name (as defined in `arch-mcp.toml`)
-    pub project: String,
+pub struct AdrLintReport {
+    /// ADR URI
+    pub uri: String,
+    /// Findings; empty when the ADR is compliant
+    pub findings: Vec<LintFinding>,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
-pub struct GetAgreementsArgs {
-    /// Programming language filter (e.g., "php", "go", "js", "ts", "py", "rust")
-    pub lang: String,
+pub struct AdrLintResponse {
+    /// Per-document lint results
+    pub documents: Vec<AdrLintReport>,
+    /// Number of linted ADRs
+    pub total_documents: u32,
+    /// Number of ADRs with at least one finding
+    pub documents_with_findings: u32,
 }
 
+/// Default word count below which a document is reported by `get_undocumented`
+const DEFAULT_MIN_WORDS: u32 = 50;
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
-pub struct AgreementsResponse {
-    /// Programming language filter applied
-    pub lang: String,
-    /// List of agreement documents for the specified language
-    pub agreements: Vec<ResourceInfo>,
-    /// Total number of agreement documents found
-    pub total_agreements: u32,
+pub struct GetUndocumentedArgs {
+    /// Optional project filter (as defined in `arch-mcp.toml`)
+    pub project: Option<String>,
+    /// Minimum word count of the document body (default: 50)
+    pub min_words: Option<u32>,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
-pub struct GetGuidesArgs {
-    /// Optional product name filter (e.g., "eva4", "psrt"). If omitted, returns all guide documents.
-    pub product: Option<String>,
+pub struct UndocumentedDocument {
+    /// Resource URI
+    pub uri: String,
+    /// Why the document was reported: missing_title, below_word_threshold, invalid_frontmatter, unreadable
+    pub reasons: Vec<String>,
+    /// Word count of the document body (without frontmatter)
+    pub word_count: u32,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
-pub struct GuidesResponse {
-    /// Product filter applied (if any)
-    pub product: Option<String>,
-    /// List of guide documents
-    pub guides: Vec<ResourceInfo>,
-    /// Total number of guide documents
-    pub total_guides: u32,
+pub struct UndocumentedResponse {
+    /// Word count threshold applied
+    pub min_words: u32,
+    /// Under-documented markdown documents
+    pub documents: Vec<UndocumentedDocument>,
+    /// Total number of reported documents
+    pub total_documents: u32,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
-pub struct ProjectOverviewResponse {
-    /// Project name
-    pub project: String,
-    /// Total number of documents in project
-    pub total_documents: u32,
-    /// Total size of all documents in bytes
-    pub total_size: u64,
-    /// Documents grouped by type
-    pub documents_by_type: std::collections::BTreeMap<String, Vec<ResourceInfo>>,
-    /// Documents grouped by area
-    pub documents_by_area: std::collections::BTreeMap<String, Vec<ResourceInfo>>,
-    /// Documents grouped by language
-    pub documents_by_language: std::collections::BTreeMap<String, Vec<ResourceInfo>>,
-    /// All documents in the project
-    pub all_documents: Vec<ResourceInfo>,
+pub struct GetUnclassifiedArgs {}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct UnclassifiedDocument {
+    pub uri: String,
+    /// Path relative to the docs root, to locate the file to move
+    pub file_path: String,
+    /// Empty or a generic fallback such as "agreements"
+    pub area: String,
+    pub category: Vec<String>,
 }
 
-#[derive(Clone)]
-pub struct DocumentServer {
-    file_reader: FileReader,
-    resources: BTreeMap<DocumentKey, ResourceInfo>,
-    tool_router: ToolRouter<DocumentServer>,
-    prompt_router: PromptRouter<DocumentServer>,
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct UnclassifiedResponse {
+    pub documents: Vec<UnclassifiedDocument>,
+    pub total_documents: u32,
 }
 
-#[tool_router]
-impl DocumentServer {
-    pub fn new_with_resources(
-        file_reader: FileReader,
-        resources: BTreeMap<DocumentKey, ResourceInfo>,
-    ) -> Self {
-        Self {
-            file_reader,
-            resources,
-            tool_router: Self::tool_router(),
-            prompt_router: Self::prompt_router(),
-        }
-    }
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetStaleDocumentsArgs {
+    /// Document type (adr, openapi, c1, ...) -> maximum age in days, overriding the server's
+    /// [staleness] thresholds for those types
+    #[serde(default)]
+    pub max_age_days: BTreeMap<String, u32>,
+    /// Optional project filter (as defined in `arch-mcp.toml`)
+    pub project: Option<String>,
+}
 
-    /// Reads file content by file path
-    fn read_file_by_path(&self, file_path: &str) -> Result<String, McpError> {
-        self.file_reader.read_file_content(file_path).map_err(|e| {
-            McpError::internal_error(
-                "file_read_error",
-                Some(json!({
-                    "file_path": file_path,
-                    "error": format!("Failed to read file: {}", e)
-                })),
-            )
-        })
-    }
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct StaleDocument {
+    pub uri: String,
+    pub document_type: String,
+    /// File modification time (RFC 3339)
+    pub last_modified: String,
+    pub age_days: u32,
+    /// Threshold the document exceeded
+    pub max_age_days: u32,
+}
 
-    /// Checks if a value matches any of the filter values (supports OR with | separator)
-    pub fn matches_filter(value: &str, filter: Option<&String>) -> bool {
-        match filter {
-            None => true,
-            Some(filter_str) => filter_str
-                .split('|')
-                .any(|filter_value| filter_value.trim() == value),
-        }
-    }
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct StaleProject {
+    /// Empty for documents outside every project
+    pub project: String,
+    /// Oldest first
+    pub documents: Vec<StaleDocument>,
+}
 
-    /// Checks if any category in the categories array matches any of the filter values
-    pub fn matches_category_filter(categories: &[String], filter: Option<&String>) -> bool {
-        match filter {
-            None => true,
-            Some(filter_str) => {
-                let filter_values: Vec<&str> = filter_str.split('|').map(str::trim).collect();
-                categories.iter().any(|category| {
-                    filter_values
-                        .iter()
-                        .any(|filter_value| filter_value == category)
-                })
-            }
-        }
-    }
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct StaleDocumentsResponse {
+    /// Thresholds applied per document type
+    pub max_age_days: BTreeMap<String, u32>,
+    /// Threshold of types not listed in `max_age_days`; other types are not checked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_max_age_days: Option<u32>,
+    /// Stale documents grouped by project
+    pub projects: Vec<StaleProject>,
+    pub total_stale: u32,
+    /// Checked documents whose modification time is unknown
+    pub unknown_age: Vec<String>,
+}
 
-    /// Filters documents based on the provided criteria
-    fn filter_documents(&self, args: &GetDocsListArgs) -> Vec<&ResourceInfo> {
-        self.resources
-            .values()
-            .filter(|info| {
-                // Check area filter
-                let area_matches = Self::matches_filter(&info.area, args.area.as_ref());
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetArchitectureGraphArgs {
+    /// Project name (as defined in `arch-mcp.toml`)
+    pub project: String,
+}
 
-                // Check lang filter
-                let lang_matches = Self::matches_filter(&info.lang, args.lang.as_ref());
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ArchitectureNode {
+    /// Alias used by relationships
+    pub id: String,
+    pub name: String,
+    /// C4 element type, e.g. person, system_ext, container_db
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub technology: Option<String>,
+    /// URIs of the documents declaring this element
+    pub sources: Vec<String>,
+}
 
-                // Check category filter - now works with array of categories
-                let category_matches =
-                    Self::matches_category_filter(&info.category, args.category.as_ref());
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ArchitectureEdge {
+    pub from: String,
+    pub to: String,
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub technology: Option<String>,
+    /// URIs of the documents declaring this relationship
+    pub sources: Vec<String>,
+}
 
-                area_matches && lang_matches && category_matches
-            })
-            .collect()
-    }
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GraphParseError {
+    /// URI of the document that could not be parsed
+    pub uri: String,
+    pub error: String,
+}
 
-    #[tool(
-        description = "Retrieves documentation content from docs:// paths. Use for reading architecture docs, API specs, guides, and technical documentation. Paths must start with 'docs://' prefix. Supports all document types including C4 diagrams, ERD diagrams, ADR documents, and API agreements. Returns raw file content as text for further processing by AI agents.",
-        annotations(
-            title = "📄 Get Documentation Resource Content",
-            read_only_hint = true,
-            destructive_hint = false,
-            idempotent_hint = true,
-            open_world_hint = false
-        )
-    )]
-    async fn get_resource_content(
-        &self,
-        Parameters(GetResourceContentArgs { path }): Parameters<GetResourceContentArgs>,
-    ) -> Result<CallToolResult, McpError> {
-        if !path.starts_with("docs://") {
-            return Err(McpError::invalid_params(
-                "invalid_path",
-                Some(json!({
-                    "error": "Path must start with 'docs://'",
-                    "provided_path": path
-                })),
-            ));
-        }
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ArchitectureGraphResponse {
+    pub project: String,
+    /// Elements merged across C1-C4 documents by alias
+    pub nodes: Vec<ArchitectureNode>,
+    /// Relationships merged by (from, to, label)
+    pub edges: Vec<ArchitectureEdge>,
+    /// Number of C-level documents read
+    pub documents_parsed: u32,
+    /// Documents skipped because they could not be read or parsed
+    pub parse_errors: Vec<GraphParseError>,
+}
 
-        // First, find the resource by URI in our resources map
-        let resource_info = self
-            .resources
-            .get(&DocumentKey::new(path.clone()))
-            .ok_or_else(|| {
-                McpError::resource_not_found(
-                    "resource_not_found",
-                    Some(json!({
-                        "uri": path,
-                        "error": "Resource not found in scanned documents"
-                    })),
-                )
-            })?;
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetProjectDependenciesArgs {
+    /// Optional project whose outgoing dependencies are reported; all projects when omitted
+    pub project: Option<String>,
+}
 
-        // Then read the file content using the file path from ResourceInfo
-        let content = self.read_file_by_path(&resource_info.file_path)?;
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ProjectDependency {
+    /// Referenced project
+    pub project: String,
+    /// C1/C2 diagram URIs referencing it
+    pub evidence: Vec<String>,
+}
 
-        Ok(CallToolResult::success(vec![Content::text(content)]))
-    }
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ProjectDependencies {
+    pub project: String,
+    pub depends_on: Vec<ProjectDependency>,
+}
 
-    #[tool(
-        description = "Lists documentation resources with advanced filtering and pagination capabilities. Use this tool to search and browse architecture documents, API specifications, technical guides, and project documentation. Supports filtering by area (backend|frontend|architecture), programming language (php|go|js|ts), and category (agreements|api-documentation|c1|c2|c3|c4|erd) using OR logic with | separator. Perfect for finding specific document types like C4 diagrams (category=c4), API documentation (category=api-documentation), or backend PHP docs (area=backend&lang=php). Returns paginated results with metadata including file paths, sizes, and URIs. Default limit: 50, max: 200. Use for document discovery, architecture analysis, and technical documentation research. Essential for understanding project structure and finding relevant documentation.",
-        annotations(
-            title = "📋 Get Documentation List with Filters",
-            read_only_hint = true,
-            destructive_hint = false,
-            idempotent_hint = true,
-            open_world_hint = false
-        )
-    )]
-    async fn get_docs_list(
-        &self,
-        Parameters(args): Parameters<GetDocsListArgs>,
-    ) -> Result<CallToolResult, McpError> {
-        // Set default values
-        let page = args.page.unwrap_or(1);
-        let limit = args.limit.unwrap_or(50);
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct UnmatchedExternalSystem {
+    /// Project whose diagram references the system
+    pub project: String,
+    pub id: String,
+    pub name: String,
+    /// Diagram URIs declaring it
+    pub sources: Vec<String>,
+}
 
-        // Validate pagination parameters
-        if page == 0 {
-            return Err(McpError::invalid_params(
-                "invalid_page",
-                Some(json!({
-                    "error": "Page must be greater than 0",
-                    "provided_page": page
-                })),
-            ));
-        }
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ProjectDependenciesResponse {
+    /// Adjacency list: project -> referenced projects
+    pub dependencies: Vec<ProjectDependencies>,
+    /// External systems that match no configured project or alias
+    pub unmatched_external_systems: Vec<UnmatchedExternalSystem>,
+    /// Diagrams that could not be read or parsed
+    pub parse_errors: Vec<GraphParseError>,
+}
 
-        if limit == 0 || limit > 200 {
-            return Err(McpError::invalid_params(
-                "invalid_limit",
-                Some(json!({
-                    "error": "Limit must be between 1 and 200",
-                    "provided_limit": limit
-                })),
-            ));
-        }
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetEndpointsForTagArgs {
+    /// Operation tag (case-insensitive), e.g. "billing"
+    pub tag: String,
+    /// Optional project filter (as defined in `arch-mcp.toml`)
+    pub project: Option<String>,
+}
 
-        // Filter documents
-        let filtered_docs = self.filter_documents(&args);
-        let total_documents = filtered_docs.len().try_into().unwrap_or(u32::MAX);
-        let total_pages = total_documents.div_ceil(limit);
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct TaggedEndpoint {
+    /// Upper-case HTTP method
+    pub method: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// URI of the spec declaring the operation
+    pub spec_uri: String,
+}
 
-        // Calculate pagination
-        let start_index = ((page - 1) * limit) as usize;
-        let end_index = std::cmp::min(start_index + limit as usize, filtered_docs.len());
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct EndpointsForTagResponse {
+    pub tag: String,
+    /// Service -> operations carrying the tag
+    pub services: BTreeMap<String, Vec<TaggedEndpoint>>,
+    pub total_operations: u32,
+}
 
-        // Get paginated results
-        let paginated_docs: Vec<ResourceInfo> = filtered_docs[start_index..end_index]
-            .iter()
-            .map(|info| (*info).clone())
-            .collect();
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetOpenApiSchemaArgs {
+    /// Schema name as declared in components.schemas / definitions (case-sensitive), e.g. "Customer"
+    pub schema_name: String,
+    /// Optional project filter (as defined in `arch-mcp.toml`)
+    pub project: Option<String>,
+    /// Optional service filter (second segment of openapi-spec/{project}/{service}/...)
+    pub service: Option<String>,
+}
 
-        // Create response
-        let response = DocsListResponse {
-            documents: paginated_docs,
-            total_pages,
-            current_page: page,
-            limit,
-            total_documents,
-        };
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct OpenApiSchemaMatch {
+    /// URI of the spec defining the schema
+    pub spec_uri: String,
+    pub project: String,
+    pub service: String,
+    /// "components.schemas" (OpenAPI 3) or "definitions" (Swagger 2.0)
+    pub section: String,
+    /// Schema definition with nested structure preserved ($ref values are not resolved)
+    pub schema: serde_json::Value,
+}
 
-        // Serialize response to JSON
-        let response_json = serde_json::to_value(&response).map_err(|e| {
-            McpError::internal_error(
-                "serialization_error",
-                Some(json!({
-                    "error": format!("Failed to serialize response: {}", e)
-                })),
-            )
-        })?;
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct OpenApiSchemaResponse {
+    pub schema_name: String,
+    /// Every spec defining the schema
+    pub matches: Vec<OpenApiSchemaMatch>,
+    /// Number of distinct definitions among the matches
+    pub distinct_definitions: u32,
+    /// True when the specs define the schema differently
+    pub diverged: bool,
+}
 
-        Ok(CallToolResult::success(vec![Content::text(
-            response_json.to_string(),
-        )]))
-    }
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct DiffOpenApiVersionsArgs {
+    /// Project name (as defined in `arch-mcp.toml`)
+    pub project: String,
+    /// Service name (second segment of openapi-spec/{project}/{service}/...)
+    pub service: String,
+    /// Older version id (third segment), e.g. "v1"
+    pub from_version: String,
+    /// Newer version id, e.g. "v2"
+    pub to_version: String,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct OpenApiVersionsDiffResponse {
+    pub project: String,
+    pub service: String,
+    pub from_version: String,
+    pub to_version: String,
+    /// URIs of the specs merged into each side, all access levels
+    pub from_specs: Vec<String>,
+    pub to_specs: Vec<String>,
+    /// Changed paths; unchanged paths are omitted
+    pub paths: BTreeMap<String, openapi::PathChange>,
+    pub total_changed_paths: u32,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetServiceVersionsArgs {
+    /// Project name (as defined in `arch-mcp.toml`)
+    pub project: String,
+    /// Optional service filter (second segment of openapi-spec/{project}/{service}/...)
+    pub service: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ServiceVersion {
+    pub version: String,
+    /// Access level (e.g. "public", "internal") -> number of specs
+    pub specs_by_access_level: BTreeMap<String, u32>,
+    pub total_specs: u32,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ServiceVersions {
+    pub service: String,
+    /// Documented versions in natural order ("v10" after "v9")
+    pub versions: Vec<ServiceVersion>,
+    /// `v<N>` versions skipped between the lowest and highest documented one
+    pub missing_versions: Vec<String>,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ServiceVersionsResponse {
+    pub project: String,
+    pub services: Vec<ServiceVersions>,
+    pub total_services: u32,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct MapErdToApiArgs {
+    /// Project name (as defined in `arch-mcp.toml`)
+    pub project: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ApiSchemaRef {
+    pub name: String,
+    /// URI of the spec declaring the schema
+    pub spec_uri: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ErdEntityMapping {
+    /// Entity name as first declared
+    pub entity: String,
+    /// ERD documents declaring the entity
+    pub erd_uris: Vec<String>,
+    /// Corresponding API schemas; empty for unmatched entities
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub schemas: Vec<ApiSchemaRef>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ErdToApiResponse {
+    pub project: String,
+    /// Entities with at least one API schema of the same name
+    pub matched: Vec<ErdEntityMapping>,
+    /// Entities no spec exposes a schema for
+    pub unmatched_entities: Vec<ErdEntityMapping>,
+    /// API schemas matching no ERD entity
+    pub unmatched_schemas: Vec<ApiSchemaRef>,
+    pub erd_documents_parsed: u32,
+    pub specs_parsed: u32,
+    /// ERDs and specs skipped because they could not be read or parsed
+    pub parse_errors: Vec<GraphParseError>,
+}
+
+/// Default and maximum number of results returned by `rank_documents`
+const DEFAULT_RANK_LIMIT: u32 = 10;
+const MAX_RANK_LIMIT: u32 = 50;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct RankDocumentsArgs {
+    /// Free-text query; split into lowercase terms
+    pub query: String,
+    /// Optional project filter (as defined in `arch-mcp.toml`)
+    pub project: Option<String>,
+    /// Number of results (default: 10, max: 50)
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct RankedDocument {
+    pub uri: String,
+    pub description: String,
+    /// TF-IDF score, higher is more relevant
+    pub score: f64,
+    /// Occurrences of each query term in the document
+    pub matched_terms: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct RankDocumentsResponse {
+    /// Query terms after tokenization
+    pub terms: Vec<String>,
+    /// Best matches, highest score first
+    pub results: Vec<RankedDocument>,
+    /// Number of documents containing at least one term
+    pub total_matches: u32,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetMostReferencedArgs {
+    /// Only rank documents of this project; links from any project count
+    pub project: Option<String>,
+    /// Number of results (default: 10, max: 50)
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ReferencedDocument {
+    pub uri: String,
+    pub description: String,
+    /// Number of other documents linking here
+    pub references: u32,
+    /// URIs of the linking documents
+    pub referenced_by: Vec<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct MostReferencedResponse {
+    /// Most-linked documents first
+    pub documents: Vec<ReferencedDocument>,
+    /// Number of documents with at least one incoming link
+    pub total_referenced: u32,
+}
+
+/// Index name of export_search_index when none is given
+const DEFAULT_SEARCH_INDEX: &str = "arch-docs";
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExportSearchIndexArgs {
+    /// Target index of the bulk actions (default: "arch-docs")
+    pub index_name: Option<String>,
+    /// Add each document's text as a `content` field (default: false)
+    pub include_content: Option<bool>,
+    /// Optional project filter (as defined in `arch-mcp.toml`)
+    pub project: Option<String>,
+    /// Documents to skip, e.g. the next_offset of a trimmed export (default: 0)
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct SearchIndexExportResponse {
+    pub index_name: String,
+    /// Exported documents, one action/document line pair each
+    pub documents: u32,
+    /// Documents matching the filter, exported or not
+    pub total_documents: u32,
+    /// Offset of the first document left out to fit `max_response_bytes`; export again
+    /// with it as `offset` to continue. Absent when nothing was left out.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<u32>,
+    /// Documents exported without content: unreadable or blocked by the content policy
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub content_skipped: Vec<String>,
+    /// ElasticSearch/OpenSearch `_bulk` body: an `index` action line (document URI as `_id`)
+    /// followed by the document's metadata line, newline-terminated
+    pub ndjson: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetProjectGraphDotArgs {
+    /// Project name (as defined in `arch-mcp.toml`)
+    pub project: String,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ProjectGraphDotResponse {
+    pub project: String,
+    /// Documents of the project, one node each
+    pub nodes: u32,
+    /// Links between two documents of the project
+    pub edges: u32,
+    /// Graphviz `digraph` with document URIs as node ids and titles as labels
+    pub dot: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetUsageStatsArgs {
+    /// Only count reads of this project's documents
+    pub project: Option<String>,
+    /// Number of results (default: 10, max: 50)
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct DocumentUsage {
+    pub uri: String,
+    /// Empty for documents no longer indexed
+    pub project: String,
+    /// get_resource_content and resources/read calls for the document
+    pub reads: u64,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct UsageStatsResponse {
+    /// Most-read documents first
+    pub documents: Vec<DocumentUsage>,
+    /// Number of documents read at least once
+    pub documents_read: u32,
+    /// Reads summed over those documents
+    pub total_reads: u64,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetParseFailuresArgs {
+    /// Optional project filter (as defined in `arch-mcp.toml`)
+    pub project: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ParseFailure {
+    /// Resource URI
+    pub uri: String,
+    /// File path relative to docs_root
+    pub file_path: String,
+    /// Format that failed to parse: openapi or frontmatter
+    pub format: String,
+    /// Parser error message
+    pub error: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ParseFailuresResponse {
+    /// Documents whose format-specific parse failed during the last scan
+    pub failures: Vec<ParseFailure>,
+    /// Total number of failures
+    pub total_failures: u32,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetDocsListArgs {
+    /// Area filter (e.g., "architecture", "backend", "frontend") - supports OR with | separator
+    pub area: Option<String>,
+    /// Language filter (e.g.,"php", "go", "ts", "js", "py", "rust") - supports OR with | separator;
+    /// matches any of a document's langs
+    pub lang: Option<String>,
+    /// Category filter (e.g., "c1", "c2", "c3", "c4", "api-documentation") - supports OR with | separator
+    pub category: Option<String>,
+    /// Project filter (e.g., "mpa") - supports OR with | separator
+    pub project: Option<String>,
+    /// ResourceInfo fields to include per document (uri is always included), e.g. ["uri", "description"]
+    pub fields: Option<Vec<String>>,
+    /// Exclude documents that failed spec validation
+    pub only_valid: Option<bool>,
+    /// Return only documents that failed spec validation
+    pub only_invalid: Option<bool>,
+    /// Return only documents whose token_estimate is at most this; documents without an
+    /// estimate (binary or over the server's max_file_size) are excluded
+    pub max_tokens: Option<u32>,
+    /// Fold language variants into one entry in the preferred language, listing the rest under other_languages
+    pub collapse_variants: Option<bool>,
+    /// Result order: "uri" (default) or "priority" (configured type_priority categories first, then URI)
+    pub sort_by: Option<String>,
+    /// Page number for pagination (default: 1)
+    pub page: Option<u32>,
+    /// Number of items per page (default: 50, max: 200)
+    pub limit: Option<u32>,
+    /// Return the page's documents grouped by "project", "area", "lang", "category" or
+    /// "mime_type" instead of a flat list. Pagination counts documents, not groups
+    pub group_by: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct DocsListResponse {
+    /// List of matching documents
+    pub documents: Vec<ResourceInfo>,
+    /// Total number of pages
+    pub total_pages: u32,
+    /// Current page number
+    pub current_page: u32,
+    /// Number of items per page
+    pub limit: u32,
+    /// Total number of matching documents
+    pub total_documents: u32,
+}
+
+/// get_docs_list response with `group_by` set
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct GroupedDocsListResponse {
+    pub group_by: String,
+    /// Group value -> the page's documents having it, "" for documents without one. A
+    /// document with several categories or langs is listed in each of their groups
+    pub groups: BTreeMap<String, Vec<ResourceInfo>>,
+    pub total_pages: u32,
+    pub current_page: u32,
+    pub limit: u32,
+    /// Total number of matching documents, each counted once
+    pub total_documents: u32,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct AdrListResponse {
+    /// List of ADR documents sorted by ADR number (or date when requested)
+    pub adr_documents: Vec<ResourceInfo>,
+    /// Total number of ADR documents
+    pub total_adr_documents: u32,
+    /// ADRs without adr_date dropped by a since/until filter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excluded_without_date: Option<u32>,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetAllAdrDocumentsArgs {
+    /// "number" (default) or "date"; undated ADRs sort last by date
+    pub sort_by: Option<String>,
+    /// Only ADRs dated on or after this day (e.g. 2024-01-01)
+    pub since: Option<String>,
+    /// Only ADRs dated on or before this day
+    pub until: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetGlobalAdrsArgs {}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetProjectOverviewArgs {
+    /// Project name (as defined in `arch-mcp.toml`)
+    pub project: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetProjectReadmeArgs {
+    /// Project name (e.g., "mpa")
+    pub project: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ProjectReadmeResponse {
+    /// Project name
+    pub project: String,
+    /// URI of the overview document
+    pub uri: String,
+    /// MIME type of the overview document
+    pub mime_type: String,
+    /// Raw file content
+    pub content: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetAgreementsArgs {
+    /// Programming language filter (e.g., "php", "go", "js", "ts", "py", "rust")
+    pub lang: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetScanPlanArgs {}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ScanPlanResponse {
+    pub total_steps: u32,
+    pub steps: Vec<ScanPlanStep>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetProjectManifestArgs {
+    /// Project name (as defined in `arch-mcp.toml`)
+    pub project: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ProjectManifestResponse {
+    pub project: String,
+    /// Manifest location relative to the docs root
+    pub file_path: String,
+    /// Parsed manifest JSON
+    pub manifest: serde_json::Value,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct AgreementsResponse {
+    /// Programming language filter applied
+    pub lang: String,
+    /// List of agreement documents for the specified language
+    pub agreements: Vec<ResourceInfo>,
+    /// Total number of agreement documents found
+    pub total_agreements: u32,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetAgreementsForProjectArgs {
+    /// Project name (as defined in `arch-mcp.toml`)
+    pub project: String,
+    /// Optional programming language filter (e.g., "php", "go")
+    pub lang: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct AgreementsForProjectResponse {
+    pub project: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    /// Agreements listing the project in frontmatter `applies_to`
+    pub scoped: Vec<ResourceInfo>,
+    /// Agreements without `applies_to`, which apply to every project
+    pub global: Vec<ResourceInfo>,
+    pub total_agreements: u32,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetAgreementsIndexArgs {}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct LanguageAgreements {
+    /// Number of agreement documents for this area and language
+    pub count: u32,
+    /// URIs of the agreement documents
+    pub uris: Vec<String>,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct AreaAgreements {
+    /// Number of agreement documents in this area
+    pub count: u32,
+    /// Agreements grouped by language
+    pub languages: BTreeMap<String, LanguageAgreements>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct AgreementsIndexResponse {
+    /// Agreements grouped by area, then by language
+    pub areas: BTreeMap<String, AreaAgreements>,
+    /// Total number of agreement documents
+    pub total_agreements: u32,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct DiffAgreementsArgs {
+    /// First programming language (e.g., "php")
+    pub lang_a: String,
+    /// Language to compare it with (e.g., "go")
+    pub lang_b: String,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct AgreementsDiffResponse {
+    pub lang_a: String,
+    pub lang_b: String,
+    /// Agreement documents of lang_a
+    pub agreements_a: u32,
+    /// Agreement documents of lang_b
+    pub agreements_b: u32,
+    /// `area/category` paths with agreements in lang_a only
+    pub categories_only_in_a: Vec<String>,
+    /// `area/category` paths with agreements in lang_b only
+    pub categories_only_in_b: Vec<String>,
+    /// Topics (`area/category/file name without extension`) covered in lang_a only -> URIs
+    pub topics_only_in_a: BTreeMap<String, Vec<String>>,
+    /// Topics covered in lang_b only -> URIs
+    pub topics_only_in_b: BTreeMap<String, Vec<String>>,
+    /// Topics covered in both languages
+    pub shared_topics: Vec<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetGuidesArgs {
+    /// Optional product name filter (e.g., "eva4", "psrt"). If omitted, returns all guide documents.
+    pub product: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GuidesResponse {
+    /// Product filter applied (if any)
+    pub product: Option<String>,
+    /// List of guide documents
+    pub guides: Vec<ResourceInfo>,
+    /// Total number of guide documents
+    pub total_guides: u32,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetStyleGuidesArgs {
+    /// Optional area filter (e.g., "backend", "frontend")
+    pub area: Option<String>,
+    /// Optional programming language filter (e.g., "php", "go")
+    pub lang: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct StyleGuidesResponse {
+    pub area: Option<String>,
+    pub lang: Option<String>,
+    /// Style guide documents, ordered by URI
+    pub style_guides: Vec<ResourceInfo>,
+    pub total_style_guides: u32,
+}
+
+/// Borrows the indexed documents; they are only copied when serialized.
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ProjectOverviewResponse<'a> {
+    /// Project name
+    pub project: &'a str,
+    /// Total number of documents in project
+    pub total_documents: u32,
+    /// Total size of all documents in bytes
+    pub total_size: u64,
+    /// Documents grouped by type
+    pub documents_by_type: BTreeMap<&'a str, Vec<&'a ResourceInfo>>,
+    /// Documents grouped by area
+    pub documents_by_area: BTreeMap<&'a str, Vec<&'a ResourceInfo>>,
+    /// Documents grouped by language
+    pub documents_by_language: BTreeMap<&'a str, Vec<&'a ResourceInfo>>,
+    /// All documents in the project
+    pub all_documents: Vec<&'a ResourceInfo>,
+    /// Expected documentation set check; absent when no expectations are configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completeness: Option<DocumentationCompleteness>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct DocumentationCompleteness {
+    /// Expected document type -> "present" or "missing"
+    pub documents: BTreeMap<String, String>,
+    /// Share of expected document types present, rounded to a whole percent
+    pub percentage: u32,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetDocumentationCoverageArgs {}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetArchitectureOverviewArgs {}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ProjectArchitectureCoverage {
+    pub project: String,
+    /// Architecture document type (c1, c2, c3, c4, erd, adr, openapi) -> document count
+    pub documents: BTreeMap<&'static str, u32>,
+    /// Architecture document types the project has no document of
+    pub missing: Vec<&'static str>,
+    /// Expected documentation set check; absent when no expectations apply
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completeness: Option<DocumentationCompleteness>,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ArchitectureOverviewResponse {
+    pub total_projects: u32,
+    pub total_documents: u32,
+    /// Total size of all documents in bytes
+    pub total_size: u64,
+    /// Primary category (c1, adr, openapi, agreements, guides, ...) -> document count
+    pub documents_by_type: BTreeMap<String, u32>,
+    /// One row per project, sorted by name
+    pub coverage: Vec<ProjectArchitectureCoverage>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetEffectiveExtensionsArgs {}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct EffectiveExtensionsResponse {
+    /// Document type (agreements, c1, c2, c3, c4, erd, adr, openapi, guide, style-guide) -> extension filter
+    pub document_types: BTreeMap<String, EffectiveExtensions>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetScanStatusArgs {}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ScanStatusResponse {
+    #[serde(flatten)]
+    pub scan: ScanStatus,
+    /// Documents tool calls currently see
+    pub documents_served: u32,
+    /// "unavailable" while the docs root cannot be read; metadata is still served
+    pub backing_store: &'static str,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetOrphanedDocumentsArgs {}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct OrphanedDocumentsResponse {
+    /// Top-level directory under the docs root ("" for files at the root) -> orphaned file
+    /// paths
+    pub directories: BTreeMap<String, Vec<String>>,
+    pub total_orphans: u32,
+    /// True when the pass stopped at orphan_scan_max_files; the list is incomplete
+    pub truncated: bool,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetPatternStatsArgs {}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct PatternStatsResponse {
+    /// Path pattern arm -> files it classified, counted once per document type accepting the
+    /// file; arms that matched nothing are omitted
+    pub patterns: BTreeMap<String, u32>,
+    /// Files walked, indexed or not
+    pub files_visited: u32,
+    /// When the scan these counts come from finished (RFC 3339); absent while the initial
+    /// scan runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_scan_at: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetServerHealthArgs {}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ServerHealthResponse {
+    /// Server package version
+    pub version: &'static str,
+    /// When the process started (RFC 3339)
+    pub started_at: String,
+    pub uptime_seconds: u64,
+    pub docs_root: String,
+    /// "unavailable" while the docs root cannot be read
+    pub backing_store: &'static str,
+    pub scan_status: ScanPhase,
+    /// When the last completed scan finished (RFC 3339); absent while the initial scan runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_scan_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_scan_duration_ms: Option<u64>,
+    /// Documents tool calls currently see
+    pub documents_served: u32,
+    /// Open MCP sessions; absent when the server is not attached to the HTTP session layer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_sessions: Option<u32>,
+    /// Resource subscriptions held by open sessions
+    pub active_subscriptions: u32,
+    /// Parse cache name (openapi_specs, frontmatter) -> lookup counters
+    pub caches: BTreeMap<String, CacheStats>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetMimeTypeFacetsArgs {}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct MimeTypeFacetsResponse {
+    /// MIME type -> number of indexed documents served with it
+    pub mime_types: BTreeMap<String, u32>,
+    /// Number of distinct MIME types
+    pub total_mime_types: u32,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ProjectCoverage {
+    pub project: String,
+    pub completeness: DocumentationCompleteness,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct DocumentationCoverageResponse {
+    /// Projects with expectations, sorted by name
+    pub projects: Vec<ProjectCoverage>,
+    /// Total number of projects listed
+    pub total_projects: u32,
+    /// Projects missing at least one expected document type
+    pub incomplete_projects: u32,
+}
+
+/// Columns of the get_architecture_overview coverage matrix
+const ARCHITECTURE_TYPES: &[&str] = &["c1", "c2", "c3", "c4", "erd", "adr", "openapi"];
+
+/// ResourceInfo fields selectable through `get_docs_list` `fields`
+const RESOURCE_FIELDS: &[&str] = &[
+    "uri",
+    "file_path",
+    "area",
+    "lang",
+    "langs",
+    "applies_to",
+    "category",
+    "project",
+    "mime_type",
+    "size",
+    "description",
+    "summary",
+    "word_count",
+    "token_estimate",
+    "valid",
+    "validation_error",
+    "variant_of",
+    "content_lang",
+    "other_languages",
+    "adr_date",
+    "adr_scope",
+    "parse_error",
+    "classification",
+];
+
+/// Fields `get_docs_list` `group_by` accepts
+const GROUP_BY_FIELDS: &[&str] = &["project", "area", "lang", "category", "mime_type"];
+
+/// Version of the structured tool response shapes; bump whenever a response field changes.
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// Experimental capability under which the server advertises `SCHEMA_VERSION` and clients
+/// declare the response schema version they understand, as `{"version": N}`
+pub const SCHEMA_CAPABILITY: &str = "schema_version";
+
+/// The response schema version a client declared in its initialize capabilities, if any.
+fn declared_schema_version(capabilities: &ClientCapabilities) -> Option<u32> {
+    let declared = capabilities.experimental.as_ref()?.get(SCHEMA_CAPABILITY)?;
+    declared.get("version")?.as_u64()?.try_into().ok()
+}
+
+/// Attached to JSON responses served before the initial scan finished
+const PARTIAL_INDEX_NOTICE: &str = "The index is still being built (or its build failed); results cover only the documents indexed so far. Call get_scan_status for progress.";
+
+/// Guidance attached to responses cut down to fit `max_response_bytes`
+const NARROW_QUERY_GUIDANCE: &str =
+    "Response exceeded max_response_bytes; narrow the query with filters or smaller pages.";
+
+/// Serializes a tool response and stamps it with `schema_version`.
+fn versioned_value<T: serde::Serialize>(
+    response: &T,
+    description: &str,
+) -> Result<serde_json::Value, McpError> {
+    let mut response_json = serde_json::to_value(response).map_err(|e| {
+        errors::internal(
+            "serialization_error",
+            format!("Failed to serialize {}: {}", description, e),
+            errors::context!({}),
+        )
+    })?;
+    if let Some(object) = response_json.as_object_mut() {
+        object.insert("schema_version".to_string(), json!(SCHEMA_VERSION));
+    }
+    Ok(response_json)
+}
+
+/// Config-derived settings of the tool layer
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    /// File stems recognized as a project's overview document, in priority order
+    pub readme_names: Vec<String>,
+    /// Upper bound for serialized JSON tool responses; unlimited when unset
+    pub max_response_bytes: Option<usize>,
+    /// Section headings every ADR must contain
+    pub adr_required_sections: Vec<String>,
+    /// Alternative headings accepted per required ADR section
+    pub adr_section_synonyms: BTreeMap<String, Vec<String>>,
+    /// Let get_resource_content resolve docs-root relative file paths
+    pub accept_file_paths: bool,
+    /// Reuse parse results across tool calls while file content is unchanged
+    pub cache_parsed_documents: bool,
+    /// How multi-document YAML specs are parsed
+    pub openapi_multi_document: MultiDocumentMode,
+    /// Reject reads whose file no longer has an extension of its document type
+    pub verify_extensions_on_read: bool,
+    /// Document type names that were scanned; tools for other types are rejected
+    pub enabled_document_types: Vec<String>,
+    /// Categories listed first by get_docs_list sort_by=priority, in order
+    pub type_priority: Vec<String>,
+    /// Content languages in preference order, used when collapsing language variants
+    pub variant_languages: Vec<String>,
+    /// Configured project -> alternative names used for it in other projects' diagrams
+    pub project_aliases: BTreeMap<String, Vec<String>>,
+    /// File name of per-project manifests
+    pub manifest_file_name: String,
+    /// Configured project -> directories searched for its manifest, shallowest first
+    pub project_manifest_dirs: BTreeMap<String, Vec<String>>,
+    /// Document types each project is expected to have
+    pub expectations: ExpectationsConfig,
+    /// Extension filters the scanner applied, per document type
+    pub effective_extensions: BTreeMap<String, EffectiveExtensions>,
+    /// Character cap for the project catalog appended to the server instructions
+    pub instructions_max_chars: usize,
+    /// MIME types whose content may be served
+    pub content: ContentConfig,
+    /// Old URI (or URI prefix ending in `/`) -> new URI for renamed documents
+    pub uri_aliases: BTreeMap<String, String>,
+    /// Tool name -> title/description advertised instead of the built-in ones
+    pub tool_overrides: BTreeMap<String, ToolOverride>,
+    /// Scans the config derives, reported by get_scan_plan
+    pub scan_plan: Vec<ScanPlanStep>,
+    /// Whether scans look for files outside every scan target
+    pub detect_orphans: bool,
+    /// Files export_search_index reads at once when it includes content
+    pub export_read_concurrency: usize,
+    /// Whether refresh_resource may update the served index
+    pub enable_reload: bool,
+    /// Files of documents hidden by `hidden_uris`, which includes may not read either
+    pub hidden_file_paths: BTreeSet<String>,
+    /// Age thresholds of get_stale_documents
+    pub staleness: StalenessConfig,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            readme_names: crate::config::default_readme_names(),
+            max_response_bytes: None,
+            adr_required_sections: crate::config::default_adr_required_sections(),
+            adr_section_synonyms: BTreeMap::new(),
+            accept_file_paths: false,
+            cache_parsed_documents: true,
+            openapi_multi_document: MultiDocumentMode::default(),
+            verify_extensions_on_read: true,
+            enabled_document_types: crate::models::DOCUMENT_TYPE_NAMES
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            type_priority: Vec::new(),
+            variant_languages: Vec::new(),
+            project_aliases: BTreeMap::new(),
+            manifest_file_name: crate::config::default_manifest_file_name(),
+            project_manifest_dirs: BTreeMap::new(),
+            expectations: ExpectationsConfig::default(),
+            effective_extensions: BTreeMap::new(),
+            instructions_max_chars: crate::config::default_instructions_max_chars(),
+            content: ContentConfig::default(),
+            uri_aliases: BTreeMap::new(),
+            tool_overrides: BTreeMap::new(),
+            scan_plan: Vec::new(),
+            detect_orphans: false,
+            export_read_concurrency: crate::config::default_export_read_concurrency(),
+            enable_reload: false,
+            hidden_file_paths: BTreeSet::new(),
+            staleness: StalenessConfig::default(),
+        }
+    }
+}
+
+/// Maximum number of `uri_aliases` rewrites followed for one lookup
+const MAX_ALIAS_DEPTH: usize = 8;
+
+/// Applies the exact alias for `uri`, or else the longest matching prefix alias (keys ending
+/// in `/`).
+fn rewrite_alias(aliases: &BTreeMap<String, String>, uri: &str) -> Option<String> {
+    if let Some(target) = aliases.get(uri) {
+        return Some(target.clone());
+    }
+    aliases
+        .iter()
+        .filter(|(from, _)| from.ends_with('/') && uri.starts_with(from.as_str()))
+        .max_by_key(|(from, _)| from.len())
+        .map(|(from, to)| format!("{}{}", to, &uri[from.len()..]))
+}
+
+/// `docs://a//b/` -> `docs://a/b`: collapses repeated slashes after the scheme and strips a
+/// trailing slash.
+fn normalize_uri(uri: &str) -> String {
+    let uri = uri.trim();
+    let (scheme, path) = uri.split_once("://").unwrap_or(("", uri));
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    if scheme.is_empty() {
+        segments.join("/")
+    } else {
+        format!("{}://{}", scheme, segments.join("/"))
+    }
+}
+
+/// Lowercased words padded with spaces (`"OAuth-Platform"` -> `" oauth platform "`), so
+/// references can be matched on whole words with `contains`.
+fn match_words(text: &str) -> String {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    format!(" {} ", words.join(" "))
+}
+
+/// Sorts ADRs by the number in their `ADR-<n>` category.
+fn sorted_by_adr_number(mut adr_documents: Vec<ResourceInfo>) -> Vec<ResourceInfo> {
+    let get_adr_number = |info: &ResourceInfo| -> u32 {
+        info.category
+            .iter()
+            .find(|cat| cat.starts_with("ADR-"))
+            .and_then(|cat| {
+                cat.strip_prefix("ADR-")
+                    .and_then(|num| num.parse::<u32>().ok())
+            })
+            .unwrap_or(0)
+    };
+    adr_documents.sort_by_key(get_adr_number);
+    adr_documents
+}
+
+/// Language-independent key of an agreement: `area/category.../file stem`, so the same
+/// topic written for two languages (`errors.md`, `errors.mdx`) compares equal.
+fn agreement_topic(info: &ResourceInfo) -> String {
+    let file_name = info.file_path.rsplit('/').next().unwrap_or(&info.file_path);
+    std::iter::once(info.area.as_str())
+        .chain(
+            info.category
+                .iter()
+                .map(String::as_str)
+                .filter(|category| *category != "agreements"),
+        )
+        .chain(std::iter::once(file_stem(file_name)))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `text` as a DOT double-quoted string.
+fn dot_quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    format!("\"{}\"", escaped)
+}
+
+/// Groups a docs list page by one of `GROUP_BY_FIELDS`.
+fn group_documents(response: DocsListResponse, group_by: &str) -> GroupedDocsListResponse {
+    let mut groups: BTreeMap<String, Vec<ResourceInfo>> = BTreeMap::new();
+    for info in response.documents {
+        let mut keys: Vec<String> = match group_by {
+            "project" => vec![info.project.clone()],
+            "area" => vec![info.area.clone()],
+            "lang" => info.languages().map(ToString::to_string).collect(),
+            "category" => info.category.clone(),
+            _ => vec![info.mime_type.clone()],
+        };
+        if keys.is_empty() {
+            keys.push(String::new());
+        }
+        keys.dedup();
+        for key in keys {
+            groups.entry(key).or_default().push(info.clone());
+        }
+    }
+    GroupedDocsListResponse {
+        group_by: group_by.to_string(),
+        groups,
+        total_pages: response.total_pages,
+        current_page: response.current_page,
+        limit: response.limit,
+        total_documents: response.total_documents,
+    }
+}
+
+/// Keeps only `uri` and `fields` of each document object in `documents`.
+fn project_fields(documents: &mut serde_json::Value, fields: &[String]) {
+    for document in documents
+        .as_array_mut()
+        .into_iter()
+        .flatten()
+        .filter_map(|d| d.as_object_mut())
+    {
+        document.retain(|key, _| key == "uri" || fields.iter().any(|f| f == key));
+    }
+}
+
+/// Removes the last group of a grouped docs list; false when none is left.
+fn drop_last_group(groups: &mut serde_json::Map<String, serde_json::Value>) -> bool {
+    let last = groups.keys().next_back().cloned();
+    last.is_some_and(|key| groups.remove(&key).is_some())
+}
+
+/// Drops the second half of `items`; false when already empty.
+fn halve<T>(items: &mut Vec<T>) -> bool {
+    if items.is_empty() {
+        return false;
+    }
+    items.truncate(items.len() / 2);
+    true
+}
+
+fn lint_finding(code: &str, severity: &str, message: String) -> LintFinding {
+    LintFinding {
+        code: code.to_string(),
+        severity: severity.to_string(),
+        message,
+    }
+}
+
+/// Checks an ADR against the template: required sections present and non-empty, frontmatter
+/// `date`/`status`, and a frontmatter `adr` number matching the filename prefix. A section
+/// also counts as present under any of its `synonyms` headings.
+fn lint_adr_content(
+    file_name: &str,
+    content: &str,
+    required_sections: &[String],
+    synonyms: &BTreeMap<String, Vec<String>>,
+) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+    let frontmatter = match markdown::parse_frontmatter(content) {
+        Ok(frontmatter) => frontmatter,
+        Err(error) => {
+            findings.push(lint_finding("invalid_frontmatter", "error", error));
+            markdown::Frontmatter {
+                body: markdown::split_frontmatter(content).map_or(content, |(_, body)| body),
+                ..Default::default()
+            }
+        }
+    };
+
+    let sections = markdown::sections(frontmatter.body);
+    for required in required_sections {
+        let accepted: Vec<&str> = std::iter::once(required.as_str())
+            .chain(
+                synonyms
+                    .iter()
+                    .filter(|(section, _)| section.eq_ignore_ascii_case(required))
+                    .flat_map(|(_, headings)| headings.iter().map(String::as_str)),
+            )
+            .collect();
+        let position = sections.iter().position(|section| {
+            let title = section.title.trim_end_matches(':').trim();
+            accepted
+                .iter()
+                .any(|heading| title.eq_ignore_ascii_case(heading))
+        });
+        match position {
+            None => findings.push(lint_finding(
+                "missing_section",
+                "error",
+                format!("Required section '{}' is missing", required),
+            )),
+            Some(index) => {
+                let section = &sections[index];
+                let has_subsections = sections
+                    .get(index + 1)
+                    .is_some_and(|next| next.level > section.level);
+                if section.content.trim().is_empty() && !has_subsections {
+                    findings.push(lint_finding(
+                        "empty_section",
+                        "warning",
+                        format!("Required section '{}' is empty", required),
+                    ));
+                }
+            }
+        }
+    }
+
+    for field in ["date", "status"] {
+        if frontmatter.metadata.get(field).is_none_or(|value| {
+            value.is_null() || value.as_str().is_some_and(|v| v.trim().is_empty())
+        }) {
+            findings.push(lint_finding(
+                &format!("missing_frontmatter_{}", field),
+                "warning",
+                format!("Frontmatter field '{}' is missing", field),
+            ));
+        }
+    }
+
+    let file_number: String = file_name.chars().take_while(char::is_ascii_digit).collect();
+    let declared_number = frontmatter.metadata.get("adr").map(|value| match value {
+        serde_json::Value::String(text) => text.chars().filter(char::is_ascii_digit).collect(),
+        other => other.to_string(),
+    });
+    if let Some(declared) = declared_number {
+        let parse = |digits: &str| digits.parse::<u64>().ok();
+        if parse(&declared).is_none() || parse(&declared) != parse(&file_number) {
+            findings.push(lint_finding(
+                "adr_number_mismatch",
+                "error",
+                format!(
+                    "Frontmatter adr '{}' does not match filename number '{}'",
+                    declared, file_number
+                ),
+            ));
+        }
+    }
+
+    findings
+}
+
+fn revision_meta(revision: &str) -> Meta {
+    let mut meta = Meta::new();
+    meta.0.insert("revision".to_string(), json!(revision));
+    meta
+}
+
+/// `Mcp-Session-Id` of an HTTP request; absent for other transports.
+fn session_id(context: &RequestContext<RoleServer>) -> Option<String> {
+    context
+        .extensions
+        .get::<axum::http::request::Parts>()?
+        .headers
+        .get(HEADER_SESSION_ID)?
+        .to_str()
+        .ok()
+        .map(ToString::to_string)
+}
+
+/// Names of every tool the server registers, for validating `tool_overrides`.
+pub fn tool_names() -> Vec<String> {
+    DocumentServer::tool_router()
+        .list_all()
+        .into_iter()
+        .map(|tool| tool.name.into_owned())
+        .collect()
+}
+
+/// Process start, reported by get_server_health; forced by `mark_started` at startup.
+static STARTED_AT: LazyLock<(Instant, chrono::DateTime<chrono::Utc>)> =
+    LazyLock::new(|| (Instant::now(), chrono::Utc::now()));
+
+/// Captures the process start time so uptime does not begin at the first health call.
+pub fn mark_started() {
+    LazyLock::force(&STARTED_AT);
+}
+
+/// Static part of the server instructions; `DocumentServer::instructions` appends the
+/// indexed projects.
+const TOOL_OVERVIEW: &str = "This server provides document access tools. Tools: get_resource_content (reads files by docs:// path; pass the returned revision as if_revision_not to skip unchanged content), get_metadata_batch (metadata for a list of docs:// URIs with not_found reporting), get_normalized_content (diff-friendly normalized content), resolve_path (maps a repository file path to its docs:// URI), refresh_resource (re-reads one edited document into the index when reloading is enabled), get_uri_map (bulk file path <-> docs:// URI mapping), resolve_links (resolves a document's relative markdown links to docs:// URIs), get_docs_list (lists documents with filtering and pagination), rank_documents (ranks documents by term-frequency relevance to a query), get_most_referenced (documents most linked to by other documents), get_project_graph_dot (a project's document link graph as Graphviz DOT), export_search_index (the catalog as an ElasticSearch/OpenSearch bulk NDJSON body, optionally with content), get_usage_stats (documents agents read most, with read counts), get_all_adr_documents (retrieves all ADR documents sorted by number), get_global_adrs (ADRs from shared locations that apply across projects), lint_adr (checks ADRs for required sections and frontmatter), get_parse_failures (lists specs and markdown that failed to parse at scan time), get_effective_extensions (extension filters the scanner used per document type), get_scan_plan (every document type, target and extensions scan the config derives), get_mime_type_facets (distinct MIME types served with document counts), get_scan_status (whether the initial index scan is still running, with progress), get_pattern_stats (files each scanner path pattern classified in the last scan), get_orphaned_documents (files outside every scan target that are never indexed), get_server_health (uptime, version, last scan, sessions and cache hit rates), get_project_overview (comprehensive project overview with statistics and grouped documents), get_project_readme (a project's README/overview document), get_project_manifest (a project's parsed manifest.json with ownership and links), get_documentation_coverage (expected documentation present/missing per project), get_architecture_overview (org-wide document totals by type and a project x document type coverage matrix), get_architecture_graph (nodes and relationships parsed from a project's C4 diagrams), get_project_dependencies (which projects reference which, from C1/C2 external systems), get_endpoints_for_tag (OpenAPI operations with a tag across services), get_openapi_schema (finds a named schema across specs and flags divergent definitions), diff_openapi_versions (added/removed/changed paths and operations between two versions of a service's API), get_service_versions (API versions each service documents, with spec counts per access level and skipped versions), map_erd_to_api (ERD entities with and without a matching OpenAPI schema), get_agreements (retrieves agreement documents filtered by programming language), get_agreements_for_project (agreements a project must honor: scoped via applies_to or global), get_agreements_index (agreements grouped by area and language with counts), diff_agreements (agreement topics covered in one programming language but not another), get_guides (retrieves guide/product documentation, optional product filter), get_style_guides (API style guides configured apart from agreements, optional area and language filters), get_undocumented (markdown documents missing a frontmatter title or below a word-count threshold), get_unclassified (documents with no project and a generic area, usually misfiled), get_stale_documents (documents older than the age threshold of their type, grouped by project). Every JSON tool response carries a schema_version field.";
+
+/// Tokenized document content keyed by file path, shared by every session's server.
+pub type TermCache = Arc<Mutex<BTreeMap<String, Arc<TermCounts>>>>;
+
+/// Resolved outgoing link targets (document URIs) keyed by source URI. Shared by clones of
+/// one server, so it lives as long as the resource map it was resolved against.
+type LinkCache = Arc<Mutex<BTreeMap<String, Arc<BTreeSet<String>>>>>;
+
+#[derive(Clone)]
+pub struct DocumentServer {
+    file_reader: FileReader,
+    store: ResourceStore,
+    options: Arc<ServerOptions>,
+    log_bridge: LogBridge,
+    /// Tags this session's events so the bridge forwards them to this session only
+    log_session: u64,
+    session_log: Arc<Mutex<Option<Arc<SessionLog>>>>,
+    term_cache: TermCache,
+    link_cache: LinkCache,
+    spec_cache: Arc<ParseCache<serde_json::Value>>,
+    frontmatter_cache: Arc<ParseCache<serde_json::Map<String, serde_json::Value>>>,
+    scan_tracker: ScanTracker,
+    session_manager: Option<Arc<LocalSessionManager>>,
+    subscriptions: SubscriptionRegistry,
+    session_subscriptions: Arc<SessionSubscriptions>,
+    usage: UsageCounters,
+    /// Clients declaring an older response schema version are warned about at initialize
+    min_client_schema: Option<u32>,
+    /// Served index this session was cloned from, for refresh_resource; never set on the
+    /// template itself
+    index: Option<SharedIndex>,
+    tool_router: ToolRouter<DocumentServer>,
+    prompt_router: PromptRouter<DocumentServer>,
+}
+
+#[tool_router]
+impl DocumentServer {
+    pub fn new_with_resources(
+        file_reader: FileReader,
+        resources: BTreeMap<DocumentKey, ResourceInfo>,
+    ) -> Self {
+        let subscriptions = SubscriptionRegistry::default();
+        Self {
+            file_reader,
+            store: ResourceStore::new(resources),
+            options: Arc::new(ServerOptions::default()),
+            log_bridge: LogBridge::default(),
+            log_session: 0,
+            session_log: Arc::new(Mutex::new(None)),
+            term_cache: TermCache::default(),
+            link_cache: LinkCache::default(),
+            spec_cache: Arc::default(),
+            frontmatter_cache: Arc::default(),
+            scan_tracker: ScanTracker::default(),
+            session_manager: None,
+            session_subscriptions: subscriptions.session(),
+            subscriptions,
+            usage: UsageCounters::default(),
+            min_client_schema: None,
+            index: None,
+            tool_router: Self::tool_router(),
+            prompt_router: Self::prompt_router(),
+        }
+    }
+
+    pub fn with_options(mut self, options: ServerOptions) -> Self {
+        self.tool_router = Self::tool_router();
+        for (name, tool_override) in &options.tool_overrides {
+            let Some(route) = self.tool_router.map.get_mut(name.as_str()) else {
+                continue;
+            };
+            if let Some(title) = &tool_override.title {
+                route.attr.title = Some(title.clone());
+                if let Some(annotations) = &mut route.attr.annotations {
+                    annotations.title = Some(title.clone());
+                }
+            }
+            if let Some(description) = &tool_override.description {
+                route.attr.description = Some(description.clone().into());
+            }
+        }
+        self.spec_cache = Arc::new(ParseCache::new(options.cache_parsed_documents));
+        self.frontmatter_cache = Arc::new(ParseCache::new(options.cache_parsed_documents));
+        self.options = Arc::new(options);
+        self
+    }
+
+    pub fn with_term_cache(mut self, term_cache: TermCache) -> Self {
+        self.term_cache = term_cache;
+        self
+    }
+
+    pub fn with_scan_tracker(mut self, scan_tracker: ScanTracker) -> Self {
+        self.scan_tracker = scan_tracker;
+        self
+    }
+
+    pub(crate) fn scan_tracker(&self) -> &ScanTracker {
+        &self.scan_tracker
+    }
+
+    /// Index build progress and backing store state, for get_scan_status and /healthz
+    pub(crate) fn scan_status(&self) -> ScanStatusResponse {
+        ScanStatusResponse {
+            scan: self.scan_tracker.status(),
+            documents_served: self.store.len().try_into().unwrap_or(u32::MAX),
+            backing_store: if self.file_reader.is_available() {
+                "available"
+            } else {
+                "unavailable"
+            },
+        }
+    }
+
+    /// Term counts of a document, tokenized on first use; None when unreadable or its MIME
+    /// type is blocked by the content policy.
+    fn term_counts(&self, info: &ResourceInfo) -> Option<Arc<TermCounts>> {
+        if !self.options.content.permits(&info.mime_type) {
+            return None;
+        }
+        if let Some(terms) = self
+            .term_cache
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(&info.file_path).cloned())
+        {
+            return Some(terms);
+        }
+
+        let content = self.file_reader.read_file_content(&info.file_path).ok()?;
+        let terms = Arc::new(TermCounts::from_text(&content));
+        if let Ok(mut cache) = self.term_cache.lock() {
+            cache.insert(info.file_path.clone(), terms.clone());
+        }
+        Some(terms)
+    }
+
+    /// Resolves a relative link target (without `#anchor`) found in `info` to a document URI.
+    fn resolve_link_uri(&self, info: &ResourceInfo, path: &str) -> Option<String> {
+        if path.is_empty() {
+            return Some(info.uri.clone());
+        }
+        resolve_relative_link(&info.file_path, path)
+            .and_then(|file_path| self.store.by_file_path(&file_path))
+            .map(|info| info.uri.clone())
+    }
+
+    /// Indexed documents a markdown document links to, excluding itself; resolved on first use.
+    /// Documents whose MIME type the content policy blocks link nowhere.
+    fn outgoing_links(&self, info: &ResourceInfo) -> Arc<BTreeSet<String>> {
+        if !self.options.content.permits(&info.mime_type) {
+            return Arc::default();
+        }
+        if let Some(links) = self
+            .link_cache
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(&info.uri).cloned())
+        {
+            return links;
+        }
+
+        let content = self
+            .file_reader
+            .read_file_content(&info.file_path)
+            .unwrap_or_default();
+        let links: BTreeSet<String> = markdown::extract_links(&content)
+            .iter()
+            .filter(|link| !link.target.contains("://") && !link.target.starts_with("mailto:"))
+            .filter_map(|link| {
+                let path = link.target.split('#').next().unwrap_or_default();
+                self.resolve_link_uri(info, path)
+            })
+            .filter(|uri| *uri != info.uri)
+            .collect();
+        let links = Arc::new(links);
+        if let Ok(mut cache) = self.link_cache.lock() {
+            cache.insert(info.uri.clone(), links.clone());
+        }
+        links
+    }
+
+    /// Display title of a document: its frontmatter title or first heading for markdown, else
+    /// the description.
+    fn document_title(&self, info: &ResourceInfo) -> String {
+        let markdown_title = (info.mime_type == "text/markdown"
+            && self.options.content.permits(&info.mime_type))
+        .then(|| self.file_reader.read_file_content(&info.file_path).ok())
+        .flatten()
+        .and_then(|content| {
+            let metadata = self.parsed_frontmatter(info, &content).ok();
+            let title = metadata
+                .as_deref()
+                .and_then(|metadata| markdown::metadata_str(metadata, "title"))
+                .map(ToString::to_string);
+            title.or_else(|| {
+                let body = markdown::split_frontmatter(&content)
+                    .map_or(content.as_str(), |(_, body)| body);
+                markdown::sections(body)
+                    .first()
+                    .map(|section| section.title.to_string())
+            })
+        });
+        markdown_title
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| info.description.clone())
+    }
+
+    /// Parsed spec for `content` read from `info`, reused while the content is unchanged.
+    fn parsed_spec(
+        &self,
+        info: &ResourceInfo,
+        content: &str,
+    ) -> Result<Arc<serde_json::Value>, String> {
+        let mode = self.options.openapi_multi_document;
+        self.spec_cache
+            .get_or_parse(&info.file_path, content, |content| {
+                openapi::parse_spec_documents(content, mode)
+            })
+    }
+
+    /// Frontmatter metadata for `content` read from `info`, reused while the content is
+    /// unchanged.
+    fn parsed_frontmatter(
+        &self,
+        info: &ResourceInfo,
+        content: &str,
+    ) -> Result<Arc<serde_json::Map<String, serde_json::Value>>, String> {
+        self.frontmatter_cache
+            .get_or_parse(&info.file_path, content, |content| {
+                markdown::parse_frontmatter(content).map(|frontmatter| frontmatter.metadata)
+            })
+    }
+
+    /// Parsed OpenAPI specs, optionally limited to one project. Unreadable or unparsable
+    /// specs are skipped (they are reported by get_parse_failures).
+    fn openapi_specs(&self, project: Option<&str>) -> Vec<(&ResourceInfo, Arc<serde_json::Value>)> {
+        self.store
+            .specs()
+            .filter(|info| project.is_none_or(|p| info.project == p))
+            .filter_map(|info| {
+                let spec = self
+                    .file_reader
+                    .read_file_content(&info.file_path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|content| self.parsed_spec(info, &content));
+                match spec {
+                    Ok(spec) => Some((info, spec)),
+                    Err(error) => {
+                        tracing::warn!(uri = %info.uri, %error, "Skipping unreadable OpenAPI spec");
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Groups a project's documents for get_project_overview without copying them.
+    fn project_overview<'s>(
+        &'s self,
+        project: &'s str,
+    ) -> Result<ProjectOverviewResponse<'s>, McpError> {
+        let all_documents: Vec<&ResourceInfo> = self.store.by_project(project).collect();
+        if all_documents.is_empty() {
+            return Err(errors::not_found(
+                "project_not_found",
+                "No documents found for the specified project",
+                errors::context!({"project": project}),
+            ));
+        }
+
+        let mut documents_by_type: BTreeMap<&str, Vec<&ResourceInfo>> = BTreeMap::new();
+        let mut documents_by_area: BTreeMap<&str, Vec<&ResourceInfo>> = BTreeMap::new();
+        let mut documents_by_language: BTreeMap<&str, Vec<&ResourceInfo>> = BTreeMap::new();
+        for &doc in &all_documents {
+            for category in &doc.category {
+                documents_by_type.entry(category).or_default().push(doc);
+            }
+            documents_by_area.entry(&doc.area).or_default().push(doc);
+            let lang = if doc.lang.is_empty() {
+                "none"
+            } else {
+                &doc.lang
+            };
+            documents_by_language.entry(lang).or_default().push(doc);
+        }
+
+        Ok(ProjectOverviewResponse {
+            project,
+            total_documents: all_documents.len().try_into().unwrap_or(u32::MAX),
+            total_size: all_documents.iter().map(|doc| u64::from(doc.size)).sum(),
+            documents_by_type,
+            documents_by_area,
+            documents_by_language,
+            all_documents,
+            completeness: self.completeness(project),
+        })
+    }
+
+    /// Checks a project's documents against the configured expectations. An expected type is
+    /// present when some document of the project carries it as a category.
+    fn completeness(&self, project: &str) -> Option<DocumentationCompleteness> {
+        let expected = self.options.expectations.required_for(project);
+        if expected.is_empty() {
+            return None;
+        }
+        let documents: BTreeMap<String, String> = expected
+            .iter()
+            .map(|kind| {
+                let present = self
+                    .store
+                    .by_project(project)
+                    .any(|info| info.category.contains(kind));
+                let status = if present { "present" } else { "missing" };
+                (kind.clone(), status.to_string())
+            })
+            .collect();
+        let present = documents.values().filter(|s| *s == "present").count();
+        let percentage = (present * 100 + documents.len() / 2) / documents.len();
+        Some(DocumentationCompleteness {
+            documents,
+            percentage: percentage.try_into().unwrap_or(100),
+        })
+    }
+
+    /// Tool overview followed by the indexed projects with document counts and the areas and
+    /// languages present, the latter capped at `instructions_max_chars`.
+    fn instructions(&self) -> String {
+        let mut projects: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut areas = BTreeSet::new();
+        let mut languages = BTreeSet::new();
+        for info in self.store.iter() {
+            if !info.project.is_empty() {
+                *projects.entry(info.project.as_str()).or_default() += 1;
+            }
+            if !info.area.is_empty() {
+                areas.insert(info.area.as_str());
+            }
+            languages.extend(info.languages());
+        }
+
+        let projects: Vec<String> = projects
+            .iter()
+            .map(|(project, count)| format!("{} ({})", project, count))
+            .collect();
+        let join = |values: BTreeSet<&str>| values.into_iter().collect::<Vec<_>>().join(", ");
+        let mut catalog = format!(
+            "Indexed projects (document count): {}. Areas: {}. Languages: {}.",
+            projects.join(", "),
+            join(areas),
+            join(languages)
+        );
+
+        let cap = self.options.instructions_max_chars;
+        if catalog.chars().count() > cap {
+            catalog = catalog.chars().take(cap.saturating_sub(3)).collect();
+            catalog.push_str("...");
+        }
+        format!("{} {}", TOOL_OVERVIEW, catalog)
+    }
+
+    /// Serializes a JSON tool response, enforcing `max_response_bytes`.
+    fn json_result<T: serde::Serialize>(
+        &self,
+        response: T,
+        description: &str,
+    ) -> Result<CallToolResult, McpError> {
+        self.bounded_result(response, description, |_| false)
+    }
+
+    /// Like `json_result`, but while the payload exceeds `max_response_bytes` calls `trim` to
+    /// drop data (returning false once nothing is left to drop). A trimmed payload is marked
+    /// with `response_truncated`; if it still does not fit, only the truncation notice remains.
+    fn bounded_result<T: serde::Serialize>(
+        &self,
+        mut response: T,
+        description: &str,
+        mut trim: impl FnMut(&mut T) -> bool,
+    ) -> Result<CallToolResult, McpError> {
+        let scan_phase = self.scan_tracker.status().status;
+        let versioned = |response: &T| {
+            let mut response_json = versioned_value(response, description)?;
+            if scan_phase != ScanPhase::Ready
+                && let Some(object) = response_json.as_object_mut()
+            {
+                object.insert("index_status".to_string(), json!(scan_phase));
+                object.insert("index_notice".to_string(), json!(PARTIAL_INDEX_NOTICE));
+            }
+            Ok::<_, McpError>(response_json)
+        };
+        let mut response_json = versioned(&response)?;
+        let Some(limit) = self.options.max_response_bytes else {
+            return Ok(CallToolResult::success(vec![Content::text(
+                response_json.to_string(),
+            )]));
+        };
+
+        let mut truncated = false;
+        loop {
+            if truncated && let Some(object) = response_json.as_object_mut() {
+                object.insert("response_truncated".to_string(), json!(true));
+                object.insert("guidance".to_string(), json!(NARROW_QUERY_GUIDANCE));
+            }
+            let text = response_json.to_string();
+            if text.len() <= limit {
+                return Ok(CallToolResult::success(vec![Content::text(text)]));
+            }
+            if !trim(&mut response) {
+                break;
+            }
+            truncated = true;
+            response_json = versioned(&response)?;
+        }
+
+        tracing::warn!(
+            limit,
+            description,
+            "Tool response exceeds max_response_bytes and was replaced by a notice"
+        );
+        let notice = versioned_value(
+            &errors::truncation_notice(limit, NARROW_QUERY_GUIDANCE),
+            "truncation notice",
+        )?;
+        Ok(CallToolResult::success(vec![Content::text(
+            notice.to_string(),
+        )]))
+    }
+
+    /// Connects the server to the tracing bridge so `logging/setLevel` subscribers receive events.
+    /// Starts a fresh log subscription too, so call it once per session like
+    /// [`Self::with_subscriptions`].
+    pub fn with_log_bridge(mut self, log_bridge: LogBridge) -> Self {
+        self.log_session = log_bridge.session_id();
+        self.log_bridge = log_bridge;
+        self.session_log = Arc::new(Mutex::new(None));
+        self
+    }
+
+    /// Lets get_server_health count the open sessions of the HTTP transport.
+    pub fn with_session_manager(mut self, session_manager: Arc<LocalSessionManager>) -> Self {
+        self.session_manager = Some(session_manager);
+        self
+    }
+
+    /// Attaches the shared subscription registry and starts a session in it; call once per
+    /// session so its subscriptions are dropped with it.
+    pub fn with_subscriptions(mut self, subscriptions: SubscriptionRegistry) -> Self {
+        self.session_subscriptions = subscriptions.session();
+        self.subscriptions = subscriptions;
+        self
+    }
+
+    /// Attaches the shared read counters reported by get_usage_stats.
+    pub fn with_usage(mut self, usage: UsageCounters) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// Sets the oldest response schema version clients are expected to understand.
+    pub fn with_min_client_schema(mut self, min_client_schema: Option<u32>) -> Self {
+        self.min_client_schema = min_client_schema;
+        self
+    }
+
+    /// Lets refresh_resource update the served index sessions are cloned from.
+    pub fn with_index(mut self, index: SharedIndex) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Swaps in re-read metadata for an indexed document and drops what the caches derived
+    /// from its old content. Returns false when the document is no longer indexed.
+    pub(crate) fn replace_resource(&mut self, info: ResourceInfo) -> bool {
+        if let Ok(mut cache) = self.term_cache.lock() {
+            cache.remove(&info.file_path);
+        }
+        if let Ok(mut cache) = self.link_cache.lock() {
+            cache.remove(&info.uri);
+        }
+        self.store.replace(info)
+    }
+
+    /// Number of indexed documents
+    pub(crate) fn resource_count(&self) -> usize {
+        self.store.len()
+    }
+
+    /// Looks up a resource by URI, following `uri_aliases` when the URI is not indexed. A miss
+    /// is retried with duplicate slashes collapsed and a trailing slash stripped, then ignoring
+    /// case; a case-insensitive match shared by several documents is an error listing them.
+    fn resolve_resource(&self, uri: &str) -> Result<Option<&ResourceInfo>, McpError> {
+        if uri.starts_with(OPENAPI_URI_SCHEME) {
+            return self.resolve_openapi_uri(uri);
+        }
+        if let Some(info) = self.follow_aliases(uri)? {
+            return Ok(Some(info));
+        }
+        let normalized = normalize_uri(uri);
+        if normalized != uri {
+            if let Some(info) = self.follow_aliases(&normalized)? {
+                tracing::debug!(requested = %uri, canonical = %info.uri, "Resolved normalized URI");
+                return Ok(Some(info));
+            }
+        }
+
+        match self.store.by_uri_ignoring_case(&normalized).as_slice() {
+            [] => Ok(None),
+            [info] => {
+                tracing::debug!(requested = %uri, canonical = %info.uri, "Resolved URI ignoring case");
+                Ok(Some(info))
+            }
+            matches => {
+                let suggestions: Vec<&str> = matches
+                    .iter()
+                    .map(|info| info.uri.as_str())
+                    .take(MAX_SUGGESTIONS)
+                    .collect();
+                Err(errors::not_found_with_suggestions(
+                    "resource_not_found",
+                    "URI is ambiguous; several documents match it when case is ignored",
+                    suggestions,
+                    errors::context!({"uri": uri}),
+                ))
+            }
+        }
+    }
+
+    /// Translates an `openapi://` address to the spec it names; an endpoint matching several
+    /// specs is an error listing their docs:// URIs.
+    fn resolve_openapi_uri(&self, uri: &str) -> Result<Option<&ResourceInfo>, McpError> {
+        match self.store.specs_at_openapi_uri(uri).as_slice() {
+            [] => Ok(None),
+            [info] => {
+                tracing::debug!(requested = %uri, canonical = %info.uri, "Resolved openapi:// URI");
+                Ok(Some(info))
+            }
+            matches => Err(errors::not_found_with_suggestions(
+                "resource_not_found",
+                "openapi:// URI is ambiguous; its endpoint matches several specs",
+                matches
+                    .iter()
+                    .map(|info| info.uri.as_str())
+                    .take(MAX_SUGGESTIONS),
+                errors::context!({"uri": uri}),
+            )),
+        }
+    }
+
+    /// Exact URI lookup following `uri_aliases`. Alias chains are followed up to
+    /// `MAX_ALIAS_DEPTH` rewrites; a chain revisiting a URI is rejected as a cycle.
+    fn follow_aliases(&self, uri: &str) -> Result<Option<&ResourceInfo>, McpError> {
+        let mut chain = vec![uri.to_string()];
+        loop {
+            let current = chain.last().cloned().unwrap_or_default();
+            if let Some(info) = self.store.get(&current) {
+                if chain.len() > 1 {
+                    tracing::debug!(requested = %uri, canonical = %info.uri, "Resolved URI alias");
+                }
+                return Ok(Some(info));
+            }
+            let Some(next) = rewrite_alias(&self.options.uri_aliases, &current) else {
+                return Ok(None);
+            };
+            let cycle = chain.contains(&next);
+            chain.push(next);
+            if cycle || chain.len() > MAX_ALIAS_DEPTH + 1 {
+                return Err(errors::invalid_argument(
+                    if cycle {
+                        "uri_alias_cycle"
+                    } else {
+                        "uri_alias_too_deep"
+                    },
+                    "uri_aliases do not resolve to an indexed document",
+                    errors::context!({"uri": uri, "chain": chain}),
+                ));
+            }
+        }
+    }
+
+    /// Rejects tools dedicated to a document type that `enabled_document_types` turned off.
+    fn ensure_type_enabled(&self, type_name: &str) -> Result<(), McpError> {
+        if self
+            .options
+            .enabled_document_types
+            .iter()
+            .any(|enabled| enabled == type_name)
+        {
+            return Ok(());
+        }
+        Err(errors::invalid_argument(
+            "document_type_disabled",
+            format!("Document type '{}' is disabled on this server", type_name),
+            errors::context!({
+                "document_type": type_name,
+                "enabled_document_types": self.options.enabled_document_types
+            }),
+        ))
+    }
+
+    /// Rejects reads of resources whose MIME type the `[content]` config blocks. Listings
+    /// still include them.
+    pub(crate) fn ensure_content_allowed(&self, info: &ResourceInfo) -> Result<(), McpError> {
+        if self.options.content.permits(&info.mime_type) {
+            return Ok(());
+        }
+        Err(errors::invalid_argument(
+            "content_type_blocked",
+            format!(
+                "Serving {} content is blocked by configuration",
+                info.mime_type
+            ),
+            errors::context!({"uri": info.uri, "mime_type": info.mime_type}),
+        ))
+    }
+
+    /// Rejects reads of resources whose file now resolves to an extension their document type
+    /// is not scanned with, e.g. after a rename behind a symlink since the last scan. Files that
+    /// no longer resolve are left to the read to report.
+    fn ensure_extension_allowed(&self, info: &ResourceInfo) -> Result<(), McpError> {
+        if !self.options.verify_extensions_on_read {
+            return Ok(());
+        }
+        let Some(allowed) = info
+            .type_name()
+            .and_then(|type_name| self.options.effective_extensions.get(type_name))
+            .filter(|allowed| !allowed.extensions.is_empty())
+        else {
+            return Ok(());
+        };
+        let Ok(extension) = self.file_reader.resolved_extension(&info.file_path) else {
+            return Ok(());
+        };
+        if extension.as_ref().is_some_and(|extension| {
+            allowed
+                .extensions
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(extension))
+        }) {
+            return Ok(());
+        }
+        Err(errors::invalid_argument(
+            "extension_not_allowed",
+            "The file no longer has an extension served for its document type; the index is \
+             stale until the next scan",
+            errors::context!({
+                "uri": info.uri,
+                "file_path": info.file_path,
+                "extension": extension,
+                "allowed_extensions": allowed.extensions
+            }),
+        ))
+    }
+
+    /// Reads file content by file path
+    pub(crate) fn read_file_by_path(&self, file_path: &str) -> Result<String, McpError> {
+        self.file_reader
+            .read_file_content(file_path)
+            .map_err(|e| self.read_error(file_path, &e))
+    }
+
+    /// Maps a failed read of an indexed file to the error tools and resource reads return.
+    fn read_error(&self, file_path: &str, error: &std::io::Error) -> McpError {
+        if !self.file_reader.is_available() {
+            return errors::backing_store_unavailable(file_path);
+        }
+        if error.kind() == std::io::ErrorKind::PermissionDenied {
+            return errors::permission_denied(file_path, error);
+        }
+        errors::read_failed(file_path, error)
+    }
+
+    /// Resolves a docs-root relative file path to its resource (exact match, then a unique
+    /// suffix match on path segment boundaries in either direction).
+    fn resolve_file_path(
+        &self,
+        file_path: &str,
+    ) -> Result<(&ResourceInfo, &'static str), McpError> {
+        let normalized = normalize_relative_path(file_path).map_err(|reason| {
+            errors::invalid_argument(
+                "invalid_path",
+                reason,
+                errors::context!({"provided_path": file_path}),
+            )
+        })?;
+
+        if let Some(info) = self.store.by_file_path(&normalized) {
+            return Ok((info, "exact"));
+        }
+
+        let is_segment_suffix = |long: &str, short: &str| {
+            long.strip_suffix(short)
+                .is_some_and(|head| head.ends_with('/'))
+        };
+        let candidates: Vec<&ResourceInfo> = self
+            .store
+            .file_paths()
+            .filter(|(indexed, _)| {
+                is_segment_suffix(indexed, &normalized) || is_segment_suffix(&normalized, indexed)
+            })
+            .map(|(_, info)| info)
+            .collect();
+
+        match candidates.as_slice() {
+            [info] => Ok((info, "suffix")),
+            [] => {
+                let file_name = normalized.rsplit('/').next().unwrap_or(&normalized);
+                let suggestions: Vec<&str> = self
+                    .store
+                    .iter()
+                    .filter(|info| info.file_path.rsplit('/').next() == Some(file_name))
+                    .map(|info| info.uri.as_str())
+                    .take(MAX_SUGGESTIONS)
+                    .collect();
+                Err(errors::not_found_with_suggestions(
+                    "resource_not_found",
+                    "No scanned document has this file path",
+                    suggestions,
+                    errors::context!({"file_path": normalized}),
+                ))
+            }
+            _ => {
+                let suggestions: Vec<&str> = candidates
+                    .iter()
+                    .map(|info| info.uri.as_str())
+                    .take(MAX_SUGGESTIONS)
+                    .collect();
+                Err(errors::not_found_with_suggestions(
+                    "resource_not_found",
+                    "File path is ambiguous; several documents match it",
+                    suggestions,
+                    errors::context!({"file_path": normalized}),
+                ))
+            }
+        }
+    }
+
+    /// All indexed resources in URI order
+    pub(crate) fn resource_infos(&self) -> impl Iterator<Item = &ResourceInfo> {
+        self.store.iter()
+    }
+
+    pub(crate) fn resource_info(&self, uri: &str) -> Option<&ResourceInfo> {
+        self.store.get(uri)
+    }
+
+    /// Filtered and paginated document list shared by `get_docs_list` and the browse pages
+    pub(crate) fn list_documents(
+        &self,
+        args: &GetDocsListArgs,
+    ) -> Result<DocsListResponse, McpError> {
+        // Set default values
+        let page = args.page.unwrap_or(1);
+        let limit = args.limit.unwrap_or(50);
+
+        // Validate pagination parameters
+        if page == 0 {
+            return Err(errors::invalid_argument(
+                "invalid_page",
+                "Page must be greater than 0",
+                errors::context!({"provided_page": page}),
+            ));
+        }
+
+        if limit == 0 || limit > 200 {
+            return Err(errors::invalid_argument(
+                "invalid_limit",
+                "Limit must be between 1 and 200",
+                errors::context!({"provided_limit": limit}),
+            ));
+        }
+
+        if args.only_valid == Some(true) && args.only_invalid == Some(true) {
+            return Err(errors::invalid_argument(
+                "conflicting_filters",
+                "only_valid and only_invalid cannot both be set",
+                errors::context!({}),
+            ));
+        }
+
+        let by_priority = match args.sort_by.as_deref() {
+            None | Some("uri") => false,
+            Some("priority") => true,
+            Some(other) => {
+                return Err(errors::invalid_argument(
+                    "invalid_sort_by",
+                    "sort_by must be \"uri\" or \"priority\"",
+                    errors::context!({"sort_by": other}),
+                ));
+            }
+        };
+
+        // Filter documents
+        let filtered_docs = self.filter_documents(args);
+        let mut entries: Vec<_> = if args.collapse_variants == Some(true) {
+            self.collapse_variants(filtered_docs)
+        } else {
+            filtered_docs
+                .into_iter()
+                .map(|info| (info, BTreeMap::new()))
+                .collect()
+        };
+        if by_priority {
+            entries.sort_by(|(a, _), (b, _)| {
+                (self.type_priority_rank(a), &a.uri).cmp(&(self.type_priority_rank(b), &b.uri))
+            });
+        }
+        let total_documents = entries.len().try_into().unwrap_or(u32::MAX);
+        let total_pages = total_documents.div_ceil(limit);
+
+        // Calculate pagination
+        let start_index = std::cmp::min(((page - 1) * limit) as usize, entries.len());
+        let end_index = std::cmp::min(start_index + limit as usize, entries.len());
+
+        // Get paginated results
+        let paginated_docs: Vec<ResourceInfo> = entries[start_index..end_index]
+            .iter()
+            .map(|(info, other_languages)| {
+                let mut info = (*info).clone();
+                info.other_languages.clone_from(other_languages);
+                info
+            })
+            .collect();
+
+        Ok(DocsListResponse {
+            documents: paginated_docs,
+            total_pages,
+            current_page: page,
+            limit,
+            total_documents,
+        })
+    }
+
+    /// Groups language variants with their primary document and keeps the variant in the most
+    /// preferred language; the others are returned as language -> URI.
+    fn collapse_variants<'a>(
+        &self,
+        documents: Vec<&'a ResourceInfo>,
+    ) -> Vec<(&'a ResourceInfo, BTreeMap<String, String>)> {
+        let preference = |info: &ResourceInfo| {
+            info.content_lang
+                .as_ref()
+                .and_then(|lang| {
+                    self.options
+                        .variant_languages
+                        .iter()
+                        .position(|l| l == lang)
+                })
+                .unwrap_or(usize::MAX)
+        };
+
+        let mut groups: BTreeMap<&str, Vec<&ResourceInfo>> = BTreeMap::new();
+        for info in documents {
+            let key = info.variant_of.as_deref().unwrap_or(&info.uri);
+            groups.entry(key).or_default().push(info);
+        }
+
+        groups
+            .into_values()
+            .filter_map(|mut group| {
+                group.sort_by_key(|info| preference(info));
+                let mut group = group.into_iter();
+                let chosen = group.next()?;
+                let others = group
+                    .map(|info| {
+                        let lang = info.content_lang.clone().unwrap_or_default();
+                        (lang, info.uri.clone())
+                    })
+                    .collect();
+                Some((chosen, others))
+            })
+            .collect()
+    }
+
+    /// Position of the document's highest-priority category in `type_priority`; unlisted
+    /// documents rank after every listed one.
+    fn type_priority_rank(&self, info: &ResourceInfo) -> usize {
+        let priority = &self.options.type_priority;
+        priority
+            .iter()
+            .position(|category| info.category.contains(category))
+            .unwrap_or(priority.len())
+    }
+
+    fn filter_documents(&self, args: &GetDocsListArgs) -> Vec<&ResourceInfo> {
+        let filters = FilterSet {
+            area: args.area.as_deref(),
+            lang: args.lang.as_deref(),
+            category: args.category.as_deref(),
+            project: args.project.as_deref(),
+            only_valid: args.only_valid == Some(true),
+            only_invalid: args.only_invalid == Some(true),
+            max_tokens: args.max_tokens,
+        };
+        self.store.iter_filtered(filters).collect()
+    }
+
+    #[tool(
+        description = "Retrieves documentation content from docs:// paths. Use for reading architecture docs, API specs, guides, and technical documentation. Paths must start with 'docs://' prefix; OpenAPI specs are also addressable as openapi://{project}/{service}/{version}/{access}/{endpoint} (resolved to their docs:// URI, ambiguous endpoints list candidates). Supports all document types including C4 diagrams, ERD diagrams, ADR documents, and API agreements. Returns raw file content as text for further processing by AI agents, followed by a JSON block with the content revision. Pass that revision as if_revision_not on later reads to get a small not_modified response instead of the unchanged body. Set strip_jsx=true to remove MDX/JSX component tags from .mdx files while keeping their inner text. Set wrap_column to soft-wrap long markdown prose lines at that column for display (code blocks, tables and headings stay intact); YAML/JSON and other formats are only wrapped when wrap_code=true as well. Set inline_includes=true to get the fully assembled document: MDX imports of .md/.mdx partials and <!-- include: path --> directives are replaced by the partial's content (within the docs root; include cycles are rejected with include_cycle). Resources whose MIME type the server's content policy blocks return content_type_blocked; so do inlined partials, as include_failed. Files that now resolve to an extension not served for their document type (stale index) return extension_not_allowed. Old URIs of renamed documents are rewritten through the configured uri_aliases; the JSON block then carries the canonical uri and aliased_from. Doubled slashes, a trailing slash and case differences are tolerated when no document matches exactly. When the server has accept_file_paths enabled, a docs-root relative file path is accepted too and resolved like resolve_path. Set split_frontmatter=true to get one JSON object instead: the revision fields, frontmatter (parsed markdown frontmatter, empty when absent or for non-markdown files) and body (the text after it, with the other options applied).",
+        annotations(
+            title = "📄 Get Documentation Resource Content",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_resource_content(
+        &self,
+        Parameters(GetResourceContentArgs {
+            path,
+            if_revision_not,
+            strip_jsx,
+            inline_includes,
+            wrap_column,
+            wrap_code,
+            split_frontmatter,
+        }): Parameters<GetResourceContentArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        if wrap_column == Some(0) {
+            return Err(errors::invalid_argument(
+                "invalid_wrap_column",
+                "wrap_column must be at least 1",
+                errors::context!({"wrap_column": 0}),
+            ));
+        }
+        let resource_info = if path.starts_with("docs://") || path.starts_with(OPENAPI_URI_SCHEME) {
+            // First, find the resource by URI (or a renamed document's old URI) in our resources map
+            self.resolve_resource(&path)?.ok_or_else(|| {
+                errors::not_found(
+                    "resource_not_found",
+                    "Resource not found in scanned documents",
+                    errors::context!({"uri": path}),
+                )
+            })?
+        } else if self.options.accept_file_paths {
+            self.resolve_file_path(&path)?.0
+        } else {
+            return Err(errors::invalid_argument(
+                "invalid_path",
+                "Path must start with 'docs://'",
+                errors::context!({"provided_path": path}),
+            ));
+        };
+
+        tracing::debug!(tool = "get_resource_content", uri = %path, "Reading resource content");
+        self.ensure_content_allowed(resource_info)?;
+        self.ensure_extension_allowed(resource_info)?;
+
+        // Then read the file content using the file path from ResourceInfo
+        let content = self.read_file_by_path(&resource_info.file_path)?;
+        self.usage.record_read(&resource_info.uri);
+        // The revision covers the assembled document, so edits to a partial change it too.
+        let content = if inline_includes == Some(true) {
+            let mut read = |path: &str| {
+                if self.options.hidden_file_paths.contains(path) {
+                    return Err("No such file or directory".to_string());
+                }
+                let mime_type = self.store.by_file_path(path).map_or_else(
+                    || DocumentScanner::get_mime_type(path),
+                    |info| info.mime_type.clone(),
+                );
+                if !self.options.content.permits(&mime_type) {
+                    return Err(format!(
+                        "Serving {} content is blocked by configuration",
+                        mime_type
+                    ));
+                }
+                self.file_reader
+                    .read_file_content(path)
+                    .map_err(|e| e.to_string())
+            };
+            transclusion::inline_includes(&resource_info.file_path, &content, &mut read).map_err(
+                |error| {
+                    let code = match error {
+                        transclusion::IncludeError::Cycle(_) => "include_cycle",
+                        _ => "include_failed",
+                    };
+                    errors::invalid_argument(
+                        code,
+                        error.to_string(),
+                        errors::context!({"uri": path}),
+                    )
+                },
+            )?
+        } else {
+            content
+        };
+        let revision = content_revision(&content);
+
+        let not_modified = if_revision_not.as_deref() == Some(revision.as_str());
+        let status = ResourceRevision {
+            uri: resource_info.uri.clone(),
+            aliased_from: (resource_info.uri != path).then_some(path),
+            revision,
+            not_modified,
+        };
+        if not_modified {
+            return self.json_result(status, "resource revision");
+        }
+
+        let (frontmatter, content) = match split_frontmatter {
+            Some(true) if resource_info.mime_type == "text/markdown" => {
+                let parsed = markdown::parse_frontmatter(&content).map_err(|error| {
+                    errors::internal(
+                        "invalid_frontmatter",
+                        format!("Failed to parse frontmatter: {}", error),
+                        errors::context!({"uri": resource_info.uri}),
+                    )
+                })?;
+                (Some(parsed.metadata), parsed.body.to_string())
+            }
+            Some(true) => (Some(serde_json::Map::new()), content),
+            _ => (None, content),
+        };
+
+        let content = if strip_jsx == Some(true) && resource_info.file_path.ends_with(".mdx") {
+            markdown::strip_jsx(&content)
+        } else {
+            content
+        };
+        let content = match wrap_column.and_then(|column| usize::try_from(column).ok()) {
+            Some(column) if resource_info.mime_type == "text/markdown" => {
+                markdown::soft_wrap(&content, column)
+            }
+            Some(column) if wrap_code == Some(true) => normalize::wrap_long_lines(&content, column),
+            _ => content,
+        };
+
+        if let Some(frontmatter) = frontmatter {
+            let revision = status.revision.clone();
+            let response = SplitContentResponse {
+                revision: status,
+                frontmatter,
+                body: content,
+            };
+            let mut result = self.json_result(response, "split content response")?;
+            result.meta = Some(revision_meta(&revision));
+            return Ok(result);
+        }
+
+        let status_json = serde_json::to_value(&status).map_err(|e| {
+            errors::internal(
+                "serialization_error",
+                format!("Failed to serialize resource revision: {}", e),
+                errors::context!({}),
+            )
+        })?;
+        let mut result = CallToolResult::success(vec![
+            Content::text(content),
+            Content::text(status_json.to_string()),
+        ]);
+        result.meta = Some(revision_meta(&status.revision));
+        Ok(result)
+    }
+
+    #[tool(
+        description = "Returns a document's content normalized for diffing: line endings unified to \\n, trailing whitespace trimmed, and for YAML/JSON files keys sorted recursively. Not pretty-printing: use it to compare two versions of a document without noise. Unparseable YAML/JSON and markdown get text normalization only.",
+        annotations(
+            title = "🧹 Get Normalized Content",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_normalized_content(
+        &self,
+        Parameters(GetNormalizedContentArgs { path }): Parameters<GetNormalizedContentArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let resource_info = self.store.get(&path).ok_or_else(|| {
+            errors::not_found(
+                "resource_not_found",
+                "Resource not found in scanned documents",
+                errors::context!({"uri": path}),
+            )
+        })?;
+
+        self.ensure_content_allowed(resource_info)?;
+        self.ensure_extension_allowed(resource_info)?;
+        let content = self.read_file_by_path(&resource_info.file_path)?;
+        let (normalization, content) =
+            normalize::normalize_content(&resource_info.file_path, &content);
+
+        let response = NormalizedContentResponse {
+            uri: path,
+            normalization: normalization.as_str().to_string(),
+            content,
+        };
+
+        self.json_result(response, "normalized content response")
+    }
+
+    #[tool(
+        description = "Retrieves metadata (ResourceInfo) for a list of docs:// URIs in one call without reading file contents. Returns found documents plus an explicit not_found list for URIs missing from the index. Use to validate and describe a known set of URIs cheaply. Max 200 URIs per call.",
+        annotations(
+            title = "🗂️ Get Metadata Batch",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_metadata_batch(
+        &self,
+        Parameters(GetMetadataBatchArgs { paths }): Parameters<GetMetadataBatchArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        if paths.len() > MAX_METADATA_BATCH_SIZE {
+            return Err(errors::invalid_argument(
+                "too_many_paths",
+                format!(
+                    "At most {} paths are allowed per call",
+                    MAX_METADATA_BATCH_SIZE
+                ),
+                errors::context!({"provided_count": paths.len()}),
+            ));
+        }
+
+        let mut documents = Vec::new();
+        let mut not_found = Vec::new();
+        for path in paths {
+            match self.store.get(&path) {
+                Some(info) => documents.push(info.clone()),
+                None => not_found.push(path),
+            }
+        }
+
+        let response = MetadataBatchResponse {
+            documents,
+            not_found,
+        };
+
+        self.json_result(response, "metadata batch response")
+    }
+
+    #[tool(
+        description = "Re-reads one indexed document after it was edited and updates its metadata (size, last_modified, summary, word_count, token_estimate, langs, applies_to, adr_date, parse_error, spec validation) in the served index without a full rescan. Returns the refreshed ResourceInfo. Sessions opened afterwards see the update. The returned ResourceInfo is this session's only view of the refresh: its other tools keep serving the metadata from when the session started. Adding, removing or renaming documents still needs a rescan or config reload. Requires enable_reload in the server config (reload_disabled otherwise).",
+        annotations(
+            title = "🔄 Refresh Resource",
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn refresh_resource(
+        &self,
+        Parameters(RefreshResourceArgs { path }): Parameters<RefreshResourceArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.options.enable_reload {
+            return Err(errors::invalid_argument(
+                "reload_disabled",
+                "Reloading is off; set enable_reload = true in the server config",
+                errors::context!({}),
+            ));
+        }
+        let Some(index) = &self.index else {
+            return Err(errors::internal(
+                "reload_unavailable",
+                "This server is not attached to a served index",
+                errors::context!({}),
+            ));
+        };
+        let info = self.resolve_resource(&path)?.ok_or_else(|| {
+            errors::not_found(
+                "resource_not_found",
+                "Resource not found in scanned documents",
+                errors::context!({"uri": path}),
+            )
+        })?;
+
+        let refreshed = index::refresh_resource(index, &self.file_reader, info)
+            .map_err(|e| self.read_error(&info.file_path, &e))?;
+        self.json_result(refreshed, "refresh resource response")
+    }
+
+    #[tool(
+        description = "Resolves a repository file path (relative to the docs root, e.g. from a code review or grep) to its docs:// URI and metadata. Tries an exact match first, then a unique suffix match. Absolute paths and '..' segments are rejected. On a miss returns resource_not_found with nearby suggestions.",
+        annotations(
+            title = "🧭 Resolve File Path to URI",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn resolve_path(
+        &self,
+        Parameters(ResolvePathArgs { file_path }): Parameters<ResolvePathArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let (resource, match_type) = self.resolve_file_path(&file_path)?;
+        let resource = resource.clone();
+
+        let response = ResolvePathResponse {
+            uri: resource.uri.clone(),
+            match_type: match_type.to_string(),
+            resource,
+        };
+
+        self.json_result(response, "resolve path response")
+    }
+
+    #[tool(
+        description = "Extracts the relative markdown links of a document and resolves each against the document's file path to a docs:// URI. Returns (text, target, uri, anchor) for every link; uri is null when the target file is not indexed. External links (http:, mailto:, ...) are skipped. Use it to follow 'see [the ERD](../erd/customer.mdx)' style references. Documents whose MIME type the server's content policy blocks return content_type_blocked.",
+        annotations(
+            title = "🔗 Resolve Document Links",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn resolve_links(
+        &self,
+        Parameters(ResolveLinksArgs { uri }): Parameters<ResolveLinksArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let resource_info = self.store.get(&uri).ok_or_else(|| {
+            errors::not_found(
+                "resource_not_found",
+                "Resource not found in scanned documents",
+                errors::context!({"uri": uri}),
+            )
+        })?;
+
+        self.ensure_content_allowed(resource_info)?;
+        let content = self.read_file_by_path(&resource_info.file_path)?;
+
+        let links = markdown::extract_links(&content)
+            .into_iter()
+            .filter(|link| !link.target.contains("://") && !link.target.starts_with("mailto:"))
+            .map(|link| {
+                let (path, anchor) = match link.target.split_once('#') {
+                    Some((path, anchor)) => (path, Some(anchor.to_string())),
+                    None => (link.target.as_str(), None),
+                };
+                ResolvedLink {
+                    uri: self.resolve_link_uri(resource_info, path),
+                    anchor,
+                    text: link.text,
+                    target: link.target,
+                }
+            })
+            .collect();
+
+        let response = ResolveLinksResponse {
+            uri: resource_info.uri.clone(),
+            links,
+        };
+
+        self.json_result(response, "resolve links response")
+    }
+
+    #[tool(
+        description = "Lists documentation resources with advanced filtering and pagination capabilities. Use this tool to search and browse architecture documents, API specifications, technical guides, and project documentation. Supports filtering by area (backend|frontend|architecture), programming language (php|go|js|ts), category (agreements|api-documentation|c1|c2|c3|c4|erd), and project using OR logic with | separator. only_valid / only_invalid filter on the spec validation result (see validate_specs_on_scan). Perfect for finding specific document types like C4 diagrams (category=c4), API documentation (category=api-documentation), or backend PHP docs (area=backend&lang=php). Returns paginated results with metadata including file paths, sizes, URIs, and for markdown a summary (first paragraph, up to ~200 chars) to preview documents without reading them, and for text documents word_count and token_estimate (a rough token count) to budget context. Set max_tokens to list only documents whose token_estimate fits that budget. Pass fields (e.g. [\"uri\", \"description\"]) to return only those ResourceInfo fields and save tokens; uri is always included. Set collapse_variants=true to fold language variants (api.md / api.ru.md) into one entry in the preferred language, with the alternatives under other_languages. Set group_by (project|area|lang|category|mime_type) to get groups (value -> documents) instead of the flat documents list: pagination still counts documents, so a page's documents are grouped and a group may continue on the next page; a document with several categories or langs appears in each of their groups but counts once. Results are ordered by URI; sort_by=\"priority\" lists the server's configured type_priority categories first (unlisted types last, ties by URI). Default limit: 50, max: 200. Use for document discovery, architecture analysis, and technical documentation research. Essential for understanding project structure and finding relevant documentation.",
+        annotations(
+            title = "📋 Get Documentation List with Filters",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_docs_list(
+        &self,
+        Parameters(args): Parameters<GetDocsListArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(group_by) = args
+            .group_by
+            .as_deref()
+            .filter(|group_by| !GROUP_BY_FIELDS.contains(group_by))
+        {
+            return Err(errors::invalid_argument(
+                "invalid_group_by",
+                format!("Cannot group by '{}'", group_by),
+                errors::context!({"group_by": group_by, "valid_group_by": GROUP_BY_FIELDS}),
+            ));
+        }
+        let response = self.list_documents(&args)?;
+
+        if let Some(unknown) = args
+            .fields
+            .iter()
+            .flatten()
+            .find(|field| !RESOURCE_FIELDS.contains(&field.as_str()))
+        {
+            return Err(errors::invalid_argument(
+                "invalid_fields",
+                format!("Unknown field '{}'", unknown),
+                errors::context!({"valid_fields": RESOURCE_FIELDS}),
+            ));
+        }
+
+        match (&args.fields, args.group_by.as_deref()) {
+            (None, None) => self.bounded_result(response, "docs list response", |response| {
+                halve(&mut response.documents)
+            }),
+            (Some(fields), None) => {
+                let mut projected = versioned_value(&response, "docs list response")?;
+                project_fields(&mut projected["documents"], fields);
+                self.bounded_result(projected, "docs list response", |projected| {
+                    projected["documents"].as_array_mut().is_some_and(halve)
+                })
+            }
+            (fields, Some(group_by)) => {
+                let grouped = group_documents(response, group_by);
+                let mut projected = versioned_value(&grouped, "docs list response")?;
+                if let (Some(fields), Some(groups)) = (fields, projected["groups"].as_object_mut())
+                {
+                    for documents in groups.values_mut() {
+                        project_fields(documents, fields);
+                    }
+                }
+                self.bounded_result(projected, "docs list response", |projected| {
+                    projected["groups"]
+                        .as_object_mut()
+                        .is_some_and(drop_last_group)
+                })
+            }
+        }
+    }
+
+    #[tool(
+        description = "Retrieves all ADR (Architecture Decision Record) documents sorted by ADR number. Returns a list of all ADR documents with their metadata including URI, description, and file paths, plus adr_date when the ADR states one. Pass sort_by=\"date\" to order chronologically (undated ADRs last) and since/until to restrict to a date range; undated ADRs are then excluded and counted in excluded_without_date. ADR documents are identified by their category starting with 'ADR-' followed by the ADR number. Perfect for discovering and analyzing architectural decisions across the project. Essential for understanding why certain architectural choices were made, tracking decision history, and ensuring consistency in future development. Use this tool to get comprehensive view of all architectural decisions made in the project.",
+        annotations(
+            title = "📋 Get All ADR Documents",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_all_adr_documents(
+        &self,
+        Parameters(GetAllAdrDocumentsArgs {
+            sort_by,
+            since,
+            until,
+        }): Parameters<GetAllAdrDocumentsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("adr")?;
+
+        let by_date = match sort_by.as_deref() {
+            None | Some("number") => false,
+            Some("date") => true,
+            Some(other) => {
+                return Err(errors::invalid_argument(
+                    "invalid_sort_by",
+                    "sort_by must be \"number\" or \"date\"",
+                    errors::context!({"sort_by": other}),
+                ));
+            }
+        };
+        let parse_bound = |name: &str, value: Option<String>| {
+            value
+                .map(|text| {
+                    date::normalize_date(&text).ok_or_else(|| {
+                        errors::invalid_argument(
+                            "invalid_date",
+                            "Expected a date such as 2024-01-15",
+                            errors::context!({name: text}),
+                        )
+                    })
+                })
+                .transpose()
+        };
+        let since = parse_bound("since", since)?;
+        let until = parse_bound("until", until)?;
+
+        // Filter documents to get only ADR documents
+        let adr_documents: Vec<ResourceInfo> = self
+            .store
+            .iter()
+            .filter(|info| {
+                // Check if any category starts with "ADR-"
+                info.category.iter().any(|cat| cat.starts_with("ADR-"))
+            })
+            .cloned()
+            .collect();
+
+        let mut sorted_adr_documents = sorted_by_adr_number(adr_documents);
+
+        let mut excluded_without_date = None;
+        if since.is_some() || until.is_some() {
+            let before = sorted_adr_documents.len();
+            sorted_adr_documents.retain(|info| info.adr_date.is_some());
+            excluded_without_date = Some(
+                (before - sorted_adr_documents.len())
+                    .try_into()
+                    .unwrap_or(u32::MAX),
+            );
+            // ISO dates compare chronologically as strings.
+            sorted_adr_documents.retain(|info| {
+                let date = info.adr_date.as_deref().unwrap_or_default();
+                since.as_deref().is_none_or(|since| date >= since)
+                    && until.as_deref().is_none_or(|until| date <= until)
+            });
+        }
+        if by_date {
+            // Stable sort keeps ADR number order among equal dates; undated last.
+            sorted_adr_documents.sort_by(|a, b| match (&a.adr_date, &b.adr_date) {
+                (Some(a), Some(b)) => a.cmp(b),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            });
+        }
+
+        // Create response
+        let response = AdrListResponse {
+            total_adr_documents: sorted_adr_documents.len().try_into().unwrap_or(u32::MAX),
+            adr_documents: sorted_adr_documents,
+            excluded_without_date,
+        };
+
+        self.bounded_result(response, "ADR response", |response| {
+            halve(&mut response.adr_documents)
+        })
+    }
+
+    #[tool(
+        description = "Retrieves ADRs applicable across projects: those stored in the configured global_adr_paths (adr_scope = global), sorted by ADR number. Project-local ADRs are excluded; use get_all_adr_documents for every ADR.",
+        annotations(
+            title = "🌐 Get Global ADRs",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_global_adrs(
+        &self,
+        _: Parameters<GetGlobalAdrsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("adr")?;
+
+        let adr_documents = sorted_by_adr_number(
+            self.store
+                .iter()
+                .filter(|info| info.adr_scope.as_deref() == Some("global"))
+                .cloned()
+                .collect(),
+        );
+
+        let response = AdrListResponse {
+            total_adr_documents: adr_documents.len().try_into().unwrap_or(u32::MAX),
+            adr_documents,
+            excluded_without_date: None,
+        };
+
+        self.bounded_result(response, "global ADR response", |response| {
+            halve(&mut response.adr_documents)
+        })
+    }
+
+    #[tool(
+        description = "Get comprehensive overview of a project with all document types, grouped by categories. Returns structured JSON with project statistics and all ResourceInfo objects organized by type, area, and language. Provides total document count, total size, and documents grouped by type (C1, C2, C3, C4, ERD, ADR, agreements), area (architecture, backend, frontend), and language (PHP, Go, JS, TS, etc.). When [expectations] are configured, completeness marks each expected document type present or missing with an overall percentage. Perfect for getting complete project understanding, analyzing documentation coverage, and understanding project structure. Essential for project analysis and documentation statistics.",
+        annotations(
+            title = "📊 Get Project Overview",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_project_overview(
+        &self,
+        Parameters(GetProjectOverviewArgs { project }): Parameters<GetProjectOverviewArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let response = self.project_overview(&project)?;
+
+        // Drop the flat list first, then the per-group documents (group keys stay).
+        self.bounded_result(response, "project overview response", |response| {
+            if !response.all_documents.is_empty() {
+                response.all_documents.clear();
+                return true;
+            }
+            [
+                &mut response.documents_by_language,
+                &mut response.documents_by_area,
+                &mut response.documents_by_type,
+            ]
+            .into_iter()
+            .find(|groups| groups.values().any(|docs| !docs.is_empty()))
+            .map(|groups| groups.values_mut().for_each(Vec::clear))
+            .is_some()
+        })
+    }
+
+    #[tool(
+        description = "Org-wide architecture dashboard aggregated over every indexed document: total projects, documents and size, document counts by type, and a coverage matrix with, per project, the number of C1-C4, ERD, ADR and OpenAPI documents, the types it lacks and, when [expectations] are configured, its completeness. Use get_project_overview for one project's documents.",
+        annotations(
+            title = "🏛️ Get Architecture Overview",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_architecture_overview(
+        &self,
+        Parameters(GetArchitectureOverviewArgs {}): Parameters<GetArchitectureOverviewArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut documents_by_type: BTreeMap<String, u32> = BTreeMap::new();
+        let mut matrix: BTreeMap<&str, BTreeMap<&'static str, u32>> = BTreeMap::new();
+        let mut total_size = 0;
+        for info in self.store.iter() {
+            total_size += u64::from(info.size);
+            let Some(primary) = info.category.first() else {
+                continue;
+            };
+            *documents_by_type.entry(primary.clone()).or_default() += 1;
+            if let Some(kind) = ARCHITECTURE_TYPES.iter().find(|kind| *kind == primary)
+                && !info.project.is_empty()
+            {
+                *matrix
+                    .entry(&info.project)
+                    .or_default()
+                    .entry(kind)
+                    .or_default() += 1;
+            }
+        }
+        // Configured projects without any architecture document still get a row.
+        for project in self.options.project_manifest_dirs.keys() {
+            matrix.entry(project).or_default();
+        }
+
+        let coverage: Vec<ProjectArchitectureCoverage> = matrix
+            .into_iter()
+            .map(|(project, counts)| ProjectArchitectureCoverage {
+                project: project.to_string(),
+                documents: ARCHITECTURE_TYPES
+                    .iter()
+                    .map(|kind| (*kind, counts.get(kind).copied().unwrap_or(0)))
+                    .collect(),
+                missing: ARCHITECTURE_TYPES
+                    .iter()
+                    .copied()
+                    .filter(|kind| !counts.contains_key(kind))
+                    .collect(),
+                completeness: self.completeness(project),
+            })
+            .collect();
+
+        let response = ArchitectureOverviewResponse {
+            total_projects: coverage.len().try_into().unwrap_or(u32::MAX),
+            total_documents: self.store.len().try_into().unwrap_or(u32::MAX),
+            total_size,
+            documents_by_type,
+            coverage,
+        };
+
+        self.json_result(response, "architecture overview response")
+    }
+
+    #[tool(
+        description = "Reports documentation completeness for every project with configured expectations ([expectations] in arch-mcp.toml): each expected document type (c1, c2, erd, adr, openapi, ...) marked present or missing, plus a percentage per project. Intended for dashboards; returns an empty list when no expectations are configured.",
+        annotations(
+            title = "✅ Get Documentation Coverage",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_documentation_coverage(
+        &self,
+        Parameters(GetDocumentationCoverageArgs {}): Parameters<GetDocumentationCoverageArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut names: BTreeSet<&str> = self
+            .store
+            .iter()
+            .map(|info| info.project.as_str())
+            .filter(|project| !project.is_empty())
+            .collect();
+        // Projects with their own expectations are listed even before they have documents.
+        names.extend(
+            self.options
+                .expectations
+                .projects
+                .keys()
+                .map(String::as_str),
+        );
+
+        let projects: Vec<ProjectCoverage> = names
+            .into_iter()
+            .filter_map(|project| {
+                Some(ProjectCoverage {
+                    completeness: self.completeness(project)?,
+                    project: project.to_string(),
+                })
+            })
+            .collect();
+
+        let response = DocumentationCoverageResponse {
+            total_projects: projects.len().try_into().unwrap_or(u32::MAX),
+            incomplete_projects: projects
+                .iter()
+                .filter(|p| p.completeness.documents.values().any(|s| s == "missing"))
+                .count()
+                .try_into()
+                .unwrap_or(u32::MAX),
+            projects,
+        };
+
+        self.json_result(response, "documentation coverage response")
+    }
+
+    #[tool(
+        description = "Returns a project's overview document (README/overview/index by convention, configurable via readme_names) with its URI and content. Use it as the canonical starting point before exploring a project. When no overview exists, returns resource_not_found with the list of the project's documents. An overview whose MIME type the server's content policy blocks returns content_type_blocked.",
+        annotations(
+            title = "📘 Get Project README",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_project_readme(
+        &self,
+        Parameters(GetProjectReadmeArgs { project }): Parameters<GetProjectReadmeArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let project_documents: Vec<&ResourceInfo> = self.store.by_project(&project).collect();
+
+        if project_documents.is_empty() {
+            return Err(errors::not_found(
+                "project_not_found",
+                "No documents found for the specified project",
+                errors::context!({"project": project}),
+            ));
+        }
+
+        let name_rank = |info: &ResourceInfo| {
+            let file_name = info.file_path.rsplit('/').next().unwrap_or(&info.file_path);
+            let stem = file_name
+                .split_once('.')
+                .map_or(file_name, |(stem, _)| stem)
+                .to_ascii_lowercase();
+            self.options
+                .readme_names
+                .iter()
+                .position(|name| *name == stem)
+        };
+
+        // Highest-priority name wins; among equals, the shallowest file.
+        let readme = project_documents
+            .iter()
+            .filter_map(|info| {
+                Some((
+                    (name_rank(info)?, info.file_path.matches('/').count()),
+                    info,
+                ))
+            })
+            .min_by_key(|(rank, _)| *rank)
+            .map(|(_, info)| *info);
+
+        let Some(readme) = readme else {
+            return Err(errors::not_found(
+                "readme_not_found",
+                "No overview document found for the project",
+                errors::context!({
+                    "project": project,
+                    "recognized_names": self.options.readme_names,
+                    "documents": project_documents.iter().map(|info| &info.uri).collect::<Vec<_>>()
+                }),
+            ));
+        };
+
+        self.ensure_content_allowed(readme)?;
+        let content = self.read_file_by_path(&readme.file_path)?;
+        let response = ProjectReadmeResponse {
+            project,
+            uri: readme.uri.clone(),
+            mime_type: readme.mime_type.clone(),
+            content,
+        };
+
+        self.json_result(response, "project readme response")
+    }
+
+    #[tool(
+        description = "Returns a project's metadata manifest (ownership, links, ...) parsed from the manifest file (manifest.json unless configured otherwise) in the project's directory: one of its configured document directories or their ancestor named after the project, shallowest first. Manifest files are metadata and never listed as documents. Returns project_not_found for unknown projects and manifest_not_found with the searched paths when no manifest exists.",
+        annotations(
+            title = "🪪 Get Project Manifest",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_project_manifest(
+        &self,
+        Parameters(GetProjectManifestArgs { project }): Parameters<GetProjectManifestArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(dirs) = self.options.project_manifest_dirs.get(&project) else {
+            return Err(errors::not_found(
+                "project_not_found",
+                "Project is not configured",
+                errors::context!({
+                    "project": project,
+                    "projects": self.options.project_manifest_dirs.keys().collect::<Vec<_>>()
+                }),
+            ));
+        };
+
+        let candidates: Vec<String> = dirs
+            .iter()
+            .map(|dir| format!("{}/{}", dir, self.options.manifest_file_name))
+            .collect();
+        let Some((file_path, content)) = candidates.iter().find_map(|file_path| {
+            let content = self.file_reader.read_file_content(file_path).ok()?;
+            Some((file_path, content))
+        }) else {
+            return Err(errors::not_found(
+                "manifest_not_found",
+                "The project has no manifest",
+                errors::context!({"project": project, "searched_paths": candidates}),
+            ));
+        };
+
+        let manifest = serde_json::from_str(&content).map_err(|e| {
+            errors::internal(
+                "manifest_parse_error",
+                format!("Invalid manifest JSON: {}", e),
+                errors::context!({"project": project, "file_path": file_path}),
+            )
+        })?;
+        let response = ProjectManifestResponse {
+            project,
+            file_path: file_path.clone(),
+            manifest,
+        };
+
+        self.json_result(response, "project manifest response")
+    }
+
+    #[tool(
+        description = "Get all agreement documents filtered by programming language. Returns API contracts, service agreements, and technical specifications for the specified language. Agreements declaring several languages in frontmatter langs match each of them. Perfect for understanding API contracts and service interfaces for a specific technology stack.",
+        annotations(
+            title = "📋 Get Agreements by Language",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_agreements(
+        &self,
+        Parameters(GetAgreementsArgs { lang }): Parameters<GetAgreementsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("agreements")?;
+
+        // Filter documents by language and agreements category
+        let agreement_documents: Vec<&ResourceInfo> = self.store.agreements(Some(&lang)).collect();
+
+        // Create response
+        let response = AgreementsResponse {
+            lang: lang.clone(),
+            agreements: agreement_documents
+                .iter()
+                .map(|doc| (*doc).clone())
+                .collect(),
+            total_agreements: agreement_documents.len().try_into().unwrap_or(u32::MAX),
+        };
+
+        self.json_result(response, "agreements response")
+    }
+
+    #[tool(
+        description = "Get the agreements a project must honor: those listing it in frontmatter applies_to (scoped) and those without applies_to, which apply to every project (global). Optional programming language filter. Use get_resource_content with the returned URIs to read them.",
+        annotations(
+            title = "🤝 Get Agreements for Project",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_agreements_for_project(
+        &self,
+        Parameters(GetAgreementsForProjectArgs { project, lang }): Parameters<
+            GetAgreementsForProjectArgs,
+        >,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("agreements")?;
+
+        let (global, scoped): (Vec<ResourceInfo>, Vec<ResourceInfo>) = self
+            .store
+            .agreements_for_project(&project)
+            .filter(|info| matches_lang_filter(info, lang.as_deref()))
+            .cloned()
+            .partition(|info| info.applies_to.is_empty());
+
+        let response = AgreementsForProjectResponse {
+            total_agreements: (scoped.len() + global.len()).try_into().unwrap_or(u32::MAX),
+            project,
+            lang,
+            scoped,
+            global,
+        };
+
+        self.json_result(response, "agreements for project response")
+    }
+
+    #[tool(
+        description = "Returns the bulk mapping between repository file paths (relative to the docs root) and docs:// URIs, in both directions, optionally filtered by project. Paginated by file path (default 1000 entries per page, max 5000). Use it to translate grep/git paths to MCP URIs in bulk instead of calling resolve_path per file.",
+        annotations(
+            title = "🗺️ Get Path/URI Map",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_uri_map(
+        &self,
+        Parameters(GetUriMapArgs {
+            project,
+            page,
+            limit,
+        }): Parameters<GetUriMapArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let page = page.unwrap_or(1);
+        let limit = limit.unwrap_or(DEFAULT_URI_MAP_LIMIT);
+
+        if page == 0 {
+            return Err(errors::invalid_argument(
+                "invalid_page",
+                "Page must be greater than 0",
+                errors::context!({"provided_page": page}),
+            ));
+        }
+
+        if limit == 0 || limit > MAX_URI_MAP_LIMIT {
+            return Err(errors::invalid_argument(
+                "invalid_limit",
+                format!("Limit must be between 1 and {}", MAX_URI_MAP_LIMIT),
+                errors::context!({"provided_limit": limit}),
+            ));
+        }
+
+        let entries: Vec<(&str, &ResourceInfo)> = self
+            .store
+            .file_paths()
+            .filter(|(_, info)| matches_filter(&info.project, project.as_deref()))
+            .collect();
+
+        let total_entries: u32 = entries.len().try_into().unwrap_or(u32::MAX);
+        let start = ((page - 1) as usize).saturating_mul(limit as usize);
+        let mut path_to_uri = BTreeMap::new();
+        let mut uri_to_path = BTreeMap::new();
+        for (file_path, info) in entries.into_iter().skip(start).take(limit as usize) {
+            path_to_uri.insert(file_path.to_string(), info.uri.clone());
+            uri_to_path.insert(info.uri.clone(), file_path.to_string());
+        }
+
+        let response = UriMapResponse {
+            path_to_uri,
+            uri_to_path,
+            total_entries,
+            current_page: page,
+            total_pages: total_entries.div_ceil(limit),
+        };
+
+        self.json_result(response, "uri map response")
+    }
+
+    #[tool(
+        description = "Returns the agreements index: all agreement documents grouped by area, then by programming language, with counts and URIs. Gives a coverage matrix of API contracts (e.g. backend/go has agreements while frontend/ts has none). Use get_agreements to fetch the documents of one language.",
+        annotations(
+            title = "🗂️ Get Agreements Index",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_agreements_index(
+        &self,
+        _: Parameters<GetAgreementsIndexArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("agreements")?;
+
+        let mut areas: BTreeMap<String, AreaAgreements> = BTreeMap::new();
+        let mut total_agreements: u32 = 0;
+
+        for info in self.store.agreements(None) {
+            let area = areas.entry(info.area.clone()).or_default();
+            area.count += 1;
+            let mut languages: Vec<&str> = info.languages().collect();
+            if languages.is_empty() {
+                languages.push("");
+            }
+            for lang in languages {
+                let language = area.languages.entry(lang.to_string()).or_default();
+                language.count += 1;
+                language.uris.push(info.uri.clone());
+            }
+            total_agreements += 1;
+        }
+
+        let response = AgreementsIndexResponse {
+            areas,
+            total_agreements,
+        };
+
+        self.json_result(response, "agreements index response")
+    }
+
+    #[tool(
+        description = "Compares the agreement topics of two programming languages (e.g. php and go): categories and topics (area/category/file name without extension) that have agreements in one language but not the other, plus the topics both cover. Surfaces contract-coverage gaps across stacks from the index alone, without reading documents. Use get_agreements to fetch the documents of one language.",
+        annotations(
+            title = "⚖️ Diff Agreements",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn diff_agreements(
+        &self,
+        Parameters(DiffAgreementsArgs { lang_a, lang_b }): Parameters<DiffAgreementsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("agreements")?;
+        if lang_a == lang_b {
+            return Err(errors::invalid_argument(
+                "invalid_languages",
+                "lang_a and lang_b must differ",
+                errors::context!({"lang_a": lang_a, "lang_b": lang_b}),
+            ));
+        }
+
+        let topics = |lang: &str| {
+            let mut topics: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            let mut agreements: u32 = 0;
+            for info in self.store.agreements(Some(lang)) {
+                topics
+                    .entry(agreement_topic(info))
+                    .or_default()
+                    .push(info.uri.clone());
+                agreements += 1;
+            }
+            (topics, agreements)
+        };
+        let (topics_a, agreements_a) = topics(&lang_a);
+        let (topics_b, agreements_b) = topics(&lang_b);
+
+        let categories = |topics: &BTreeMap<String, Vec<String>>| -> BTreeSet<String> {
+            topics
+                .keys()
+                .map(|topic| topic.rsplit_once('/').map_or("", |(category, _)| category))
+                .map(ToString::to_string)
+                .collect()
+        };
+        let (categories_a, categories_b) = (categories(&topics_a), categories(&topics_b));
+        let only_in = |topics: &BTreeMap<String, Vec<String>>,
+                       other: &BTreeMap<String, Vec<String>>| {
+            topics
+                .iter()
+                .filter(|(topic, _)| !other.contains_key(*topic))
+                .map(|(topic, uris)| (topic.clone(), uris.clone()))
+                .collect()
+        };
+
+        let response = AgreementsDiffResponse {
+            categories_only_in_a: categories_a.difference(&categories_b).cloned().collect(),
+            categories_only_in_b: categories_b.difference(&categories_a).cloned().collect(),
+            topics_only_in_a: only_in(&topics_a, &topics_b),
+            topics_only_in_b: only_in(&topics_b, &topics_a),
+            shared_topics: topics_a
+                .keys()
+                .filter(|topic| topics_b.contains_key(*topic))
+                .cloned()
+                .collect(),
+            lang_a,
+            lang_b,
+            agreements_a,
+            agreements_b,
+        };
+
+        self.json_result(response, "agreements diff response")
+    }
+
+    #[tool(
+        description = "Lints ADRs against the template: required sections (default Status, Context, Decision, Consequences; configurable, with accepted synonym headings per section) present and non-empty, frontmatter date and status set, and frontmatter adr number matching the filename. Pass uri for one ADR or project for all of a project's ADRs. Returns per-document findings with severity (error/warning).",
+        annotations(
+            title = "🧪 Lint ADRs",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn lint_adr(
+        &self,
+        Parameters(LintAdrArgs { uri, project }): Parameters<LintAdrArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("adr")?;
+
+        let targets: Vec<&ResourceInfo> = match (&uri, &project) {
+            (Some(uri), None) => vec![self.store.get(uri).ok_or_else(|| {
+                errors::not_found(
+                    "resource_not_found",
+                    "Resource not found in scanned documents",
+                    errors::context!({"uri": uri}),
+                )
+            })?],
+            (None, Some(project)) => self
+                .store
+                .adrs()
+                .filter(|info| info.project == *project)
+                .collect(),
+            _ => {
+                return Err(errors::invalid_argument(
+                    "invalid_target",
+                    "Provide exactly one of uri or project",
+                    errors::context!({}),
+                ));
+            }
+        };
+
+        let documents: Vec<AdrLintReport> = targets
+            .into_iter()
+            .map(|info| {
+                let file_name = info.file_path.rsplit('/').next().unwrap_or(&info.file_path);
+                let findings = match self.file_reader.read_file_content(&info.file_path) {
+                    Ok(content) => lint_adr_content(
+                        file_name,
+                        &content,
+                        &self.options.adr_required_sections,
+                        &self.options.adr_section_synonyms,
+                    ),
+                    Err(e) => vec![lint_finding(
+                        "unreadable",
+                        "error",
+                        format!("Failed to read file: {}", e),
+                    )],
+                };
+                AdrLintReport {
+                    uri: info.uri.clone(),
+                    findings,
+                }
+            })
+            .collect();
+
+        let response = AdrLintResponse {
+            total_documents: documents.len().try_into().unwrap_or(u32::MAX),
+            documents_with_findings: documents
+                .iter()
+                .filter(|doc| !doc.findings.is_empty())
+                .count()
+                .try_into()
+                .unwrap_or(u32::MAX),
+            documents,
+        };
+
+        self.json_result(response, "ADR lint response")
+    }
+
+    #[tool(
+        description = "Finds under-documented markdown files: documents without a frontmatter title (only the path-derived description exists) or whose body is shorter than min_words (default 50). Optional project filter. Returns offending URIs with reasons (missing_title, below_word_threshold, invalid_frontmatter, unreadable). Use for documentation-quality checks.",
+        annotations(
+            title = "🔎 Get Undocumented Documents",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_undocumented(
+        &self,
+        Parameters(GetUndocumentedArgs { project, min_words }): Parameters<GetUndocumentedArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let min_words = min_words.unwrap_or(DEFAULT_MIN_WORDS);
+
+        let documents: Vec<UndocumentedDocument> = self
+            .store
+            .iter()
+            .filter(|info| {
+                info.mime_type == "text/markdown"
+                    && project.as_ref().is_none_or(|p| info.project == *p)
+            })
+            .filter_map(|info| {
+                let mut reasons = Vec::new();
+                let mut words = 0;
+                match self.file_reader.read_file_content(&info.file_path) {
+                    Ok(content) => match self.parsed_frontmatter(info, &content) {
+                        Ok(metadata) => {
+                            if markdown::metadata_str(&metadata, "title").is_none() {
+                                reasons.push("missing_title".to_string());
+                            }
+                            let body = markdown::split_frontmatter(&content)
+                                .map_or(content.as_str(), |(_, body)| body);
+                            words = markdown::word_count(body);
+                        }
+                        Err(_) => {
+                            reasons.push("invalid_frontmatter".to_string());
+                            words = markdown::word_count(&content);
+                        }
+                    },
+                    Err(e) => {
+                        tracing::warn!(tool = "get_undocumented", uri = %info.uri, "Failed to read document: {}", e);
+                        reasons.push("unreadable".to_string());
+                    }
+                }
+                let word_count = words.try_into().unwrap_or(u32::MAX);
+                if word_count < min_words && !reasons.iter().any(|r| r == "unreadable") {
+                    reasons.push("below_word_threshold".to_string());
+                }
+
+                (!reasons.is_empty()).then(|| UndocumentedDocument {
+                    uri: info.uri.clone(),
+                    reasons,
+                    word_count,
+                })
+            })
+            .collect();
+
+        let response = UndocumentedResponse {
+            min_words,
+            total_documents: documents.len().try_into().unwrap_or(u32::MAX),
+            documents,
+        };
+
+        self.json_result(response, "undocumented response")
+    }
+
+    #[tool(
+        description = "Lists documents the scanner could not classify: no project and an empty or generic area (e.g. an agreement placed directly under the agreements root instead of backend/<lang>/...). These are usually misfiled. Covers the whole catalog; unlike get_undocumented it checks placement, not content.",
+        annotations(
+            title = "🧺 Get Unclassified Documents",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_unclassified(
+        &self,
+        Parameters(GetUnclassifiedArgs {}): Parameters<GetUnclassifiedArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let documents: Vec<UnclassifiedDocument> = self
+            .store
+            .unclassified()
+            .map(|info| UnclassifiedDocument {
+                uri: info.uri.clone(),
+                file_path: info.file_path.clone(),
+                area: info.area.clone(),
+                category: info.category.clone(),
+            })
+            .collect();
+
+        let response = UnclassifiedResponse {
+            total_documents: documents.len().try_into().unwrap_or(u32::MAX),
+            documents,
+        };
+
+        self.json_result(response, "unclassified response")
+    }
+
+    /// Documents older than their type's threshold (`staleness` with `max_age_days` layered on
+    /// top) at `now`, grouped by project, plus checked documents of unknown age.
+    pub(crate) fn stale_documents(
+        &self,
+        max_age_days: &BTreeMap<String, u32>,
+        project: Option<&str>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> StaleDocumentsResponse {
+        let mut thresholds = self.options.staleness.max_age_days.clone();
+        thresholds.extend(max_age_days.clone());
+        let default_max_age_days = self.options.staleness.default_max_age_days;
+
+        let mut stale: BTreeMap<String, Vec<StaleDocument>> = BTreeMap::new();
+        let mut unknown_age = Vec::new();
+        for info in self
+            .store
+            .iter()
+            .filter(|info| matches_filter(&info.project, project))
+        {
+            let Some(type_name) = info.type_name() else {
+                continue;
+            };
+            let Some(max_age) = thresholds.get(type_name).copied().or(default_max_age_days) else {
+                continue;
+            };
+            let Some((last_modified, modified)) = info.last_modified.as_ref().and_then(|text| {
+                chrono::DateTime::parse_from_rfc3339(text)
+                    .ok()
+                    .map(|modified| (text, modified))
+            }) else {
+                unknown_age.push(info.uri.clone());
+                continue;
+            };
+            let age_days = (now - modified.to_utc()).num_days().max(0);
+            if age_days > i64::from(max_age) {
+                stale
+                    .entry(info.project.clone())
+                    .or_default()
+                    .push(StaleDocument {
+                        uri: info.uri.clone(),
+                        document_type: type_name.to_string(),
+                        last_modified: last_modified.clone(),
+                        age_days: age_days.try_into().unwrap_or(u32::MAX),
+                        max_age_days: max_age,
+                    });
+            }
+        }
+
+        let projects: Vec<StaleProject> = stale
+            .into_iter()
+            .map(|(project, mut documents)| {
+                documents.sort_by(|a, b| b.age_days.cmp(&a.age_days).then(a.uri.cmp(&b.uri)));
+                StaleProject { project, documents }
+            })
+            .collect();
+        StaleDocumentsResponse {
+            max_age_days: thresholds,
+            default_max_age_days,
+            total_stale: projects
+                .iter()
+                .map(|project| project.documents.len())
+                .sum::<usize>()
+                .try_into()
+                .unwrap_or(u32::MAX),
+            projects,
+            unknown_age,
+        }
+    }
+
+    #[tool(
+        description = "Reports documents not modified for longer than the age threshold of their type, for governance reviews (e.g. OpenAPI specs should track the code while ADRs may age). Thresholds come from the server's [staleness] config; pass max_age_days (document type -> days) to override them per type. Types without a threshold are not checked. Returns stale documents grouped by project, oldest first, with age_days and last_modified, and lists checked documents whose modification time is unknown under unknown_age. Optional project filter.",
+        annotations(
+            title = "⏳ Get Stale Documents",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn get_stale_documents(
+        &self,
+        Parameters(GetStaleDocumentsArgs {
+            max_age_days,
+            project,
+        }): Parameters<GetStaleDocumentsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(unknown) = max_age_days
+            .keys()
+            .find(|name| !crate::models::DOCUMENT_TYPE_NAMES.contains(&name.as_str()))
+        {
+            return Err(errors::invalid_argument(
+                "unknown_document_type",
+                format!(
+                    "Unknown document type; expected one of {}",
+                    crate::models::DOCUMENT_TYPE_NAMES.join(", ")
+                ),
+                errors::context!({"document_type": unknown}),
+            ));
+        }
+
+        let response = self.stale_documents(&max_age_days, project.as_deref(), chrono::Utc::now());
+        self.json_result(response, "stale documents response")
+    }
+
+    #[tool(
+        description = "Builds a machine-readable architecture graph for a project from its C1-C4 documents: Mermaid C4Context/C4Container/C4Component blocks and C4-PlantUML macros (Person, System, Container, Component, Rel, ...). Returns nodes (id, name, type, description, technology) and edges (from, to, label, technology) merged across levels, each with the source URIs. Documents that fail to parse are listed in parse_errors without failing the call.",
+        annotations(
+            title = "🕸️ Get Architecture Graph",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_architecture_graph(
+        &self,
+        Parameters(GetArchitectureGraphArgs { project }): Parameters<GetArchitectureGraphArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let documents: Vec<&ResourceInfo> = self
+            .store
+            .by_project(&project)
+            .filter(|info| {
+                info.category
+                    .iter()
+                    .any(|cat| matches!(cat.as_str(), "c1" | "c2" | "c3" | "c4"))
+            })
+            .collect();
+
+        if documents.is_empty() {
+            return Err(errors::not_found(
+                "project_not_found",
+                "No C4 documents found for the specified project",
+                errors::context!({"project": project}),
+            ));
+        }
+
+        let mut nodes: BTreeMap<String, ArchitectureNode> = BTreeMap::new();
+        let mut edges: BTreeMap<(String, String, String), ArchitectureEdge> = BTreeMap::new();
+        let mut parse_errors = Vec::new();
+        let add_source = |sources: &mut Vec<String>, uri: &str| {
+            if !sources.iter().any(|s| s == uri) {
+                sources.push(uri.to_string());
+            }
+        };
+
+        for info in &documents {
+            let file_name = info.file_path.rsplit('/').next().unwrap_or(&info.file_path);
+            let diagram = self
+                .file_reader
+                .read_file_content(&info.file_path)
+                .map_err(|e| format!("Failed to read file: {}", e))
+                .and_then(|content| c4_graph::parse_document(file_name, &content));
+            let diagram = match diagram {
+                Ok(diagram) => diagram,
+                Err(error) => {
+                    tracing::warn!(tool = "get_architecture_graph", uri = %info.uri, %error, "Failed to parse diagram");
+                    parse_errors.push(GraphParseError {
+                        uri: info.uri.clone(),
+                        error,
+                    });
+                    continue;
+                }
+            };
+
+            for node in diagram.nodes {
+                let entry = nodes
+                    .entry(node.id.clone())
+                    .or_insert_with(|| ArchitectureNode {
+                        id: node.id,
+                        name: node.name,
+                        kind: node.kind,
+                        description: String::new(),
+                        technology: None,
+                        sources: Vec::new(),
+                    });
+                if entry.description.is_empty() {
+                    entry.description = node.description;
+                }
+                if entry.technology.is_none() {
+                    entry.technology = node.technology;
+                }
+                add_source(&mut entry.sources, &info.uri);
+            }
+            for edge in diagram.edges {
+                let key = (edge.from.clone(), edge.to.clone(), edge.label.clone());
+                let entry = edges.entry(key).or_insert_with(|| ArchitectureEdge {
+                    from: edge.from,
+                    to: edge.to,
+                    label: edge.label,
+                    technology: None,
+                    sources: Vec::new(),
+                });
+                if entry.technology.is_none() {
+                    entry.technology = edge.technology;
+                }
+                add_source(&mut entry.sources, &info.uri);
+            }
+        }
+
+        let response = ArchitectureGraphResponse {
+            project,
+            nodes: nodes.into_values().collect(),
+            edges: edges.into_values().collect(),
+            documents_parsed: (documents.len() - parse_errors.len())
+                .try_into()
+                .unwrap_or(u32::MAX),
+            parse_errors,
+        };
+
+        self.json_result(response, "architecture graph response")
+    }
+
+    #[tool(
+        description = "Cross-project dependency overview: scans every project's C1/C2 diagrams for external systems (System_Ext, Container_Ext, ...) whose name or alias matches another configured project or one of its aliases (case-insensitive, whole words). Returns an adjacency list project -> referenced projects with evidencing diagram URIs, plus external systems matching no project. Optional project filter for outgoing dependencies of one project.",
+        annotations(
+            title = "🔗 Get Project Dependencies",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_project_dependencies(
+        &self,
+        Parameters(GetProjectDependenciesArgs { project }): Parameters<GetProjectDependenciesArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut known_projects: BTreeMap<String, Vec<String>> =
+            self.options.project_aliases.clone();
+        for info in self.store.iter() {
+            if !info.project.is_empty() {
+                known_projects.entry(info.project.clone()).or_default();
+            }
+        }
+        let candidates: Vec<(&String, String)> = known_projects
+            .iter()
+            .flat_map(|(name, aliases)| {
+                std::iter::once(name)
+                    .chain(aliases)
+                    .map(move |candidate| (name, match_words(candidate)))
+            })
+            .filter(|(_, words)| !words.trim().is_empty())
+            .collect();
+
+        let mut dependencies: BTreeMap<String, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+        let mut unmatched: BTreeMap<(String, String), UnmatchedExternalSystem> = BTreeMap::new();
+        let mut parse_errors = Vec::new();
+
+        let diagrams = self.store.iter().filter(|info| {
+            !info.project.is_empty()
+                && project.as_ref().is_none_or(|p| info.project == *p)
+                && info.category.iter().any(|cat| cat == "c1" || cat == "c2")
+        });
+        for info in diagrams {
+            dependencies.entry(info.project.clone()).or_default();
+            let file_name = info.file_path.rsplit('/').next().unwrap_or(&info.file_path);
+            let diagram = match self
+                .file_reader
+                .read_file_content(&info.file_path)
+                .map_err(|e| format!("Failed to read file: {}", e))
+                .and_then(|content| c4_graph::parse_document(file_name, &content))
+            {
+                Ok(diagram) => diagram,
+                Err(error) => {
+                    parse_errors.push(GraphParseError {
+                        uri: info.uri.clone(),
+                        error,
+                    });
+                    continue;
+                }
+            };
+
+            for node in diagram.nodes {
+                if !node.kind.ends_with("_ext") || node.kind.starts_with("person") {
+                    continue;
+                }
+                let texts = [match_words(&node.name), match_words(&node.id)];
+                let referenced = candidates
+                    .iter()
+                    .find(|(name, words)| {
+                        **name != info.project
+                            && texts.iter().any(|text| text.contains(words.as_str()))
+                    })
+                    .map(|(name, _)| (*name).clone());
+
+                let sources = match referenced {
+                    Some(referenced) => dependencies
+                        .entry(info.project.clone())
+                        .or_default()
+                        .entry(referenced)
+                        .or_default(),
+                    None => {
+                        // The diagram's own system drawn as external is not a dependency.
+                        if candidates.iter().any(|(name, words)| {
+                            **name == info.project
+                                && texts.iter().any(|t| t.contains(words.as_str()))
+                        }) {
+                            continue;
+                        }
+                        &mut unmatched
+                            .entry((info.project.clone(), node.id.clone()))
+                            .or_insert_with(|| UnmatchedExternalSystem {
+                                project: info.project.clone(),
+                                id: node.id.clone(),
+                                name: node.name.clone(),
+                                sources: Vec::new(),
+                            })
+                            .sources
+                    }
+                };
+                if !sources.contains(&info.uri) {
+                    sources.push(info.uri.clone());
+                }
+            }
+        }
+
+        let response = ProjectDependenciesResponse {
+            dependencies: dependencies
+                .into_iter()
+                .map(|(project, depends_on)| ProjectDependencies {
+                    project,
+                    depends_on: depends_on
+                        .into_iter()
+                        .map(|(project, evidence)| ProjectDependency { project, evidence })
+                        .collect(),
+                })
+                .collect(),
+            unmatched_external_systems: unmatched.into_values().collect(),
+            parse_errors,
+        };
+
+        self.json_result(response, "project dependencies response")
+    }
+
+    #[tool(
+        description = "Finds all OpenAPI operations carrying a tag (e.g. a bounded context like billing), across services and spec files. Tag matching is case-insensitive; optional project filter. Returns method, path, operationId, summary and spec URI grouped by service. When nothing matches, returns tag_not_found with the available tags.",
+        annotations(
+            title = "🏷️ Get Endpoints For Tag",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_endpoints_for_tag(
+        &self,
+        Parameters(GetEndpointsForTagArgs { tag, project }): Parameters<GetEndpointsForTagArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("openapi")?;
+
+        let mut services: BTreeMap<String, Vec<TaggedEndpoint>> = BTreeMap::new();
+        let mut available_tags = BTreeSet::new();
+
+        for (info, spec) in self.openapi_specs(project.as_deref()) {
+            // Known-layout specs carry the service as their second category.
+            let service = info.category.get(1).unwrap_or(&info.project);
+            for operation in openapi::operations(&spec) {
+                available_tags.extend(operation.tags.iter().cloned());
+                if !operation.tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+                    continue;
+                }
+                services
+                    .entry(service.clone())
+                    .or_default()
+                    .push(TaggedEndpoint {
+                        method: operation.method,
+                        path: operation.path,
+                        operation_id: operation.operation_id,
+                        summary: operation.summary,
+                        spec_uri: info.uri.clone(),
+                    });
+            }
+        }
+
+        if services.is_empty() {
+            return Err(errors::not_found(
+                "tag_not_found",
+                "No operations carry the requested tag",
+                errors::context!({"tag": tag, "project": project, "available_tags": available_tags}),
+            ));
+        }
+
+        let response = EndpointsForTagResponse {
+            tag,
+            total_operations: services
+                .values()
+                .map(Vec::len)
+                .sum::<usize>()
+                .try_into()
+                .unwrap_or(u32::MAX),
+            services,
+        };
+
+        self.json_result(response, "endpoints for tag response")
+    }
+
+    #[tool(
+        description = "Looks up a named schema across all OpenAPI specs (components.schemas) and Swagger 2.0 specs (definitions), so you don't need to know which file defines it. Optional project and service filters. Returns each defining spec URI with the schema as JSON (nested structure preserved, $ref not resolved); diverged=true when the definitions differ. On a miss returns schema_not_found with similarly named schemas.",
+        annotations(
+            title = "🧬 Get OpenAPI Schema",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_openapi_schema(
+        &self,
+        Parameters(GetOpenApiSchemaArgs {
+            schema_name,
+            project,
+            service,
+        }): Parameters<GetOpenApiSchemaArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("openapi")?;
+
+        let mut matches = Vec::new();
+        let mut similar = BTreeSet::new();
+        let needle = schema_name.to_lowercase();
+
+        for (info, spec) in self.openapi_specs(project.as_deref()) {
+            let spec_service = info.category.get(1).cloned().unwrap_or_default();
+            if service.as_ref().is_some_and(|s| *s != spec_service) {
+                continue;
+            }
+            match openapi::find_schema(&spec, &schema_name) {
+                Some((section, schema)) => matches.push(OpenApiSchemaMatch {
+                    spec_uri: info.uri.clone(),
+                    project: info.project.clone(),
+                    service: spec_service,
+                    section: section.to_string(),
+                    schema: schema.clone(),
+                }),
+                None => similar.extend(
+                    openapi::schema_names(&spec)
+                        .into_iter()
+                        .filter(|name| name.to_lowercase().contains(&needle))
+                        .map(ToString::to_string),
+                ),
+            }
+        }
+
+        if matches.is_empty() {
+            return Err(errors::not_found_with_suggestions(
+                "schema_not_found",
+                "No spec defines the requested schema",
+                similar.into_iter().take(MAX_SUGGESTIONS),
+                errors::context!({"schema_name": schema_name}),
+            ));
+        }
+
+        let mut distinct: Vec<&serde_json::Value> = Vec::new();
+        for m in &matches {
+            if !distinct.contains(&&m.schema) {
+                distinct.push(&m.schema);
+            }
+        }
+        let distinct_definitions: u32 = distinct.len().try_into().unwrap_or(u32::MAX);
+
+        let response = OpenApiSchemaResponse {
+            schema_name,
+            matches,
+            distinct_definitions,
+            diverged: distinct_definitions > 1,
+        };
+
+        self.json_result(response, "OpenAPI schema response")
+    }
+
+    #[tool(
+        description = "Compares two versions of a service's OpenAPI contract. Each version's specs (all access levels) are merged, then paths are reported as added, removed or changed; changed paths list added/removed operations and, per operation, added/removed response codes and required request fields (parameters as in:name, body properties as body:name). $ref is not resolved. Returns openapi_version_not_found with the service's versions when either version has no spec.",
+        annotations(
+            title = "🔀 Diff OpenAPI Versions",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn diff_openapi_versions(
+        &self,
+        Parameters(DiffOpenApiVersionsArgs {
+            project,
+            service,
+            from_version,
+            to_version,
+        }): Parameters<DiffOpenApiVersionsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("openapi")?;
+
+        let mut versions: BTreeMap<String, Vec<(&ResourceInfo, Arc<serde_json::Value>)>> =
+            BTreeMap::new();
+        for (info, spec) in self.openapi_specs(Some(&project)) {
+            if info.category.get(1) != Some(&service) {
+                continue;
+            }
+            let version = info.category.get(2).cloned().unwrap_or_default();
+            versions.entry(version).or_default().push((info, spec));
+        }
+
+        let mut merged = Vec::new();
+        for version in [&from_version, &to_version] {
+            let Some(specs) = versions.get(version) else {
+                return Err(errors::not_found(
+                    "openapi_version_not_found",
+                    "The service has no OpenAPI spec for the requested version",
+                    errors::context!({
+                        "project": project,
+                        "service": service,
+                        "version": version,
+                        "available_versions": versions.keys().collect::<Vec<_>>(),
+                    }),
+                ));
+            };
+            let uris: Vec<String> = specs.iter().map(|(info, _)| info.uri.clone()).collect();
+            merged.push((
+                uris,
+                openapi::merge_paths(specs.iter().map(|(_, spec)| &**spec)),
+            ));
+        }
+        let (to_specs, new_paths) = merged.pop().unwrap_or_default();
+        let (from_specs, old_paths) = merged.pop().unwrap_or_default();
+
+        let paths = openapi::diff_paths(&old_paths, &new_paths);
+        let response = OpenApiVersionsDiffResponse {
+            total_changed_paths: paths.len().try_into().unwrap_or(u32::MAX),
+            project,
+            service,
+            from_version,
+            to_version,
+            from_specs,
+            to_specs,
+            paths,
+        };
+
+        self.json_result(response, "OpenAPI versions diff response")
+    }
+
+    #[tool(
+        description = "Lists the API versions each OpenAPI service of a project documents (version directories under openapi-spec/{project}/{service}/), naturally sorted (v10 after v9), with spec counts per access level. missing_versions lists v<N> versions skipped between the lowest and highest documented one (none when they are more than 100 apart, e.g. date-style versions). Optional service filter.",
+        annotations(
+            title = "🏷️ Get Service Versions",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_service_versions(
+        &self,
+        Parameters(GetServiceVersionsArgs { project, service }): Parameters<GetServiceVersionsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("openapi")?;
+
+        // service -> version -> access level -> specs
+        let mut counts: BTreeMap<&str, BTreeMap<&str, BTreeMap<String, u32>>> = BTreeMap::new();
+        for info in self.store.specs().filter(|info| info.project == project) {
+            let [_, spec_service, version, access_level, ..] = info.category.as_slice() else {
+                continue;
+            };
+            *counts
+                .entry(spec_service)
+                .or_default()
+                .entry(version)
+                .or_default()
+                .entry(access_level.clone())
+                .or_default() += 1;
+        }
+        if counts.is_empty() {
+            return Err(errors::not_found(
+                "project_not_found",
+                "No OpenAPI specs found for the specified project",
+                errors::context!({"project": project}),
+            ));
+        }
+        if let Some(service) = &service {
+            if !counts.contains_key(service.as_str()) {
+                return Err(errors::not_found_with_suggestions(
+                    "service_not_found",
+                    "The project has no OpenAPI specs for the requested service",
+                    counts.keys().copied(),
+                    errors::context!({"project": project, "service": service}),
+                ));
+            }
+            counts.retain(|name, _| *name == service);
+        }
+
+        let services: Vec<ServiceVersions> = counts
+            .into_iter()
+            .map(|(service, versions)| {
+                let mut versions: Vec<ServiceVersion> = versions
+                    .into_iter()
+                    .map(|(version, specs_by_access_level)| ServiceVersion {
+                        version: version.to_string(),
+                        total_specs: specs_by_access_level.values().sum(),
+                        specs_by_access_level,
+                    })
+                    .collect();
+                versions.sort_by(|a, b| openapi::compare_versions(&a.version, &b.version));
+                ServiceVersions {
+                    service: service.to_string(),
+                    missing_versions: openapi::missing_versions(
+                        versions.iter().map(|v| v.version.as_str()),
+                    ),
+                    versions,
+                }
+            })
+            .collect();
+
+        let response = ServiceVersionsResponse {
+            project,
+            total_services: services.len().try_into().unwrap_or(u32::MAX),
+            services,
+        };
+
+        self.json_result(response, "service versions response")
+    }
+
+    #[tool(
+        description = "Correlates a project's ERD entities (Mermaid erDiagram, PlantUML entity/class/table) with the schemas its OpenAPI specs declare, to surface data-model/API drift. Names match ignoring case, separators and a plural suffix (order_items ~ OrderItem). Returns matched entities with their schemas, entities without an API schema and schemas without an entity. ERDs and specs that fail to parse are listed in parse_errors; the rest is still reported.",
+        annotations(
+            title = "🧬 Map ERD To API",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn map_erd_to_api(
+        &self,
+        Parameters(MapErdToApiArgs { project }): Parameters<MapErdToApiArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("erd")?;
+        self.ensure_type_enabled("openapi")?;
+
+        let erds: Vec<&ResourceInfo> = self
+            .store
+            .by_project(&project)
+            .filter(|info| info.category.iter().any(|cat| cat == "erd"))
+            .collect();
+        let specs: Vec<&ResourceInfo> = self
+            .store
+            .specs()
+            .filter(|info| info.project == project)
+            .collect();
+        if erds.is_empty() && specs.is_empty() {
+            return Err(errors::not_found(
+                "project_not_found",
+                "No ERD or OpenAPI documents found for the specified project",
+                errors::context!({"project": project}),
+            ));
+        }
+
+        let mut parse_errors = Vec::new();
+        let mut entities: BTreeMap<String, ErdEntityMapping> = BTreeMap::new();
+        for info in &erds {
+            let file_name = info.file_path.rsplit('/').next().unwrap_or(&info.file_path);
+            let parsed = self
+                .file_reader
+                .read_file_content(&info.file_path)
+                .map_err(|e| format!("Failed to read file: {}", e))
+                .and_then(|content| erd::parse_document(file_name, &content));
+            match parsed {
+                Ok(names) => {
+                    for name in names {
+                        let entry = entities.entry(erd::match_key(&name)).or_insert_with(|| {
+                            ErdEntityMapping {
+                                entity: name,
+                                erd_uris: Vec::new(),
+                                schemas: Vec::new(),
+                            }
+                        });
+                        if !entry.erd_uris.contains(&info.uri) {
+                            entry.erd_uris.push(info.uri.clone());
+                        }
+                    }
+                }
+                Err(error) => parse_errors.push(GraphParseError {
+                    uri: info.uri.clone(),
+                    error,
+                }),
+            }
+        }
+
+        let mut unmatched_schemas = Vec::new();
+        for info in &specs {
+            let spec = self
+                .file_reader
+                .read_file_content(&info.file_path)
+                .map_err(|e| format!("Failed to read file: {}", e))
+                .and_then(|content| self.parsed_spec(info, &content));
+            let spec = match spec {
+                Ok(spec) => spec,
+                Err(error) => {
+                    parse_errors.push(GraphParseError {
+                        uri: info.uri.clone(),
+                        error,
+                    });
+                    continue;
+                }
+            };
+            for name in openapi::schema_names(&spec) {
+                let schema = ApiSchemaRef {
+                    name: name.to_string(),
+                    spec_uri: info.uri.clone(),
+                };
+                match entities.get_mut(&erd::match_key(name)) {
+                    Some(entity) => entity.schemas.push(schema),
+                    None => unmatched_schemas.push(schema),
+                }
+            }
+        }
+        unmatched_schemas.sort_by(|a, b| (&a.name, &a.spec_uri).cmp(&(&b.name, &b.spec_uri)));
+
+        let failed = |documents: &[&ResourceInfo]| {
+            documents
+                .iter()
+                .filter(|info| parse_errors.iter().any(|e| e.uri == info.uri))
+                .count()
+        };
+        let erd_documents_parsed = (erds.len() - failed(&erds)).try_into().unwrap_or(u32::MAX);
+        let specs_parsed = (specs.len() - failed(&specs))
+            .try_into()
+            .unwrap_or(u32::MAX);
+        let (matched, unmatched_entities) = entities
+            .into_values()
+            .partition(|entity| !entity.schemas.is_empty());
+
+        let response = ErdToApiResponse {
+            project,
+            matched,
+            unmatched_entities,
+            unmatched_schemas,
+            erd_documents_parsed,
+            specs_parsed,
+            parse_errors,
+        };
+
+        self.json_result(response, "ERD to API mapping response")
+    }
+
+    #[tool(
+        description = "Ranks text documents by relevance to a free-text query using term frequency over their full content, for exploratory questions where ordering matters. score = sum over query terms of count(term, doc) / sqrt(doc length in tokens) * ln(1 + N / df(term)), with N the number of ranked documents and df the number containing the term. Optional project filter; returns the top limit (default 10, max 50) with scores and per-term counts. Documents whose MIME type the server's content policy blocks are not ranked.",
+        annotations(
+            title = "🎯 Rank Documents",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn rank_documents(
+        &self,
+        Parameters(RankDocumentsArgs {
+            query,
+            project,
+            limit,
+        }): Parameters<RankDocumentsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut terms = search::tokenize(&query);
+        terms.sort();
+        terms.dedup();
+        if terms.is_empty() {
+            return Err(errors::invalid_argument(
+                "invalid_query",
+                "Query must contain at least one word of two or more characters",
+                errors::context!({"query": query}),
+            ));
+        }
+        let limit = limit.unwrap_or(DEFAULT_RANK_LIMIT);
+        if limit == 0 || limit > MAX_RANK_LIMIT {
+            return Err(errors::invalid_argument(
+                "invalid_limit",
+                format!("Limit must be between 1 and {}", MAX_RANK_LIMIT),
+                errors::context!({"provided_limit": limit}),
+            ));
+        }
+
+        let documents: Vec<(&ResourceInfo, Arc<TermCounts>)> = self
+            .store
+            .iter()
+            .filter(|info| {
+                project.as_ref().is_none_or(|p| info.project == *p)
+                    && (info.mime_type.starts_with("text/") || info.mime_type.ends_with("yaml"))
+            })
+            .filter_map(|info| Some((info, self.term_counts(info)?)))
+            .collect();
+
+        let mut document_frequency: BTreeMap<&str, u32> = BTreeMap::new();
+        for (_, counts) in &documents {
+            for term in &terms {
+                if counts.counts.contains_key(term) {
+                    *document_frequency.entry(term.as_str()).or_default() += 1;
+                }
+            }
+        }
+        let total = documents.len().try_into().unwrap_or(u32::MAX);
+
+        let mut results: Vec<RankedDocument> = documents
+            .iter()
+            .filter_map(|(info, counts)| {
+                let score = search::score(&terms, counts, &document_frequency, total);
+                (score > 0.0).then(|| RankedDocument {
+                    uri: info.uri.clone(),
+                    description: info.description.clone(),
+                    score: (score * 10_000.0).round() / 10_000.0,
+                    matched_terms: terms
+                        .iter()
+                        .filter_map(|term| Some((term.clone(), *counts.counts.get(term)?)))
+                        .collect(),
+                })
+            })
+            .collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.uri.cmp(&b.uri)));
+        let total_matches = results.len().try_into().unwrap_or(u32::MAX);
+        results.truncate(limit as usize);
+
+        let response = RankDocumentsResponse {
+            terms,
+            results,
+            total_matches,
+        };
+
+        self.json_result(response, "rank documents response")
+    }
+
+    #[tool(
+        description = "Returns the documents agents read most (get_resource_content and resources/read calls per document) with their read counts, to prioritize documentation upkeep. Counts cover the server's lifetime, or longer when usage_stats_file persists them across restarts. Optional project filter; limit defaults to 10, max 50.",
+        annotations(
+            title = "📈 Get Usage Stats",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn get_usage_stats(
+        &self,
+        Parameters(GetUsageStatsArgs { project, limit }): Parameters<GetUsageStatsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let limit = limit.unwrap_or(DEFAULT_RANK_LIMIT);
+        if limit == 0 || limit > MAX_RANK_LIMIT {
+            return Err(errors::invalid_argument(
+                "invalid_limit",
+                format!("Limit must be between 1 and {}", MAX_RANK_LIMIT),
+                errors::context!({"provided_limit": limit}),
+            ));
+        }
+
+        let mut documents: Vec<DocumentUsage> = self
+            .usage
+            .counts()
+            .into_iter()
+            .filter_map(|(uri, reads)| {
+                let document_project = self
+                    .store
+                    .get(&uri)
+                    .map(|info| info.project.clone())
+                    .unwrap_or_default();
+                project
+                    .as_ref()
+                    .is_none_or(|p| document_project == *p)
+                    .then_some(DocumentUsage {
+                        uri,
+                        project: document_project,
+                        reads,
+                    })
+            })
+            .collect();
+        documents.sort_by(|a, b| b.reads.cmp(&a.reads).then_with(|| a.uri.cmp(&b.uri)));
+        let documents_read = documents.len().try_into().unwrap_or(u32::MAX);
+        let total_reads = documents.iter().map(|document| document.reads).sum();
+        documents.truncate(limit as usize);
+
+        let response = UsageStatsResponse {
+            documents,
+            documents_read,
+            total_reads,
+        };
+        self.json_result(response, "usage stats response")
+    }
+
+    #[tool(
+        description = "Returns the documents most linked to from other indexed markdown documents, with the number and URIs of the linking documents. Highly referenced documents are often foundational (glossaries, core ADRs) and good starting points. Optional project filter (links from any project count); limit defaults to 10, max 50.",
+        annotations(
+            title = "🔗 Get Most Referenced Documents",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_most_referenced(
+        &self,
+        Parameters(GetMostReferencedArgs { project, limit }): Parameters<GetMostReferencedArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let limit = limit.unwrap_or(DEFAULT_RANK_LIMIT);
+        if limit == 0 || limit > MAX_RANK_LIMIT {
+            return Err(errors::invalid_argument(
+                "invalid_limit",
+                format!("Limit must be between 1 and {}", MAX_RANK_LIMIT),
+                errors::context!({"provided_limit": limit}),
+            ));
+        }
+
+        let mut referrers: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for info in self
+            .store
+            .iter()
+            .filter(|info| info.mime_type == "text/markdown")
+        {
+            for target in self.outgoing_links(info).iter() {
+                referrers
+                    .entry(target.clone())
+                    .or_default()
+                    .push(info.uri.clone());
+            }
+        }
+
+        let mut documents: Vec<ReferencedDocument> = referrers
+            .into_iter()
+            .filter_map(|(uri, referenced_by)| {
+                let info = self.store.get(&uri)?;
+                project
+                    .as_ref()
+                    .is_none_or(|p| info.project == *p)
+                    .then(|| ReferencedDocument {
+                        description: info.description.clone(),
+                        references: referenced_by.len().try_into().unwrap_or(u32::MAX),
+                        referenced_by,
+                        uri,
+                    })
+            })
+            .collect();
+        documents.sort_by(|a, b| {
+            b.references
+                .cmp(&a.references)
+                .then_with(|| a.uri.cmp(&b.uri))
+        });
+        let total_referenced = documents.len().try_into().unwrap_or(u32::MAX);
+        documents.truncate(limit as usize);
+
+        let response = MostReferencedResponse {
+            documents,
+            total_referenced,
+        };
+
+        self.bounded_result(response, "most referenced response", |response| {
+            halve(&mut response.documents)
+        })
+    }
+
+    #[tool(
+        description = "Returns a project's document graph in Graphviz DOT, ready to render: one node per document (labelled with its frontmatter title, first heading or description) and an edge per relative markdown link between two documents of the project. Documents nothing links to, and non-markdown documents, appear as isolated nodes. Returns project_not_found when the project has no documents.",
+        annotations(
+            title = "🗺️ Get Project Graph (DOT)",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_project_graph_dot(
+        &self,
+        Parameters(GetProjectGraphDotArgs { project }): Parameters<GetProjectGraphDotArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let documents: Vec<&ResourceInfo> = self.store.by_project(&project).collect();
+        if documents.is_empty() {
+            return Err(errors::not_found(
+                "project_not_found",
+                "No documents found for the specified project",
+                errors::context!({"project": project}),
+            ));
+        }
+
+        let mut node_lines: Vec<String> = documents
+            .iter()
+            .map(|info| {
+                format!(
+                    "  {} [label={}];\n",
+                    dot_quote(&info.uri),
+                    dot_quote(&self.document_title(info))
+                )
+            })
+            .collect();
+        let mut edge_lines = Vec::new();
+        for info in documents
+            .iter()
+            .filter(|info| info.mime_type == "text/markdown")
+        {
+            for target in self.outgoing_links(info).iter() {
+                if self
+                    .store
+                    .get(target)
+                    .is_some_and(|target| target.project == project)
+                {
+                    edge_lines.push(format!(
+                        "  {} -> {};\n",
+                        dot_quote(&info.uri),
+                        dot_quote(target)
+                    ));
+                }
+            }
+        }
+        let render = |node_lines: &[String], edge_lines: &[String]| {
+            format!(
+                "digraph {} {{\n  rankdir=LR;\n  node [shape=box];\n{}{}}}\n",
+                dot_quote(&project),
+                node_lines.concat(),
+                edge_lines.concat()
+            )
+        };
+
+        let response = ProjectGraphDotResponse {
+            project: project.clone(),
+            nodes: node_lines.len().try_into().unwrap_or(u32::MAX),
+            edges: edge_lines.len().try_into().unwrap_or(u32::MAX),
+            dot: render(&node_lines, &edge_lines),
+        };
+        // Edges go first; nodes only once no edge is left, so no edge names a dropped node
+        self.bounded_result(response, "project graph response", |response| {
+            if !halve(&mut edge_lines) && !halve(&mut node_lines) {
+                return false;
+            }
+            response.nodes = node_lines.len().try_into().unwrap_or(u32::MAX);
+            response.edges = edge_lines.len().try_into().unwrap_or(u32::MAX);
+            response.dot = render(&node_lines, &edge_lines);
+            true
+        })
+    }
+
+    #[tool(
+        description = "Exports the catalog as an ElasticSearch/OpenSearch bulk-ingest body (NDJSON) for external docs search: per document an index action line with the URI as _id, then the document's metadata (the ResourceInfo fields). Set include_content=true to add each document's text as content (unreadable files and MIME types the content policy blocks are exported without it and listed in content_skipped); up to export_read_concurrency files are read at once and the export keeps catalog order). index_name sets the target index (default arch-docs; lowercase, no spaces or \\/*?\"<>|,#:). Optional project filter. POST the ndjson field to /_bulk as is. When the export exceeds max_response_bytes, whole documents are left out from the end and next_offset says where to continue (pass it as offset).",
+        annotations(
+            title = "📦 Export Search Index",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn export_search_index(
+        &self,
+        Parameters(ExportSearchIndexArgs {
+            index_name,
+            include_content,
+            project,
+            offset,
+        }): Parameters<ExportSearchIndexArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let index_name = index_name.unwrap_or_else(|| DEFAULT_SEARCH_INDEX.to_string());
+        let invalid = index_name.is_empty()
+            || index_name.starts_with(['-', '_', '+'])
+            || index_name
+                .chars()
+                .any(|c| c.is_uppercase() || c.is_whitespace() || "\\/*?\"<>|,#:".contains(c));
+        if invalid {
+            return Err(errors::invalid_argument(
+                "invalid_index_name",
+                "index_name must be lowercase, must not start with -, _ or + and must not \
+                 contain spaces or any of \\/*?\"<>|,#:",
+                errors::context!({"index_name": index_name}),
+            ));
+        }
+
+        let offset = offset.unwrap_or(0);
+        let matching: Vec<&ResourceInfo> = self
+            .store
+            .iter()
+            .filter(|info| matches_filter(&info.project, project.as_deref()))
+            .collect();
+        // Reads finish in any order; the map is keyed by URI so the export keeps catalog order
+        let mut contents: BTreeMap<String, String> = if include_content == Some(true) {
+            let files: Vec<(String, String)> = matching
+                .iter()
+                .skip(offset as usize)
+                .filter(|info| self.options.content.permits(&info.mime_type))
+                .map(|info| (info.uri.clone(), info.file_path.clone()))
+                .collect();
+            let reads = files.into_iter().map(|(uri, file_path)| {
+                let file_reader = self.file_reader.clone();
+                async move {
+                    let content = tokio::task::spawn_blocking(move || {
+                        file_reader.read_file_content(&file_path)
+                    })
+                    .await
+                    .ok()?
+                    .ok()?;
+                    Some((uri, content))
+                }
+            });
+            futures::stream::iter(reads)
+                .buffer_unordered(self.options.export_read_concurrency.max(1))
+                .filter_map(std::future::ready)
+                .collect()
+                .await
+        } else {
+            BTreeMap::new()
+        };
+        // (uri, action and document lines) per exported document
+        let mut pairs: Vec<(String, String)> = Vec::new();
+        let mut content_skipped = Vec::new();
+        for info in matching.iter().skip(offset as usize) {
+            let mut document = serde_json::to_value(info).map_err(|e| {
+                errors::internal(
+                    "serialization_error",
+                    format!("Failed to serialize search index document: {}", e),
+                    errors::context!({"uri": info.uri}),
+                )
+            })?;
+            if include_content == Some(true) {
+                match (contents.remove(&info.uri), document.as_object_mut()) {
+                    (Some(content), Some(fields)) => {
+                        fields.insert("content".to_string(), json!(content));
+                    }
+                    _ => content_skipped.push(info.uri.clone()),
+                }
+            }
+            let action = json!({"index": {"_index": index_name, "_id": info.uri}});
+            pairs.push((info.uri.clone(), format!("{}\n{}\n", action, document)));
+        }
+
+        let response = SearchIndexExportResponse {
+            index_name,
+            documents: pairs.len().try_into().unwrap_or(u32::MAX),
+            total_documents: matching.len().try_into().unwrap_or(u32::MAX),
+            next_offset: None,
+            content_skipped,
+            ndjson: pairs.iter().map(|(_, lines)| lines.as_str()).collect(),
+        };
+        // Whole action/document pairs are dropped, so the body stays valid for _bulk
+        self.bounded_result(response, "search index export response", |response| {
+            if !halve(&mut pairs) {
+                return false;
+            }
+            response.documents = pairs.len().try_into().unwrap_or(u32::MAX);
+            response.next_offset = Some(offset.saturating_add(response.documents));
+            response
+                .content_skipped
+                .retain(|uri| pairs.iter().any(|(kept, _)| kept == uri));
+            response.ndjson = pairs.iter().map(|(_, lines)| lines.as_str()).collect();
+            true
+        })
+    }
+
+    #[tool(
+        description = "Diagnostic: returns, per document type (agreements, c1, c2, c3, c4, erd, adr, openapi, guide, style-guide), the extension list the scanner used after normalization, the preferred-format order applied when a document exists in several formats, and any required file stem (c1/c2/c3). Use it to find out why a file was not indexed.",
+        annotations(
+            title = "🧩 Get Effective Extensions",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_effective_extensions(
+        &self,
+        Parameters(GetEffectiveExtensionsArgs {}): Parameters<GetEffectiveExtensionsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let response = EffectiveExtensionsResponse {
+            document_types: self.options.effective_extensions.clone(),
+        };
+
+        self.json_result(response, "effective extensions response")
+    }
+
+    #[tool(
+        description = "Returns the scan plan derived from the config: every (document_type, target, extensions) scan the indexer runs, in order, with the project or guide product the documents are registered under, preferred extensions and whether the type is enabled. Use it to find out why documents are not picked up: a missing target, an extension outside the list, or a disabled type.",
+        annotations(
+            title = "🗺️ Get Scan Plan",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_scan_plan(
+        &self,
+        Parameters(GetScanPlanArgs {}): Parameters<GetScanPlanArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let response = ScanPlanResponse {
+            total_steps: self.options.scan_plan.len().try_into().unwrap_or(u32::MAX),
+            steps: self.options.scan_plan.clone(),
+        };
+
+        self.json_result(response, "scan plan response")
+    }
+
+    #[tool(
+        description = "Reports whether the document index is ready: status scanning (initial scan still running; other tools answer from the documents indexed so far and flag it with index_status), ready or failed (with error), plus sources scanned of the total, documents found so far, documents currently served and, with verify_readability, unreadable_files. backing_store is unavailable while the docs root cannot be read: metadata tools keep working, content reads return backing_store_unavailable, and the index is rescanned once the root is back. Poll it after startup before relying on complete results.",
+        annotations(
+            title = "⏳ Get Scan Status",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn get_scan_status(
+        &self,
+        Parameters(GetScanStatusArgs {}): Parameters<GetScanStatusArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.json_result(self.scan_status(), "scan status response")
+    }
+
+    #[tool(
+        description = "Lists files under the docs root that have an extension some document type is scanned with but lie outside every configured scan target, so they are never indexed. Grouped by top-level directory. Usually documents whose directory is missing from the config. Requires detect_orphans in the server config; results come from the last completed scan.",
+        annotations(
+            title = "🧭 Get Orphaned Documents",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn get_orphaned_documents(
+        &self,
+        Parameters(GetOrphanedDocumentsArgs {}): Parameters<GetOrphanedDocumentsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.options.detect_orphans {
+            return Err(errors::invalid_argument(
+                "orphan_detection_disabled",
+                "Orphan detection is off; set detect_orphans = true in the server config",
+                errors::context!({}),
+            ));
+        }
+
+        let scan = self.scan_tracker.status();
+        let mut directories: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for path in scan.orphans {
+            let directory = path.split_once('/').map_or("", |(directory, _)| directory);
+            directories
+                .entry(directory.to_string())
+                .or_default()
+                .push(path);
+        }
+        let response = OrphanedDocumentsResponse {
+            total_orphans: scan.orphaned_files.unwrap_or_default(),
+            truncated: scan.orphan_scan_truncated,
+            directories,
+        };
+        self.bounded_result(response, "orphaned documents response", |response| {
+            response
+                .directories
+                .values_mut()
+                .max_by_key(|paths| paths.len())
+                .is_some_and(halve)
+        })
+    }
+
+    #[tool(
+        description = "Reports how many files each scanner path pattern classified in the last completed scan: the known layouts c4_system, c4_service, erd, adr and the OpenAPI variants openapi, openapi_endpoints, openapi_sub_category and openapi_sub_category_endpoints, plus scan_target for files indexed by their path under a configured target. Use it when tuning scan targets or deciding which layouts are still in use.",
+        annotations(
+            title = "🧮 Get Pattern Stats",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn get_pattern_stats(
+        &self,
+        Parameters(GetPatternStatsArgs {}): Parameters<GetPatternStatsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let scan = self.scan_tracker.status();
+        let response = PatternStatsResponse {
+            patterns: scan.pattern_matches,
+            files_visited: scan.files_visited,
+            last_scan_at: scan.last_scan_at,
+        };
+        self.json_result(response, "pattern stats response")
+    }
+
+    #[tool(
+        description = "Reports server health from in-process state: version, start time and uptime, docs root and backing store state, scan status with when the last scan finished and how long it took, documents served, open MCP sessions, and hits, misses and hit rate of the parsed OpenAPI spec and frontmatter caches. Use it in long sessions to check the index is fresh without an HTTP call.",
+        annotations(
+            title = "🩺 Get Server Health",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = false,
+            open_world_hint = false
+        )
+    )]
+    async fn get_server_health(
+        &self,
+        Parameters(GetServerHealthArgs {}): Parameters<GetServerHealthArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let (started, started_at) = *STARTED_AT;
+        let active_sessions = match &self.session_manager {
+            Some(manager) => Some(manager.sessions.read().await.len()),
+            None => None,
+        };
+        let ScanStatusResponse {
+            scan,
+            documents_served,
+            backing_store,
+        } = self.scan_status();
+        let response = ServerHealthResponse {
+            version: env!("CARGO_PKG_VERSION"),
+            started_at: started_at.to_rfc3339(),
+            uptime_seconds: started.elapsed().as_secs(),
+            docs_root: self.file_reader.docs_root().to_string(),
+            backing_store,
+            scan_status: scan.status,
+            last_scan_at: scan.last_scan_at,
+            last_scan_duration_ms: scan.last_scan_duration_ms,
+            documents_served,
+            active_sessions: active_sessions.map(|count| count.try_into().unwrap_or(u32::MAX)),
+            active_subscriptions: self
+                .subscriptions
+                .active_subscriptions()
+                .try_into()
+                .unwrap_or(u32::MAX),
+            caches: BTreeMap::from([
+                ("openapi_specs".to_string(), self.spec_cache.stats()),
+                ("frontmatter".to_string(), self.frontmatter_cache.stats()),
+            ]),
+        };
+
+        self.json_result(response, "server health response")
+    }
+
+    #[tool(
+        description = "Diagnostic: returns the distinct MIME types of all indexed documents with document counts. Use it to confirm which formats are served; unexpected text/plain entries usually point at misnamed files.",
+        annotations(
+            title = "🧾 Get MIME Type Facets",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_mime_type_facets(
+        &self,
+        Parameters(GetMimeTypeFacetsArgs {}): Parameters<GetMimeTypeFacetsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut mime_types: BTreeMap<String, u32> = BTreeMap::new();
+        for info in self.store.iter() {
+            *mime_types.entry(info.mime_type.clone()).or_default() += 1;
+        }
+        let response = MimeTypeFacetsResponse {
+            total_mime_types: mime_types.len().try_into().unwrap_or(u32::MAX),
+            mime_types,
+        };
+
+        self.json_result(response, "MIME type facets response")
+    }
+
+    #[tool(
+        description = "Lists documents whose format-specific parse failed during the last scan: OpenAPI specs that are not valid YAML/JSON and markdown with malformed frontmatter. Such documents are still served as raw text. Optional project filter. Returns URI, file path, format and parser error.",
+        annotations(
+            title = "🩹 Get Parse Failures",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_parse_failures(
+        &self,
+        Parameters(GetParseFailuresArgs { project }): Parameters<GetParseFailuresArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let failures: Vec<ParseFailure> = self
+            .store
+            .iter()
+            .filter(|info| project.as_ref().is_none_or(|p| info.project == *p))
+            .filter_map(|info| {
+                let error = info.parse_error.clone()?;
+                Some(ParseFailure {
+                    uri: info.uri.clone(),
+                    file_path: info.file_path.clone(),
+                    format: info.parsed_format().unwrap_or("unknown").to_string(),
+                    error,
+                })
+            })
+            .collect();
+
+        let response = ParseFailuresResponse {
+            total_failures: failures.len().try_into().unwrap_or(u32::MAX),
+            failures,
+        };
+
+        self.json_result(response, "parse failures response")
+    }
 
     #[tool(
-        description = "Retrieves all ADR (Architecture Decision Record) documents sorted by ADR number. Returns a list of all ADR documents with their metadata including URI, description, and file paths. ADR documents are identified by their category starting with 'ADR-' followed by the ADR number. Perfect for discovering and analyzing architectural decisions across the project. Essential for understanding why certain architectural choices were made, tracking decision history, and ensuring consistency in future development. Use this tool to get comprehensive view of all architectural decisions made in the project.",
+        description = "Get guide documents (product documentation). Returns installation, configuration, and how-to docs. Optional product filter (e.g., eva4, psrt). Use get_resource_content with the returned URI to read a guide.",
         annotations(
-            title = "📋 Get All ADR Documents",
+            title = "📚 Get Guides",
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true,
+            open_world_hint = false
+        )
+    )]
+    async fn get_guides(
+        &self,
+        Parameters(GetGuidesArgs { product }): Parameters<GetGuidesArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("guide")?;
+
+        let guide_documents: Vec<ResourceInfo> = self
+            .store
+            .iter()
+            .filter(|info| {
+                info.area == "guides" && product.as_ref().is_none_or(|p| info.project == *p)
+            })
+            .cloned()
+            .collect();
+
+        let total_guides = guide_documents.len().try_into().unwrap_or(u32::MAX);
+        let response = GuidesResponse {
+            product: product.clone(),
+            guides: guide_documents,
+            total_guides,
+        };
+
+        self.json_result(response, "guides response")
+    }
+
+    #[tool(
+        description = "Get API style guides: the documents under the configured style_guides paths, which are indexed apart from agreements (get_agreements does not return them) because they are large. Optional area (e.g., backend, frontend) and programming language filters. Use get_resource_content with the returned URIs to read them.",
+        annotations(
+            title = "🎨 Get API Style Guides",
             read_only_hint = true,
             destructive_hint = false,
             idempotent_hint = true,
             open_world_hint = false
         )
-    )]
-    async fn get_all_adr_documents(
-        &self,
-        _: Parameters<GetAllAdrDocumentsArgs>,
-    ) -> Result<CallToolResult, McpError> {
-        // Filter documents to get only ADR documents
-        let adr_documents: Vec<ResourceInfo> = self
-            .resources
-            .values()
-            .filter(|info| {
-                // Check if any category starts with "ADR-"
-                info.category.iter().any(|cat| cat.starts_with("ADR-"))
+    )]
+    async fn get_style_guides(
+        &self,
+        Parameters(GetStyleGuidesArgs { area, lang }): Parameters<GetStyleGuidesArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.ensure_type_enabled("style-guide")?;
+
+        let style_guides: Vec<ResourceInfo> = self
+            .store
+            .by_category("style-guide")
+            .filter(|info| area.as_ref().is_none_or(|area| info.area == *area))
+            .filter(|info| matches_lang_filter(info, lang.as_deref()))
+            .cloned()
+            .collect();
+
+        let response = StyleGuidesResponse {
+            total_style_guides: style_guides.len().try_into().unwrap_or(u32::MAX),
+            area,
+            lang,
+            style_guides,
+        };
+
+        self.json_result(response, "style guides response")
+    }
+}
+
+#[prompt_router]
+impl DocumentServer {}
+
+#[prompt_handler]
+impl ServerHandler for DocumentServer {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let call = ToolCallContext::new(self, request, context);
+        self.tool_router
+            .call(call)
+            .instrument(logging::session_span(self.log_session))
+            .await
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        Ok(ListToolsResult {
+            tools: self.tool_router.list_all(),
+            meta: None,
+            next_cursor: None,
+        })
+    }
+
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: ProtocolVersion::V_2024_11_05,
+            capabilities: ServerCapabilities::builder()
+                .enable_logging()
+                .enable_prompts()
+                .enable_resources()
+                .enable_tools()
+                .enable_experimental_with(ExperimentalCapabilities::from([(
+                    SCHEMA_CAPABILITY.to_string(),
+                    json!({"version": SCHEMA_VERSION})
+                        .as_object()
+                        .cloned()
+                        .unwrap_or_default(),
+                )]))
+                .build(),
+            server_info: Implementation::from_build_env(),
+            instructions: Some(self.instructions()),
+        }
+    }
+
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        let mut session_log = self.session_log.lock().map_err(|_| {
+            errors::internal(
+                "log_state_poisoned",
+                "Logging state is unavailable",
+                errors::context!({}),
+            )
+        })?;
+        match session_log.as_ref() {
+            Some(session) => session.set_level(request.level),
+            None => {
+                *session_log = Some(self.log_bridge.register(
+                    context.peer,
+                    self.log_session,
+                    request.level,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let resources: Vec<Resource> = self
+            .store
+            .iter()
+            .map(|info| {
+                let mut resource = RawResource::new(info.uri.clone(), info.description.clone());
+                resource.description = Some(info.description.clone());
+                resource.mime_type = Some(info.mime_type.clone());
+                resource.size = Some(info.size);
+                resource.no_annotation()
+            })
+            .collect();
+
+        Ok(ListResourcesResult {
+            resources,
+            next_cursor: None,
+            meta: None,
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        // First, find the resource by URI (or a renamed document's old URI) in our resources map
+        let resource_info = self.resolve_resource(&request.uri)?.ok_or_else(|| {
+            errors::not_found(
+                "resource_not_found",
+                "Resource not found in scanned documents",
+                errors::context!({"uri": request.uri}),
+            )
+        })?;
+
+        let _session = logging::session_span(self.log_session).entered();
+        tracing::debug!(uri = %request.uri, "Reading resource");
+        self.ensure_content_allowed(resource_info)?;
+        self.ensure_extension_allowed(resource_info)?;
+
+        // Then read the file content using the file path from ResourceInfo
+        let content = self.read_file_by_path(&resource_info.file_path)?;
+        self.usage.record_read(&resource_info.uri);
+        let meta = revision_meta(&content_revision(&content));
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: resource_info.uri.clone(),
+                mime_type: Some(resource_info.mime_type.clone()),
+                text: content,
+                meta: Some(meta),
+            }],
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        let mut resource_templates = Vec::new();
+        if self.ensure_type_enabled("openapi").is_ok() {
+            resource_templates.push(
+                RawResourceTemplate {
+                    uri_template: format!(
+                        "{}{{project}}/{{service}}/{{version}}/{{access}}/{{endpoint}}",
+                        OPENAPI_URI_SCHEME
+                    ),
+                    name: "openapi-spec".to_string(),
+                    title: Some("OpenAPI spec".to_string()),
+                    description: Some(
+                        "OpenAPI spec by service, version and access level. endpoint is the \
+                         spec file below the access level ([sub_category/]name, extension \
+                         optional); reads resolve to the canonical docs:// URI."
+                            .to_string(),
+                    ),
+                    mime_type: Some("application/x-yaml".to_string()),
+                    icons: None,
+                }
+                .no_annotation(),
+            );
+        }
+        Ok(ListResourceTemplatesResult {
+            next_cursor: None,
+            resource_templates,
+            meta: None,
+        })
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        let Some(info) = self.resolve_resource(&request.uri)? else {
+            return Err(errors::not_found(
+                "resource_not_found",
+                "Cannot subscribe to resource that does not exist",
+                errors::context!({"uri": request.uri}),
+            ));
+        };
+        self.session_subscriptions
+            .subscribe(session_id(&context).as_deref(), &info.uri);
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        // Unsubscribing from something never subscribed (or since deleted) is not an error.
+        let uri = match self.resolve_resource(&request.uri) {
+            Ok(Some(info)) => info.uri.clone(),
+            _ => request.uri,
+        };
+        self.session_subscriptions
+            .unsubscribe(session_id(&context).as_deref(), &uri);
+        Ok(())
+    }
+
+    async fn initialize(
+        &self,
+        request: InitializeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<InitializeResult, McpError> {
+        let _session = logging::session_span(self.log_session).entered();
+        if let Some(min_client_schema) = self.min_client_schema {
+            match declared_schema_version(&request.capabilities) {
+                Some(declared) if declared < min_client_schema => tracing::warn!(
+                    "Client {} understands response schema version {}, older than the required {}; \
+                     tool responses may not parse",
+                    request.client_info.name,
+                    declared,
+                    min_client_schema
+                ),
+                _ => {}
+            }
+        }
+        if let Some(http_request_part) = context.extensions.get::<axum::http::request::Parts>() {
+            let initialize_headers = &http_request_part.headers;
+            let initialize_uri = &http_request_part.uri;
+            tracing::info!(?initialize_headers, %initialize_uri, "initialize from http server");
+        }
+        Ok(self.get_info())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rmcp::{
+        ClientHandler, RoleClient, ServiceExt,
+        model::{LoggingLevel, LoggingMessageNotificationParam},
+        service::{NotificationContext, RunningService},
+    };
+    use tempfile::TempDir;
+    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_resource_content_tool_attributes() {
+        let router = DocumentServer::tool_router();
+        assert!(router.has_route("get_resource_content"));
+
+        let tools = router.list_all();
+        assert!(tools.iter().any(|t| t.name == "get_resource_content"));
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_content_invalid_path() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path().to_string_lossy().to_string();
+        let docs = DocumentServer::new_with_resources(
+            FileReader::new(docs_root).expect("file reader"),
+            BTreeMap::new(),
+        );
+        let args = GetResourceContentArgs {
+            path: "invalid/path".to_string(),
+            ..Default::default()
+        };
+
+        let result = docs.get_resource_content(Parameters(args)).await;
+        assert!(result.is_err());
+
+        if let Err(error) = result {
+            assert_eq!(error.code.0, -32602);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_content_splits_frontmatter_from_body() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let dir = temp_dir.path().join("architecture/mpa/adr");
+        std::fs::create_dir_all(&dir).expect("dir");
+        std::fs::write(
+            dir.join("001.md"),
+            "---\ntitle: Outbox\nstatus: accepted\n---\n# Outbox\n",
+        )
+        .expect("write");
+        std::fs::write(dir.join("002.md"), "# Retries\n").expect("write");
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                test_resource("docs://architecture/mpa/adr/001.md", "mpa", &["adr"]),
+                test_resource("docs://architecture/mpa/adr/002.md", "mpa", &["adr"]),
+            ],
+        );
+        let split = |path: &str| {
+            docs.get_resource_content(Parameters(GetResourceContentArgs {
+                path: path.to_string(),
+                split_frontmatter: Some(true),
+                ..Default::default()
+            }))
+        };
+
+        let result = split("docs://architecture/mpa/adr/001.md")
+            .await
+            .expect("with frontmatter");
+        let json = result_json(&result);
+        assert_eq!(
+            json["frontmatter"],
+            json!({"title": "Outbox", "status": "accepted"})
+        );
+        assert_eq!(json["body"], "# Outbox\n");
+        assert_eq!(json["uri"], "docs://architecture/mpa/adr/001.md");
+        assert!(json["revision"].is_string());
+
+        let result = split("docs://architecture/mpa/adr/002.md")
+            .await
+            .expect("without frontmatter");
+        let json = result_json(&result);
+        assert_eq!(json["frontmatter"], json!({}));
+        assert_eq!(json["body"], "# Retries\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unreadable_file_returns_permission_denied() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().expect("temp dir");
+        let file = temp_dir.path().join("architecture/mpa/adr/001.mdx");
+        std::fs::create_dir_all(file.parent().expect("parent")).expect("dir");
+        std::fs::write(&file, "# Outbox\n").expect("write");
+        let docs = server_with(
+            &temp_dir,
+            vec![test_resource(
+                "docs://architecture/mpa/adr/001.mdx",
+                "mpa",
+                &["adr"],
+            )],
+        );
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert_eq!(
+            docs.read_error("architecture/mpa/adr/001.mdx", &denied)
+                .message,
+            "permission_denied"
+        );
+
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o000)).expect("chmod");
+        if std::fs::read(&file).is_ok() {
+            // Privileged users read files regardless of their mode
+            return;
+        }
+        let error = docs
+            .get_resource_content(Parameters(GetResourceContentArgs {
+                path: "docs://architecture/mpa/adr/001.mdx".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .expect_err("unreadable");
+        assert_eq!(error.message, "permission_denied");
+        assert_eq!(
+            error.data.expect("data")["file_path"],
+            "architecture/mpa/adr/001.mdx"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_read_rejects_file_renamed_to_disallowed_extension() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let dir = temp_dir.path().join("architecture/mpa/adr");
+        std::fs::create_dir_all(&dir).expect("dir");
+        std::fs::write(dir.join("001.mdx"), "# Outbox\n").expect("write");
+        let docs = server_with(
+            &temp_dir,
+            vec![test_resource(
+                "docs://architecture/mpa/adr/001.mdx",
+                "mpa",
+                &["adr"],
+            )],
+        )
+        .with_options(ServerOptions {
+            effective_extensions: BTreeMap::from([(
+                "adr".to_string(),
+                EffectiveExtensions {
+                    extensions: vec!["mdx".to_string()],
+                    preferred: Vec::new(),
+                    file_stem: None,
+                },
+            )]),
+            ..Default::default()
+        });
+        let read = || {
+            docs.get_resource_content(Parameters(GetResourceContentArgs {
+                path: "docs://architecture/mpa/adr/001.mdx".to_string(),
+                ..Default::default()
+            }))
+        };
+        read().await.expect("read");
+
+        std::fs::rename(dir.join("001.mdx"), dir.join("001.sh")).expect("rename");
+        std::os::unix::fs::symlink("001.sh", dir.join("001.mdx")).expect("symlink");
+        let error = read().await.expect_err("disallowed extension");
+        assert_eq!(error.message, "extension_not_allowed");
+        assert_eq!(error.data.expect("data")["extension"], "sh");
+
+        let options = ServerOptions {
+            verify_extensions_on_read: false,
+            ..(*docs.options).clone()
+        };
+        let docs = docs.with_options(options);
+        docs.get_resource_content(Parameters(GetResourceContentArgs {
+            path: "docs://architecture/mpa/adr/001.mdx".to_string(),
+            ..Default::default()
+        }))
+        .await
+        .expect("guard disabled");
+    }
+
+    #[tokio::test]
+    async fn test_missing_docs_root_serves_metadata_and_rejects_reads() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let file = temp_dir.path().join("architecture/mpa/adr/001.mdx");
+        std::fs::create_dir_all(file.parent().expect("parent")).expect("dir");
+        std::fs::write(&file, "# Outbox\n").expect("write");
+        let docs = server_with(
+            &temp_dir,
+            vec![test_resource(
+                "docs://architecture/mpa/adr/001.mdx",
+                "mpa",
+                &["adr"],
+            )],
+        );
+        let read = || {
+            docs.get_resource_content(Parameters(GetResourceContentArgs {
+                path: "docs://architecture/mpa/adr/001.mdx".to_string(),
+                ..Default::default()
+            }))
+        };
+        read().await.expect("read");
+
+        std::fs::remove_dir_all(temp_dir.path()).expect("remove docs root");
+        assert_eq!(read().await.expect_err("gone").message, "file_read_error");
+        read().await.expect_err("gone");
+        assert_eq!(
+            read().await.expect_err("gone").message,
+            "backing_store_unavailable"
+        );
+
+        let listed = result_json(
+            &docs
+                .get_docs_list(Parameters(GetDocsListArgs::default()))
+                .await
+                .expect("metadata"),
+        );
+        assert_eq!(listed["total_documents"], 1);
+        let status = result_json(
+            &docs
+                .get_scan_status(Parameters(GetScanStatusArgs {}))
+                .await
+                .expect("status"),
+        );
+        assert_eq!(status["backing_store"], "unavailable");
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_content_conditional_read() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let file = temp_dir.path().join("docs/mpa/adr/001.mdx");
+        std::fs::create_dir_all(file.parent().expect("parent")).expect("dir");
+        std::fs::write(&file, "# Outbox\n").expect("write");
+        let mut adr = test_resource("docs://architecture/mpa/adr/001.mdx", "mpa", &["adr"]);
+        adr.file_path = "docs/mpa/adr/001.mdx".to_string();
+        let docs = server_with(&temp_dir, vec![adr]);
+
+        let read = |if_revision_not: Option<String>| {
+            docs.get_resource_content(Parameters(GetResourceContentArgs {
+                path: "docs://architecture/mpa/adr/001.mdx".to_string(),
+                if_revision_not,
+                ..Default::default()
+            }))
+        };
+
+        let first = read(None).await.expect("read");
+        assert_eq!(first.content[0].as_text().expect("text").text, "# Outbox\n");
+        let revision = result_json_at(&first, 1)["revision"].clone();
+        let again = read(None).await.expect("read");
+        assert_eq!(result_json_at(&again, 1)["revision"], revision);
+
+        let unchanged = read(revision.as_str().map(ToString::to_string))
+            .await
+            .expect("conditional read");
+        assert_eq!(unchanged.content.len(), 1);
+        assert_eq!(result_json(&unchanged)["not_modified"], true);
+
+        std::fs::write(&file, "# Outbox v2\n").expect("rewrite");
+        let changed = read(revision.as_str().map(ToString::to_string))
+            .await
+            .expect("conditional read");
+        assert_eq!(
+            changed.content[0].as_text().expect("text").text,
+            "# Outbox v2\n"
+        );
+        assert_ne!(result_json_at(&changed, 1)["revision"], revision);
+        assert_eq!(result_json_at(&changed, 1)["not_modified"], false);
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_content_inline_includes() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        for (relative, content) in [
+            (
+                "docs/guides/setup.mdx",
+                "import Intro from './_intro.mdx';\n\n# Setup\n<Intro />\n",
+            ),
+            ("docs/guides/_intro.mdx", "Shared intro.\n"),
+            ("docs/guides/loop.md", "<!-- include: loop.md -->\n"),
+        ] {
+            let file = temp_dir.path().join(relative);
+            std::fs::create_dir_all(file.parent().expect("parent")).expect("dir");
+            std::fs::write(&file, content).expect("write");
+        }
+        let resource = |uri: &str, file_path: &str| ResourceInfo {
+            file_path: file_path.to_string(),
+            ..test_resource(uri, "", &["guide"])
+        };
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                resource("docs://guides/setup.mdx", "docs/guides/setup.mdx"),
+                resource("docs://guides/loop.md", "docs/guides/loop.md"),
+            ],
+        );
+        let read = |path: &str, inline_includes: Option<bool>| {
+            docs.get_resource_content(Parameters(GetResourceContentArgs {
+                path: path.to_string(),
+                inline_includes,
+                ..Default::default()
+            }))
+        };
+
+        let assembled = read("docs://guides/setup.mdx", Some(true))
+            .await
+            .expect("assembled");
+        assert_eq!(
+            assembled.content[0].as_text().expect("text").text,
+            "\n# Setup\nShared intro.\n"
+        );
+        let raw = read("docs://guides/setup.mdx", None).await.expect("raw");
+        assert!(
+            raw.content[0]
+                .as_text()
+                .expect("text")
+                .text
+                .contains("<Intro />")
+        );
+
+        let error = read("docs://guides/loop.md", Some(true))
+            .await
+            .expect_err("cycle");
+        assert_eq!(error.message, "include_cycle");
+        assert_eq!(
+            error.data.expect("data")["error"],
+            "include cycle: docs/guides/loop.md -> docs/guides/loop.md"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_content_enforces_mime_type_lists() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let mut resources = Vec::new();
+        for (name, mime_type) in [
+            ("notes.md", "text/markdown"),
+            ("dump.bin", "application/octet-stream"),
+        ] {
+            std::fs::write(temp_dir.path().join(name), "content").expect("write");
+            resources.push(ResourceInfo {
+                file_path: name.to_string(),
+                mime_type: mime_type.to_string(),
+                ..test_resource(&format!("docs://misc/{}", name), "", &["misc"])
+            });
+        }
+        let base = server_with(&temp_dir, resources);
+        let with_content = |content: ContentConfig| {
+            base.clone().with_options(ServerOptions {
+                content,
+                ..Default::default()
+            })
+        };
+        let read = |docs: DocumentServer, path: &'static str| async move {
+            docs.get_resource_content(Parameters(GetResourceContentArgs {
+                path: path.to_string(),
+                ..Default::default()
+            }))
+            .await
+        };
+
+        assert!(read(base.clone(), "docs://misc/dump.bin").await.is_ok());
+
+        let denied = with_content(ContentConfig {
+            denied_mime_types: vec!["application/octet-stream".to_string()],
+            ..Default::default()
+        });
+        let error = read(denied.clone(), "docs://misc/dump.bin")
+            .await
+            .expect_err("denied");
+        assert_eq!(error.message, "content_type_blocked");
+        assert_eq!(
+            error.data.expect("data")["mime_type"],
+            "application/octet-stream"
+        );
+        assert!(read(denied, "docs://misc/notes.md").await.is_ok());
+
+        let allowed = with_content(ContentConfig {
+            allowed_mime_types: vec!["text/*".to_string()],
+            ..Default::default()
+        });
+        assert!(read(allowed.clone(), "docs://misc/notes.md").await.is_ok());
+        assert!(read(allowed.clone(), "docs://misc/dump.bin").await.is_err());
+        let listing = allowed
+            .list_documents(&GetDocsListArgs::default())
+            .expect("listing");
+        assert_eq!(listing.total_documents, 2);
+    }
+
+    #[tokio::test]
+    async fn test_content_policy_covers_readmes_and_inlined_partials() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        for (relative, content) in [
+            ("docs/mpa/README.md", "# MPA\n<!-- include: keys.yaml -->\n"),
+            ("docs/mpa/keys.yaml", "secret: value\n"),
+        ] {
+            let file = temp_dir.path().join(relative);
+            std::fs::create_dir_all(file.parent().expect("parent")).expect("dir");
+            std::fs::write(&file, content).expect("write");
+        }
+        let readme = ResourceInfo {
+            file_path: "docs/mpa/README.md".to_string(),
+            ..test_resource("docs://architecture/mpa/README.md", "mpa", &["c1"])
+        };
+        let base = server_with(&temp_dir, vec![readme]);
+        let with_denied = |mime_type: &str| {
+            base.clone().with_options(ServerOptions {
+                content: ContentConfig {
+                    denied_mime_types: vec![mime_type.to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+        };
+
+        let error = with_denied("text/markdown")
+            .get_project_readme(Parameters(GetProjectReadmeArgs {
+                project: "mpa".to_string(),
+            }))
+            .await
+            .expect_err("blocked readme");
+        assert_eq!(error.message, "content_type_blocked");
+
+        let docs = with_denied("application/x-yaml");
+        let error = docs
+            .get_resource_content(Parameters(GetResourceContentArgs {
+                path: "docs://architecture/mpa/README.md".to_string(),
+                inline_includes: Some(true),
+                ..Default::default()
+            }))
+            .await
+            .expect_err("blocked partial");
+        assert_eq!(error.message, "include_failed");
+        assert!(
+            error.data.expect("data")["error"]
+                .as_str()
+                .expect("error text")
+                .contains("application/x-yaml")
+        );
+        assert!(
+            docs.get_project_readme(Parameters(GetProjectReadmeArgs {
+                project: "mpa".to_string(),
+            }))
+            .await
+            .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_content_follows_uri_aliases() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        std::fs::write(temp_dir.path().join("api.yaml"), "openapi: 3.0.0\n").expect("write");
+        let spec = ResourceInfo {
+            file_path: "api.yaml".to_string(),
+            ..test_resource(
+                "docs://openapi/mpa/billing/v2/public/api.yaml",
+                "mpa",
+                &["openapi"],
+            )
+        };
+        let docs = server_with(&temp_dir, vec![spec]).with_options(ServerOptions {
+            uri_aliases: BTreeMap::from([
+                (
+                    "docs://openapi/billing/".to_string(),
+                    "docs://openapi/mpa/billing/".to_string(),
+                ),
+                (
+                    "docs://specs/billing.yaml".to_string(),
+                    "docs://openapi/billing/v2/public/api.yaml".to_string(),
+                ),
+                (
+                    "docs://loop/a.md".to_string(),
+                    "docs://loop/b.md".to_string(),
+                ),
+                (
+                    "docs://loop/b.md".to_string(),
+                    "docs://loop/a.md".to_string(),
+                ),
+            ]),
+            ..Default::default()
+        });
+        let read = |path: &str| {
+            docs.get_resource_content(Parameters(GetResourceContentArgs {
+                path: path.to_string(),
+                ..Default::default()
+            }))
+        };
+
+        for requested in [
+            "docs://openapi/billing/v2/public/api.yaml",
+            "docs://specs/billing.yaml",
+        ] {
+            let result = read(requested).await.expect("aliased read");
+            let status = result_json_at(&result, 1);
+            assert_eq!(
+                status["uri"],
+                "docs://openapi/mpa/billing/v2/public/api.yaml"
+            );
+            assert_eq!(status["aliased_from"], requested);
+        }
+        let direct = read("docs://openapi/mpa/billing/v2/public/api.yaml")
+            .await
+            .expect("direct read");
+        assert!(result_json_at(&direct, 1).get("aliased_from").is_none());
+
+        let error = read("docs://loop/a.md").await.expect_err("cycle");
+        assert_eq!(error.message, "uri_alias_cycle");
+        assert_eq!(
+            error.data.expect("data")["chain"],
+            json!(["docs://loop/a.md", "docs://loop/b.md", "docs://loop/a.md"])
+        );
+        let error = read("docs://unknown.md").await.expect_err("missing");
+        assert_eq!(error.message, "resource_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_content_accepts_file_paths_when_enabled() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let relative = "architecture/mpa/adr/001-outbox.mdx";
+        let file = temp_dir.path().join(relative);
+        std::fs::create_dir_all(file.parent().expect("parent")).expect("dir");
+        std::fs::write(&file, "# Outbox\n").expect("write");
+        let adr = ResourceInfo {
+            file_path: relative.to_string(),
+            ..test_resource(
+                "docs://architecture/mpa/adr/001-outbox.mdx",
+                "mpa",
+                &["adr"],
+            )
+        };
+        let strict = server_with(&temp_dir, vec![adr]);
+        let lenient = strict.clone().with_options(ServerOptions {
+            accept_file_paths: true,
+            ..Default::default()
+        });
+        let read = |docs: DocumentServer, path: &str| {
+            let path = path.to_string();
+            async move {
+                docs.get_resource_content(Parameters(GetResourceContentArgs {
+                    path,
+                    ..Default::default()
+                }))
+                .await
+            }
+        };
+
+        let result = read(lenient.clone(), "./architecture/mpa/adr/001-outbox.mdx")
+            .await
+            .expect("file path read");
+        assert_eq!(
+            result.content[0].as_text().expect("text").text,
+            "# Outbox\n"
+        );
+        let status = result_json_at(&result, 1);
+        assert_eq!(status["uri"], "docs://architecture/mpa/adr/001-outbox.mdx");
+        assert_eq!(
+            status["aliased_from"],
+            "./architecture/mpa/adr/001-outbox.mdx"
+        );
+
+        for rejected in ["../architecture/mpa/adr/001-outbox.mdx", "/etc/passwd"] {
+            let error = read(lenient.clone(), rejected).await.expect_err("rejected");
+            assert_eq!(error.message, "invalid_path");
+        }
+        let error = read(strict, relative).await.expect_err("strict");
+        assert_eq!(error.message, "invalid_path");
+        assert_eq!(
+            error.data.expect("data")["error"],
+            "Path must start with 'docs://'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_content_tolerates_uri_formatting() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let mut resources = Vec::new();
+        for name in ["Proj-A/adr/001-foo.mdx", "mpa/adr/002.md", "mpa/adr/002.MD"] {
+            let relative = format!("architecture/{}", name);
+            let file = temp_dir.path().join(&relative);
+            std::fs::create_dir_all(file.parent().expect("parent")).expect("dir");
+            std::fs::write(&file, name).expect("write");
+            resources.push(ResourceInfo {
+                file_path: relative,
+                ..test_resource(&format!("docs://architecture/{}", name), "mpa", &["adr"])
+            });
+        }
+        let docs = server_with(&temp_dir, resources);
+        let read = |path: &str| {
+            docs.get_resource_content(Parameters(GetResourceContentArgs {
+                path: path.to_string(),
+                ..Default::default()
+            }))
+        };
+
+        for requested in [
+            "docs://architecture/Proj-A/adr/001-foo.mdx/",
+            "docs://architecture//Proj-A/adr///001-foo.mdx",
+            "docs://ARCHITECTURE/proj-a/adr/001-FOO.mdx",
+        ] {
+            let result = read(requested).await.expect("tolerant read");
+            assert_eq!(
+                result_json_at(&result, 1)["uri"],
+                "docs://architecture/Proj-A/adr/001-foo.mdx"
+            );
+        }
+        let exact = read("docs://architecture/mpa/adr/002.MD")
+            .await
+            .expect("exact read");
+        assert_eq!(
+            result_json_at(&exact, 1)["uri"],
+            "docs://architecture/mpa/adr/002.MD"
+        );
+
+        let error = read("docs://architecture/mpa/adr/002.Md")
+            .await
+            .expect_err("ambiguous");
+        assert_eq!(error.message, "resource_not_found");
+        assert_eq!(
+            error.data.expect("data")["suggestions"],
+            json!([
+                "docs://architecture/mpa/adr/002.MD",
+                "docs://architecture/mpa/adr/002.md"
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_content_strip_jsx_only_for_mdx() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let body = "<Callout>\nKeep <b>this</b>\n</Callout>\n";
+        let mut resources = Vec::new();
+        for name in ["001.mdx", "002.md"] {
+            let relative = format!("docs/mpa/adr/{}", name);
+            let file = temp_dir.path().join(&relative);
+            std::fs::create_dir_all(file.parent().expect("parent")).expect("dir");
+            std::fs::write(&file, body).expect("write");
+            let mut info = test_resource(
+                &format!("docs://architecture/mpa/adr/{}", name),
+                "mpa",
+                &["adr"],
+            );
+            info.file_path = relative;
+            resources.push(info);
+        }
+        let docs = server_with(&temp_dir, resources);
+
+        let read = |path: &str, strip_jsx: Option<bool>| {
+            docs.get_resource_content(Parameters(GetResourceContentArgs {
+                path: path.to_string(),
+                strip_jsx,
+                ..Default::default()
+            }))
+        };
+        let text = |result: CallToolResult| result.content[0].as_text().expect("text").text.clone();
+
+        let stripped = read("docs://architecture/mpa/adr/001.mdx", Some(true))
+            .await
+            .expect("read");
+        assert_eq!(text(stripped), "Keep this\n");
+
+        let raw = read("docs://architecture/mpa/adr/001.mdx", None)
+            .await
+            .expect("read");
+        assert_eq!(text(raw), body);
+
+        let markdown = read("docs://architecture/mpa/adr/002.md", Some(true))
+            .await
+            .expect("read");
+        assert_eq!(text(markdown), body);
+    }
+
+    #[tokio::test]
+    async fn test_get_resource_content_wraps_markdown_and_leaves_yaml_intact() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let markdown =
+            "# Outbox\nEvents are stored with the business change and published later.\n";
+        let yaml = "info: {title: Billing API, description: Charges customers for their orders}\n";
+        let mut adr = test_resource("docs://architecture/mpa/adr/001.md", "mpa", &["adr"]);
+        adr.file_path = "docs/mpa/adr/001.md".to_string();
+        let spec = write_spec(&temp_dir, "mpa", "billing", yaml);
+        std::fs::create_dir_all(temp_dir.path().join("docs/mpa/adr")).expect("dir");
+        std::fs::write(temp_dir.path().join(&adr.file_path), markdown).expect("write");
+        let spec_uri = spec.uri.clone();
+        let docs = server_with(&temp_dir, vec![adr, spec]);
+
+        let read = |path: &str, wrap_code: Option<bool>| {
+            docs.get_resource_content(Parameters(GetResourceContentArgs {
+                path: path.to_string(),
+                wrap_column: Some(30),
+                wrap_code,
+                ..Default::default()
+            }))
+        };
+        let text = |result: CallToolResult| result.content[0].as_text().expect("text").text.clone();
+
+        let wrapped = read("docs://architecture/mpa/adr/001.md", None)
+            .await
+            .expect("read");
+        assert_eq!(
+            text(wrapped),
+            "# Outbox\nEvents are stored with the\nbusiness change and published\nlater.\n"
+        );
+        let spec_text = read(&spec_uri, None).await.expect("read");
+        assert_eq!(text(spec_text), yaml);
+        let forced = text(read(&spec_uri, Some(true)).await.expect("read"));
+        assert!(forced.lines().all(|line| line.chars().count() <= 30));
+        assert_eq!(forced.replace('\n', ""), yaml.trim_end());
+    }
+
+    #[tokio::test]
+    async fn test_get_normalized_content_normalizes_crlf_and_whitespace() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let file = temp_dir.path().join("docs/mpa/adr/001.mdx");
+        std::fs::create_dir_all(file.parent().expect("parent")).expect("dir");
+        std::fs::write(&file, "# Outbox   \r\n\r\nBody\t\r\n\r\n").expect("write");
+        let mut adr = test_resource("docs://architecture/mpa/adr/001.mdx", "mpa", &["adr"]);
+        adr.file_path = "docs/mpa/adr/001.mdx".to_string();
+        let docs = server_with(&temp_dir, vec![adr]);
+
+        let result = docs
+            .get_normalized_content(Parameters(GetNormalizedContentArgs {
+                path: "docs://architecture/mpa/adr/001.mdx".to_string(),
+            }))
+            .await
+            .expect("normalized");
+        let json = result_json(&result);
+        assert_eq!(json["normalization"], "text");
+        assert_eq!(json["content"], "# Outbox\n\nBody\n");
+    }
+
+    #[test]
+    fn test_tool_overrides_patch_advertised_metadata() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = server_with(&temp_dir, Vec::new()).with_options(ServerOptions {
+            tool_overrides: BTreeMap::from([(
+                "get_docs_list".to_string(),
+                ToolOverride {
+                    title: Some("Dokumente".to_string()),
+                    description: Some("Listet Dokumente".to_string()),
+                },
+            )]),
+            ..ServerOptions::default()
+        });
+
+        let tools = docs.tool_router.list_all();
+        let tool = |name: &str| {
+            tools
+                .iter()
+                .find(|tool| tool.name == name)
+                .expect("tool listed")
+        };
+        let docs_list = tool("get_docs_list");
+        assert_eq!(docs_list.description.as_deref(), Some("Listet Dokumente"));
+        assert_eq!(docs_list.title.as_deref(), Some("Dokumente"));
+        assert_eq!(
+            docs_list
+                .annotations
+                .as_ref()
+                .and_then(|a| a.title.as_deref()),
+            Some("Dokumente")
+        );
+        let guides = tool("get_guides");
+        assert!(
+            guides
+                .description
+                .as_deref()
+                .is_some_and(|d| d.starts_with("Get guide documents"))
+        );
+        assert_eq!(
+            guides.annotations.as_ref().and_then(|a| a.title.as_deref()),
+            Some("📚 Get Guides")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_docs_list_tool_attributes() {
+        let router = DocumentServer::tool_router();
+        assert!(router.has_route("get_docs_list"));
+
+        let tools = router.list_all();
+        assert!(tools.iter().any(|t| t.name == "get_docs_list"));
+    }
+
+    #[tokio::test]
+    async fn test_get_docs_list_pagination_validation() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path().to_string_lossy().to_string();
+        let docs = DocumentServer::new_with_resources(
+            FileReader::new(docs_root).expect("file reader"),
+            BTreeMap::new(),
+        );
+        let args = GetDocsListArgs {
+            page: Some(0), // Invalid page
+            limit: Some(50),
+            ..Default::default()
+        };
+
+        let result = docs.get_docs_list(Parameters(args)).await;
+        assert!(result.is_err());
+
+        if let Err(error) = result {
+            assert_eq!(error.code.0, -32602);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_docs_list_limit_validation() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path().to_string_lossy().to_string();
+        let docs = DocumentServer::new_with_resources(
+            FileReader::new(docs_root).expect("file reader"),
+            BTreeMap::new(),
+        );
+        let args = GetDocsListArgs {
+            page: Some(1),
+            limit: Some(201), // Invalid limit (max is 200)
+            ..Default::default()
+        };
+
+        let result = docs.get_docs_list(Parameters(args)).await;
+        assert!(result.is_err());
+
+        if let Err(error) = result {
+            assert_eq!(error.code.0, -32602);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_file_by_path_success() {
+        // Create a temporary test file
+        let temp_dir = std::env::temp_dir();
+        let test_file = temp_dir.join("test_read_file.txt");
+        let test_content = "Test file content for reading";
+
+        std::fs::write(&test_file, test_content).expect("Failed to write test file");
+
+        // Create DocumentServer instance with a mock FileReader that can read our test file
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path().to_string_lossy().to_string();
+        let docs = DocumentServer::new_with_resources(
+            FileReader::new(docs_root).expect("file reader"),
+            BTreeMap::new(),
+        );
+
+        // Test reading the file (this will fail if the file doesn't exist in the docs root)
+        // We'll test the error case since we can't easily mock the FileReader
+        let result = docs.read_file_by_path("nonexistent_file.txt");
+        assert!(result.is_err());
+
+        // Clean up
+        let _ = std::fs::remove_file(&test_file);
+    }
+
+    #[tokio::test]
+    async fn test_read_file_by_path_error_handling() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path().to_string_lossy().to_string();
+        let docs = DocumentServer::new_with_resources(
+            FileReader::new(docs_root).expect("file reader"),
+            BTreeMap::new(),
+        );
+        let result = docs.read_file_by_path("nonexistent_file.txt");
+
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert_eq!(error.code.0, -32603); // Internal error
+            assert!(error.data.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_all_adr_documents_tool_attributes() {
+        let router = DocumentServer::tool_router();
+        assert!(router.has_route("get_all_adr_documents"));
+
+        let tools = router.list_all();
+        assert!(tools.iter().any(|t| t.name == "get_all_adr_documents"));
+    }
+
+    #[tokio::test]
+    async fn test_get_project_overview_tool_attributes() {
+        let router = DocumentServer::tool_router();
+        assert!(router.has_route("get_project_overview"));
+
+        let tools = router.list_all();
+        assert!(tools.iter().any(|t| t.name == "get_project_overview"));
+    }
+
+    #[tokio::test]
+    async fn test_get_project_overview_project_not_found() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path().to_string_lossy().to_string();
+        let docs = DocumentServer::new_with_resources(
+            FileReader::new(docs_root).expect("file reader"),
+            BTreeMap::new(),
+        );
+        let args = GetProjectOverviewArgs {
+            project: "nonexistent_project".to_string(),
+        };
+
+        let result = docs.get_project_overview(Parameters(args)).await;
+        assert!(result.is_err());
+
+        if let Err(error) = result {
+            assert_eq!(error.code.0, -32002);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_agreements_tool_attributes() {
+        let router = DocumentServer::tool_router();
+        assert!(router.has_route("get_agreements"));
+
+        let tools = router.list_all();
+        assert!(tools.iter().any(|t| t.name == "get_agreements"));
+    }
+
+    #[tokio::test]
+    async fn test_get_agreements_success() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path().to_string_lossy().to_string();
+        let docs = DocumentServer::new_with_resources(
+            FileReader::new(docs_root).expect("file reader"),
+            BTreeMap::new(),
+        );
+        let args = GetAgreementsArgs {
+            lang: "php".to_string(),
+        };
+
+        let result = docs.get_agreements(Parameters(args)).await;
+        // This will succeed even with empty results since we don't have agreements in test data
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_agreements_for_project_includes_scoped_and_global() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let agreement = |uri: &str, applies_to: &[&str]| ResourceInfo {
+            area: "backend".to_string(),
+            lang: "go".to_string(),
+            applies_to: applies_to.iter().map(ToString::to_string).collect(),
+            ..test_resource(uri, "", &["agreements"])
+        };
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                agreement("docs://agreements/backend/go/errors.md", &["crm", "mpa"]),
+                agreement("docs://agreements/backend/go/style.md", &[]),
+                test_resource("docs://architecture/mpa/adr/001.mdx", "mpa", &["adr"]),
+            ],
+        );
+        let agreements_for = |project: &str| {
+            let docs = docs.clone();
+            let project = project.to_string();
+            async move {
+                result_json(
+                    &docs
+                        .get_agreements_for_project(Parameters(GetAgreementsForProjectArgs {
+                            project,
+                            lang: None,
+                        }))
+                        .await
+                        .expect("agreements"),
+                )
+            }
+        };
+
+        for project in ["crm", "mpa"] {
+            let json = agreements_for(project).await;
+            assert_eq!(
+                json["scoped"][0]["uri"],
+                "docs://agreements/backend/go/errors.md"
+            );
+            assert_eq!(
+                json["global"][0]["uri"],
+                "docs://agreements/backend/go/style.md"
+            );
+            assert_eq!(json["total_agreements"], 2);
+        }
+
+        let billing = agreements_for("billing").await;
+        assert_eq!(billing["scoped"], json!([]));
+        assert_eq!(billing["total_agreements"], 1);
+
+        let php = docs
+            .get_agreements_for_project(Parameters(GetAgreementsForProjectArgs {
+                project: "mpa".to_string(),
+                lang: Some("php".to_string()),
+            }))
+            .await
+            .expect("agreements");
+        assert_eq!(result_json(&php)["total_agreements"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_agreements_index_groups_by_area_and_language() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let agreement = |uri: &str, area: &str, lang: &str| {
+            let mut info = test_resource(uri, "", &["agreements"]);
+            info.area = area.to_string();
+            info.lang = lang.to_string();
+            info
+        };
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                agreement("docs://agreements/backend/go/errors.md", "backend", "go"),
+                agreement("docs://agreements/backend/go/logging.md", "backend", "go"),
+                agreement("docs://agreements/backend/php/errors.md", "backend", "php"),
+                agreement("docs://agreements/frontend/ts/state.md", "frontend", "ts"),
+                test_resource("docs://architecture/mpa/adr/001.mdx", "mpa", &["adr"]),
+            ],
+        );
+
+        let result = docs
+            .get_agreements_index(Parameters(GetAgreementsIndexArgs {}))
+            .await
+            .expect("index");
+        let json = result_json(&result);
+
+        assert_eq!(json["total_agreements"], 4);
+        assert_eq!(json["areas"]["backend"]["count"], 3);
+        assert_eq!(json["areas"]["backend"]["languages"]["go"]["count"], 2);
+        assert_eq!(
+            json["areas"]["backend"]["languages"]["php"]["uris"],
+            json!(["docs://agreements/backend/php/errors.md"])
+        );
+        assert_eq!(json["areas"]["frontend"]["languages"]["ts"]["count"], 1);
+        assert!(json["areas"]["frontend"]["languages"].get("go").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_multi_language_agreement_matches_each_language() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let mut shared = test_resource(
+            "docs://agreements/backend/shared/errors.md",
+            "",
+            &["agreements"],
+        );
+        shared.lang = "go".to_string();
+        shared.langs = vec!["go".to_string(), "php".to_string()];
+        let mut php_only = test_resource(
+            "docs://agreements/backend/php/style.md",
+            "",
+            &["agreements"],
+        );
+        php_only.lang = "php".to_string();
+        let docs = server_with(&temp_dir, vec![shared, php_only]);
+
+        let agreements = |lang: &str| {
+            let docs = docs.clone();
+            let lang = lang.to_string();
+            async move {
+                let result = docs
+                    .get_agreements(Parameters(GetAgreementsArgs { lang }))
+                    .await
+                    .expect("agreements");
+                let json = result_json(&result);
+                json["agreements"]
+                    .as_array()
+                    .expect("agreements")
+                    .iter()
+                    .filter_map(|doc| doc["uri"].as_str().map(ToString::to_string))
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(
+            agreements("go").await,
+            vec!["docs://agreements/backend/shared/errors.md"]
+        );
+        assert_eq!(
+            agreements("php").await,
+            vec![
+                "docs://agreements/backend/php/style.md",
+                "docs://agreements/backend/shared/errors.md"
+            ]
+        );
+
+        let listing = docs
+            .list_documents(&GetDocsListArgs {
+                lang: Some("php".to_string()),
+                ..Default::default()
+            })
+            .expect("listing");
+        assert_eq!(listing.total_documents, 2);
+
+        let index = docs
+            .get_agreements_index(Parameters(GetAgreementsIndexArgs {}))
+            .await
+            .expect("index");
+        let json = result_json(&index);
+        assert_eq!(json["total_agreements"], 2);
+        assert_eq!(
+            json["areas"]["architecture"]["languages"]["php"]["count"],
+            2
+        );
+        assert_eq!(json["areas"]["architecture"]["languages"]["go"]["count"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_tools_for_disabled_document_types_are_rejected() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = server_with(&temp_dir, Vec::new()).with_options(ServerOptions {
+            enabled_document_types: vec!["adr".to_string()],
+            ..Default::default()
+        });
+
+        let error = docs
+            .get_guides(Parameters(GetGuidesArgs { product: None }))
+            .await
+            .expect_err("guides disabled");
+        assert_eq!(error.message, "document_type_disabled");
+        assert_eq!(error.data.expect("data")["document_type"], "guide");
+        assert!(
+            docs.get_global_adrs(Parameters(GetGlobalAdrsArgs {}))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_guides_tool_attributes() {
+        let router = DocumentServer::tool_router();
+        assert!(router.has_route("get_guides"));
+
+        let tools = router.list_all();
+        assert!(tools.iter().any(|t| t.name == "get_guides"));
+    }
+
+    #[tokio::test]
+    async fn test_get_guides_success() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs_root = temp_dir.path().to_string_lossy().to_string();
+        let docs = DocumentServer::new_with_resources(
+            FileReader::new(docs_root).expect("file reader"),
+            BTreeMap::new(),
+        );
+        let args = GetGuidesArgs { product: None };
+
+        let result = docs.get_guides(Parameters(args)).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_style_guides_filters_by_area_and_lang() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let style_guide = |uri: &str, area: &str, lang: &str| ResourceInfo {
+            area: area.to_string(),
+            lang: lang.to_string(),
+            ..test_resource(uri, "", &["style-guide", "api-documentation"])
+        };
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                style_guide("docs://style-guides/backend/php/rest.md", "backend", "php"),
+                style_guide("docs://style-guides/backend/go/rest.md", "backend", "go"),
+                style_guide("docs://style-guides/frontend/ts/rest.md", "frontend", "ts"),
+                test_resource("docs://agreements/backend/php/http.md", "", &["agreements"]),
+            ],
+        );
+        let style_guides = |area: Option<&str>, lang: Option<&str>| {
+            let docs = docs.clone();
+            let args = GetStyleGuidesArgs {
+                area: area.map(ToString::to_string),
+                lang: lang.map(ToString::to_string),
+            };
+            async move {
+                result_json(
+                    &docs
+                        .get_style_guides(Parameters(args))
+                        .await
+                        .expect("guides"),
+                )
+            }
+        };
+
+        assert_eq!(style_guides(None, None).await["total_style_guides"], 3);
+        let backend = style_guides(Some("backend"), None).await;
+        assert_eq!(backend["total_style_guides"], 2);
+        let php = style_guides(Some("backend"), Some("php")).await;
+        assert_eq!(php["total_style_guides"], 1);
+        assert_eq!(
+            php["style_guides"][0]["uri"],
+            "docs://style-guides/backend/php/rest.md"
+        );
+    }
+
+    fn test_resource(uri: &str, project: &str, category: &[&str]) -> ResourceInfo {
+        ResourceInfo {
+            uri: uri.to_string(),
+            file_path: uri.trim_start_matches("docs://").to_string(),
+            area: "architecture".to_string(),
+            lang: String::new(),
+            category: category.iter().map(ToString::to_string).collect(),
+            project: project.to_string(),
+            mime_type: "text/markdown".to_string(),
+            size: 10,
+            description: format!("Test document {}", uri),
+            ..Default::default()
+        }
+    }
+
+    fn server_with(temp_dir: &TempDir, resources: Vec<ResourceInfo>) -> DocumentServer {
+        let docs_root = temp_dir.path().to_string_lossy().to_string();
+        DocumentServer::new_with_resources(
+            FileReader::new(docs_root).expect("file reader"),
+            resources
+                .into_iter()
+                .map(|info| (DocumentKey::new(info.uri.clone()), info))
+                .collect(),
+        )
+    }
+
+    fn result_json(result: &CallToolResult) -> serde_json::Value {
+        result_json_at(result, 0)
+    }
+
+    fn result_json_at(result: &CallToolResult, index: usize) -> serde_json::Value {
+        let text = result.content[index]
+            .as_text()
+            .map(|t| t.text.clone())
+            .expect("text content");
+        serde_json::from_str(&text).expect("json response")
+    }
+
+    #[tokio::test]
+    async fn test_get_metadata_batch_reports_found_and_missing() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                test_resource("docs://architecture/proj-a/c1.mdx", "proj-a", &["c1"]),
+                test_resource("docs://architecture/proj-a/c2.mdx", "proj-a", &["c2"]),
+            ],
+        );
+        let args = GetMetadataBatchArgs {
+            paths: vec![
+                "docs://architecture/proj-a/c2.mdx".to_string(),
+                "docs://missing.mdx".to_string(),
+                "docs://architecture/proj-a/c1.mdx".to_string(),
+            ],
+        };
+
+        let result = docs
+            .get_metadata_batch(Parameters(args))
+            .await
+            .expect("batch result");
+        let response: MetadataBatchResponse =
+            serde_json::from_value(result_json(&result)).expect("response shape");
+
+        let uris: Vec<&str> = response.documents.iter().map(|d| d.uri.as_str()).collect();
+        assert_eq!(
+            uris,
+            vec![
+                "docs://architecture/proj-a/c2.mdx",
+                "docs://architecture/proj-a/c1.mdx"
+            ]
+        );
+        assert_eq!(response.not_found, vec!["docs://missing.mdx"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_metadata_batch_rejects_oversized_input() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = server_with(&temp_dir, Vec::new());
+        let args = GetMetadataBatchArgs {
+            paths: vec!["docs://x.md".to_string(); MAX_METADATA_BATCH_SIZE + 1],
+        };
+
+        let result = docs.get_metadata_batch(Parameters(args)).await;
+        assert_eq!(result.expect_err("too many paths").code.0, -32602);
+    }
+
+    #[tokio::test]
+    async fn test_get_unclassified_reports_misfiled_agreement() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let misfiled = ResourceInfo {
+            area: "agreements".to_string(),
+            ..test_resource("docs://agreements/naming.md", "", &["agreements"])
+        };
+        let filed = ResourceInfo {
+            area: "backend".to_string(),
+            lang: "php".to_string(),
+            ..test_resource(
+                "docs://agreements/backend/php/style.md",
+                "",
+                &["agreements"],
+            )
+        };
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                misfiled,
+                filed,
+                test_resource("docs://architecture/mpa/adr/001.mdx", "mpa", &["adr"]),
+            ],
+        );
+
+        let result = docs
+            .get_unclassified(Parameters(GetUnclassifiedArgs {}))
+            .await
+            .expect("unclassified");
+        let json = result_json(&result);
+        assert_eq!(json["total_documents"], 1);
+        assert_eq!(json["documents"][0]["uri"], "docs://agreements/naming.md");
+        assert_eq!(json["documents"][0]["file_path"], "agreements/naming.md");
+    }
+
+    #[tokio::test]
+    async fn test_get_undocumented_reports_missing_title_and_short_body() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let long_body = "word ".repeat(60);
+        let write = |name: &str, content: &str| {
+            std::fs::write(temp_dir.path().join(name), content).expect("write doc");
+        };
+        write(
+            "titled.md",
+            &format!("---\ntitle: Outbox\n---\n{}", long_body),
+        );
+        write("untitled.md", &long_body);
+        write("short.md", "---\ntitle: Short\n---\ntoo short");
+
+        let mut other_project = test_resource("docs://untitled-b.md", "proj-b", &["adr"]);
+        other_project.file_path = "untitled.md".to_string();
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                test_resource("docs://titled.md", "proj-a", &["adr"]),
+                test_resource("docs://untitled.md", "proj-a", &["adr"]),
+                test_resource("docs://short.md", "proj-a", &["adr"]),
+                other_project,
+            ],
+        );
+        let args = GetUndocumentedArgs {
+            project: Some("proj-a".to_string()),
+            min_words: None,
+        };
+
+        let result = docs
+            .get_undocumented(Parameters(args))
+            .await
+            .expect("undocumented result");
+        let response: UndocumentedResponse =
+            serde_json::from_value(result_json(&result)).expect("response shape");
+
+        assert_eq!(response.total_documents, 2);
+        let short = &response.documents[0];
+        assert_eq!(short.uri, "docs://short.md");
+        assert_eq!(short.reasons, vec!["below_word_threshold"]);
+        assert_eq!(short.word_count, 2);
+        let untitled = &response.documents[1];
+        assert_eq!(untitled.uri, "docs://untitled.md");
+        assert_eq!(untitled.reasons, vec!["missing_title"]);
+    }
+
+    fn resolve_fixture(temp_dir: &TempDir) -> DocumentServer {
+        let mut adr = test_resource(
+            "docs://architecture/mpa/adr/007-outbox.mdx",
+            "mpa",
+            &["adr", "ADR-007"],
+        );
+        adr.file_path = "docs/architecture/mpa/adr/007-outbox.mdx".to_string();
+        let mut erd = test_resource("docs://architecture/erd/mpa/customer.mdx", "mpa", &["erd"]);
+        erd.file_path = "docs/architecture/mpa/erd/customer.mdx".to_string();
+        server_with(temp_dir, vec![adr, erd])
+    }
+
+    #[tokio::test]
+    async fn test_resolve_path_exact_and_suffix() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = resolve_fixture(&temp_dir);
+
+        let exact = docs
+            .resolve_path(Parameters(ResolvePathArgs {
+                file_path: "./docs\\architecture\\mpa/adr/007-outbox.mdx".to_string(),
+            }))
+            .await
+            .expect("exact match");
+        let exact = result_json(&exact);
+        assert_eq!(exact["uri"], "docs://architecture/mpa/adr/007-outbox.mdx");
+        assert_eq!(exact["match_type"], "exact");
+
+        let repo_path = docs
+            .resolve_path(Parameters(ResolvePathArgs {
+                file_path: "content/docs/architecture/mpa/erd/customer.mdx".to_string(),
+            }))
+            .await
+            .expect("suffix match");
+        let repo_path = result_json(&repo_path);
+        assert_eq!(repo_path["uri"], "docs://architecture/erd/mpa/customer.mdx");
+        assert_eq!(repo_path["match_type"], "suffix");
+
+        let partial = docs
+            .resolve_path(Parameters(ResolvePathArgs {
+                file_path: "adr/007-outbox.mdx".to_string(),
+            }))
+            .await
+            .expect("suffix match");
+        assert_eq!(result_json(&partial)["match_type"], "suffix");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_path_rejects_traversal_and_absolute() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = resolve_fixture(&temp_dir);
+
+        for file_path in ["../secrets.md", "/etc/passwd", "docs/../../x.md"] {
+            let result = docs
+                .resolve_path(Parameters(ResolvePathArgs {
+                    file_path: file_path.to_string(),
+                }))
+                .await;
+            assert_eq!(result.expect_err("rejected").code.0, -32602);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_path_not_found_suggests_same_file_name() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = resolve_fixture(&temp_dir);
+
+        let error = docs
+            .resolve_path(Parameters(ResolvePathArgs {
+                file_path: "other/place/customer.mdx".to_string(),
+            }))
+            .await
+            .expect_err("not found");
+        assert_eq!(error.code.0, -32002);
+        assert_eq!(
+            error.data.expect("data")["suggestions"],
+            json!(["docs://architecture/erd/mpa/customer.mdx"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_links_maps_relative_targets() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let adr_dir = temp_dir.path().join("docs/architecture/mpa/adr");
+        std::fs::create_dir_all(&adr_dir).expect("adr dir");
+        std::fs::write(
+            adr_dir.join("007-outbox.mdx"),
+            "See [the ERD](../erd/customer.mdx#orders), [self](./007-outbox.mdx), \
+             [missing](../erd/invoice.mdx), [top](#context) and [site](https://example.com).\n",
+        )
+        .expect("write adr");
+        let docs = resolve_fixture(&temp_dir);
+
+        let result = docs
+            .resolve_links(Parameters(ResolveLinksArgs {
+                uri: "docs://architecture/mpa/adr/007-outbox.mdx".to_string(),
+            }))
+            .await
+            .expect("links resolved");
+        let links = result_json(&result)["links"].clone();
+
+        assert_eq!(
+            links,
+            json!([
+                {
+                    "text": "the ERD",
+                    "target": "../erd/customer.mdx#orders",
+                    "uri": "docs://architecture/erd/mpa/customer.mdx",
+                    "anchor": "orders"
+                },
+                {
+                    "text": "self",
+                    "target": "./007-outbox.mdx",
+                    "uri": "docs://architecture/mpa/adr/007-outbox.mdx"
+                },
+                { "text": "missing", "target": "../erd/invoice.mdx", "uri": null },
+                {
+                    "text": "top",
+                    "target": "#context",
+                    "uri": "docs://architecture/mpa/adr/007-outbox.mdx",
+                    "anchor": "context"
+                }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_project_readme_prefers_configured_names() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let mut index = test_resource("docs://architecture/mpa/adr/index.mdx", "mpa", &["adr"]);
+        index.file_path = "docs/mpa/adr/index.mdx".to_string();
+        let mut readme = test_resource("docs://architecture/mpa/README.md", "mpa", &["c1"]);
+        readme.file_path = "docs/mpa/README.md".to_string();
+        let other = test_resource("docs://architecture/crm/adr/001.mdx", "crm", &["adr"]);
+        std::fs::create_dir_all(temp_dir.path().join("docs/mpa")).expect("dir");
+        std::fs::write(temp_dir.path().join("docs/mpa/README.md"), "# MPA\n").expect("write");
+        let docs = server_with(&temp_dir, vec![index, readme, other]);
+
+        let result = docs
+            .get_project_readme(Parameters(GetProjectReadmeArgs {
+                project: "mpa".to_string(),
+            }))
+            .await
+            .expect("readme found");
+        let json = result_json(&result);
+        assert_eq!(json["uri"], "docs://architecture/mpa/README.md");
+        assert_eq!(json["content"], "# MPA\n");
+
+        let docs = docs.with_options(ServerOptions {
+            readme_names: vec!["overview".to_string()],
+            ..Default::default()
+        });
+        let error = docs
+            .get_project_readme(Parameters(GetProjectReadmeArgs {
+                project: "crm".to_string(),
+            }))
+            .await
+            .expect_err("no overview");
+        assert_eq!(error.message, "readme_not_found");
+        assert_eq!(
+            error.data.expect("data")["documents"],
+            json!(["docs://architecture/crm/adr/001.mdx"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_project_manifest_returns_parsed_manifest_or_not_found() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        std::fs::create_dir_all(temp_dir.path().join("docs/mpa/adr")).expect("dir");
+        std::fs::write(
+            temp_dir.path().join("docs/mpa/manifest.json"),
+            r#"{"owner": "team-billing", "links": {"repo": "https://git.example/mpa"}}"#,
+        )
+        .expect("write");
+        let docs = server_with(&temp_dir, Vec::new()).with_options(ServerOptions {
+            project_manifest_dirs: BTreeMap::from([
+                (
+                    "mpa".to_string(),
+                    vec!["docs/mpa".to_string(), "docs/mpa/adr".to_string()],
+                ),
+                ("crm".to_string(), vec!["docs/crm".to_string()]),
+            ]),
+            ..Default::default()
+        });
+
+        let result = docs
+            .get_project_manifest(Parameters(GetProjectManifestArgs {
+                project: "mpa".to_string(),
+            }))
+            .await
+            .expect("manifest found");
+        let json = result_json(&result);
+        assert_eq!(json["file_path"], "docs/mpa/manifest.json");
+        assert_eq!(json["manifest"]["owner"], "team-billing");
+
+        let error = docs
+            .get_project_manifest(Parameters(GetProjectManifestArgs {
+                project: "crm".to_string(),
+            }))
+            .await
+            .expect_err("no manifest");
+        assert_eq!(error.message, "manifest_not_found");
+        assert_eq!(
+            error.data.expect("data")["searched_paths"],
+            json!(["docs/crm/manifest.json"])
+        );
+
+        let error = docs
+            .get_project_manifest(Parameters(GetProjectManifestArgs {
+                project: "unknown".to_string(),
+            }))
+            .await
+            .expect_err("unknown project");
+        assert_eq!(error.message, "project_not_found");
+    }
+
+    fn expectations_server(temp_dir: &TempDir) -> DocumentServer {
+        server_with(
+            temp_dir,
+            vec![
+                test_resource("docs://architecture/mpa/c4/c1.mdx", "mpa", &["c1"]),
+                test_resource("docs://architecture/mpa/c4/c2.mdx", "mpa", &["c2"]),
+                test_resource(
+                    "docs://architecture/mpa/adr/001-outbox.mdx",
+                    "mpa",
+                    &["adr", "ADR-001"],
+                ),
+                test_resource("docs://architecture/crm/c4/c1.mdx", "crm", &["c1"]),
+            ],
+        )
+        .with_options(ServerOptions {
+            expectations: ExpectationsConfig {
+                required: vec![
+                    "c1".to_string(),
+                    "c2".to_string(),
+                    "erd".to_string(),
+                    "adr".to_string(),
+                ],
+                projects: BTreeMap::from([("crm".to_string(), vec!["c1".to_string()])]),
+            },
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_get_project_overview_reports_missing_erd() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = expectations_server(&temp_dir);
+
+        let result = docs
+            .get_project_overview(Parameters(GetProjectOverviewArgs {
+                project: "mpa".to_string(),
+            }))
+            .await
+            .expect("overview");
+        let json = result_json(&result);
+
+        assert_eq!(
+            json["completeness"],
+            json!({
+                "documents": {"adr": "present", "c1": "present", "c2": "present", "erd": "missing"},
+                "percentage": 75
+            })
+        );
+
+        let json = result_json(
+            &server_with(
+                &temp_dir,
+                vec![test_resource("docs://a.md", "mpa", &["c1"])],
+            )
+            .get_project_overview(Parameters(GetProjectOverviewArgs {
+                project: "mpa".to_string(),
+            }))
+            .await
+            .expect("overview"),
+        );
+        assert!(json.get("completeness").is_none());
+    }
+
+    fn overview_resources() -> Vec<ResourceInfo> {
+        (0..40)
+            .map(|i| {
+                let mut info = test_resource(
+                    &format!("docs://architecture/mpa/adr/{:03}.mdx", i),
+                    "mpa",
+                    &["adr", &format!("ADR-{:03}", i % 4)],
+                );
+                info.lang = if i % 2 == 0 { "go" } else { "" }.to_string();
+                info
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_project_overview_groups_borrowed_documents() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let resources = overview_resources();
+        let expected = serde_json::to_value(&resources[0]).expect("value");
+        let docs = server_with(&temp_dir, resources);
+
+        let overview = docs.project_overview("mpa").expect("overview");
+
+        let json = serde_json::to_value(&overview).expect("value");
+        assert_eq!(json["all_documents"][0], expected);
+        assert_eq!(json["documents_by_type"]["adr"][0], expected);
+        assert_eq!(
+            json["documents_by_type"]["adr"].as_array().map(Vec::len),
+            Some(40)
+        );
+        assert_eq!(
+            json["documents_by_type"]["ADR-000"]
+                .as_array()
+                .map(Vec::len),
+            Some(10)
+        );
+        assert_eq!(json["documents_by_area"]["architecture"][0], expected);
+        assert_eq!(json["documents_by_language"]["go"][0], expected);
+        assert_eq!(
+            json["documents_by_language"]["none"]
+                .as_array()
+                .map(Vec::len),
+            Some(20)
+        );
+    }
+
+    /// Swaps the allocator of the whole test binary, so only built with `count-allocations`.
+    #[cfg(feature = "count-allocations")]
+    mod allocations {
+        use super::*;
+
+        /// Counts heap allocations made by the current thread, so tests can assert on them while
+        /// other tests run in parallel.
+        struct CountingAllocator;
+
+        thread_local! {
+            static ALLOCATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+        }
+
+        unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+            unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+                let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+                unsafe { std::alloc::System.alloc(layout) }
+            }
+
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+                unsafe { std::alloc::System.dealloc(ptr, layout) }
+            }
+
+            unsafe fn realloc(
+                &self,
+                ptr: *mut u8,
+                layout: std::alloc::Layout,
+                new_size: usize,
+            ) -> *mut u8 {
+                let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+                unsafe { std::alloc::System.realloc(ptr, layout, new_size) }
+            }
+        }
+
+        #[global_allocator]
+        static GLOBAL: CountingAllocator = CountingAllocator;
+
+        fn allocations_during<T>(f: impl FnOnce() -> T) -> (T, usize) {
+            let before = ALLOCATIONS.with(std::cell::Cell::get);
+            let value = f();
+            (value, ALLOCATIONS.with(std::cell::Cell::get) - before)
+        }
+
+        #[test]
+        fn test_project_overview_groups_without_copying_documents() {
+            let temp_dir = TempDir::new().expect("temp dir");
+            let resources = overview_resources();
+            let ((), clone_allocations) = allocations_during(|| drop(resources[0].clone()));
+            let docs = server_with(&temp_dir, resources);
+
+            // Copying each document into its groups took several clones per document;
+            // borrowing must stay below the cost of copying every document even once.
+            let (overview, allocations) = allocations_during(|| docs.project_overview("mpa"));
+            assert!(overview.is_ok());
+            assert!(
+                allocations < clone_allocations * 40,
+                "grouping 40 documents made {} allocations, one copy of each costs {}",
+                allocations,
+                clone_allocations * 40
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_documentation_coverage_lists_projects() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = expectations_server(&temp_dir);
+
+        let result = docs
+            .get_documentation_coverage(Parameters(GetDocumentationCoverageArgs {}))
+            .await
+            .expect("coverage");
+        let json = result_json(&result);
+
+        assert_eq!(json["total_projects"], 2);
+        assert_eq!(json["incomplete_projects"], 1);
+        assert_eq!(json["projects"][0]["project"], "crm");
+        assert_eq!(json["projects"][0]["completeness"]["percentage"], 100);
+        assert_eq!(json["projects"][1]["project"], "mpa");
+        assert_eq!(
+            json["projects"][1]["completeness"]["documents"]["erd"],
+            "missing"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_architecture_overview_combines_projects() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                test_resource("docs://architecture/mpa/c4/c1.mdx", "mpa", &["c1"]),
+                test_resource("docs://architecture/mpa/c4/c2.mdx", "mpa", &["c2"]),
+                test_resource("docs://architecture/mpa/adr/001.mdx", "mpa", &["adr"]),
+                test_resource("docs://architecture/mpa/adr/002.mdx", "mpa", &["adr"]),
+                test_resource("docs://architecture/crm/erd/crm.mdx", "crm", &["erd"]),
+                test_resource("docs://agreements/backend/go/style.md", "", &["agreements"]),
+            ],
+        )
+        .with_options(ServerOptions {
+            expectations: ExpectationsConfig {
+                required: vec!["c1".to_string(), "erd".to_string()],
+                ..Default::default()
+            },
+            ..ServerOptions::default()
+        });
+
+        let overview = result_json(
+            &docs
+                .get_architecture_overview(Parameters(GetArchitectureOverviewArgs {}))
+                .await
+                .expect("overview"),
+        );
+        assert_eq!(overview["total_projects"], 2);
+        assert_eq!(overview["total_documents"], 6);
+        assert_eq!(overview["total_size"], 60);
+        assert_eq!(
+            overview["documents_by_type"],
+            json!({"adr": 2, "agreements": 1, "c1": 1, "c2": 1, "erd": 1})
+        );
+
+        let crm = &overview["coverage"][0];
+        assert_eq!(crm["project"], "crm");
+        assert_eq!(crm["documents"]["erd"], 1);
+        assert_eq!(crm["documents"]["c1"], 0);
+        assert_eq!(
+            crm["missing"],
+            json!(["c1", "c2", "c3", "c4", "adr", "openapi"])
+        );
+        assert_eq!(crm["completeness"]["percentage"], 50);
+
+        let mpa = &overview["coverage"][1];
+        assert_eq!(mpa["project"], "mpa");
+        assert_eq!(mpa["documents"]["adr"], 2);
+        assert_eq!(mpa["missing"], json!(["c3", "c4", "erd", "openapi"]));
+        assert_eq!(mpa["completeness"]["documents"]["erd"], "missing");
+    }
+
+    #[tokio::test]
+    async fn test_responses_are_flagged_while_scanning() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let tracker = ScanTracker::default();
+        let docs = server_with(
+            &temp_dir,
+            vec![test_resource("docs://a.md", "mpa", &["c1"])],
+        )
+        .with_scan_tracker(tracker.clone());
+
+        let ready = result_json(
+            &docs
+                .get_scan_status(Parameters(GetScanStatusArgs {}))
+                .await
+                .expect("status"),
+        );
+        assert_eq!(ready["status"], "ready");
+        assert_eq!(ready["documents_served"], 1);
+        assert!(ready.get("index_status").is_none());
+
+        tracker.update(|status| {
+            status.status = ScanPhase::Scanning;
+            status.sources_total = 4;
+            status.sources_scanned = 1;
+        });
+        let scanning = result_json(
+            &docs
+                .get_mime_type_facets(Parameters(GetMimeTypeFacetsArgs {}))
+                .await
+                .expect("facets"),
+        );
+        assert_eq!(scanning["index_status"], "scanning");
+        assert_eq!(scanning["mime_types"]["text/markdown"], 1);
+        let status = result_json(
+            &docs
+                .get_scan_status(Parameters(GetScanStatusArgs {}))
+                .await
+                .expect("status"),
+        );
+        assert_eq!(status["status"], "scanning");
+        assert_eq!(status["sources_scanned"], 1);
+        assert_eq!(status["sources_total"], 4);
+    }
+
+    #[tokio::test]
+    async fn test_get_server_health_is_populated_after_scan() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let adr = temp_dir.path().join("architecture/mpa/adr/001-outbox.mdx");
+        std::fs::create_dir_all(adr.parent().expect("parent")).expect("dir");
+        std::fs::write(&adr, "# ADR\n").expect("write adr");
+        let config_path = temp_dir.path().join("arch-mcp.toml");
+        std::fs::write(
+            &config_path,
+            "agreements = []\n\n[[projects]]\nname = \"mpa\"\nadr = [\"architecture/mpa/adr\"]\n",
+        )
+        .expect("write config");
+        let file_reader =
+            FileReader::new(temp_dir.path().to_string_lossy().to_string()).expect("reader");
+        let config = crate::config::Config::load(Some(&config_path)).expect("config");
+        let docs = crate::index::ServedIndex::build(config, String::new(), &file_reader)
+            .server
+            .with_session_manager(Arc::new(LocalSessionManager::default()));
+
+        let health = result_json(
+            &docs
+                .get_server_health(Parameters(GetServerHealthArgs {}))
+                .await
+                .expect("health"),
+        );
+        assert_eq!(health["version"], env!("CARGO_PKG_VERSION"));
+        assert!(health["started_at"].as_str().is_some());
+        assert!(health["uptime_seconds"].as_u64().is_some());
+        assert_eq!(health["docs_root"], file_reader.docs_root());
+        assert_eq!(health["backing_store"], "available");
+        assert_eq!(health["scan_status"], "ready");
+        assert!(health["last_scan_at"].as_str().is_some());
+        assert!(health["last_scan_duration_ms"].as_u64().is_some());
+        assert_eq!(health["documents_served"], 1);
+        assert_eq!(health["active_sessions"], 0);
+        assert_eq!(health["active_subscriptions"], 0);
+        assert_eq!(health["caches"]["openapi_specs"]["hits"], 0);
+        assert!(health["caches"]["frontmatter"]["misses"].as_u64().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_resource_rereads_one_document_into_the_index() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let adr_dir = temp_dir.path().join("architecture/mpa/adr");
+        std::fs::create_dir_all(&adr_dir).expect("dir");
+        std::fs::write(adr_dir.join("001-outbox.mdx"), "# Outbox\n\nOld summary.\n")
+            .expect("write adr");
+        std::fs::write(adr_dir.join("002-queues.mdx"), "# Queues\n\nQueues.\n").expect("write adr");
+        let config_path = temp_dir.path().join("arch-mcp.toml");
+        std::fs::write(
+            &config_path,
+            "agreements = []\nenable_reload = true\n\n[[projects]]\nname = \"mpa\"\nadr = [\"architecture/mpa/adr\"]\n",
+        )
+        .expect("write config");
+        let file_reader =
+            FileReader::new(temp_dir.path().to_string_lossy().to_string()).expect("reader");
+        let config = crate::config::Config::load(Some(&config_path)).expect("config");
+        let index: SharedIndex = Arc::new(std::sync::RwLock::new(
+            crate::index::ServedIndex::build(config, String::new(), &file_reader),
+        ));
+        let docs = crate::index::current_server(&index).with_index(index.clone());
+        let outbox = "docs://architecture/mpa/adr/001-outbox.mdx";
+        let queues = "docs://architecture/mpa/adr/002-queues.mdx";
+        let queues_size = docs.store.get(queues).expect("queues").size;
+
+        let edited = "---\ndate: 2024-01-15\n---\n# Outbox\n\nNew summary with more words.\n";
+        std::fs::write(adr_dir.join("001-outbox.mdx"), edited).expect("edit adr");
+        let refreshed = result_json(
+            &docs
+                .refresh_resource(Parameters(RefreshResourceArgs {
+                    path: outbox.to_string(),
+                }))
+                .await
+                .expect("refresh"),
+        );
+        assert_eq!(refreshed["size"], edited.len());
+        assert_eq!(refreshed["summary"], "New summary with more words.");
+        assert_eq!(refreshed["word_count"], 11);
+        assert_eq!(refreshed["adr_date"], "2024-01-15");
+
+        let served = crate::index::current_server(&index);
+        let updated = served.store.get(outbox).expect("outbox");
+        assert_eq!(updated.size as usize, edited.len());
+        assert_eq!(
+            updated.summary.as_deref(),
+            Some("New summary with more words.")
+        );
+        assert_eq!(served.store.get(queues).expect("queues").size, queues_size);
+        assert_eq!(
+            docs.store.get(outbox).expect("snapshot").summary.as_deref(),
+            Some("Old summary.")
+        );
+
+        let error = docs
+            .refresh_resource(Parameters(RefreshResourceArgs {
+                path: "docs://architecture/mpa/adr/missing.mdx".to_string(),
+            }))
+            .await
+            .expect_err("missing");
+        assert_eq!(error.message, "resource_not_found");
+
+        let disabled = docs.with_options(ServerOptions::default());
+        let error = disabled
+            .refresh_resource(Parameters(RefreshResourceArgs {
+                path: outbox.to_string(),
+            }))
+            .await
+            .expect_err("disabled");
+        assert_eq!(error.message, "reload_disabled");
+    }
+
+    #[tokio::test]
+    async fn test_hidden_uris_are_neither_listed_nor_readable() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let agreements = temp_dir.path().join("docs/backend/go");
+        std::fs::create_dir_all(&agreements).expect("dir");
+        std::fs::write(
+            agreements.join("errors.md"),
+            "# Errors\n\n<!-- include: ./keys.md -->\n",
+        )
+        .expect("write agreement");
+        std::fs::write(agreements.join("keys.md"), "Signing keys\n").expect("write agreement");
+        let config_path = temp_dir.path().join("arch-mcp.toml");
+        std::fs::write(
+            &config_path,
+            "agreements = [\"docs/backend\"]\nhidden_uris = [\"docs://agreements/**/keys.md\"]\naccept_file_paths = true\nprojects = []\n",
+        )
+        .expect("write config");
+        let file_reader =
+            FileReader::new(temp_dir.path().to_string_lossy().to_string()).expect("reader");
+        let config = crate::config::Config::load(Some(&config_path)).expect("config");
+        let docs = crate::index::ServedIndex::build(config, String::new(), &file_reader).server;
+        let hidden = "docs://agreements/backend/go/keys.md";
+
+        let listing = result_json(
+            &docs
+                .get_docs_list(Parameters(GetDocsListArgs::default()))
+                .await
+                .expect("list"),
+        );
+        assert_eq!(listing["total_documents"], 1);
+        assert_eq!(
+            listing["documents"][0]["uri"],
+            "docs://agreements/backend/go/errors.md"
+        );
+        assert!(docs.resource_infos().all(|info| info.uri != hidden));
+
+        for path in [hidden, "docs/backend/go/keys.md"] {
+            let error = docs
+                .get_resource_content(Parameters(GetResourceContentArgs {
+                    path: path.to_string(),
+                    ..Default::default()
+                }))
+                .await
+                .expect_err("hidden");
+            assert_eq!(error.message, "resource_not_found");
+        }
+        let error = docs
+            .get_resource_content(Parameters(GetResourceContentArgs {
+                path: "docs://agreements/backend/go/errors.md".to_string(),
+                inline_includes: Some(true),
+                ..Default::default()
+            }))
+            .await
+            .expect_err("hidden include");
+        assert_eq!(error.message, "include_failed");
+    }
+
+    #[tokio::test]
+    async fn test_get_stale_documents_applies_per_type_age_thresholds() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let day = std::time::Duration::from_secs(24 * 60 * 60);
+        let now = std::time::SystemTime::now();
+        for (path, age_days) in [
+            ("architecture/mpa/adr/001-outbox.mdx", 100),
+            ("architecture/mpa/adr/002-queues.mdx", 10),
+            ("architecture/crm/adr/001-cache.mdx", 45),
+        ] {
+            let path = temp_dir.path().join(path);
+            std::fs::create_dir_all(path.parent().expect("parent")).expect("dir");
+            std::fs::File::create(&path)
+                .and_then(|file| file.set_modified(now - day * age_days))
+                .expect("write adr");
+        }
+        let config_path = temp_dir.path().join("arch-mcp.toml");
+        std::fs::write(
+            &config_path,
+            "agreements = []\n[staleness.max_age_days]\nadr = 30\n\n\
+             [[projects]]\nname = \"mpa\"\nadr = [\"architecture/mpa/adr\"]\n\n\
+             [[projects]]\nname = \"crm\"\nadr = [\"architecture/crm/adr\"]\n",
+        )
+        .expect("write config");
+        let file_reader =
+            FileReader::new(temp_dir.path().to_string_lossy().to_string()).expect("reader");
+        let config = crate::config::Config::load(Some(&config_path)).expect("config");
+        let docs = crate::index::ServedIndex::build(config, String::new(), &file_reader).server;
+
+        let report = result_json(
+            &docs
+                .get_stale_documents(Parameters(GetStaleDocumentsArgs::default()))
+                .await
+                .expect("report"),
+        );
+        assert_eq!(report["total_stale"], 2);
+        assert_eq!(report["projects"][0]["project"], "crm");
+        assert_eq!(report["projects"][0]["documents"][0]["age_days"], 45);
+        assert_eq!(report["projects"][1]["project"], "mpa");
+        let mpa = &report["projects"][1]["documents"];
+        assert_eq!(mpa.as_array().map(Vec::len), Some(1));
+        assert_eq!(mpa[0]["uri"], "docs://architecture/mpa/adr/001-outbox.mdx");
+        assert_eq!(mpa[0]["age_days"], 100);
+        assert_eq!(mpa[0]["max_age_days"], 30);
+        assert!(mpa[0]["last_modified"].as_str().is_some());
+
+        let report = result_json(
+            &docs
+                .get_stale_documents(Parameters(GetStaleDocumentsArgs {
+                    max_age_days: BTreeMap::from([("adr".to_string(), 5)]),
+                    project: Some("mpa".to_string()),
+                }))
+                .await
+                .expect("overridden"),
+        );
+        assert_eq!(report["total_stale"], 2);
+        assert_eq!(report["projects"].as_array().map(Vec::len), Some(1));
+
+        let undated = server_with(
+            &temp_dir,
+            vec![test_resource(
+                "docs://architecture/mpa/adr/003-retries.mdx",
+                "mpa",
+                &["adr"],
+            )],
+        )
+        .with_options(ServerOptions {
+            staleness: crate::config::StalenessConfig {
+                default_max_age_days: Some(30),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let report = result_json(
+            &undated
+                .get_stale_documents(Parameters(GetStaleDocumentsArgs::default()))
+                .await
+                .expect("undated"),
+        );
+        assert_eq!(report["total_stale"], 0);
+        assert_eq!(
+            report["unknown_age"],
+            json!(["docs://architecture/mpa/adr/003-retries.mdx"])
+        );
+
+        let error = docs
+            .get_stale_documents(Parameters(GetStaleDocumentsArgs {
+                max_age_days: BTreeMap::from([("adrs".to_string(), 5)]),
+                project: None,
+            }))
+            .await
+            .expect_err("unknown type");
+        assert_eq!(error.message, "unknown_document_type");
+    }
+
+    #[tokio::test]
+    async fn test_session_subscriptions_end_with_the_session_server() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let base = server_with(
+            &temp_dir,
+            vec![test_resource(
+                "docs://architecture/mpa/adr/001.mdx",
+                "mpa",
+                &["adr"],
+            )],
+        );
+        let registry = SubscriptionRegistry::default();
+        let first = base.clone().with_subscriptions(registry.clone());
+        let second = base.with_subscriptions(registry.clone());
+
+        let uri = "docs://architecture/mpa/adr/001.mdx";
+        first.session_subscriptions.subscribe(Some("first"), uri);
+        second.session_subscriptions.subscribe(Some("second"), uri);
+        let health = result_json(
+            &first
+                .get_server_health(Parameters(GetServerHealthArgs {}))
+                .await
+                .expect("health"),
+        );
+        assert_eq!(health["active_subscriptions"], 2);
+
+        drop(first);
+        assert_eq!(registry.active_subscriptions(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_effective_extensions_returns_configured_filters() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = server_with(&temp_dir, Vec::new()).with_options(ServerOptions {
+            effective_extensions: BTreeMap::from([(
+                "c1".to_string(),
+                EffectiveExtensions {
+                    extensions: vec!["mdx".to_string(), "puml".to_string()],
+                    preferred: vec!["puml".to_string()],
+                    file_stem: Some("c1".to_string()),
+                },
+            )]),
+            ..Default::default()
+        });
+
+        let result = docs
+            .get_effective_extensions(Parameters(GetEffectiveExtensionsArgs {}))
+            .await
+            .expect("extensions");
+        assert_eq!(
+            result_json(&result)["document_types"],
+            json!({"c1": {"extensions": ["mdx", "puml"], "preferred": ["puml"], "file_stem": "c1"}})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_mime_type_facets_counts_documents() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let with_mime = |uri: &str, mime_type: &str| ResourceInfo {
+            mime_type: mime_type.to_string(),
+            ..test_resource(uri, "mpa", &[])
+        };
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                with_mime("docs://architecture/mpa/adr/001.md", "text/markdown"),
+                with_mime("docs://architecture/mpa/adr/002.md", "text/markdown"),
+                with_mime("docs://openapi/mpa/api.yaml", "application/yaml"),
+                with_mime("docs://architecture/mpa/notes", "text/plain"),
+            ],
+        );
+
+        let result = docs
+            .get_mime_type_facets(Parameters(GetMimeTypeFacetsArgs {}))
+            .await
+            .expect("facets");
+        let json = result_json(&result);
+        assert_eq!(
+            json["mime_types"],
+            json!({"application/yaml": 1, "text/markdown": 2, "text/plain": 1})
+        );
+        assert_eq!(json["total_mime_types"], 3);
+    }
+
+    fn large_project_server(temp_dir: &TempDir, max_response_bytes: usize) -> DocumentServer {
+        let resources = (0..120)
+            .map(|i| {
+                test_resource(
+                    &format!("docs://architecture/mpa/adr/{:03}-decision.mdx", i),
+                    "mpa",
+                    &["adr", &format!("ADR-{:03}", i)],
+                )
             })
-            .cloned()
             .collect();
+        server_with(temp_dir, resources).with_options(ServerOptions {
+            max_response_bytes: Some(max_response_bytes),
+            ..Default::default()
+        })
+    }
 
-        // Sort by ADR number (extract number from category)
-        let mut sorted_adr_documents = adr_documents;
-        sorted_adr_documents.sort_by(|a, b| {
-            let get_adr_number = |info: &ResourceInfo| -> u32 {
-                info.category
-                    .iter()
-                    .find(|cat| cat.starts_with("ADR-"))
-                    .and_then(|cat| {
-                        cat.strip_prefix("ADR-")
-                            .and_then(|num| num.parse::<u32>().ok())
-                    })
-                    .unwrap_or(0)
-            };
+    fn bounded_json(result: &CallToolResult, limit: usize) -> serde_json::Value {
+        let text = &result.content[0].as_text().expect("text").text;
+        assert!(
+            text.len() <= limit,
+            "{} bytes over {} limit",
+            text.len(),
+            limit
+        );
+        serde_json::from_str(text).expect("valid json")
+    }
+
+    #[tokio::test]
+    async fn test_max_response_bytes_trims_docs_and_adr_lists() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let limit = 8_000;
+        let docs = large_project_server(&temp_dir, limit);
+
+        let list = docs
+            .get_docs_list(Parameters(GetDocsListArgs {
+                limit: Some(100),
+                ..Default::default()
+            }))
+            .await
+            .expect("docs list");
+        let list = bounded_json(&list, limit);
+        assert_eq!(list["response_truncated"], true);
+        assert_eq!(list["total_documents"], 120);
+        let returned = list["documents"].as_array().expect("documents").len();
+        assert!(returned > 0 && returned < 100);
+
+        let adrs = docs
+            .get_all_adr_documents(Parameters(GetAllAdrDocumentsArgs::default()))
+            .await
+            .expect("adr list");
+        let adrs = bounded_json(&adrs, limit);
+        assert_eq!(adrs["response_truncated"], true);
+        assert_eq!(adrs["total_adr_documents"], 120);
+    }
+
+    #[tokio::test]
+    async fn test_max_response_bytes_trims_overview_groups_in_order() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let limit = 60_000;
+        let docs = large_project_server(&temp_dir, limit);
+
+        let overview = docs
+            .get_project_overview(Parameters(GetProjectOverviewArgs {
+                project: "mpa".to_string(),
+            }))
+            .await
+            .expect("overview");
+        let overview = bounded_json(&overview, limit);
+        assert_eq!(overview["response_truncated"], true);
+        assert_eq!(overview["all_documents"], json!([]));
+        assert_eq!(overview["total_documents"], 120);
+        assert!(overview["documents_by_language"].is_object());
+
+        let docs = large_project_server(&temp_dir, 1_000);
+        let notice = docs
+            .get_project_overview(Parameters(GetProjectOverviewArgs {
+                project: "mpa".to_string(),
+            }))
+            .await
+            .expect("overview");
+        let notice = bounded_json(&notice, 1_000);
+        assert_eq!(notice["response_truncated"], true);
+        assert_eq!(notice["max_response_bytes"], 1_000);
+        assert!(notice.get("project").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_docs_list_field_projection() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = server_with(
+            &temp_dir,
+            vec![test_resource(
+                "docs://architecture/mpa/adr/001.mdx",
+                "mpa",
+                &["adr"],
+            )],
+        );
+
+        let result = docs
+            .get_docs_list(Parameters(GetDocsListArgs {
+                fields: Some(vec!["description".to_string()]),
+                ..Default::default()
+            }))
+            .await
+            .expect("projected list");
+        let json = result_json(&result);
+        assert_eq!(
+            json["documents"],
+            json!([{
+                "uri": "docs://architecture/mpa/adr/001.mdx",
+                "description": "Test document docs://architecture/mpa/adr/001.mdx"
+            }])
+        );
+        assert_eq!(json["total_documents"], 1);
+
+        let error = docs
+            .get_docs_list(Parameters(GetDocsListArgs {
+                fields: Some(vec!["uri".to_string(), "title".to_string()]),
+                ..Default::default()
+            }))
+            .await
+            .expect_err("unknown field");
+        assert_eq!(error.code.0, -32602);
+        assert_eq!(
+            error.data.expect("data")["valid_fields"],
+            json!(RESOURCE_FIELDS)
+        );
+    }
+
+    #[test]
+    fn test_resource_fields_cover_resource_info() {
+        let info = ResourceInfo {
+            valid: Some(false),
+            validation_error: Some("broken".to_string()),
+            variant_of: Some("docs://a.md".to_string()),
+            content_lang: Some("ru".to_string()),
+            other_languages: BTreeMap::from([("en".to_string(), "docs://a.md".to_string())]),
+            summary: Some("Preview".to_string()),
+            word_count: Some(1),
+            token_estimate: Some(2),
+            adr_date: Some("2024-01-15".to_string()),
+            adr_scope: Some("global".to_string()),
+            parse_error: Some("broken".to_string()),
+            langs: vec!["go".to_string()],
+            applies_to: vec!["mpa".to_string()],
+            classification: Some(crate::models::Classification::Adr {
+                number: "001".to_string(),
+            }),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(info).expect("serialize");
+        assert_eq!(
+            value.as_object().map(|o| o.len()),
+            Some(RESOURCE_FIELDS.len())
+        );
+        for key in value.as_object().expect("object").keys() {
+            assert!(
+                RESOURCE_FIELDS.contains(&key.as_str()),
+                "{} not selectable",
+                key
+            );
+        }
+    }
 
-            let a_number = get_adr_number(a);
-            let b_number = get_adr_number(b);
-            a_number.cmp(&b_number)
+    #[tokio::test]
+    async fn test_get_docs_list_collapses_language_variants() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let variant = |uri: &str, lang: &str, variant_of: Option<&str>| {
+            let mut info = test_resource(uri, "", &["agreements"]);
+            info.content_lang = Some(lang.to_string());
+            info.variant_of = variant_of.map(ToString::to_string);
+            info
+        };
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                variant("docs://agreements/go/api.md", "en", None),
+                variant(
+                    "docs://agreements/go/api.ru.md",
+                    "ru",
+                    Some("docs://agreements/go/api.md"),
+                ),
+                test_resource("docs://agreements/go/errors.md", "", &["agreements"]),
+            ],
+        )
+        .with_options(ServerOptions {
+            variant_languages: vec!["ru".to_string(), "en".to_string()],
+            ..Default::default()
         });
 
-        // Create response
-        let response = AdrListResponse {
-            adr_documents: sorted_adr_documents.clone(),
-            total_adr_documents: sorted_adr_documents.len().try_into().unwrap_or(u32::MAX),
+        let collapsed = docs
+            .list_documents(&GetDocsListArgs {
+                collapse_variants: Some(true),
+                ..Default::default()
+            })
+            .expect("collapsed");
+        assert_eq!(collapsed.total_documents, 2);
+        let api = &collapsed.documents[0];
+        assert_eq!(api.uri, "docs://agreements/go/api.ru.md");
+        assert_eq!(
+            api.other_languages,
+            BTreeMap::from([("en".to_string(), "docs://agreements/go/api.md".to_string())])
+        );
+        let errors = &collapsed.documents[1];
+        assert_eq!(errors.uri, "docs://agreements/go/errors.md");
+        assert!(errors.other_languages.is_empty());
+
+        let expanded = docs
+            .list_documents(&GetDocsListArgs::default())
+            .expect("expanded");
+        assert_eq!(expanded.total_documents, 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_architecture_graph_merges_levels() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let c4_dir = temp_dir.path().join("architecture/mpa/c4/services");
+        std::fs::create_dir_all(&c4_dir).expect("dir");
+        std::fs::write(
+            temp_dir.path().join("architecture/mpa/c4/c1.mdx"),
+            "```mermaid\nC4Context\nPerson(customer, \"Customer\")\n\
+             System(billing, \"Billing\", \"Issues invoices\")\n\
+             Rel(customer, billing, \"Pays\")\n```\n",
+        )
+        .expect("write c1");
+        std::fs::write(
+            temp_dir.path().join("architecture/mpa/c4/c2.mdx"),
+            "```mermaid\nC4Container\nPerson(customer, \"Customer\", \"Pays invoices\")\n\
+             Container(api, \"Billing API\", \"Rust\")\nRel(customer, api, \"Pays\", \"HTTPS\")\n```\n",
+        )
+        .expect("write c2");
+        std::fs::write(c4_dir.join("broken.puml"), "Container(api, \"API\"\n").expect("write");
+        let c4 = |path: &str, category: &str| {
+            let mut info = test_resource(&format!("docs://{}", path), "mpa", &[category]);
+            info.file_path = path.to_string();
+            info
         };
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                c4("architecture/mpa/c4/c1.mdx", "c1"),
+                c4("architecture/mpa/c4/c2.mdx", "c2"),
+                c4("architecture/mpa/c4/services/broken.puml", "c4"),
+                test_resource("docs://architecture/mpa/adr/001.mdx", "mpa", &["adr"]),
+            ],
+        );
 
-        // Serialize response to JSON
-        let response_json = serde_json::to_value(&response).map_err(|e| {
-            McpError::internal_error(
-                "serialization_error",
-                Some(json!({
-                    "error": format!("Failed to serialize ADR response: {}", e)
-                })),
-            )
-        })?;
+        let result = docs
+            .get_architecture_graph(Parameters(GetArchitectureGraphArgs {
+                project: "mpa".to_string(),
+            }))
+            .await
+            .expect("graph");
+        let json = result_json(&result);
 
-        Ok(CallToolResult::success(vec![Content::text(
-            response_json.to_string(),
-        )]))
+        assert_eq!(json["documents_parsed"], 2);
+        assert_eq!(
+            json["parse_errors"][0]["uri"],
+            "docs://architecture/mpa/c4/services/broken.puml"
+        );
+        let customer = &json["nodes"][2];
+        assert_eq!(customer["id"], "customer");
+        assert_eq!(customer["type"], "person");
+        assert_eq!(customer["description"], "Pays invoices");
+        assert_eq!(
+            customer["sources"],
+            json!([
+                "docs://architecture/mpa/c4/c1.mdx",
+                "docs://architecture/mpa/c4/c2.mdx"
+            ])
+        );
+        assert_eq!(json["edges"].as_array().map(Vec::len), Some(2));
+        assert_eq!(json["edges"][0]["to"], "api");
+        assert_eq!(json["edges"][0]["technology"], "HTTPS");
+
+        let error = docs
+            .get_architecture_graph(Parameters(GetArchitectureGraphArgs {
+                project: "crm".to_string(),
+            }))
+            .await
+            .expect_err("unknown project");
+        assert_eq!(error.code.0, -32002);
     }
 
-    #[tool(
-        description = "Get comprehensive overview of a project with all document types, grouped by categories. Returns structured JSON with project statistics and all ResourceInfo objects organized by type, area, and language. Provides total document count, total size, and documents grouped by type (C1, C2, C3, C4, ERD, ADR, agreements), area (architecture, backend, frontend), and language (PHP, Go, JS, TS, etc.). Perfect for getting complete project understanding, analyzing documentation coverage, and understanding project structure. Essential for project analysis and documentation statistics.",
-        annotations(
-            title = "📊 Get Project Overview",
-            read_only_hint = true,
-            destructive_hint = false,
-            idempotent_hint = true,
-            open_world_hint = false
+    #[tokio::test]
+    async fn test_get_project_dependencies_matches_projects_and_aliases() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        std::fs::create_dir_all(temp_dir.path().join("architecture/mpa/c4")).expect("dir");
+        std::fs::write(
+            temp_dir.path().join("architecture/mpa/c4/c1.mdx"),
+            "```mermaid\nC4Context\nSystem(mpa, \"MPA\")\n\
+             System_Ext(auth, \"OAuth Platform\", \"Issues tokens\")\n\
+             System_Ext(crm_api, \"CRM\")\nSystem_Ext(bank, \"Bank\")\n\
+             Person_Ext(user, \"OAuth user\")\n```\n",
         )
-    )]
-    async fn get_project_overview(
-        &self,
-        Parameters(GetProjectOverviewArgs { project }): Parameters<GetProjectOverviewArgs>,
-    ) -> Result<CallToolResult, McpError> {
-        // Filter documents by project
-        let project_documents: Vec<&ResourceInfo> = self
-            .resources
-            .values()
-            .filter(|info| info.project == project)
-            .collect();
+        .expect("write c1");
+        let c1 = {
+            let mut info = test_resource("docs://architecture/mpa/c1.mdx", "mpa", &["c1"]);
+            info.file_path = "architecture/mpa/c4/c1.mdx".to_string();
+            info
+        };
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                c1,
+                test_resource("docs://architecture/crm/adr/001.mdx", "crm", &["adr"]),
+            ],
+        )
+        .with_options(ServerOptions {
+            project_aliases: BTreeMap::from([
+                ("oauth".to_string(), vec!["OAuth Platform".to_string()]),
+                ("crm".to_string(), Vec::new()),
+                ("mpa".to_string(), Vec::new()),
+            ]),
+            ..Default::default()
+        });
 
-        if project_documents.is_empty() {
-            return Err(McpError::resource_not_found(
-                "project_not_found",
-                Some(json!({
-                    "project": project,
-                    "error": "No documents found for the specified project"
-                })),
-            ));
-        }
+        let result = docs
+            .get_project_dependencies(Parameters(GetProjectDependenciesArgs { project: None }))
+            .await
+            .expect("dependencies");
+        let json = result_json(&result);
 
-        // Calculate statistics
-        let total_documents = project_documents.len().try_into().unwrap_or(u32::MAX);
-        let total_size: u64 = project_documents
-            .iter()
-            .map(|doc| u64::from(doc.size))
-            .sum();
+        assert_eq!(
+            json["dependencies"],
+            json!([{
+                "project": "mpa",
+                "depends_on": [
+                    {"project": "crm", "evidence": ["docs://architecture/mpa/c1.mdx"]},
+                    {"project": "oauth", "evidence": ["docs://architecture/mpa/c1.mdx"]}
+                ]
+            }])
+        );
+        assert_eq!(
+            json["unmatched_external_systems"],
+            json!([{
+                "project": "mpa",
+                "id": "bank",
+                "name": "Bank",
+                "sources": ["docs://architecture/mpa/c1.mdx"]
+            }])
+        );
+    }
 
-        // Group documents by type (category)
-        let mut documents_by_type: std::collections::BTreeMap<String, Vec<ResourceInfo>> =
-            std::collections::BTreeMap::new();
-        for doc in &project_documents {
-            for category in &doc.category {
-                documents_by_type
-                    .entry(category.clone())
-                    .or_default()
-                    .push((*doc).clone());
-            }
+    #[tokio::test]
+    async fn test_get_global_adrs_excludes_project_local() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let adr = |uri: &str, number: &str, scope: &str| ResourceInfo {
+            adr_scope: Some(scope.to_string()),
+            ..test_resource(uri, "mpa", &["adr", number])
+        };
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                adr(
+                    "docs://architecture/mpa/adr/001-outbox.mdx",
+                    "ADR-001",
+                    "project",
+                ),
+                adr(
+                    "docs://architecture/shared/adr/010-tracing.mdx",
+                    "ADR-010",
+                    "global",
+                ),
+                adr(
+                    "docs://architecture/shared/adr/002-logging.mdx",
+                    "ADR-002",
+                    "global",
+                ),
+            ],
+        );
+
+        let result = docs
+            .get_global_adrs(Parameters(GetGlobalAdrsArgs {}))
+            .await
+            .expect("global ADRs");
+        let json = result_json(&result);
+
+        assert_eq!(json["total_adr_documents"], 2);
+        assert_eq!(
+            json["adr_documents"][0]["uri"],
+            "docs://architecture/shared/adr/002-logging.mdx"
+        );
+        assert_eq!(json["adr_documents"][1]["adr_scope"], "global");
+    }
+
+    #[tokio::test]
+    async fn test_get_all_adr_documents_by_date() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let adr = |number: &str, date: Option<&str>| ResourceInfo {
+            adr_date: date.map(ToString::to_string),
+            ..test_resource(
+                &format!("docs://architecture/mpa/adr/{}.mdx", number),
+                "mpa",
+                &["adr", number],
+            )
+        };
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                adr("ADR-001", Some("2024-03-01")),
+                adr("ADR-002", None),
+                adr("ADR-003", Some("2023-11-20")),
+            ],
+        );
+        let list = |args: GetAllAdrDocumentsArgs| {
+            let docs = docs.clone();
+            async move { docs.get_all_adr_documents(Parameters(args)).await }
+        };
+        let uris = |json: &serde_json::Value| -> Vec<String> {
+            json["adr_documents"]
+                .as_array()
+                .expect("array")
+                .iter()
+                .map(|doc| {
+                    doc["uri"]
+                        .as_str()
+                        .expect("uri")
+                        .replace("docs://architecture/mpa/adr/", "")
+                })
+                .collect()
+        };
+
+        let json = result_json(
+            &list(GetAllAdrDocumentsArgs {
+                sort_by: Some("date".to_string()),
+                ..Default::default()
+            })
+            .await
+            .expect("sorted"),
+        );
+        assert_eq!(
+            uris(&json),
+            vec!["ADR-003.mdx", "ADR-001.mdx", "ADR-002.mdx"]
+        );
+        assert!(json.get("excluded_without_date").is_none());
+
+        let json = result_json(
+            &list(GetAllAdrDocumentsArgs {
+                since: Some("1 Jan 2024".to_string()),
+                ..Default::default()
+            })
+            .await
+            .expect("filtered"),
+        );
+        assert_eq!(uris(&json), vec!["ADR-001.mdx"]);
+        assert_eq!(json["excluded_without_date"], 1);
+
+        let error = list(GetAllAdrDocumentsArgs {
+            until: Some("soon".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect_err("invalid date");
+        assert_eq!(error.message, "invalid_date");
+        let error = list(GetAllAdrDocumentsArgs {
+            sort_by: Some("title".to_string()),
+            ..Default::default()
+        })
+        .await
+        .expect_err("invalid sort");
+        assert_eq!(error.message, "invalid_sort_by");
+    }
+
+    #[tokio::test]
+    async fn test_export_search_index_emits_bulk_action_document_pairs() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        std::fs::write(temp_dir.path().join("outbox.md"), "# Outbox\n").expect("write");
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                ResourceInfo {
+                    file_path: "outbox.md".to_string(),
+                    ..test_resource("docs://misc/outbox.md", "mpa", &["adr"])
+                },
+                test_resource("docs://misc/missing.md", "mpa", &["adr"]),
+                test_resource("docs://misc/crm.md", "crm", &["adr"]),
+            ],
+        );
+
+        let json = result_json(
+            &docs
+                .export_search_index(Parameters(ExportSearchIndexArgs {
+                    index_name: Some("team-docs".to_string()),
+                    include_content: Some(true),
+                    project: Some("mpa".to_string()),
+                    offset: None,
+                }))
+                .await
+                .expect("export"),
+        );
+        assert_eq!(json["documents"], 2);
+        assert_eq!(json["content_skipped"], json!(["docs://misc/missing.md"]));
+        let ndjson = json["ndjson"].as_str().expect("ndjson");
+        assert!(ndjson.ends_with('\n'));
+        let lines: Vec<serde_json::Value> = ndjson
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("one JSON object per line"))
+            .collect();
+        assert_eq!(lines.len(), 4);
+        for pair in lines.chunks(2) {
+            let (action, document) = (&pair[0], &pair[1]);
+            assert_eq!(action["index"]["_index"], "team-docs");
+            assert_eq!(action["index"]["_id"], document["uri"]);
+            assert_eq!(document["project"], "mpa");
+            assert!(document.get("schema_version").is_none());
         }
+        assert_eq!(lines[1]["uri"], "docs://misc/missing.md");
+        assert!(lines[1].get("content").is_none());
+        assert_eq!(lines[3]["content"], "# Outbox\n");
 
-        // Group documents by area
-        let mut documents_by_area: std::collections::BTreeMap<String, Vec<ResourceInfo>> =
-            std::collections::BTreeMap::new();
-        for doc in &project_documents {
-            documents_by_area
-                .entry(doc.area.clone())
-                .or_default()
-                .push((*doc).clone());
+        let json = result_json(
+            &docs
+                .export_search_index(Parameters(ExportSearchIndexArgs::default()))
+                .await
+                .expect("defaults"),
+        );
+        assert_eq!(json["documents"], 3);
+        assert!(
+            !json["ndjson"]
+                .as_str()
+                .expect("ndjson")
+                .contains("\"content\"")
+        );
+        assert!(
+            json["ndjson"]
+                .as_str()
+                .expect("ndjson")
+                .contains("\"_index\":\"arch-docs\"")
+        );
+
+        let full_length = docs
+            .export_search_index(Parameters(ExportSearchIndexArgs::default()))
+            .await
+            .expect("full export")
+            .content[0]
+            .as_text()
+            .expect("text content")
+            .text
+            .len();
+        let bounded = docs.clone().with_options(ServerOptions {
+            max_response_bytes: Some(full_length - 1),
+            ..Default::default()
+        });
+        let json = result_json(
+            &bounded
+                .export_search_index(Parameters(ExportSearchIndexArgs::default()))
+                .await
+                .expect("trimmed export"),
+        );
+        assert_eq!(json["response_truncated"], true);
+        assert_eq!(
+            (
+                &json["documents"],
+                &json["total_documents"],
+                &json["next_offset"]
+            ),
+            (&json!(1), &json!(3), &json!(1))
+        );
+        assert_eq!(json["ndjson"].as_str().expect("ndjson").lines().count(), 2);
+
+        let json = result_json(
+            &docs
+                .export_search_index(Parameters(ExportSearchIndexArgs {
+                    offset: Some(1),
+                    ..Default::default()
+                }))
+                .await
+                .expect("continued export"),
+        );
+        assert_eq!(json["documents"], 2);
+        assert!(json.get("next_offset").is_none());
+        assert!(
+            json["ndjson"]
+                .as_str()
+                .expect("ndjson")
+                .starts_with("{\"index\":{\"_id\":\"docs://misc/missing.md\"")
+        );
+
+        let error = docs
+            .export_search_index(Parameters(ExportSearchIndexArgs {
+                index_name: Some("Team Docs".to_string()),
+                ..Default::default()
+            }))
+            .await
+            .expect_err("invalid index");
+        assert_eq!(error.message, "invalid_index_name");
+    }
+
+    #[tokio::test]
+    async fn test_export_search_index_order_does_not_depend_on_read_completion() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        // The first documents are the largest, so their reads tend to finish last
+        let resources: Vec<ResourceInfo> = (0..24)
+            .map(|i| {
+                let file_name = format!("doc-{:02}.md", i);
+                std::fs::write(
+                    temp_dir.path().join(&file_name),
+                    format!("# Doc {}\n", i).repeat((24 - i) * 2000),
+                )
+                .expect("write");
+                ResourceInfo {
+                    file_path: file_name.clone(),
+                    ..test_resource(&format!("docs://misc/{}", file_name), "mpa", &["adr"])
+                }
+            })
+            .collect();
+        let base = server_with(&temp_dir, resources);
+
+        let mut exports = Vec::new();
+        for concurrency in [1, 16] {
+            let docs = base.clone().with_options(ServerOptions {
+                export_read_concurrency: concurrency,
+                ..ServerOptions::default()
+            });
+            let json = result_json(
+                &docs
+                    .export_search_index(Parameters(ExportSearchIndexArgs {
+                        include_content: Some(true),
+                        ..ExportSearchIndexArgs::default()
+                    }))
+                    .await
+                    .expect("export"),
+            );
+            assert_eq!(json["documents"], 24);
+            assert!(json.get("content_skipped").is_none());
+            exports.push(json["ndjson"].as_str().expect("ndjson").to_string());
         }
+        assert_eq!(exports[0], exports[1]);
 
-        // Group documents by language
-        let mut documents_by_language: std::collections::BTreeMap<String, Vec<ResourceInfo>> =
-            std::collections::BTreeMap::new();
-        for doc in &project_documents {
-            let lang = if doc.lang.is_empty() {
-                "none".to_string()
-            } else {
-                doc.lang.clone()
-            };
-            documents_by_language
-                .entry(lang)
-                .or_default()
-                .push((*doc).clone());
+        let ids: Vec<String> = exports[1]
+            .lines()
+            .step_by(2)
+            .map(|line| {
+                let action: serde_json::Value = serde_json::from_str(line).expect("action line");
+                action["index"]["_id"].as_str().expect("_id").to_string()
+            })
+            .collect();
+        let expected: Vec<String> = (0..24)
+            .map(|i| format!("docs://misc/doc-{:02}.md", i))
+            .collect();
+        assert_eq!(ids, expected);
+    }
+
+    #[tokio::test]
+    async fn test_get_project_graph_dot_links_documents_by_title() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let mut resources = Vec::new();
+        for (name, project, content) in [
+            (
+                "overview.md",
+                "mpa",
+                "---\ntitle: Overview\n---\nSee [outbox](adr.md), [terms](glossary.md#a) and [crm](crm.md).\n",
+            ),
+            (
+                "adr.md",
+                "mpa",
+                "# Outbox \"pattern\"\n[Back](overview.md)\n",
+            ),
+            ("glossary.md", "mpa", "No heading here.\n"),
+            ("isolated.md", "mpa", "# Isolated\n"),
+            ("crm.md", "crm", "# CRM\n"),
+        ] {
+            std::fs::write(temp_dir.path().join(name), content).expect("write");
+            resources.push(ResourceInfo {
+                file_path: name.to_string(),
+                description: format!("Document {}", name),
+                ..test_resource(&format!("docs://misc/{}", name), project, &["misc"])
+            });
         }
+        let docs = server_with(&temp_dir, resources);
 
-        // Create response
-        let response = ProjectOverviewResponse {
-            project: project.clone(),
-            total_documents,
-            total_size,
-            documents_by_type,
-            documents_by_area,
-            documents_by_language,
-            all_documents: project_documents.iter().map(|doc| (*doc).clone()).collect(),
-        };
+        let result = docs
+            .get_project_graph_dot(Parameters(GetProjectGraphDotArgs {
+                project: "mpa".to_string(),
+            }))
+            .await
+            .expect("graph");
+        let json = result_json(&result);
+        assert_eq!(
+            (json["nodes"].as_u64(), json["edges"].as_u64()),
+            (Some(4), Some(3))
+        );
+        let dot = json["dot"].as_str().expect("dot");
+        assert!(dot.starts_with("digraph \"mpa\" {\n"));
+        assert!(dot.ends_with("}\n"));
+        for line in [
+            "  \"docs://misc/overview.md\" [label=\"Overview\"];",
+            "  \"docs://misc/adr.md\" [label=\"Outbox \\\"pattern\\\"\"];",
+            "  \"docs://misc/glossary.md\" [label=\"Document glossary.md\"];",
+            "  \"docs://misc/isolated.md\" [label=\"Isolated\"];",
+            "  \"docs://misc/overview.md\" -> \"docs://misc/adr.md\";",
+            "  \"docs://misc/overview.md\" -> \"docs://misc/glossary.md\";",
+            "  \"docs://misc/adr.md\" -> \"docs://misc/overview.md\";",
+        ] {
+            assert!(
+                dot.lines().any(|l| l == line),
+                "missing {} in\n{}",
+                line,
+                dot
+            );
+        }
+        assert!(!dot.contains("crm.md"));
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
 
-        // Serialize response to JSON
-        let response_json = serde_json::to_value(&response).map_err(|e| {
-            McpError::internal_error(
-                "serialization_error",
-                Some(json!({
-                    "error": format!("Failed to serialize project overview response: {}", e)
-                })),
-            )
-        })?;
+        let bounded = docs.clone().with_options(ServerOptions {
+            max_response_bytes: Some(result.content[0].as_text().expect("text").text.len() - 1),
+            ..Default::default()
+        });
+        let json = result_json(
+            &bounded
+                .get_project_graph_dot(Parameters(GetProjectGraphDotArgs {
+                    project: "mpa".to_string(),
+                }))
+                .await
+                .expect("trimmed graph"),
+        );
+        assert_eq!(json["response_truncated"], true);
+        assert_eq!(json["nodes"], 4);
+        let edges = json["edges"].as_u64().expect("edges");
+        assert!(edges < 3);
+        let dot = json["dot"].as_str().expect("dot");
+        assert_eq!(
+            dot.lines().filter(|l| l.contains(" -> ")).count() as u64,
+            edges
+        );
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot_quote("a\r\nb"), "\"a\\r\\nb\"");
 
-        Ok(CallToolResult::success(vec![Content::text(
-            response_json.to_string(),
-        )]))
+        let error = docs
+            .get_project_graph_dot(Parameters(GetProjectGraphDotArgs {
+                project: "unknown".to_string(),
+            }))
+            .await
+            .expect_err("unknown project");
+        assert_eq!(error.message, "project_not_found");
     }
 
-    #[tool(
-        description = "Get all agreement documents filtered by programming language. Returns API contracts, service agreements, and technical specifications for the specified language. Perfect for understanding API contracts and service interfaces for a specific technology stack.",
-        annotations(
-            title = "📋 Get Agreements by Language",
-            read_only_hint = true,
-            destructive_hint = false,
-            idempotent_hint = true,
-            open_world_hint = false
-        )
-    )]
-    async fn get_agreements(
-        &self,
-        Parameters(GetAgreementsArgs { lang }): Parameters<GetAgreementsArgs>,
-    ) -> Result<CallToolResult, McpError> {
-        // Filter documents by language and agreements category
-        let agreement_documents: Vec<&ResourceInfo> = self
-            .resources
-            .values()
-            .filter(|info| info.lang == lang && info.category.iter().any(|cat| cat == "agreements"))
-            .collect();
+    #[tokio::test]
+    async fn test_get_most_referenced_ranks_hub_first() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let mut resources = Vec::new();
+        for (name, content) in [
+            ("glossary.md", "# Glossary\n"),
+            (
+                "billing.md",
+                "See [terms](glossary.md#invoice) and [ADR](adr.md).",
+            ),
+            (
+                "crm.md",
+                "Uses the [glossary](./glossary.md) and [itself](crm.md).",
+            ),
+            (
+                "adr.md",
+                "[Glossary](glossary.md), [web](https://example.com)",
+            ),
+        ] {
+            std::fs::write(temp_dir.path().join(name), content).expect("write");
+            resources.push(ResourceInfo {
+                file_path: name.to_string(),
+                mime_type: "text/markdown".to_string(),
+                ..test_resource(&format!("docs://misc/{}", name), "mpa", &["misc"])
+            });
+        }
+        let docs = server_with(&temp_dir, resources);
 
-        // Create response
-        let response = AgreementsResponse {
-            lang: lang.clone(),
-            agreements: agreement_documents
-                .iter()
-                .map(|doc| (*doc).clone())
-                .collect(),
-            total_agreements: agreement_documents.len().try_into().unwrap_or(u32::MAX),
-        };
+        let result = docs
+            .get_most_referenced(Parameters(GetMostReferencedArgs {
+                project: None,
+                limit: None,
+            }))
+            .await
+            .expect("ranking");
+        let json = result_json(&result);
 
-        // Serialize response to JSON
-        let response_json = serde_json::to_value(&response).map_err(|e| {
-            McpError::internal_error(
-                "serialization_error",
-                Some(json!({
-                    "error": format!("Failed to serialize agreements response: {}", e)
-                })),
-            )
-        })?;
+        assert_eq!(json["total_referenced"], 2);
+        assert_eq!(json["documents"][0]["uri"], "docs://misc/glossary.md");
+        assert_eq!(json["documents"][0]["references"], 3);
+        assert_eq!(
+            json["documents"][1]["referenced_by"],
+            json!(["docs://misc/billing.md"])
+        );
 
-        Ok(CallToolResult::success(vec![Content::text(
-            response_json.to_string(),
-        )]))
+        let result = docs
+            .get_most_referenced(Parameters(GetMostReferencedArgs {
+                project: Some("crm".to_string()),
+                limit: Some(5),
+            }))
+            .await
+            .expect("ranking");
+        assert_eq!(result_json(&result)["total_referenced"], 0);
     }
 
-    #[tool(
-        description = "Get guide documents (product documentation). Returns installation, configuration, and how-to docs. Optional product filter (e.g., eva4, psrt). Use get_resource_content with the returned URI to read a guide.",
-        annotations(
-            title = "📚 Get Guides",
-            read_only_hint = true,
-            destructive_hint = false,
-            idempotent_hint = true,
-            open_world_hint = false
-        )
-    )]
-    async fn get_guides(
-        &self,
-        Parameters(GetGuidesArgs { product }): Parameters<GetGuidesArgs>,
-    ) -> Result<CallToolResult, McpError> {
-        let guide_documents: Vec<ResourceInfo> = self
-            .resources
-            .values()
-            .filter(|info| {
-                info.area == "guides" && product.as_ref().is_none_or(|p| info.project == *p)
+    #[tokio::test]
+    async fn test_get_orphaned_documents_groups_by_top_level_directory() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let tracker = ScanTracker::default();
+        tracker.update(|status| {
+            status.orphans = vec![
+                "architecture/proj-b/adr/001.mdx".to_string(),
+                "architecture/proj-c/c4/c1.mdx".to_string(),
+                "readme.md".to_string(),
+            ];
+            status.orphaned_files = Some(3);
+        });
+        let docs = server_with(&temp_dir, Vec::new()).with_scan_tracker(tracker);
+
+        let error = docs
+            .get_orphaned_documents(Parameters(GetOrphanedDocumentsArgs {}))
+            .await
+            .expect_err("disabled");
+        assert_eq!(error.message, "orphan_detection_disabled");
+
+        let docs = docs.with_options(ServerOptions {
+            detect_orphans: true,
+            ..Default::default()
+        });
+        let result = docs
+            .get_orphaned_documents(Parameters(GetOrphanedDocumentsArgs {}))
+            .await
+            .expect("orphans");
+        let json = result_json(&result);
+        assert_eq!(json["total_orphans"], 3);
+        assert_eq!(json["truncated"], false);
+        assert_eq!(
+            json["directories"],
+            json!({
+                "": ["readme.md"],
+                "architecture": [
+                    "architecture/proj-b/adr/001.mdx",
+                    "architecture/proj-c/c4/c1.mdx",
+                ],
             })
-            .cloned()
-            .collect();
+        );
+    }
 
-        let total_guides = guide_documents.len().try_into().unwrap_or(u32::MAX);
-        let response = GuidesResponse {
-            product: product.clone(),
-            guides: guide_documents,
-            total_guides,
+    #[tokio::test]
+    async fn test_diff_agreements_reports_asymmetric_topics() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let agreement = |lang: &str, category: &str, file_name: &str| {
+            let file_path = format!("content/docs/backend/{}/{}/{}", lang, category, file_name);
+            ResourceInfo {
+                file_path: file_path.clone(),
+                area: "backend".to_string(),
+                lang: lang.to_string(),
+                ..test_resource(
+                    &format!(
+                        "docs://agreements/backend/{}/{}/{}",
+                        lang, category, file_name
+                    ),
+                    "",
+                    &["agreements", category],
+                )
+            }
         };
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                agreement("php", "errors", "handling.md"),
+                agreement("php", "errors", "codes.md"),
+                agreement("php", "logging", "format.md"),
+                agreement("go", "errors", "handling.mdx"),
+                agreement("go", "tracing", "spans.md"),
+            ],
+        );
 
-        let response_json = serde_json::to_value(&response).map_err(|e| {
-            McpError::internal_error(
-                "serialization_error",
-                Some(json!({
-                    "error": format!("Failed to serialize guides response: {}", e)
-                })),
-            )
-        })?;
+        let result = docs
+            .diff_agreements(Parameters(DiffAgreementsArgs {
+                lang_a: "php".to_string(),
+                lang_b: "go".to_string(),
+            }))
+            .await
+            .expect("diff");
+        let json = result_json(&result);
 
-        Ok(CallToolResult::success(vec![Content::text(
-            response_json.to_string(),
-        )]))
+        assert_eq!(json["agreements_a"], 3);
+        assert_eq!(json["agreements_b"], 2);
+        assert_eq!(json["categories_only_in_a"], json!(["backend/logging"]));
+        assert_eq!(json["categories_only_in_b"], json!(["backend/tracing"]));
+        assert_eq!(
+            json["topics_only_in_a"],
+            json!({
+                "backend/errors/codes": ["docs://agreements/backend/php/errors/codes.md"],
+                "backend/logging/format": ["docs://agreements/backend/php/logging/format.md"],
+            })
+        );
+        assert_eq!(
+            json["topics_only_in_b"],
+            json!({"backend/tracing/spans": ["docs://agreements/backend/go/tracing/spans.md"]})
+        );
+        assert_eq!(json["shared_topics"], json!(["backend/errors/handling"]));
+
+        let error = docs
+            .diff_agreements(Parameters(DiffAgreementsArgs {
+                lang_a: "go".to_string(),
+                lang_b: "go".to_string(),
+            }))
+            .await
+            .expect_err("same language");
+        assert_eq!(error.message, "invalid_languages");
     }
-}
 
-#[prompt_router]
-impl DocumentServer {}
+    #[tokio::test]
+    async fn test_get_usage_stats_counts_reads_per_document() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let mut resources = Vec::new();
+        for (name, project) in [("billing.md", "mpa"), ("crm.md", "crm")] {
+            std::fs::write(temp_dir.path().join(name), "# Doc\n").expect("write");
+            resources.push(ResourceInfo {
+                file_path: name.to_string(),
+                ..test_resource(&format!("docs://misc/{}", name), project, &["misc"])
+            });
+        }
+        let usage = UsageCounters::default();
+        let docs = server_with(&temp_dir, resources).with_usage(usage.clone());
+        for uri in [
+            "docs://misc/billing.md",
+            "docs://misc/billing.md",
+            "docs://misc/crm.md",
+        ] {
+            docs.get_resource_content(Parameters(GetResourceContentArgs {
+                path: uri.to_string(),
+                ..Default::default()
+            }))
+            .await
+            .expect("read");
+        }
+        assert_eq!(usage.counts()["docs://misc/billing.md"], 2);
 
-#[tool_handler]
-#[prompt_handler]
-impl ServerHandler for DocumentServer {
-    fn get_info(&self) -> ServerInfo {
-        ServerInfo {
-            protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder()
-                .enable_prompts()
-                .enable_resources()
-                .enable_tools()
-                .build(),
-            server_info: Implementation::from_build_env(),
-            instructions: Some("This server provides document access tools. Tools: get_resource_content (reads files by docs:// path), get_docs_list (lists documents with filtering and pagination), get_all_adr_documents (retrieves all ADR documents sorted by number), get_project_overview (comprehensive project overview with statistics and grouped documents), get_agreements (retrieves agreement documents filtered by programming language), get_guides (retrieves guide/product documentation, optional product filter).".to_string()),
+        let result = docs
+            .get_usage_stats(Parameters(GetUsageStatsArgs {
+                project: None,
+                limit: Some(1),
+            }))
+            .await
+            .expect("usage");
+        let json = result_json(&result);
+        assert_eq!(json["documents_read"], 2);
+        assert_eq!(json["total_reads"], 3);
+        assert_eq!(
+            json["documents"],
+            json!([{"uri": "docs://misc/billing.md", "project": "mpa", "reads": 2}])
+        );
+
+        let result = docs
+            .get_usage_stats(Parameters(GetUsageStatsArgs {
+                project: Some("crm".to_string()),
+                limit: None,
+            }))
+            .await
+            .expect("usage");
+        assert_eq!(
+            result_json(&result)["documents"][0]["uri"],
+            "docs://misc/crm.md"
+        );
+    }
+
+    fn write_spec(temp_dir: &TempDir, project: &str, service: &str, content: &str) -> ResourceInfo {
+        write_versioned_spec(temp_dir, project, service, "v1", "public", content)
+    }
+
+    fn write_versioned_spec(
+        temp_dir: &TempDir,
+        project: &str,
+        service: &str,
+        version: &str,
+        access_level: &str,
+        content: &str,
+    ) -> ResourceInfo {
+        let relative = format!(
+            "{}/{}/{}/{}/api.yaml",
+            project, service, version, access_level
+        );
+        let path = format!("openapi-spec/{}", relative);
+        let full_path = temp_dir.path().join(&path);
+        std::fs::create_dir_all(full_path.parent().expect("parent")).expect("dir");
+        std::fs::write(&full_path, content).expect("write spec");
+        ResourceInfo {
+            file_path: path,
+            mime_type: "application/x-yaml".to_string(),
+            ..test_resource(
+                &format!("docs://openapi/{}", relative),
+                project,
+                &["openapi", service, version, access_level],
+            )
         }
     }
 
-    async fn list_resources(
-        &self,
-        _request: Option<PaginatedRequestParams>,
-        _: RequestContext<RoleServer>,
-    ) -> Result<ListResourcesResult, McpError> {
-        let resources: Vec<Resource> = self
-            .resources
-            .values()
-            .map(|info| {
-                let mut resource = RawResource::new(info.uri.clone(), info.description.clone());
-                resource.description = Some(info.description.clone());
-                resource.mime_type = Some(info.mime_type.clone());
-                resource.size = Some(info.size);
-                resource.no_annotation()
-            })
+    #[tokio::test]
+    async fn test_openapi_uri_reads_canonical_spec() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = server_with(
+            &temp_dir,
+            vec![write_spec(
+                &temp_dir,
+                "mpa",
+                "billing",
+                "openapi: 3.0.0\ninfo: {}\npaths: {}\n",
+            )],
+        );
+
+        let result = docs
+            .get_resource_content(Parameters(GetResourceContentArgs {
+                path: "openapi://mpa/billing/v1/public/api".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .expect("openapi:// read");
+        let status: serde_json::Value =
+            serde_json::from_str(&result.content[1].as_text().expect("status").text)
+                .expect("status json");
+        assert_eq!(
+            status["uri"],
+            "docs://openapi/mpa/billing/v1/public/api.yaml"
+        );
+        assert_eq!(
+            status["aliased_from"],
+            "openapi://mpa/billing/v1/public/api"
+        );
+
+        let missing = docs
+            .get_resource_content(Parameters(GetResourceContentArgs {
+                path: "openapi://mpa/billing/v2/public/api".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .expect_err("unknown version");
+        assert_eq!(missing.message, "resource_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_get_service_versions_counts_access_levels_and_gaps() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let spec = "openapi: 3.0.0\ninfo: {}\npaths: {}\n";
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                write_versioned_spec(&temp_dir, "mpa", "billing", "v1", "public", spec),
+                write_versioned_spec(&temp_dir, "mpa", "billing", "v2", "public", spec),
+                write_versioned_spec(&temp_dir, "mpa", "billing", "v2", "internal", spec),
+                write_versioned_spec(&temp_dir, "mpa", "billing", "v10", "public", spec),
+                write_versioned_spec(&temp_dir, "mpa", "activation", "v1", "public", spec),
+            ],
+        );
+
+        let result = docs
+            .get_service_versions(Parameters(GetServiceVersionsArgs {
+                project: "mpa".to_string(),
+                service: Some("billing".to_string()),
+            }))
+            .await
+            .expect("versions");
+        let json = result_json(&result);
+        assert_eq!(json["total_services"], 1);
+        let billing = &json["services"][0];
+        let versions: Vec<&str> = billing["versions"]
+            .as_array()
+            .expect("versions")
+            .iter()
+            .filter_map(|v| v["version"].as_str())
             .collect();
+        assert_eq!(versions, ["v1", "v2", "v10"]);
+        assert_eq!(
+            billing["versions"][0]["specs_by_access_level"],
+            json!({"public": 1})
+        );
+        assert_eq!(
+            billing["versions"][1]["specs_by_access_level"],
+            json!({"internal": 1, "public": 1})
+        );
+        assert_eq!(billing["versions"][1]["total_specs"], 2);
+        assert_eq!(
+            billing["missing_versions"].as_array().map(Vec::len),
+            Some(7)
+        );
 
-        Ok(ListResourcesResult {
-            resources,
-            next_cursor: None,
-            meta: None,
-        })
+        let all = docs
+            .get_service_versions(Parameters(GetServiceVersionsArgs {
+                project: "mpa".to_string(),
+                service: None,
+            }))
+            .await
+            .expect("all services");
+        assert_eq!(result_json(&all)["total_services"], 2);
+
+        let error = docs
+            .get_service_versions(Parameters(GetServiceVersionsArgs {
+                project: "mpa".to_string(),
+                service: Some("bill".to_string()),
+            }))
+            .await
+            .expect_err("unknown service");
+        assert_eq!(error.message, "service_not_found");
     }
 
-    async fn read_resource(
-        &self,
-        request: ReadResourceRequestParams,
-        _: RequestContext<RoleServer>,
-    ) -> Result<ReadResourceResult, McpError> {
-        // First, find the resource by URI in our resources map
-        let resource_info = self
-            .resources
-            .get(&DocumentKey::new(request.uri.clone()))
-            .ok_or_else(|| {
-                McpError::resource_not_found(
-                    "resource_not_found",
-                    Some(json!({
-                        "uri": request.uri,
-                        "error": "Resource not found in scanned documents"
-                    })),
-                )
-            })?;
+    #[tokio::test]
+    async fn test_diff_openapi_versions_merges_access_levels() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                write_versioned_spec(
+                    &temp_dir,
+                    "mpa",
+                    "billing",
+                    "v1",
+                    "public",
+                    "openapi: 3.0.0\ninfo: {}\npaths:\n  /invoices:\n    \
+                     get: {parameters: [{name: limit, in: query}], responses: {'200': {}}}\n  \
+                     /legacy:\n    get: {responses: {'200': {}}}\n",
+                ),
+                write_versioned_spec(
+                    &temp_dir,
+                    "mpa",
+                    "billing",
+                    "v2",
+                    "public",
+                    "openapi: 3.0.0\ninfo: {}\npaths:\n  /invoices:\n    \
+                     get: {parameters: [{name: limit, in: query, required: true}], \
+                     responses: {'200': {}}}\n",
+                ),
+                write_versioned_spec(
+                    &temp_dir,
+                    "mpa",
+                    "billing",
+                    "v2",
+                    "internal",
+                    "openapi: 3.0.0\ninfo: {}\npaths:\n  /refunds:\n    \
+                     post: {responses: {'201': {}}}\n",
+                ),
+            ],
+        );
 
-        // Then read the file content using the file path from ResourceInfo
-        let content = self.read_file_by_path(&resource_info.file_path)?;
+        let result = docs
+            .diff_openapi_versions(Parameters(DiffOpenApiVersionsArgs {
+                project: "mpa".to_string(),
+                service: "billing".to_string(),
+                from_version: "v1".to_string(),
+                to_version: "v2".to_string(),
+            }))
+            .await
+            .expect("diff");
+        let json = result_json(&result);
+        assert_eq!(json["total_changed_paths"], 3);
+        assert_eq!(json["to_specs"].as_array().map(Vec::len), Some(2));
+        assert_eq!(json["paths"]["/refunds"]["change"], "added");
+        assert_eq!(json["paths"]["/legacy"]["change"], "removed");
+        assert_eq!(json["paths"]["/legacy"]["removed_operations"][0], "GET");
+        assert_eq!(json["paths"]["/invoices"]["change"], "changed");
+        assert_eq!(
+            json["paths"]["/invoices"]["changed_operations"]["GET"]["added_required_fields"],
+            json!(["query:limit"])
+        );
 
-        Ok(ReadResourceResult {
-            contents: vec![ResourceContents::TextResourceContents {
-                uri: request.uri.clone(),
-                mime_type: Some(resource_info.mime_type.clone()),
-                text: content,
-                meta: None,
-            }],
-        })
+        let error = docs
+            .diff_openapi_versions(Parameters(DiffOpenApiVersionsArgs {
+                project: "mpa".to_string(),
+                service: "billing".to_string(),
+                from_version: "v1".to_string(),
+                to_version: "v3".to_string(),
+            }))
+            .await
+            .expect_err("unknown version");
+        assert_eq!(error.message, "openapi_version_not_found");
+        assert_eq!(
+            error.data.expect("data")["available_versions"],
+            json!(["v1", "v2"])
+        );
     }
 
-    async fn list_resource_templates(
-        &self,
-        _request: Option<PaginatedRequestParams>,
-        _: RequestContext<RoleServer>,
-    ) -> Result<ListResourceTemplatesResult, McpError> {
-        Ok(ListResourceTemplatesResult {
-            next_cursor: None,
-            resource_templates: Vec::new(),
-            meta: None,
-        })
+    #[tokio::test]
+    async fn test_get_endpoints_for_tag_groups_by_service() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                write_spec(
+                    &temp_dir,
+                    "mpa",
+                    "billing",
+                    "openapi: 3.0.0\ninfo: {}\npaths:\n  /invoices:\n    \
+                     get: {operationId: listInvoices, tags: [Billing]}\n    \
+                     post: {operationId: createInvoice, tags: [invoicing]}\n",
+                ),
+                write_spec(
+                    &temp_dir,
+                    "mpa",
+                    "activation",
+                    "openapi: 3.0.0\ninfo: {}\npaths:\n  /activations/{id}/bill:\n    \
+                     put: {operationId: billActivation, summary: Bill, tags: [billing, activation]}\n",
+                ),
+                write_spec(&temp_dir, "mpa", "broken", "paths: [unclosed\n"),
+            ],
+        );
+
+        let result = docs
+            .get_endpoints_for_tag(Parameters(GetEndpointsForTagArgs {
+                tag: "BILLING".to_string(),
+                project: Some("mpa".to_string()),
+            }))
+            .await
+            .expect("endpoints");
+        let json = result_json(&result);
+        assert_eq!(json["total_operations"], 2);
+        assert_eq!(
+            json["services"]["activation"],
+            json!([{
+                "method": "PUT",
+                "path": "/activations/{id}/bill",
+                "operation_id": "billActivation",
+                "summary": "Bill",
+                "spec_uri": "docs://openapi/mpa/activation/v1/public/api.yaml"
+            }])
+        );
+        assert_eq!(
+            json["services"]["billing"][0]["operation_id"],
+            "listInvoices"
+        );
+
+        let error = docs
+            .get_endpoints_for_tag(Parameters(GetEndpointsForTagArgs {
+                tag: "payments".to_string(),
+                project: None,
+            }))
+            .await
+            .expect_err("unknown tag");
+        assert_eq!(error.message, "tag_not_found");
+        assert_eq!(
+            error.data.expect("data")["available_tags"],
+            json!(["Billing", "activation", "billing", "invoicing"])
+        );
     }
 
-    async fn subscribe(
-        &self,
-        request: SubscribeRequestParams,
-        _: RequestContext<RoleServer>,
-    ) -> Result<(), McpError> {
-        // Check if the resource exists
-        if !self
-            .resources
-            .contains_key(&DocumentKey::new(request.uri.clone()))
-        {
-            return Err(McpError::resource_not_found(
-                "resource_not_found",
-                Some(json!({
-                    "uri": request.uri,
-                    "error": "Cannot subscribe to resource that does not exist"
-                })),
-            ));
-        }
-        // Subscription is successful (no-op for static resources)
-        Ok(())
+    #[tokio::test]
+    async fn test_get_openapi_schema_flags_divergent_definitions() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                write_spec(
+                    &temp_dir,
+                    "mpa",
+                    "billing",
+                    "openapi: 3.0.0\ninfo: {}\ncomponents:\n  schemas:\n    Customer:\n      \
+                     type: object\n      properties:\n        id: {type: string}\n        \
+                     address:\n          type: object\n          properties: {city: {type: string}}\n",
+                ),
+                write_spec(
+                    &temp_dir,
+                    "crm",
+                    "customers",
+                    "swagger: '2.0'\ninfo: {}\ndefinitions:\n  Customer:\n    type: object\n    \
+                     properties: {id: {type: integer}}\n  CustomerList: {type: array}\n",
+                ),
+            ],
+        );
+
+        let result = docs
+            .get_openapi_schema(Parameters(GetOpenApiSchemaArgs {
+                schema_name: "Customer".to_string(),
+                project: None,
+                service: None,
+            }))
+            .await
+            .expect("schema");
+        let json = result_json(&result);
+        assert_eq!(json["diverged"], true);
+        assert_eq!(json["distinct_definitions"], 2);
+        assert_eq!(json["matches"][0]["section"], "definitions");
+        assert_eq!(
+            json["matches"][0]["schema"]["properties"]["id"]["type"],
+            "integer"
+        );
+        assert_eq!(
+            json["matches"][1]["schema"]["properties"]["address"]["properties"]["city"]["type"],
+            "string"
+        );
+
+        let result = docs
+            .get_openapi_schema(Parameters(GetOpenApiSchemaArgs {
+                schema_name: "Customer".to_string(),
+                project: Some("mpa".to_string()),
+                service: Some("billing".to_string()),
+            }))
+            .await
+            .expect("schema");
+        assert_eq!(result_json(&result)["diverged"], false);
+
+        let error = docs
+            .get_openapi_schema(Parameters(GetOpenApiSchemaArgs {
+                schema_name: "customerlist".to_string(),
+                project: None,
+                service: None,
+            }))
+            .await
+            .expect_err("case-sensitive miss");
+        assert_eq!(error.message, "schema_not_found");
+        assert_eq!(
+            error.data.expect("data")["suggestions"],
+            json!(["CustomerList"])
+        );
     }
 
-    async fn unsubscribe(
-        &self,
-        _request: UnsubscribeRequestParams,
-        _: RequestContext<RoleServer>,
-    ) -> Result<(), McpError> {
-        // Unsubscription is successful (no-op for static resources)
-        Ok(())
+    #[tokio::test]
+    async fn test_map_erd_to_api_reports_drift_and_parse_errors() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let erd_dir = temp_dir.path().join("architecture/mpa/erd/services");
+        std::fs::create_dir_all(&erd_dir).expect("dir");
+        std::fs::write(
+            temp_dir.path().join("architecture/mpa/erd/billing.mdx"),
+            "```mermaid\nerDiagram\n  customers ||--o{ invoice_lines : owns\n  \
+             LEDGER_ENTRY {\n    string id\n  }\n```\n",
+        )
+        .expect("write erd");
+        std::fs::write(
+            erd_dir.join("broken.puml"),
+            "entity Payment {\n  id : uuid\n",
+        )
+        .expect("write erd");
+        let erd = |path: &str| {
+            let mut info = test_resource(&format!("docs://{}", path), "mpa", &["erd"]);
+            info.file_path = path.to_string();
+            info
+        };
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                erd("architecture/mpa/erd/billing.mdx"),
+                erd("architecture/mpa/erd/services/broken.puml"),
+                write_spec(
+                    &temp_dir,
+                    "mpa",
+                    "billing",
+                    "openapi: 3.0.0\ninfo: {}\ncomponents:\n  schemas:\n    \
+                     Customer: {type: object}\n    InvoiceLine: {type: object}\n    \
+                     Error: {type: object}\n",
+                ),
+                write_spec(&temp_dir, "mpa", "broken", "openapi: [\n"),
+            ],
+        );
+
+        let result = docs
+            .map_erd_to_api(Parameters(MapErdToApiArgs {
+                project: "mpa".to_string(),
+            }))
+            .await
+            .expect("mapping");
+        let json = result_json(&result);
+        assert_eq!(json["matched"][0]["entity"], "customers");
+        assert_eq!(json["matched"][0]["schemas"][0]["name"], "Customer");
+        assert_eq!(json["matched"][1]["schemas"][0]["name"], "InvoiceLine");
+        assert_eq!(json["unmatched_entities"][0]["entity"], "LEDGER_ENTRY");
+        assert_eq!(
+            json["unmatched_entities"][0]["erd_uris"],
+            json!(["docs://architecture/mpa/erd/billing.mdx"])
+        );
+        assert_eq!(json["unmatched_schemas"][0]["name"], "Error");
+        assert_eq!(json["erd_documents_parsed"], 1);
+        assert_eq!(json["specs_parsed"], 1);
+        let failed: Vec<&str> = json["parse_errors"]
+            .as_array()
+            .expect("errors")
+            .iter()
+            .filter_map(|e| e["uri"].as_str())
+            .collect();
+        assert_eq!(
+            failed,
+            vec![
+                "docs://architecture/mpa/erd/services/broken.puml",
+                "docs://openapi/mpa/broken/v1/public/api.yaml"
+            ]
+        );
+
+        let error = docs
+            .map_erd_to_api(Parameters(MapErdToApiArgs {
+                project: "crm".to_string(),
+            }))
+            .await
+            .expect_err("unknown project");
+        assert_eq!(error.message, "project_not_found");
     }
 
-    async fn initialize(
-        &self,
-        _request: InitializeRequestParams,
-        context: RequestContext<RoleServer>,
-    ) -> Result<InitializeResult, McpError> {
-        if let Some(http_request_part) = context.extensions.get::<axum::http::request::Parts>() {
-            let initialize_headers = &http_request_part.headers;
-            let initialize_uri = &http_request_part.uri;
-            tracing::info!(?initialize_headers, %initialize_uri, "initialize from http server");
+    #[tokio::test]
+    async fn test_parsed_specs_and_frontmatter_are_reused_until_content_changes() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let spec_content = "openapi: 3.0.0\ninfo: {}\ncomponents:\n  schemas:\n    \
+                            Customer: {type: object}\n";
+        let spec = write_spec(&temp_dir, "mpa", "billing", spec_content);
+        let spec_path = temp_dir.path().join(&spec.file_path);
+        std::fs::write(
+            temp_dir.path().join("guide.md"),
+            "---\ntitle: Guide\n---\nShort.\n",
+        )
+        .expect("write guide");
+        let docs = server_with(
+            &temp_dir,
+            vec![spec, test_resource("docs://guide.md", "mpa", &["guide"])],
+        );
+        let schema_args = || {
+            Parameters(GetOpenApiSchemaArgs {
+                schema_name: "Customer".to_string(),
+                project: None,
+                service: None,
+            })
+        };
+
+        docs.get_openapi_schema(schema_args())
+            .await
+            .expect("schema");
+        docs.clone()
+            .get_openapi_schema(schema_args())
+            .await
+            .expect("schema");
+        assert_eq!(docs.spec_cache.parses(), 1);
+
+        std::fs::write(
+            &spec_path,
+            format!("{}    Invoice: {{type: object}}\n", spec_content),
+        )
+        .expect("rewrite spec");
+        docs.get_openapi_schema(schema_args())
+            .await
+            .expect("schema");
+        assert_eq!(docs.spec_cache.parses(), 2);
+
+        for _ in 0..2 {
+            docs.get_undocumented(Parameters(GetUndocumentedArgs {
+                project: None,
+                min_words: None,
+            }))
+            .await
+            .expect("undocumented");
         }
-        Ok(self.get_info())
+        assert_eq!(docs.frontmatter_cache.parses(), 1);
+
+        let uncached = docs.with_options(ServerOptions {
+            cache_parsed_documents: false,
+            ..Default::default()
+        });
+        uncached
+            .get_openapi_schema(schema_args())
+            .await
+            .expect("schema");
+        uncached
+            .get_openapi_schema(schema_args())
+            .await
+            .expect("schema");
+        assert_eq!(uncached.spec_cache.parses(), 2);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use tempfile::TempDir;
+    #[tokio::test]
+    async fn test_content_policy_hides_blocked_documents_from_ranking_and_links() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let mut resources = Vec::new();
+        for (name, mime_type, content) in [
+            (
+                "glossary.md",
+                "text/markdown",
+                "# Glossary\nThe outbox relay.\n",
+            ),
+            (
+                "billing.md",
+                "text/markdown",
+                "See the [glossary](glossary.md).\n",
+            ),
+            (
+                "secrets.yaml",
+                "application/x-yaml",
+                "outbox_password: hunter2\n",
+            ),
+        ] {
+            std::fs::write(temp_dir.path().join(name), content).expect("write");
+            resources.push(ResourceInfo {
+                file_path: name.to_string(),
+                mime_type: mime_type.to_string(),
+                ..test_resource(&format!("docs://misc/{}", name), "mpa", &["misc"])
+            });
+        }
+        let base = server_with(&temp_dir, resources);
+        let with_denied = |mime_type: &str| {
+            base.clone().with_options(ServerOptions {
+                content: ContentConfig {
+                    denied_mime_types: vec![mime_type.to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+        };
+        let rank = |docs: DocumentServer, query: &'static str| async move {
+            result_json(
+                &docs
+                    .rank_documents(Parameters(RankDocumentsArgs {
+                        query: query.to_string(),
+                        project: None,
+                        limit: None,
+                    }))
+                    .await
+                    .expect("ranked"),
+            )
+        };
 
-    use super::*;
+        let docs = with_denied("application/x-yaml");
+        assert_eq!(rank(docs.clone(), "hunter2").await["total_matches"], 0);
+        let json = rank(docs, "outbox").await;
+        assert_eq!(json["total_matches"], 1);
+        assert_eq!(json["results"][0]["uri"], "docs://misc/glossary.md");
+
+        let docs = with_denied("text/markdown");
+        let error = docs
+            .resolve_links(Parameters(ResolveLinksArgs {
+                uri: "docs://misc/billing.md".to_string(),
+            }))
+            .await
+            .expect_err("blocked");
+        assert_eq!(error.message, "content_type_blocked");
+        let referenced = result_json(
+            &docs
+                .get_most_referenced(Parameters(GetMostReferencedArgs {
+                    project: None,
+                    limit: None,
+                }))
+                .await
+                .expect("ranking"),
+        );
+        assert_eq!(referenced["total_referenced"], 0);
+    }
 
     #[tokio::test]
-    async fn test_get_resource_content_tool_attributes() {
-        let router = DocumentServer::tool_router();
-        assert!(router.has_route("get_resource_content"));
+    async fn test_rank_documents_orders_by_relevance() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let adr_dir = temp_dir.path().join("architecture/mpa/adr");
+        std::fs::create_dir_all(&adr_dir).expect("dir");
+        std::fs::write(
+            adr_dir.join("001.mdx"),
+            "# Outbox\nOutbox events are relayed from the outbox table.\n",
+        )
+        .expect("write");
+        std::fs::write(
+            adr_dir.join("002.mdx"),
+            format!(
+                "# Queues\nWe considered an outbox once. {}\n",
+                "Queues scale. ".repeat(30)
+            ),
+        )
+        .expect("write");
+        std::fs::write(adr_dir.join("003.mdx"), "# Logging\nStructured logs.\n").expect("write");
+        let docs = server_with(
+            &temp_dir,
+            ["001", "002", "003"]
+                .iter()
+                .map(|n| {
+                    test_resource(
+                        &format!("docs://architecture/mpa/adr/{}.mdx", n),
+                        "mpa",
+                        &["adr"],
+                    )
+                })
+                .collect(),
+        );
 
-        let tools = router.list_all();
-        assert!(tools.iter().any(|t| t.name == "get_resource_content"));
+        let result = docs
+            .rank_documents(Parameters(RankDocumentsArgs {
+                query: "outbox events".to_string(),
+                project: None,
+                limit: None,
+            }))
+            .await
+            .expect("ranked");
+        let json = result_json(&result);
+        assert_eq!(json["terms"], json!(["events", "outbox"]));
+        assert_eq!(json["total_matches"], 2);
+        assert_eq!(
+            json["results"][0]["uri"],
+            "docs://architecture/mpa/adr/001.mdx"
+        );
+        assert_eq!(
+            json["results"][0]["matched_terms"],
+            json!({"events": 1, "outbox": 3})
+        );
+        assert_eq!(
+            json["results"][1]["uri"],
+            "docs://architecture/mpa/adr/002.mdx"
+        );
+        assert!(json["results"][0]["score"].as_f64() > json["results"][1]["score"].as_f64());
+        assert_eq!(docs.term_cache.lock().expect("cache").len(), 3);
+
+        let error = docs
+            .rank_documents(Parameters(RankDocumentsArgs {
+                query: "a !".to_string(),
+                project: None,
+                limit: None,
+            }))
+            .await
+            .expect_err("no terms");
+        assert_eq!(error.code.0, -32602);
     }
 
     #[tokio::test]
-    async fn test_get_resource_content_invalid_path() {
+    async fn test_get_parse_failures_reports_malformed_spec() {
         let temp_dir = TempDir::new().expect("temp dir");
-        let docs_root = temp_dir.path().to_string_lossy().to_string();
-        let docs = DocumentServer::new_with_resources(
-            FileReader::new(docs_root).expect("file reader"),
-            BTreeMap::new(),
-        );
-        let args = GetResourceContentArgs {
-            path: "invalid/path".to_string(),
+        let spec_dir = temp_dir
+            .path()
+            .join("openapi-spec/billing/invoices/v1/public");
+        std::fs::create_dir_all(&spec_dir).expect("dir");
+        std::fs::write(
+            spec_dir.join("broken.yaml"),
+            "openapi: 3.0.0\ninfo: [unclosed\n",
+        )
+        .expect("write broken");
+        std::fs::write(spec_dir.join("ok.yaml"), "openapi: 3.0.0\ninfo: {}\n").expect("write ok");
+        let spec = |name: &str| ResourceInfo {
+            file_path: format!("openapi-spec/billing/invoices/v1/public/{}", name),
+            mime_type: "application/x-yaml".to_string(),
+            ..test_resource(
+                &format!("docs://openapi/billing/invoices/v1/public/{}", name),
+                "billing",
+                &["openapi", "invoices", "v1", "public"],
+            )
         };
+        let mut resources: BTreeMap<DocumentKey, ResourceInfo> =
+            [spec("broken.yaml"), spec("ok.yaml")]
+                .into_iter()
+                .map(|info| (DocumentKey::new(info.uri.clone()), info))
+                .collect();
+        let file_reader =
+            FileReader::new(temp_dir.path().to_string_lossy().to_string()).expect("file reader");
+        crate::models::DocumentScanner::detect_parse_failures(
+            &mut resources,
+            &file_reader,
+            MultiDocumentMode::default(),
+        );
+        let docs = DocumentServer::new_with_resources(file_reader, resources);
 
-        let result = docs.get_resource_content(Parameters(args)).await;
-        assert!(result.is_err());
+        let result = docs
+            .get_parse_failures(Parameters(GetParseFailuresArgs { project: None }))
+            .await
+            .expect("report");
+        let json = result_json(&result);
+        assert_eq!(json["total_failures"], 1);
+        assert_eq!(
+            json["failures"][0]["uri"],
+            "docs://openapi/billing/invoices/v1/public/broken.yaml"
+        );
+        assert_eq!(json["failures"][0]["format"], "openapi");
+        assert!(
+            json["failures"][0]["error"]
+                .as_str()
+                .is_some_and(|e| e.starts_with("Invalid YAML/JSON"))
+        );
 
-        if let Err(error) = result {
-            assert_eq!(error.code.0, -32602);
-        }
+        let result = docs
+            .get_parse_failures(Parameters(GetParseFailuresArgs {
+                project: Some("crm".to_string()),
+            }))
+            .await
+            .expect("report");
+        assert_eq!(result_json(&result)["total_failures"], 0);
     }
 
     #[tokio::test]
-    async fn test_get_docs_list_tool_attributes() {
-        let router = DocumentServer::tool_router();
-        assert!(router.has_route("get_docs_list"));
+    async fn test_lint_adr_reports_findings_per_document() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let adr_dir = temp_dir.path().join("docs/mpa/adr");
+        std::fs::create_dir_all(&adr_dir).expect("dir");
+        std::fs::write(
+            adr_dir.join("001-outbox.mdx"),
+            "---\nadr: 1\ndate: 2024-01-10\nstatus: accepted\n---\n# Outbox\n\
+             ## Status\nAccepted\n## Context\nWhy\n## Decision\n### Details\nHow\n\
+             ## Consequences\nTrade-offs\n",
+        )
+        .expect("write compliant");
+        std::fs::write(
+            adr_dir.join("002-saga.mdx"),
+            "---\nadr: ADR-003\n---\n# Saga\n## Status\n\n## Decision\nUse sagas\n",
+        )
+        .expect("write non-compliant");
+        let adr = |name: &str| {
+            let mut info = test_resource(
+                &format!("docs://architecture/mpa/adr/{}", name),
+                "mpa",
+                &["adr"],
+            );
+            info.file_path = format!("docs/mpa/adr/{}", name);
+            info
+        };
+        let docs = server_with(&temp_dir, vec![adr("001-outbox.mdx"), adr("002-saga.mdx")]);
 
-        let tools = router.list_all();
-        assert!(tools.iter().any(|t| t.name == "get_docs_list"));
+        let result = docs
+            .lint_adr(Parameters(LintAdrArgs {
+                uri: None,
+                project: Some("mpa".to_string()),
+            }))
+            .await
+            .expect("linted");
+        let json = result_json(&result);
+        assert_eq!(json["total_documents"], 2);
+        assert_eq!(json["documents_with_findings"], 1);
+        assert_eq!(json["documents"][0]["findings"], json!([]));
+
+        let codes: Vec<&str> = json["documents"][1]["findings"]
+            .as_array()
+            .expect("findings")
+            .iter()
+            .filter_map(|finding| finding["code"].as_str())
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                "empty_section",
+                "missing_section",
+                "missing_section",
+                "missing_frontmatter_date",
+                "missing_frontmatter_status",
+                "adr_number_mismatch"
+            ]
+        );
+
+        let error = docs
+            .lint_adr(Parameters(LintAdrArgs {
+                uri: Some("docs://architecture/mpa/adr/001-outbox.mdx".to_string()),
+                project: Some("mpa".to_string()),
+            }))
+            .await
+            .expect_err("ambiguous target");
+        assert_eq!(error.code.0, -32602);
     }
 
     #[tokio::test]
-    async fn test_get_docs_list_pagination_validation() {
+    async fn test_lint_adr_accepts_section_synonyms() {
         let temp_dir = TempDir::new().expect("temp dir");
-        let docs_root = temp_dir.path().to_string_lossy().to_string();
-        let docs = DocumentServer::new_with_resources(
-            FileReader::new(docs_root).expect("file reader"),
-            BTreeMap::new(),
+        let adr_dir = temp_dir.path().join("docs/mpa/adr");
+        std::fs::create_dir_all(&adr_dir).expect("dir");
+        std::fs::write(
+            adr_dir.join("003-cache.md"),
+            "---\nadr: 3\ndate: 2024-02-01\nstatus: accepted\n---\n# Cache\n\
+             ## Background\nSlow reads\n## Decision\nAdd a cache\n## Implications:\nStale data\n",
+        )
+        .expect("write compliant");
+        std::fs::write(
+            adr_dir.join("004-queue.md"),
+            "---\nadr: 4\ndate: 2024-02-02\nstatus: proposed\n---\n# Queue\n\
+             ## Motivation\nBursts\n## Decision\nAdd a queue\n",
+        )
+        .expect("write non-compliant");
+        let adr = |name: &str| ResourceInfo {
+            file_path: format!("docs/mpa/adr/{}", name),
+            ..test_resource(
+                &format!("docs://architecture/mpa/adr/{}", name),
+                "mpa",
+                &["adr"],
+            )
+        };
+        let docs = server_with(&temp_dir, vec![adr("003-cache.md"), adr("004-queue.md")])
+            .with_options(ServerOptions {
+                adr_required_sections: vec![
+                    "Context".to_string(),
+                    "Decision".to_string(),
+                    "Consequences".to_string(),
+                ],
+                adr_section_synonyms: BTreeMap::from([
+                    ("context".to_string(), vec!["Background".to_string()]),
+                    ("Consequences".to_string(), vec!["Implications".to_string()]),
+                ]),
+                ..Default::default()
+            });
+
+        let result = docs
+            .lint_adr(Parameters(LintAdrArgs {
+                uri: None,
+                project: Some("mpa".to_string()),
+            }))
+            .await
+            .expect("linted");
+        let json = result_json(&result);
+        assert_eq!(json["documents"][0]["findings"], json!([]));
+        let messages: Vec<&str> = json["documents"][1]["findings"]
+            .as_array()
+            .expect("findings")
+            .iter()
+            .filter_map(|finding| finding["message"].as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Required section 'Context' is missing",
+                "Required section 'Consequences' is missing"
+            ]
         );
-        let args = GetDocsListArgs {
-            area: None,
-            lang: None,
-            category: None,
-            page: Some(0), // Invalid page
-            limit: Some(50),
+    }
+
+    #[tokio::test]
+    async fn test_get_docs_list_validity_filters() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let mut valid = test_resource("docs://openapi/billing/v1/invoices.yaml", "billing", &[]);
+        valid.valid = Some(true);
+        let mut broken = test_resource("docs://openapi/billing/v1/payments.yaml", "billing", &[]);
+        broken.valid = Some(false);
+        broken.validation_error = Some("Missing 'info' section".to_string());
+        let guide = test_resource("docs://guides/eva4/intro.md", "", &["guides"]);
+        let docs = server_with(&temp_dir, vec![valid, broken, guide]);
+
+        let uris = |args: GetDocsListArgs| {
+            let response = docs.list_documents(&args).expect("listed");
+            response
+                .documents
+                .into_iter()
+                .map(|info| info.uri)
+                .collect::<Vec<_>>()
         };
 
-        let result = docs.get_docs_list(Parameters(args)).await;
-        assert!(result.is_err());
+        assert_eq!(
+            uris(GetDocsListArgs {
+                only_invalid: Some(true),
+                ..Default::default()
+            }),
+            vec!["docs://openapi/billing/v1/payments.yaml"]
+        );
+        assert_eq!(
+            uris(GetDocsListArgs {
+                only_valid: Some(true),
+                ..Default::default()
+            })
+            .len(),
+            2
+        );
 
-        if let Err(error) = result {
-            assert_eq!(error.code.0, -32602);
-        }
+        let conflict = docs.list_documents(&GetDocsListArgs {
+            only_valid: Some(true),
+            only_invalid: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(conflict.expect_err("conflict").code.0, -32602);
     }
 
     #[tokio::test]
-    async fn test_get_docs_list_limit_validation() {
+    async fn test_get_docs_list_groups_page_documents() {
         let temp_dir = TempDir::new().expect("temp dir");
-        let docs_root = temp_dir.path().to_string_lossy().to_string();
-        let docs = DocumentServer::new_with_resources(
-            FileReader::new(docs_root).expect("file reader"),
-            BTreeMap::new(),
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                test_resource(
+                    "docs://architecture/mpa/adr/001.mdx",
+                    "mpa",
+                    &["adr", "ADR-001"],
+                ),
+                test_resource("docs://architecture/mpa/c1.mdx", "mpa", &["c1"]),
+                test_resource("docs://architecture/crm/c1.mdx", "crm", &["c1"]),
+            ],
         );
-        let args = GetDocsListArgs {
-            area: None,
-            lang: None,
-            category: None,
-            page: Some(1),
-            limit: Some(201), // Invalid limit (max is 200)
+        let grouped = |group_by: &str, page: u32, limit: u32| {
+            docs.get_docs_list(Parameters(GetDocsListArgs {
+                group_by: Some(group_by.to_string()),
+                fields: Some(vec!["project".to_string()]),
+                page: Some(page),
+                limit: Some(limit),
+                ..Default::default()
+            }))
         };
 
-        let result = docs.get_docs_list(Parameters(args)).await;
-        assert!(result.is_err());
+        let json = result_json(&grouped("category", 1, 50).await.expect("by category"));
+        assert!(json.get("documents").is_none());
+        assert_eq!(json["total_documents"], 3);
+        let groups = &json["groups"];
+        assert_eq!(
+            groups["adr"][0]["uri"],
+            "docs://architecture/mpa/adr/001.mdx"
+        );
+        assert_eq!(groups["ADR-001"], groups["adr"]);
+        assert_eq!(groups["c1"].as_array().map(Vec::len), Some(2));
+        assert_eq!(
+            groups["c1"][0],
+            json!({"uri": "docs://architecture/crm/c1.mdx", "project": "crm"})
+        );
 
-        if let Err(error) = result {
-            assert_eq!(error.code.0, -32602);
-        }
+        // Pages hold `limit` documents in URI order; mpa continues on page 2.
+        let first = result_json(&grouped("project", 1, 2).await.expect("page 1"));
+        assert_eq!(first["total_pages"], 2);
+        assert_eq!(first["groups"]["crm"].as_array().map(Vec::len), Some(1));
+        assert_eq!(first["groups"]["mpa"].as_array().map(Vec::len), Some(1));
+        let second = result_json(&grouped("project", 2, 2).await.expect("page 2"));
+        assert_eq!(
+            second["groups"]["mpa"][0]["uri"],
+            "docs://architecture/mpa/c1.mdx"
+        );
+        assert!(second["groups"].get("crm").is_none());
+
+        let error = grouped("size", 1, 50).await.expect_err("unknown field");
+        assert_eq!(error.message, "invalid_group_by");
     }
 
     #[tokio::test]
-    async fn test_read_file_by_path_success() {
-        // Create a temporary test file
-        let temp_dir = std::env::temp_dir();
-        let test_file = temp_dir.join("test_read_file.txt");
-        let test_content = "Test file content for reading";
-
-        std::fs::write(&test_file, test_content).expect("Failed to write test file");
-
-        // Create DocumentServer instance with a mock FileReader that can read our test file
+    async fn test_get_docs_list_sorts_by_type_priority() {
         let temp_dir = TempDir::new().expect("temp dir");
-        let docs_root = temp_dir.path().to_string_lossy().to_string();
-        let docs = DocumentServer::new_with_resources(
-            FileReader::new(docs_root).expect("file reader"),
-            BTreeMap::new(),
-        );
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                test_resource(
+                    "docs://agreements/backend/php/style.md",
+                    "",
+                    &["agreements"],
+                ),
+                test_resource(
+                    "docs://architecture/mpa/adr/002.mdx",
+                    "mpa",
+                    &["adr", "ADR-002"],
+                ),
+                test_resource(
+                    "docs://architecture/mpa/adr/001.mdx",
+                    "mpa",
+                    &["adr", "ADR-001"],
+                ),
+                test_resource("docs://architecture/mpa/c1.mdx", "mpa", &["c1"]),
+                test_resource("docs://architecture/mpa/c2.mdx", "mpa", &["c2"]),
+                test_resource("docs://guides/eva4/intro.md", "", &["guides"]),
+            ],
+        )
+        .with_options(ServerOptions {
+            type_priority: vec!["c2".to_string(), "adr".to_string(), "c1".to_string()],
+            ..Default::default()
+        });
+        let uris = |sort_by: Option<&str>| {
+            docs.list_documents(&GetDocsListArgs {
+                sort_by: sort_by.map(ToString::to_string),
+                ..Default::default()
+            })
+            .expect("listed")
+            .documents
+            .into_iter()
+            .map(|info| info.uri)
+            .collect::<Vec<_>>()
+        };
 
-        // Test reading the file (this will fail if the file doesn't exist in the docs root)
-        // We'll test the error case since we can't easily mock the FileReader
-        let result = docs.read_file_by_path("nonexistent_file.txt");
-        assert!(result.is_err());
+        assert_eq!(
+            uris(Some("priority")),
+            vec![
+                "docs://architecture/mpa/c2.mdx",
+                "docs://architecture/mpa/adr/001.mdx",
+                "docs://architecture/mpa/adr/002.mdx",
+                "docs://architecture/mpa/c1.mdx",
+                "docs://agreements/backend/php/style.md",
+                "docs://guides/eva4/intro.md",
+            ]
+        );
+        assert_eq!(uris(None), uris(Some("uri")));
+        assert_eq!(uris(None)[0], "docs://agreements/backend/php/style.md");
 
-        // Clean up
-        let _ = std::fs::remove_file(&test_file);
+        let error = docs
+            .list_documents(&GetDocsListArgs {
+                sort_by: Some("size".to_string()),
+                ..Default::default()
+            })
+            .expect_err("unknown order");
+        assert_eq!(error.message, "invalid_sort_by");
     }
 
     #[tokio::test]
-    async fn test_read_file_by_path_error_handling() {
+    async fn test_get_uri_map_round_trips_and_paginates() {
         let temp_dir = TempDir::new().expect("temp dir");
-        let docs_root = temp_dir.path().to_string_lossy().to_string();
-        let docs = DocumentServer::new_with_resources(
-            FileReader::new(docs_root).expect("file reader"),
-            BTreeMap::new(),
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                test_resource("docs://architecture/mpa/adr/001.mdx", "mpa", &["adr"]),
+                test_resource("docs://architecture/mpa/adr/002.mdx", "mpa", &["adr"]),
+                test_resource("docs://architecture/crm/adr/001.mdx", "crm", &["adr"]),
+            ],
         );
-        let result = docs.read_file_by_path("nonexistent_file.txt");
 
-        assert!(result.is_err());
-        if let Err(error) = result {
-            assert_eq!(error.code.0, -32603); // Internal error
-            assert!(error.data.is_some());
+        let map = docs
+            .get_uri_map(Parameters(GetUriMapArgs {
+                project: Some("mpa".to_string()),
+                page: None,
+                limit: None,
+            }))
+            .await
+            .expect("uri map");
+        let map = result_json(&map);
+        assert_eq!(map["total_entries"], 2);
+        let path_to_uri = map["path_to_uri"].as_object().expect("path_to_uri");
+        let uri_to_path = map["uri_to_path"].as_object().expect("uri_to_path");
+        assert_eq!(path_to_uri.len(), 2);
+        for (path, uri) in path_to_uri {
+            assert_eq!(uri_to_path[uri.as_str().expect("uri")], *path);
+            let resolved = docs
+                .resolve_path(Parameters(ResolvePathArgs {
+                    file_path: path.clone(),
+                }))
+                .await
+                .expect("resolvable");
+            assert_eq!(result_json(&resolved)["uri"], *uri);
         }
+
+        let second_page = docs
+            .get_uri_map(Parameters(GetUriMapArgs {
+                project: None,
+                page: Some(2),
+                limit: Some(2),
+            }))
+            .await
+            .expect("uri map page");
+        let second_page = result_json(&second_page);
+        assert_eq!(second_page["total_pages"], 2);
+        assert_eq!(
+            second_page["path_to_uri"]
+                .as_object()
+                .expect("path_to_uri")
+                .len(),
+            1
+        );
     }
 
-    #[test]
-    fn test_matches_filter_function() {
-        // Test with no filter (should match everything)
-        assert!(DocumentServer::matches_filter("any_value", None));
-
-        // Test with exact match
-        let exact = Some("exact".to_string());
-        assert!(DocumentServer::matches_filter("exact", exact.as_ref()));
-
-        // Test with OR logic
-        let or_filter = Some("value1|value2".to_string());
-        assert!(DocumentServer::matches_filter("value1", or_filter.as_ref()));
-        assert!(DocumentServer::matches_filter("value2", or_filter.as_ref()));
-
-        // Test with no match
-        assert!(!DocumentServer::matches_filter(
-            "nomatch",
-            or_filter.as_ref()
-        ));
+    #[tokio::test]
+    async fn test_tool_responses_carry_schema_version() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let docs = resolve_fixture(&temp_dir);
+
+        let list = docs
+            .get_docs_list(Parameters(GetDocsListArgs::default()))
+            .await
+            .expect("docs list");
+        let batch = docs
+            .get_metadata_batch(Parameters(GetMetadataBatchArgs { paths: Vec::new() }))
+            .await
+            .expect("metadata batch");
 
-        // Test with whitespace
-        let ws_filter = Some(" value1 | value2 ".to_string());
-        assert!(DocumentServer::matches_filter("value1", ws_filter.as_ref()));
+        for result in [list, batch] {
+            assert_eq!(
+                result_json(&result)["schema_version"],
+                json!(SCHEMA_VERSION)
+            );
+        }
     }
 
     #[test]
-    fn test_matches_category_filter_function() {
-        // Test with no filter (should match everything)
-        assert!(DocumentServer::matches_category_filter(
-            &["any_value".to_string()],
+    fn test_declared_schema_version_is_read_from_the_experimental_capability() {
+        let declaring = |declaration: serde_json::Value| ClientCapabilities {
+            experimental: Some(ExperimentalCapabilities::from([(
+                SCHEMA_CAPABILITY.to_string(),
+                declaration.as_object().cloned().expect("object"),
+            )])),
+            ..ClientCapabilities::default()
+        };
+        assert_eq!(
+            declared_schema_version(&declaring(json!({"version": 2}))),
+            Some(2)
+        );
+        assert_eq!(
+            declared_schema_version(&declaring(json!({"version": "2"}))),
             None
-        ));
+        );
+        assert_eq!(declared_schema_version(&declaring(json!({}))), None);
+        assert_eq!(
+            declared_schema_version(&ClientCapabilities::default()),
+            None
+        );
 
-        // Test with exact match
-        let exact_cat = Some("exact".to_string());
-        assert!(DocumentServer::matches_category_filter(
-            &["exact".to_string()],
-            exact_cat.as_ref()
-        ));
+        let docs = server_with(&TempDir::new().expect("temp dir"), Vec::new());
+        let advertised = docs
+            .get_info()
+            .capabilities
+            .experimental
+            .expect("experimental");
+        assert_eq!(
+            advertised[SCHEMA_CAPABILITY]["version"],
+            json!(SCHEMA_VERSION)
+        );
+    }
 
-        // Test with OR logic
-        let or_filter = Some("value1|value2".to_string());
-        assert!(DocumentServer::matches_category_filter(
-            &["value1".to_string()],
-            or_filter.as_ref()
-        ));
-        assert!(DocumentServer::matches_category_filter(
-            &["value2".to_string()],
-            or_filter.as_ref()
-        ));
+    /// (`SCHEMA_VERSION`, fingerprint of every response schema) per released shape. A change
+    /// to any response field changes the fingerprint and needs a new entry with a bumped
+    /// version.
+    const RESPONSE_SCHEMA_HISTORY: &[(u32, u64)] =
+        &[(2, 0x0462_80fc_1f57_ed1b), (3, 0x9190_96fa_278c_a6b0)];
 
-        // Test with multiple categories - should match if any category matches
-        assert!(DocumentServer::matches_category_filter(
-            &["value1".to_string(), "other".to_string()],
-            or_filter.as_ref()
-        ));
-        assert!(DocumentServer::matches_category_filter(
-            &["other".to_string(), "value2".to_string()],
-            or_filter.as_ref()
-        ));
+    macro_rules! response_schemas {
+        ($($response:ty),* $(,)?) => {
+            vec![$(
+                (stringify!($response), serde_json::to_value(schemars::schema_for!($response))
+                    .expect("schema")),
+            )*]
+        };
+    }
 
-        // Test with no match
-        assert!(!DocumentServer::matches_category_filter(
-            &["nomatch".to_string()],
-            or_filter.as_ref()
-        ));
+    /// FNV-1a, stable across Rust releases unlike `DefaultHasher`
+    fn fingerprint(bytes: &[u8]) -> u64 {
+        bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
 
-        // Test with whitespace
-        let ws_filter = Some(" value1 | value2 ".to_string());
-        assert!(DocumentServer::matches_category_filter(
-            &["value1".to_string()],
-            ws_filter.as_ref()
-        ));
+    #[test]
+    fn test_response_shape_changes_bump_schema_version() {
+        let schemas = response_schemas!(
+            SplitContentResponse,
+            NormalizedContentResponse,
+            MetadataBatchResponse,
+            ResolvePathResponse,
+            ResolveLinksResponse,
+            UriMapResponse,
+            AdrLintResponse,
+            UndocumentedResponse,
+            UnclassifiedResponse,
+            StaleDocumentsResponse,
+            ArchitectureGraphResponse,
+            ProjectDependenciesResponse,
+            EndpointsForTagResponse,
+            OpenApiSchemaResponse,
+            OpenApiVersionsDiffResponse,
+            ServiceVersionsResponse,
+            ErdToApiResponse,
+            RankDocumentsResponse,
+            MostReferencedResponse,
+            SearchIndexExportResponse,
+            ProjectGraphDotResponse,
+            UsageStatsResponse,
+            ParseFailuresResponse,
+            DocsListResponse,
+            GroupedDocsListResponse,
+            AdrListResponse,
+            ProjectReadmeResponse,
+            ScanPlanResponse,
+            ProjectManifestResponse,
+            AgreementsResponse,
+            AgreementsForProjectResponse,
+            AgreementsIndexResponse,
+            AgreementsDiffResponse,
+            GuidesResponse,
+            StyleGuidesResponse,
+            ProjectOverviewResponse<'static>,
+            ArchitectureOverviewResponse,
+            EffectiveExtensionsResponse,
+            ScanStatusResponse,
+            OrphanedDocumentsResponse,
+            PatternStatsResponse,
+            ServerHealthResponse,
+            MimeTypeFacetsResponse,
+            DocumentationCoverageResponse,
+        );
+        let current = fingerprint(serde_json::to_string(&schemas).expect("schemas").as_bytes());
 
-        // Test agreements category
-        let agreements = Some("agreements".to_string());
-        let api = Some("api".to_string());
-        assert!(DocumentServer::matches_category_filter(
-            &["agreements".to_string(), "api".to_string()],
-            agreements.as_ref()
-        ));
-        assert!(DocumentServer::matches_category_filter(
-            &["agreements".to_string(), "api".to_string()],
-            api.as_ref()
-        ));
+        assert!(
+            RESPONSE_SCHEMA_HISTORY
+                .windows(2)
+                .all(|pair| pair[0].0 < pair[1].0 && pair[0].1 != pair[1].1)
+        );
+        assert_eq!(
+            RESPONSE_SCHEMA_HISTORY.last(),
+            Some(&(SCHEMA_VERSION, current)),
+            "response schemas changed (fingerprint {:#x}): bump SCHEMA_VERSION and append \
+             ({}, {:#x}) to RESPONSE_SCHEMA_HISTORY",
+            current,
+            SCHEMA_VERSION + 1,
+            current
+        );
     }
 
-    #[tokio::test]
-    async fn test_get_all_adr_documents_tool_attributes() {
-        let router = DocumentServer::tool_router();
-        assert!(router.has_route("get_all_adr_documents"));
+    struct LogCollector(tokio::sync::mpsc::UnboundedSender<LoggingMessageNotificationParam>);
 
-        let tools = router.list_all();
-        assert!(tools.iter().any(|t| t.name == "get_all_adr_documents"));
+    impl ClientHandler for LogCollector {
+        async fn on_logging_message(
+            &self,
+            params: LoggingMessageNotificationParam,
+            _context: NotificationContext<RoleClient>,
+        ) {
+            let _ = self.0.send(params);
+        }
     }
 
-    #[tokio::test]
-    async fn test_get_project_overview_tool_attributes() {
-        let router = DocumentServer::tool_router();
-        assert!(router.has_route("get_project_overview"));
-
-        let tools = router.list_all();
-        assert!(tools.iter().any(|t| t.name == "get_project_overview"));
+    /// Serves `docs` to an in-process client subscribed to its logs at `level`.
+    async fn connect_log_client(
+        docs: DocumentServer,
+        level: LoggingLevel,
+    ) -> (
+        RunningService<RoleClient, LogCollector>,
+        tokio::sync::mpsc::UnboundedReceiver<LoggingMessageNotificationParam>,
+    ) {
+        let (server_io, client_io) = tokio::io::duplex(4096);
+        tokio::spawn(async move {
+            let running = docs.serve(server_io).await.expect("serve server");
+            let _ = running.waiting().await;
+        });
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let client = LogCollector(tx)
+            .serve(client_io)
+            .await
+            .expect("serve client");
+        client
+            .peer()
+            .set_level(SetLevelRequestParams { meta: None, level })
+            .await
+            .expect("set level");
+        (client, rx)
     }
 
-    #[tokio::test]
-    async fn test_get_project_overview_project_not_found() {
+    #[test]
+    fn test_get_info_advertises_logging() {
         let temp_dir = TempDir::new().expect("temp dir");
         let docs_root = temp_dir.path().to_string_lossy().to_string();
         let docs = DocumentServer::new_with_resources(
             FileReader::new(docs_root).expect("file reader"),
             BTreeMap::new(),
         );
-        let args = GetProjectOverviewArgs {
-            project: "nonexistent_project".to_string(),
-        };
-
-        let result = docs.get_project_overview(Parameters(args)).await;
-        assert!(result.is_err());
-
-        if let Err(error) = result {
-            assert_eq!(error.code.0, -32002);
-        }
+        assert!(docs.get_info().capabilities.logging.is_some());
     }
 
-    #[tokio::test]
-    async fn test_get_agreements_tool_attributes() {
-        let router = DocumentServer::tool_router();
-        assert!(router.has_route("get_agreements"));
+    #[test]
+    fn test_get_info_lists_indexed_projects() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let mut agreement = test_resource("docs://agreements/backend/go/style.md", "", &["style"]);
+        agreement.area = "backend".to_string();
+        agreement.lang = "go".to_string();
+        let docs = server_with(
+            &temp_dir,
+            vec![
+                test_resource("docs://architecture/mpa/c4/c1.mdx", "mpa", &["c1"]),
+                test_resource("docs://architecture/mpa/c4/c2.mdx", "mpa", &["c2"]),
+                test_resource("docs://architecture/crm/c4/c1.mdx", "crm", &["c1"]),
+                agreement,
+            ],
+        );
 
-        let tools = router.list_all();
-        assert!(tools.iter().any(|t| t.name == "get_agreements"));
+        let instructions = docs.get_info().instructions.expect("instructions");
+        assert!(instructions.starts_with(TOOL_OVERVIEW));
+        assert!(instructions.contains("crm (1), mpa (2)"));
+        assert!(instructions.contains("Languages: go."));
+
+        let docs = docs.with_options(ServerOptions {
+            instructions_max_chars: 30,
+            ..Default::default()
+        });
+        let instructions = docs.get_info().instructions.expect("instructions");
+        let catalog = &instructions[TOOL_OVERVIEW.len() + 1..];
+        assert_eq!(catalog.chars().count(), 30);
+        assert!(catalog.ends_with("..."));
     }
 
     #[tokio::test]
-    async fn test_get_agreements_success() {
+    async fn test_log_notifications_respect_session_level() {
+        let bridge = LogBridge::default();
+        let _guard = tracing_subscriber::registry()
+            .with(bridge.layer())
+            .set_default();
+
         let temp_dir = TempDir::new().expect("temp dir");
         let docs_root = temp_dir.path().to_string_lossy().to_string();
         let docs = DocumentServer::new_with_resources(
             FileReader::new(docs_root).expect("file reader"),
             BTreeMap::new(),
+        )
+        .with_log_bridge(bridge);
+
+        let (server_io, client_io) = tokio::io::duplex(4096);
+        tokio::spawn(async move {
+            let running = docs.serve(server_io).await.expect("serve server");
+            let _ = running.waiting().await;
+        });
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let client = LogCollector(tx)
+            .serve(client_io)
+            .await
+            .expect("serve client");
+        let set_level = |level| SetLevelRequestParams { meta: None, level };
+
+        client
+            .peer()
+            .set_level(set_level(LoggingLevel::Warning))
+            .await
+            .expect("set warning level");
+        tracing::info!(
+            tool = "get_resource_content",
+            uri = "docs://a.md",
+            "below level"
         );
-        let args = GetAgreementsArgs {
-            lang: "php".to_string(),
-        };
 
-        let result = docs.get_agreements(Parameters(args)).await;
-        // This will succeed even with empty results since we don't have agreements in test data
-        assert!(result.is_ok());
-    }
+        client
+            .peer()
+            .set_level(set_level(LoggingLevel::Info))
+            .await
+            .expect("set info level");
+        tracing::info!(
+            tool = "get_resource_content",
+            uri = "docs://b.md",
+            "above level"
+        );
 
-    #[tokio::test]
-    async fn test_get_guides_tool_attributes() {
-        let router = DocumentServer::tool_router();
-        assert!(router.has_route("get_guides"));
+        let message = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .expect("notification in time")
+            .expect("notification");
+        assert_eq!(message.level, LoggingLevel::Info);
+        assert_eq!(message.data["message"], "above level");
+        assert_eq!(message.data["tool"], "get_resource_content");
+        assert_eq!(message.data["uri"], "docs://b.md");
 
-        let tools = router.list_all();
-        assert!(tools.iter().any(|t| t.name == "get_guides"));
+        client.cancel().await.expect("cancel client");
     }
 
     #[tokio::test]
-    async fn test_get_guides_success() {
+    async fn test_log_levels_are_kept_per_session() {
+        let bridge = LogBridge::default();
+        let _guard = tracing_subscriber::registry()
+            .with(bridge.layer())
+            .set_default();
+
         let temp_dir = TempDir::new().expect("temp dir");
         let docs_root = temp_dir.path().to_string_lossy().to_string();
-        let docs = DocumentServer::new_with_resources(
+        let base = DocumentServer::new_with_resources(
             FileReader::new(docs_root).expect("file reader"),
             BTreeMap::new(),
         );
-        let args = GetGuidesArgs { product: None };
+        let connect =
+            |level| connect_log_client(base.clone().with_log_bridge(bridge.clone()), level);
+        let (quiet, mut quiet_rx) = connect(LoggingLevel::Warning).await;
+        let (verbose, mut verbose_rx) = connect(LoggingLevel::Info).await;
 
-        let result = docs.get_guides(Parameters(args)).await;
-        assert!(result.is_ok());
+        tracing::info!(tool = "get_resource_content", "info event");
+        tracing::warn!(tool = "get_resource_content", "warning event");
+
+        let timeout = std::time::Duration::from_secs(5);
+        let message = tokio::time::timeout(timeout, verbose_rx.recv())
+            .await
+            .expect("notification in time")
+            .expect("notification");
+        assert_eq!(message.data["message"], "info event");
+        let message = tokio::time::timeout(timeout, quiet_rx.recv())
+            .await
+            .expect("notification in time")
+            .expect("notification");
+        assert_eq!(message.level, LoggingLevel::Warning);
+        assert_eq!(message.data["message"], "warning event");
+
+        quiet.cancel().await.expect("cancel client");
+        verbose.cancel().await.expect("cancel client");
+    }
+
+    #[tokio::test]
+    async fn test_request_logs_reach_only_the_requesting_session() {
+        let bridge = LogBridge::default();
+        let _guard = tracing_subscriber::registry()
+            .with(bridge.layer())
+            .set_default();
+
+        let temp_dir = TempDir::new().expect("temp dir");
+        std::fs::create_dir_all(temp_dir.path().join("misc")).expect("dir");
+        std::fs::write(temp_dir.path().join("misc/a.md"), "# A\n").expect("write");
+        let base = server_with(&temp_dir, vec![test_resource("docs://misc/a.md", "", &[])]);
+        let connect =
+            |level| connect_log_client(base.clone().with_log_bridge(bridge.clone()), level);
+        let (reader, mut reader_rx) = connect(LoggingLevel::Debug).await;
+        let (other, mut other_rx) = connect(LoggingLevel::Debug).await;
+
+        reader
+            .peer()
+            .call_tool(CallToolRequestParams {
+                meta: None,
+                name: "get_resource_content".into(),
+                arguments: json!({"path": "docs://misc/a.md"}).as_object().cloned(),
+                task: None,
+            })
+            .await
+            .expect("read");
+        tracing::warn!("unscoped event");
+
+        let timeout = std::time::Duration::from_secs(5);
+        let message = tokio::time::timeout(timeout, reader_rx.recv())
+            .await
+            .expect("notification in time")
+            .expect("notification");
+        assert_eq!(message.data["tool"], "get_resource_content");
+        assert_eq!(message.data["uri"], "docs://misc/a.md");
+        let message = tokio::time::timeout(timeout, other_rx.recv())
+            .await
+            .expect("notification in time")
+            .expect("notification");
+        assert_eq!(message.data["message"], "unscoped event");
+
+        reader.cancel().await.expect("cancel client");
+        other.cancel().await.expect("cancel client");
     }
 }
@@ -0,0 +1,281 @@
+use std::{collections::HashMap, fmt, sync::Arc, time::Duration};
+
+use futures::Stream;
+use rmcp::{
+    model::{ClientJsonRpcMessage, ServerJsonRpcMessage},
+    transport::streamable_http_server::session::{
+        ServerSseMessage, SessionId, SessionManager,
+        local::{LocalSessionManager, SessionConfig},
+    },
+};
+use tokio::sync::Mutex;
+
+/// Inactivity after which a session is closed while sessions are capped, so clients that
+/// disappear without `DELETE` give their slot back.
+const IDLE_SESSION_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Time a new session has to complete its `initialize` request before its slot is freed.
+const INITIALIZE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Session manager for the HTTP transport; idle sessions expire only when `max_sessions`
+/// caps them.
+pub fn local_session_manager(max_sessions: Option<usize>) -> LocalSessionManager {
+    LocalSessionManager {
+        sessions: Default::default(),
+        session_config: SessionConfig {
+            keep_alive: max_sessions.map(|_| IDLE_SESSION_TIMEOUT),
+            ..Default::default()
+        },
+    }
+}
+
+/// Session manager capping concurrent MCP sessions. Sessions count from creation until they
+/// are closed, which the HTTP service does on `DELETE` and when a session's connection ends.
+/// Sessions whose `initialize` fails or never arrives are closed here.
+pub struct SessionLimit<M> {
+    inner: Arc<M>,
+    max_sessions: Option<usize>,
+    initialize_timeout: Duration,
+    /// Sessions created and not yet closed, and whether they initialized; the lock also
+    /// serializes creating and closing
+    active: Arc<Mutex<HashMap<SessionId, bool>>>,
+}
+
+impl<M> SessionLimit<M> {
+    /// Unlimited when `max_sessions` is None.
+    pub fn new(inner: Arc<M>, max_sessions: Option<usize>) -> Self {
+        Self {
+            inner,
+            max_sessions,
+            initialize_timeout: INITIALIZE_TIMEOUT,
+            active: Arc::default(),
+        }
+    }
+
+    #[cfg(test)]
+    fn with_initialize_timeout(mut self, initialize_timeout: Duration) -> Self {
+        self.initialize_timeout = initialize_timeout;
+        self
+    }
+}
+
+impl<M: SessionManager> SessionLimit<M> {
+    /// Frees the slot of a session that is still counted and closes it in the inner manager;
+    /// with `only_uninitialized`, sessions that completed `initialize` are left open.
+    async fn release(
+        inner: &M,
+        active: &Mutex<HashMap<SessionId, bool>>,
+        id: &SessionId,
+        only_uninitialized: bool,
+    ) -> Result<(), M::Error> {
+        {
+            let mut active = active.lock().await;
+            match active.get(id) {
+                Some(&initialized) if !(only_uninitialized && initialized) => {
+                    active.remove(id);
+                }
+                _ => return Ok(()),
+            }
+        }
+        inner.close_session(id).await
+    }
+}
+
+#[derive(Debug)]
+pub enum SessionLimitError<E> {
+    LimitReached(usize),
+    Session(E),
+}
+
+impl<E: fmt::Display> fmt::Display for SessionLimitError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LimitReached(max) => write!(
+                f,
+                "session limit reached: the server allows {} concurrent sessions; close an \
+                 unused session or retry later",
+                max
+            ),
+            Self::Session(error) => error.fmt(f),
+        }
+    }
+}
+
+impl<E: std::error::Error> std::error::Error for SessionLimitError<E> {}
+
+impl<M: SessionManager> SessionManager for SessionLimit<M> {
+    type Error = SessionLimitError<M::Error>;
+    type Transport = M::Transport;
+
+    async fn create_session(&self) -> Result<(SessionId, Self::Transport), Self::Error> {
+        let mut active = self.active.lock().await;
+        if let Some(max) = self.max_sessions
+            && active.len() >= max
+        {
+            tracing::warn!(max_sessions = max, "Rejecting new session: limit reached");
+            return Err(SessionLimitError::LimitReached(max));
+        }
+        let (id, transport) = self
+            .inner
+            .create_session()
+            .await
+            .map_err(SessionLimitError::Session)?;
+        active.insert(id.clone(), false);
+        tokio::spawn({
+            let inner = self.inner.clone();
+            let active = self.active.clone();
+            let id = id.clone();
+            let initialize_timeout = self.initialize_timeout;
+            async move {
+                tokio::time::sleep(initialize_timeout).await;
+                if let Err(error) = Self::release(&inner, &active, &id, true).await {
+                    tracing::warn!(session = %id, "Failed to close uninitialized session: {}", error);
+                }
+            }
+        });
+        Ok((id, transport))
+    }
+
+    async fn initialize_session(
+        &self,
+        id: &SessionId,
+        message: ClientJsonRpcMessage,
+    ) -> Result<ServerJsonRpcMessage, Self::Error> {
+        match self.inner.initialize_session(id, message).await {
+            Ok(response) => {
+                if let Some(initialized) = self.active.lock().await.get_mut(id) {
+                    *initialized = true;
+                }
+                Ok(response)
+            }
+            Err(error) => {
+                if let Err(close_error) = Self::release(&self.inner, &self.active, id, false).await
+                {
+                    tracing::warn!(session = %id, "Failed to close session: {}", close_error);
+                }
+                Err(SessionLimitError::Session(error))
+            }
+        }
+    }
+
+    async fn has_session(&self, id: &SessionId) -> Result<bool, Self::Error> {
+        self.inner
+            .has_session(id)
+            .await
+            .map_err(SessionLimitError::Session)
+    }
+
+    async fn close_session(&self, id: &SessionId) -> Result<(), Self::Error> {
+        // Closing runs on DELETE and again when the session task ends; count it once.
+        Self::release(&self.inner, &self.active, id, false)
+            .await
+            .map_err(SessionLimitError::Session)
+    }
+
+    async fn create_stream(
+        &self,
+        id: &SessionId,
+        message: ClientJsonRpcMessage,
+    ) -> Result<impl Stream<Item = ServerSseMessage> + Send + Sync + 'static, Self::Error> {
+        self.inner
+            .create_stream(id, message)
+            .await
+            .map_err(SessionLimitError::Session)
+    }
+
+    async fn accept_message(
+        &self,
+        id: &SessionId,
+        message: ClientJsonRpcMessage,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .accept_message(id, message)
+            .await
+            .map_err(SessionLimitError::Session)
+    }
+
+    async fn create_standalone_stream(
+        &self,
+        id: &SessionId,
+    ) -> Result<impl Stream<Item = ServerSseMessage> + Send + Sync + 'static, Self::Error> {
+        self.inner
+            .create_standalone_stream(id)
+            .await
+            .map_err(SessionLimitError::Session)
+    }
+
+    async fn resume(
+        &self,
+        id: &SessionId,
+        last_event_id: String,
+    ) -> Result<impl Stream<Item = ServerSseMessage> + Send + Sync + 'static, Self::Error> {
+        self.inner
+            .resume(id, last_event_id)
+            .await
+            .map_err(SessionLimitError::Session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sessions_beyond_the_limit_are_rejected_until_one_closes() {
+        let sessions = SessionLimit::new(Arc::new(LocalSessionManager::default()), Some(2));
+        let (first, _first_transport) = sessions.create_session().await.expect("first");
+        let (_second, _second_transport) = sessions.create_session().await.expect("second");
+
+        let Err(error) = sessions.create_session().await else {
+            panic!("session over the limit was created");
+        };
+        assert!(matches!(error, SessionLimitError::LimitReached(2)));
+        assert!(error.to_string().contains("allows 2 concurrent sessions"));
+
+        sessions.close_session(&first).await.expect("close");
+        sessions.close_session(&first).await.expect("close twice");
+        let (_third, _third_transport) = sessions.create_session().await.expect("freed slot");
+        assert!(sessions.create_session().await.is_err());
+
+        let unlimited = SessionLimit::new(Arc::new(LocalSessionManager::default()), None);
+        for _ in 0..3 {
+            unlimited.create_session().await.expect("unlimited");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sessions_that_fail_or_skip_initialize_free_their_slot() {
+        let inner = Arc::new(local_session_manager(Some(1)));
+        let sessions = SessionLimit::new(inner.clone(), Some(1))
+            .with_initialize_timeout(Duration::from_millis(100));
+        let ping: ClientJsonRpcMessage = serde_json::from_value(
+            serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "ping"}),
+        )
+        .expect("message");
+
+        let (failed, _transport) = sessions.create_session().await.expect("first");
+        inner.close_session(&failed).await.expect("close inner");
+        assert!(sessions.initialize_session(&failed, ping).await.is_err());
+        let (_abandoned, _transport) = sessions.create_session().await.expect("freed slot");
+        assert!(sessions.create_session().await.is_err());
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while sessions.create_session().await.is_err() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("uninitialized session released");
+    }
+
+    #[test]
+    fn test_idle_sessions_expire_only_when_capped() {
+        assert_eq!(
+            local_session_manager(Some(4)).session_config.keep_alive,
+            Some(IDLE_SESSION_TIMEOUT)
+        );
+        assert_eq!(local_session_manager(None).session_config.keep_alive, None);
+    }
+}
@@ -0,0 +1,583 @@
+use std::collections::BTreeMap;
+
+use crate::models::{DocumentKey, ResourceInfo};
+
+/// Areas that say nothing about where a document belongs: none at all, or the document type
+/// name the scanner falls back to for agreements outside backend/frontend/... folders.
+const GENERIC_AREAS: &[&str] = &["", "agreements", "docs"];
+
+/// Language of documents that apply to every language (see `agreements_defaults`); they
+/// match any language filter
+pub const ALL_LANGUAGES: &str = "all";
+
+/// Scheme of the semantic OpenAPI addresses resolved by `specs_at_openapi_uri`
+pub const OPENAPI_URI_SCHEME: &str = "openapi://";
+
+/// Metadata filters combined with AND; each value may list alternatives separated by `|`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FilterSet<'a> {
+    pub area: Option<&'a str>,
+    /// Matches any of a document's languages
+    pub lang: Option<&'a str>,
+    /// Matches any of a document's categories
+    pub category: Option<&'a str>,
+    pub project: Option<&'a str>,
+    /// Drop specs that failed validation
+    pub only_valid: bool,
+    /// Keep only specs that failed validation
+    pub only_invalid: bool,
+    /// Keep only documents with a `token_estimate` of at most this
+    pub max_tokens: Option<u32>,
+}
+
+impl FilterSet<'_> {
+    pub fn matches(&self, info: &ResourceInfo) -> bool {
+        let is_invalid = info.valid == Some(false);
+        matches_filter(&info.area, self.area)
+            && matches_lang_filter(info, self.lang)
+            && matches_category_filter(&info.category, self.category)
+            && matches_filter(&info.project, self.project)
+            && !(self.only_valid && is_invalid || self.only_invalid && !is_invalid)
+            && self
+                .max_tokens
+                .is_none_or(|max| info.token_estimate.is_some_and(|tokens| tokens <= max))
+    }
+}
+
+/// Checks if a value matches any of the filter values (supports OR with | separator)
+pub fn matches_filter(value: &str, filter: Option<&str>) -> bool {
+    match filter {
+        None => true,
+        Some(filter_str) => filter_str
+            .split('|')
+            .any(|filter_value| filter_value.trim() == value),
+    }
+}
+
+/// Checks if any of a document's languages matches any of the filter values (supports OR with
+/// | separator); documents in `ALL_LANGUAGES` match every filter
+pub fn matches_lang_filter(info: &ResourceInfo, filter: Option<&str>) -> bool {
+    filter.is_none()
+        || info
+            .languages()
+            .any(|lang| lang == ALL_LANGUAGES || matches_filter(lang, filter))
+}
+
+/// Checks if any of `values` matches any of the filter values (supports OR with | separator)
+pub fn matches_category_filter(values: &[String], filter: Option<&str>) -> bool {
+    match filter {
+        None => true,
+        Some(filter_str) => {
+            let filter_values: Vec<&str> = filter_str.split('|').map(str::trim).collect();
+            values.iter().any(|value| {
+                filter_values
+                    .iter()
+                    .any(|filter_value| filter_value == value)
+            })
+        }
+    }
+}
+
+/// Indexed documents keyed by URI, with the secondary indexes and typed queries tools share.
+#[derive(Debug, Clone, Default)]
+pub struct ResourceStore {
+    resources: BTreeMap<DocumentKey, ResourceInfo>,
+    /// Reverse index: file path relative to docs root -> resource key
+    file_path_index: BTreeMap<String, DocumentKey>,
+    /// Lowercased URI -> resource keys, for case-insensitive lookups
+    folded_uri_index: BTreeMap<String, Vec<DocumentKey>>,
+}
+
+impl ResourceStore {
+    pub fn new(resources: BTreeMap<DocumentKey, ResourceInfo>) -> Self {
+        let file_path_index = resources
+            .iter()
+            .map(|(key, info)| (info.file_path.clone(), key.clone()))
+            .collect();
+        let mut folded_uri_index: BTreeMap<String, Vec<DocumentKey>> = BTreeMap::new();
+        for (key, info) in &resources {
+            folded_uri_index
+                .entry(info.uri.to_lowercase())
+                .or_default()
+                .push(key.clone());
+        }
+        Self {
+            resources,
+            file_path_index,
+            folded_uri_index,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// Swaps in new metadata for the document with `info.uri`. URI and file path stay the
+    /// same, so the secondary indexes stay valid. Returns false when no such document exists.
+    pub fn replace(&mut self, info: ResourceInfo) -> bool {
+        match self.resources.get_mut(&DocumentKey::new(info.uri.clone())) {
+            Some(current) if current.file_path == info.file_path => {
+                *current = info;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Document with exactly this URI
+    pub fn get(&self, uri: &str) -> Option<&ResourceInfo> {
+        self.resources.get(&DocumentKey::new(uri.to_string()))
+    }
+
+    /// Document at this file path relative to the docs root
+    pub fn by_file_path(&self, file_path: &str) -> Option<&ResourceInfo> {
+        self.file_path_index
+            .get(file_path)
+            .and_then(|key| self.resources.get(key))
+    }
+
+    /// (file path, document) pairs in file path order
+    pub fn file_paths(&self) -> impl Iterator<Item = (&str, &ResourceInfo)> {
+        self.file_path_index
+            .iter()
+            .filter_map(|(file_path, key)| Some((file_path.as_str(), self.resources.get(key)?)))
+    }
+
+    /// Documents whose URI equals `uri` ignoring case
+    pub fn by_uri_ignoring_case(&self, uri: &str) -> Vec<&ResourceInfo> {
+        self.folded_uri_index
+            .get(&uri.to_lowercase())
+            .into_iter()
+            .flatten()
+            .filter_map(|key| self.resources.get(key))
+            .collect()
+    }
+
+    /// All documents in URI order
+    pub fn iter(&self) -> impl Iterator<Item = &ResourceInfo> {
+        self.resources.values()
+    }
+
+    pub fn by_project<'s>(&'s self, project: &str) -> impl Iterator<Item = &'s ResourceInfo> {
+        self.iter().filter(move |info| info.project == project)
+    }
+
+    pub fn by_category<'s>(&'s self, category: &str) -> impl Iterator<Item = &'s ResourceInfo> {
+        self.iter()
+            .filter(move |info| info.category.iter().any(|c| c == category))
+    }
+
+    pub fn adrs(&self) -> impl Iterator<Item = &ResourceInfo> {
+        self.by_category("adr")
+    }
+
+    /// OpenAPI specs (documents whose first category is "openapi")
+    pub fn specs(&self) -> impl Iterator<Item = &ResourceInfo> {
+        self.iter()
+            .filter(|info| info.category.first().is_some_and(|c| c == "openapi"))
+    }
+
+    /// Specs addressed by an `openapi://{project}/{service}/{version}/{access}/{endpoint}` URI.
+    /// `endpoint` is the spec's `[sub_category/]file name` below the access level; without
+    /// an exact match it may omit the extension or the sub-category. Several results mean the
+    /// endpoint is ambiguous; none when nothing matches or `uri` is not an openapi:// URI.
+    pub fn specs_at_openapi_uri(&self, uri: &str) -> Vec<&ResourceInfo> {
+        let Some(address) = uri.strip_prefix(OPENAPI_URI_SCHEME) else {
+            return Vec::new();
+        };
+        let segments: Vec<&str> = address.split('/').filter(|s| !s.is_empty()).collect();
+        let [project, service, version, access_level, endpoint @ ..] = segments.as_slice() else {
+            return Vec::new();
+        };
+        let endpoint = endpoint.join("/");
+        let candidates: Vec<(&ResourceInfo, String)> = self
+            .specs()
+            .filter(|info| {
+                info.project == *project
+                    && info
+                        .category
+                        .get(1..4)
+                        .is_some_and(|c| c == [*service, *version, *access_level])
+            })
+            .map(|info| {
+                let filename = info.file_path.rsplit('/').next().unwrap_or_default();
+                let path = match info.category.get(4) {
+                    Some(sub_category) => format!("{}/{}", sub_category, filename),
+                    None => filename.to_string(),
+                };
+                (info, path)
+            })
+            .collect();
+
+        let exact: Vec<&ResourceInfo> = candidates
+            .iter()
+            .filter(|(_, path)| *path == endpoint)
+            .map(|(info, _)| *info)
+            .collect();
+        if !exact.is_empty() {
+            return exact;
+        }
+        candidates
+            .iter()
+            .filter(|(_, path)| {
+                let stem = path
+                    .rsplit_once('.')
+                    .map_or(path.as_str(), |(stem, _)| stem);
+                stem == endpoint || stem.rsplit('/').next() == Some(endpoint.as_str())
+            })
+            .map(|(info, _)| *info)
+            .collect()
+    }
+
+    /// Agreement documents, limited to those applying to `lang` when given
+    pub fn agreements<'s>(&'s self, lang: Option<&str>) -> impl Iterator<Item = &'s ResourceInfo> {
+        self.by_category("agreements")
+            .filter(move |info| matches_lang_filter(info, lang))
+    }
+
+    /// Agreements scoped to `project` via `applies_to`, and global ones (no `applies_to`).
+    pub fn agreements_for_project<'s>(
+        &'s self,
+        project: &'s str,
+    ) -> impl Iterator<Item = &'s ResourceInfo> {
+        self.by_category("agreements").filter(move |info| {
+            info.applies_to.is_empty() || info.applies_to.iter().any(|p| p == project)
+        })
+    }
+
+    /// Documents with neither a project nor a specific area, often misfiled.
+    pub fn unclassified(&self) -> impl Iterator<Item = &ResourceInfo> {
+        self.iter()
+            .filter(|info| info.project.is_empty() && GENERIC_AREAS.contains(&info.area.as_str()))
+    }
+
+    pub fn iter_filtered<'s>(
+        &'s self,
+        filters: FilterSet<'_>,
+    ) -> impl Iterator<Item = &'s ResourceInfo> {
+        self.iter().filter(move |info| filters.matches(info))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource(uri: &str, project: &str, category: &[&str], lang: &str) -> ResourceInfo {
+        ResourceInfo {
+            uri: uri.to_string(),
+            file_path: uri.trim_start_matches("docs://").to_string(),
+            area: "architecture".to_string(),
+            lang: lang.to_string(),
+            category: category.iter().map(ToString::to_string).collect(),
+            project: project.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn store() -> ResourceStore {
+        let mut shared = resource(
+            "docs://agreements/backend/shared/errors.md",
+            "",
+            &["agreements"],
+            "go",
+        );
+        shared.langs = vec!["go".to_string(), "php".to_string()];
+        let mut broken = resource(
+            "docs://openapi/mpa/billing/v1/public/api.yaml",
+            "mpa",
+            &["openapi", "billing"],
+            "",
+        );
+        broken.valid = Some(false);
+        let resources = [
+            resource(
+                "docs://architecture/mpa/adr/001.mdx",
+                "mpa",
+                &["adr", "ADR-001"],
+                "",
+            ),
+            resource(
+                "docs://architecture/crm/adr/002.mdx",
+                "crm",
+                &["adr", "ADR-002"],
+                "",
+            ),
+            resource(
+                "docs://agreements/backend/php/style.md",
+                "",
+                &["agreements"],
+                "php",
+            ),
+            shared,
+            broken,
+        ];
+        ResourceStore::new(
+            resources
+                .into_iter()
+                .map(|info| (DocumentKey::new(info.uri.clone()), info))
+                .collect(),
+        )
+    }
+
+    fn uris<'a>(infos: impl Iterator<Item = &'a ResourceInfo>) -> Vec<&'a str> {
+        infos.map(|info| info.uri.as_str()).collect()
+    }
+
+    #[test]
+    fn typed_queries_select_by_project_category_and_language() {
+        let store = store();
+
+        assert_eq!(store.len(), 5);
+        assert_eq!(
+            uris(store.by_project("mpa")),
+            vec![
+                "docs://architecture/mpa/adr/001.mdx",
+                "docs://openapi/mpa/billing/v1/public/api.yaml"
+            ]
+        );
+        assert_eq!(uris(store.adrs()).len(), 2);
+        assert_eq!(
+            uris(store.specs()),
+            vec!["docs://openapi/mpa/billing/v1/public/api.yaml"]
+        );
+        assert_eq!(
+            uris(store.agreements(Some("go"))),
+            vec!["docs://agreements/backend/shared/errors.md"]
+        );
+        assert_eq!(uris(store.agreements(Some("php"))).len(), 2);
+        assert_eq!(uris(store.agreements(None)).len(), 2);
+    }
+
+    #[test]
+    fn agreements_for_all_languages_match_any_language_filter() {
+        let mut qa = resource(
+            "docs://agreements/quality-assurance/testing/e2e.md",
+            "",
+            &["agreements", "testing"],
+            ALL_LANGUAGES,
+        );
+        qa.langs = vec![ALL_LANGUAGES.to_string()];
+        let store = ResourceStore::new(BTreeMap::from([(DocumentKey::new(qa.uri.clone()), qa)]));
+
+        for lang in ["php", "go", "kotlin|swift"] {
+            assert_eq!(
+                uris(store.agreements(Some(lang))),
+                vec!["docs://agreements/quality-assurance/testing/e2e.md"],
+                "{lang}"
+            );
+            let filters = FilterSet {
+                lang: Some(lang),
+                ..Default::default()
+            };
+            assert_eq!(uris(store.iter_filtered(filters)).len(), 1, "{lang}");
+        }
+        assert_eq!(uris(store.agreements(None)).len(), 1);
+    }
+
+    #[test]
+    fn lookups_by_uri_file_path_and_folded_uri() {
+        let store = store();
+
+        assert!(store.get("docs://architecture/mpa/adr/001.mdx").is_some());
+        assert!(store.get("docs://architecture/MPA/adr/001.mdx").is_none());
+        assert_eq!(
+            uris(
+                store
+                    .by_uri_ignoring_case("docs://architecture/MPA/adr/001.mdx")
+                    .into_iter()
+            ),
+            vec!["docs://architecture/mpa/adr/001.mdx"]
+        );
+        assert_eq!(
+            store
+                .by_file_path("architecture/crm/adr/002.mdx")
+                .map(|info| info.uri.as_str()),
+            Some("docs://architecture/crm/adr/002.mdx")
+        );
+        assert_eq!(
+            store.file_paths().next().map(|(path, _)| path),
+            Some("agreements/backend/php/style.md")
+        );
+    }
+
+    #[test]
+    fn openapi_uris_resolve_exactly_or_report_ambiguity() {
+        let spec = |path: &str, category: &[&str]| {
+            resource(
+                &format!("docs://openapi/mpa/billing/v1/{}", path),
+                "mpa",
+                category,
+                "",
+            )
+        };
+        let public = ["openapi", "billing", "v1", "public"];
+        let resources = [
+            spec("public/api.yaml", &public),
+            spec("public/refunds.yaml", &public),
+            spec(
+                "public/invoices/endpoints/api.yaml",
+                &["openapi", "billing", "v1", "public", "invoices"],
+            ),
+            spec(
+                "internal/api.yaml",
+                &["openapi", "billing", "v1", "internal"],
+            ),
+        ];
+        let store = ResourceStore::new(
+            resources
+                .into_iter()
+                .map(|info| (DocumentKey::new(info.uri.clone()), info))
+                .collect(),
+        );
+        let resolve = |uri: &str| uris(store.specs_at_openapi_uri(uri).into_iter());
+
+        assert_eq!(
+            resolve("openapi://mpa/billing/v1/public/api.yaml"),
+            vec!["docs://openapi/mpa/billing/v1/public/api.yaml"]
+        );
+        assert_eq!(
+            resolve("openapi://mpa/billing/v1/public/invoices/api"),
+            vec!["docs://openapi/mpa/billing/v1/public/invoices/endpoints/api.yaml"]
+        );
+        assert_eq!(
+            resolve("openapi://mpa/billing/v1/public/refunds"),
+            vec!["docs://openapi/mpa/billing/v1/public/refunds.yaml"]
+        );
+        assert_eq!(resolve("openapi://mpa/billing/v1/public/api").len(), 2);
+        assert!(resolve("openapi://mpa/billing/v2/public/api").is_empty());
+        assert!(resolve("docs://openapi/mpa/billing/v1/public/api.yaml").is_empty());
+    }
+
+    #[test]
+    fn filter_set_combines_alternatives_and_validity() {
+        let store = store();
+
+        let filters = FilterSet {
+            project: Some("mpa | crm"),
+            category: Some("adr"),
+            ..Default::default()
+        };
+        assert_eq!(uris(store.iter_filtered(filters)).len(), 2);
+
+        let filters = FilterSet {
+            lang: Some("php"),
+            ..Default::default()
+        };
+        assert_eq!(uris(store.iter_filtered(filters)).len(), 2);
+
+        let filters = FilterSet {
+            only_invalid: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            uris(store.iter_filtered(filters)),
+            vec!["docs://openapi/mpa/billing/v1/public/api.yaml"]
+        );
+        let filters = FilterSet {
+            only_valid: true,
+            ..Default::default()
+        };
+        assert_eq!(uris(store.iter_filtered(filters)).len(), 4);
+    }
+
+    #[test]
+    fn max_tokens_keeps_documents_with_a_small_enough_estimate() {
+        let mut sized = store().iter().cloned().collect::<Vec<_>>();
+        for (info, tokens) in sized.iter_mut().zip([Some(100), Some(2_000), None]) {
+            info.token_estimate = tokens;
+        }
+        let sized = ResourceStore::new(
+            sized
+                .into_iter()
+                .map(|info| (DocumentKey::new(info.uri.clone()), info))
+                .collect(),
+        );
+        let filters = FilterSet {
+            max_tokens: Some(1_000),
+            ..Default::default()
+        };
+        assert_eq!(uris(sized.iter_filtered(filters)).len(), 1);
+    }
+
+    #[test]
+    fn test_matches_filter_function() {
+        // Test with no filter (should match everything)
+        assert!(matches_filter("any_value", None));
+
+        // Test with exact match
+        let exact = Some("exact".to_string());
+        assert!(matches_filter("exact", exact.as_deref()));
+
+        // Test with OR logic
+        let or_filter = Some("value1|value2".to_string());
+        assert!(matches_filter("value1", or_filter.as_deref()));
+        assert!(matches_filter("value2", or_filter.as_deref()));
+
+        // Test with no match
+        assert!(!matches_filter("nomatch", or_filter.as_deref()));
+
+        // Test with whitespace
+        let ws_filter = Some(" value1 | value2 ".to_string());
+        assert!(matches_filter("value1", ws_filter.as_deref()));
+    }
+
+    #[test]
+    fn test_matches_category_filter_function() {
+        // Test with no filter (should match everything)
+        assert!(matches_category_filter(&["any_value".to_string()], None));
+
+        // Test with exact match
+        let exact_cat = Some("exact".to_string());
+        assert!(matches_category_filter(
+            &["exact".to_string()],
+            exact_cat.as_deref()
+        ));
+
+        // Test with OR logic
+        let or_filter = Some("value1|value2".to_string());
+        assert!(matches_category_filter(
+            &["value1".to_string()],
+            or_filter.as_deref()
+        ));
+        assert!(matches_category_filter(
+            &["value2".to_string()],
+            or_filter.as_deref()
+        ));
+
+        // Test with multiple categories - should match if any category matches
+        assert!(matches_category_filter(
+            &["value1".to_string(), "other".to_string()],
+            or_filter.as_deref()
+        ));
+        assert!(matches_category_filter(
+            &["other".to_string(), "value2".to_string()],
+            or_filter.as_deref()
+        ));
+
+        // Test with no match
+        assert!(!matches_category_filter(
+            &["nomatch".to_string()],
+            or_filter.as_deref()
+        ));
+
+        // Test with whitespace
+        let ws_filter = Some(" value1 | value2 ".to_string());
+        assert!(matches_category_filter(
+            &["value1".to_string()],
+            ws_filter.as_deref()
+        ));
+
+        // Test agreements category
+        let agreements = Some("agreements".to_string());
+        let api = Some("api".to_string());
+        assert!(matches_category_filter(
+            &["agreements".to_string(), "api".to_string()],
+            agreements.as_deref()
+        ));
+        assert!(matches_category_filter(
+            &["agreements".to_string(), "api".to_string()],
+            api.as_deref()
+        ));
+    }
+}
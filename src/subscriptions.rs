@@ -0,0 +1,127 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// Source of keys for sessions that arrive without an `Mcp-Session-Id` header.
+static UNNAMED_SESSIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Resource subscriptions of all MCP sessions: session id -> subscribed URIs. Clones share
+/// the registry, so it survives the server being rebuilt by a config reload.
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionRegistry {
+    sessions: Arc<Mutex<BTreeMap<String, BTreeSet<String>>>>,
+}
+
+impl SubscriptionRegistry {
+    /// Handle for one session. Once its last clone is dropped, i.e. the session ended, the
+    /// subscriptions made through it are removed.
+    pub fn session(&self) -> Arc<SessionSubscriptions> {
+        Arc::new(SessionSubscriptions {
+            registry: self.clone(),
+            unnamed_id: format!(
+                "unnamed-{}",
+                UNNAMED_SESSIONS.fetch_add(1, Ordering::Relaxed)
+            ),
+            session_ids: Mutex::default(),
+        })
+    }
+
+    /// Subscribed URIs summed over all sessions
+    pub fn active_subscriptions(&self) -> usize {
+        self.sessions
+            .lock()
+            .map(|sessions| sessions.values().map(BTreeSet::len).sum())
+            .unwrap_or_default()
+    }
+
+    fn remove_session(&self, session_id: &str) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.remove(session_id);
+        }
+    }
+}
+
+/// One session's view of the registry.
+#[derive(Debug)]
+pub struct SessionSubscriptions {
+    registry: SubscriptionRegistry,
+    /// Key used when a request carries no session id (e.g. stdio or tests)
+    unnamed_id: String,
+    /// Session ids this handle subscribed under, cleared on drop
+    session_ids: Mutex<BTreeSet<String>>,
+}
+
+impl SessionSubscriptions {
+    /// Records `uri` for the session; returns false when it was already subscribed.
+    pub fn subscribe(&self, session_id: Option<&str>, uri: &str) -> bool {
+        let session_id = session_id.unwrap_or(&self.unnamed_id);
+        if let Ok(mut ids) = self.session_ids.lock() {
+            ids.insert(session_id.to_string());
+        }
+        self.registry.sessions.lock().is_ok_and(|mut sessions| {
+            sessions
+                .entry(session_id.to_string())
+                .or_default()
+                .insert(uri.to_string())
+        })
+    }
+
+    /// Forgets `uri` for the session; returns false when it was not subscribed.
+    pub fn unsubscribe(&self, session_id: Option<&str>, uri: &str) -> bool {
+        let session_id = session_id.unwrap_or(&self.unnamed_id);
+        self.registry.sessions.lock().is_ok_and(|mut sessions| {
+            let Some(uris) = sessions.get_mut(session_id) else {
+                return false;
+            };
+            let removed = uris.remove(uri);
+            if uris.is_empty() {
+                sessions.remove(session_id);
+            }
+            removed
+        })
+    }
+}
+
+impl Drop for SessionSubscriptions {
+    fn drop(&mut self) {
+        let ids = self
+            .session_ids
+            .get_mut()
+            .map(std::mem::take)
+            .unwrap_or_default();
+        for session_id in ids {
+            self.registry.remove_session(&session_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscriptions_are_idempotent_and_cleared_with_the_session() {
+        let registry = SubscriptionRegistry::default();
+        let first = registry.session();
+        let second = registry.session();
+
+        assert!(first.subscribe(Some("a"), "docs://x.md"));
+        assert!(!first.subscribe(Some("a"), "docs://x.md"));
+        assert!(second.subscribe(None, "docs://x.md"));
+        assert_eq!(registry.active_subscriptions(), 2);
+
+        assert!(!first.unsubscribe(Some("a"), "docs://never.md"));
+        assert!(!first.unsubscribe(Some("b"), "docs://x.md"));
+        assert!(first.unsubscribe(Some("a"), "docs://x.md"));
+        assert!(first.subscribe(Some("a"), "docs://y.md"));
+
+        drop(first);
+        assert_eq!(registry.active_subscriptions(), 1);
+        drop(second);
+        assert_eq!(registry.active_subscriptions(), 0);
+    }
+}
@@ -0,0 +1,108 @@
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{
+        Arc, PoisonError, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// Reads per document URI by get_resource_content and resources/read. Clones share the
+/// counters, so they survive the server being rebuilt by a config reload. A read of a URI
+/// already counted only takes the shared lock.
+#[derive(Debug, Clone, Default)]
+pub struct UsageCounters {
+    reads: Arc<RwLock<BTreeMap<String, AtomicU64>>>,
+}
+
+impl UsageCounters {
+    pub fn record_read(&self, uri: &str) {
+        let reads = self.reads.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(count) = reads.get(uri) {
+            count.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        drop(reads);
+        self.reads
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(uri.to_string())
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// URI -> reads so far
+    pub fn counts(&self) -> BTreeMap<String, u64> {
+        self.reads
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(uri, count)| (uri.clone(), count.load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    /// Counters saved by `save`; a missing file starts empty.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(error) => return Err(error.to_string()),
+        };
+        let counts: BTreeMap<String, u64> =
+            serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        let reads = counts
+            .into_iter()
+            .map(|(uri, count)| (uri, AtomicU64::new(count)))
+            .collect();
+        Ok(Self {
+            reads: Arc::new(RwLock::new(reads)),
+        })
+    }
+
+    /// Writes the counters as a JSON object of URI -> reads.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(&self.counts()).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn counters_survive_a_save_and_load_round_trip() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let path = temp_dir.path().join("usage.json");
+        assert!(
+            UsageCounters::load(&path)
+                .expect("missing file")
+                .counts()
+                .is_empty()
+        );
+
+        let usage = UsageCounters::default();
+        usage.record_read("docs://a.md");
+        usage.record_read("docs://a.md");
+        usage.record_read("docs://b.md");
+        usage.save(&path).expect("save");
+
+        let loaded = UsageCounters::load(&path).expect("load");
+        assert_eq!(
+            loaded.counts(),
+            BTreeMap::from([
+                ("docs://a.md".to_string(), 2),
+                ("docs://b.md".to_string(), 1)
+            ])
+        );
+        loaded.record_read("docs://b.md");
+        assert_eq!(loaded.counts()["docs://b.md"], 2);
+
+        std::fs::write(&path, "[1, 2]").expect("corrupt");
+        assert!(UsageCounters::load(&path).is_err());
+    }
+}
@@ -0,0 +1,300 @@
+/// Element declared by a C4 macro (`Person`, `System`, `Container`, `ComponentDb`, ...).
+#[derive(Debug, Clone, PartialEq)]
+pub struct C4Node {
+    /// Alias used by relationships
+    pub id: String,
+    pub name: String,
+    /// Macro name in snake case, e.g. `container_db`, `system_ext`
+    pub kind: String,
+    pub description: String,
+    pub technology: Option<String>,
+}
+
+/// Relationship declared by a `Rel`-family macro, oriented from source to target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct C4Edge {
+    pub from: String,
+    pub to: String,
+    pub label: String,
+    pub technology: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct C4Diagram {
+    pub nodes: Vec<C4Node>,
+    pub edges: Vec<C4Edge>,
+}
+
+/// Parses a diagram document by file extension: `.puml` files as C4-PlantUML, markdown
+/// files through their fenced `mermaid` (C4 diagrams only) and `plantuml` blocks.
+pub fn parse_document(file_name: &str, content: &str) -> Result<C4Diagram, String> {
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("puml" | "plantuml") => parse_plantuml(content),
+        _ => {
+            let mut diagram = C4Diagram::default();
+            for (language, block) in fenced_blocks(content) {
+                let parsed = match language {
+                    "mermaid" => parse_mermaid(block)?,
+                    "plantuml" | "puml" => parse_plantuml(block)?,
+                    _ => continue,
+                };
+                diagram.nodes.extend(parsed.nodes);
+                diagram.edges.extend(parsed.edges);
+            }
+            Ok(diagram)
+        }
+    }
+}
+
+/// Parses a Mermaid block. Only `C4Context`/`C4Container`/`C4Component`/`C4Dynamic`/
+/// `C4Deployment` diagrams are read; other Mermaid diagrams yield nothing.
+pub fn parse_mermaid(block: &str) -> Result<C4Diagram, String> {
+    let is_c4 = block
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with("%%"))
+        .is_some_and(|header| header.starts_with("C4"));
+    if !is_c4 {
+        return Ok(C4Diagram::default());
+    }
+    parse_macros(block)
+}
+
+/// Parses C4-PlantUML source (`@startuml` ... `@enduml`, includes are ignored).
+pub fn parse_plantuml(source: &str) -> Result<C4Diagram, String> {
+    parse_macros(source)
+}
+
+/// Fenced code blocks as (info string language, body) pairs; unterminated fences are dropped.
+pub fn fenced_blocks(content: &str) -> Vec<(&str, &str)> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(&str, usize)> = None;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if let Some(info) = trimmed.strip_prefix("```") {
+            match open {
+                None => {
+                    open = Some((
+                        info.split_whitespace().next().unwrap_or(""),
+                        offset + line.len(),
+                    ))
+                }
+                Some((language, start)) => {
+                    blocks.push((language, &content[start..offset]));
+                    open = None;
+                }
+            }
+        }
+        offset += line.len();
+    }
+    blocks
+}
+
+fn parse_macros(source: &str) -> Result<C4Diagram, String> {
+    let mut diagram = C4Diagram::default();
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        let Some((name, args)) =
+            split_macro_call(line).map_err(|e| format!("line {}: {}", index + 1, e))?
+        else {
+            continue;
+        };
+
+        if let Some(edge) = relationship(name, &args) {
+            diagram
+                .edges
+                .push(edge.map_err(|e| format!("line {}: {}", index + 1, e))?);
+        } else if let Some(node) = element(name, &args) {
+            diagram
+                .nodes
+                .push(node.map_err(|e| format!("line {}: {}", index + 1, e))?);
+        }
+    }
+    Ok(diagram)
+}
+
+/// `Rel(a, b, "label")` -> ("Rel", ["a", "b", "label"]). Keyword arguments (`$tags="x"`)
+/// are dropped. Returns `None` for lines that are not macro calls.
+fn split_macro_call(line: &str) -> Result<Option<(&str, Vec<String>)>, String> {
+    let Some(open) = line.find('(') else {
+        return Ok(None);
+    };
+    let name = &line[..open];
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Ok(None);
+    }
+
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quote = false;
+    let mut depth = 0usize;
+    for c in line[open + 1..].chars() {
+        match c {
+            '"' => in_quote = !in_quote,
+            '(' if !in_quote => {
+                depth += 1;
+                current.push(c);
+            }
+            ')' if !in_quote && depth > 0 => {
+                depth -= 1;
+                current.push(c);
+            }
+            ')' if !in_quote => {
+                args.push(current);
+                let args = args
+                    .into_iter()
+                    .map(|arg| arg.trim().to_string())
+                    .filter(|arg| !arg.starts_with('$'))
+                    .collect();
+                return Ok(Some((name, args)));
+            }
+            ',' if !in_quote && depth == 0 => args.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    Err(format!("unterminated {}(...) call", name))
+}
+
+fn relationship(name: &str, args: &[String]) -> Option<Result<C4Edge, String>> {
+    let reversed = match name {
+        "Rel" | "BiRel" | "Rel_Neighbor" | "RelIndex" => false,
+        "Rel_Back" | "Rel_Back_Neighbor" => true,
+        _ if name.starts_with("Rel_") || name.starts_with("BiRel_") => false,
+        _ => return None,
+    };
+    // RelIndex(index, from, to, label, ...) carries a leading sequence number.
+    let args = if name == "RelIndex" {
+        args.get(1..).unwrap_or_default()
+    } else {
+        args
+    };
+    let [from, to, rest @ ..] = args else {
+        return Some(Err(format!("{} needs a source and a target", name)));
+    };
+    let (from, to) = if reversed { (to, from) } else { (from, to) };
+    Some(Ok(C4Edge {
+        from: from.clone(),
+        to: to.clone(),
+        label: rest.first().cloned().unwrap_or_default(),
+        technology: rest.get(1).filter(|t| !t.is_empty()).cloned(),
+    }))
+}
+
+fn element(name: &str, args: &[String]) -> Option<Result<C4Node, String>> {
+    let base = name.trim_end_matches("_Ext");
+    let has_technology = match base {
+        "Person" | "System" | "SystemDb" | "SystemQueue" => false,
+        "Container" | "ContainerDb" | "ContainerQueue" | "Component" | "ComponentDb"
+        | "ComponentQueue" => true,
+        _ => return None,
+    };
+    let [id, label, rest @ ..] = args else {
+        return Some(Err(format!("{} needs an alias and a label", name)));
+    };
+    let (technology, description) = if has_technology {
+        (rest.first().filter(|t| !t.is_empty()).cloned(), rest.get(1))
+    } else {
+        (None, rest.first())
+    };
+    Some(Ok(C4Node {
+        id: id.clone(),
+        name: label.clone(),
+        kind: snake_case(name),
+        description: description.cloned().unwrap_or_default(),
+        technology,
+    }))
+}
+
+/// `ContainerDb_Ext` -> `container_db_ext`
+fn snake_case(name: &str) -> String {
+    let mut output = String::with_capacity(name.len() + 4);
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && previous.is_some_and(|p| p.is_ascii_lowercase()) {
+            output.push('_');
+        }
+        output.push(c.to_ascii_lowercase());
+        previous = Some(c);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mermaid_c4_container() {
+        let content = "# Billing\n```mermaid\nC4Container\n  title Billing\n  \
+                       Person(customer, \"Customer\", \"Pays invoices\")\n  \
+                       Container_Boundary(b, \"Billing\") {\n    \
+                       Container(api, \"Billing API\", \"Rust, axum\", \"Issues invoices\")\n    \
+                       ContainerDb(db, \"Invoices DB\", \"PostgreSQL\", \"Stores invoices, totals\")\n  \
+                       }\n  Rel(customer, api, \"Uses\", \"HTTPS\")\n  \
+                       Rel_Back(db, api, \"Reads/writes\")\n```\n\
+                       ```mermaid\nflowchart LR\n  Rel(a, b)\n```\n";
+        let diagram = parse_document("c2.mdx", content).expect("parsed");
+
+        let ids: Vec<(&str, &str)> = diagram
+            .nodes
+            .iter()
+            .map(|n| (n.id.as_str(), n.kind.as_str()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                ("customer", "person"),
+                ("api", "container"),
+                ("db", "container_db")
+            ]
+        );
+        assert_eq!(diagram.nodes[2].description, "Stores invoices, totals");
+        assert_eq!(diagram.nodes[1].technology.as_deref(), Some("Rust, axum"));
+        assert_eq!(
+            diagram.edges,
+            vec![
+                C4Edge {
+                    from: "customer".to_string(),
+                    to: "api".to_string(),
+                    label: "Uses".to_string(),
+                    technology: Some("HTTPS".to_string()),
+                },
+                C4Edge {
+                    from: "api".to_string(),
+                    to: "db".to_string(),
+                    label: "Reads/writes".to_string(),
+                    technology: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_plantuml_macros() {
+        let source = "@startuml\n!include <C4/C4_Container>\n\
+                      System_Ext(bank, \"Bank\", \"Settles payments\", $tags=\"external\")\n\
+                      Container(api, \"Billing API\", \"Rust\", \"Issues invoices\")\n\
+                      Rel_D(api, bank, \"Charges\", \"ISO 8583\")\n@enduml\n";
+        let diagram = parse_document("billing.puml", source).expect("parsed");
+
+        assert_eq!(diagram.nodes[0].kind, "system_ext");
+        assert_eq!(diagram.nodes[0].description, "Settles payments");
+        assert_eq!(diagram.nodes[0].technology, None);
+        assert_eq!(diagram.edges[0].from, "api");
+        assert_eq!(diagram.edges[0].to, "bank");
+        assert_eq!(diagram.edges[0].technology.as_deref(), Some("ISO 8583"));
+    }
+
+    #[test]
+    fn test_parse_reports_malformed_macro() {
+        let source = "@startuml\nContainer(api, \"Billing API\"\n@enduml\n";
+        let error = parse_plantuml(source).expect_err("unterminated");
+        assert_eq!(error, "line 2: unterminated Container(...) call");
+
+        assert!(parse_plantuml("Rel(api)\n").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+const MONTHS: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Normalizes a loosely written calendar date to ISO `YYYY-MM-DD`.
+///
+/// Accepted: `2024-01-15` (a trailing time is ignored), `2024/01/15`, `2024.01.15`,
+/// day-first `15.01.2024` / `15/01/2024`, and month names (`January 15, 2024`,
+/// `15 Jan 2024`).
+pub fn normalize_date(text: &str) -> Option<String> {
+    let text = text.trim();
+    // ISO timestamp: keep the date part.
+    let text = match text.split_once('T') {
+        Some((date, _)) if date.len() == 10 && date.as_bytes()[4] == b'-' => date,
+        _ => text,
+    };
+    let parts: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || matches!(c, '-' | '/' | '.' | ','))
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    let (year, month, day) = match parts.as_slice() {
+        [a, b, c, ..] if is_number(a) && is_number(b) && is_number(c) => {
+            if a.len() == 4 {
+                (a.parse().ok()?, b.parse().ok()?, c.parse().ok()?)
+            } else if c.len() == 4 {
+                (c.parse().ok()?, b.parse().ok()?, a.parse().ok()?)
+            } else {
+                return None;
+            }
+        }
+        [month, day, year] if is_number(day) && is_number(year) => {
+            (year.parse().ok()?, month_number(month)?, day.parse().ok()?)
+        }
+        [day, month, year] if is_number(day) && is_number(year) => {
+            (year.parse().ok()?, month_number(month)?, day.parse().ok()?)
+        }
+        _ => return None,
+    };
+
+    valid_date(year, month, day).then(|| format!("{:04}-{:02}-{:02}", year, month, day))
+}
+
+fn is_number(part: &str) -> bool {
+    part.chars().all(|c| c.is_ascii_digit())
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let name = name.to_ascii_lowercase();
+    let prefix = name.get(..3)?;
+    let position = MONTHS.iter().position(|month| *month == prefix)?;
+    u32::try_from(position + 1).ok()
+}
+
+fn valid_date(year: u32, month: u32, day: u32) -> bool {
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1000..=9999).contains(&year) && (1..=days).contains(&day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_iso_dates() {
+        assert_eq!(normalize_date("2024-01-15").as_deref(), Some("2024-01-15"));
+        assert_eq!(
+            normalize_date("2024-01-15T10:30:00Z").as_deref(),
+            Some("2024-01-15")
+        );
+        assert_eq!(normalize_date("2024/1/5").as_deref(), Some("2024-01-05"));
+        assert_eq!(normalize_date("2024-02-30"), None);
+        assert_eq!(normalize_date("2023-02-29"), None);
+    }
+
+    #[test]
+    fn test_normalize_loose_dates() {
+        assert_eq!(normalize_date("15.01.2024").as_deref(), Some("2024-01-15"));
+        assert_eq!(
+            normalize_date("January 15, 2024").as_deref(),
+            Some("2024-01-15")
+        );
+        assert_eq!(normalize_date("3 Sept 2023").as_deref(), Some("2023-09-03"));
+        assert_eq!(normalize_date("accepted"), None);
+        assert_eq!(normalize_date("15 Foo 2024"), None);
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::BTreeSet;
+
+use super::c4_graph::fenced_blocks;
+
+/// Parses an ERD document by file extension: `.puml` files as PlantUML, markdown files
+/// through their fenced `mermaid` (`erDiagram` only) and `plantuml` blocks. Returns entity
+/// names in declaration order without duplicates.
+pub fn parse_document(file_name: &str, content: &str) -> Result<Vec<String>, String> {
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+    let entities = match extension.as_deref() {
+        Some("puml" | "plantuml") => parse_plantuml(content)?,
+        _ => {
+            let mut entities = Vec::new();
+            for (language, block) in fenced_blocks(content) {
+                match language {
+                    "mermaid" => entities.extend(parse_mermaid(block)?),
+                    "plantuml" | "puml" => entities.extend(parse_plantuml(block)?),
+                    _ => {}
+                }
+            }
+            entities
+        }
+    };
+    let mut seen = BTreeSet::new();
+    Ok(entities
+        .into_iter()
+        .filter(|entity| seen.insert(entity.clone()))
+        .collect())
+}
+
+/// Entities of a Mermaid `erDiagram` block, from attribute blocks (`CUSTOMER { ... }`),
+/// relationships (`CUSTOMER ||--o{ ORDER : places`) and bare entity lines. Other Mermaid
+/// diagrams yield nothing.
+pub fn parse_mermaid(block: &str) -> Result<Vec<String>, String> {
+    let mut lines = block
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with("%%"));
+    if !lines
+        .next()
+        .is_some_and(|(_, header)| header.starts_with("erDiagram"))
+    {
+        return Ok(Vec::new());
+    }
+
+    let mut entities = Vec::new();
+    let mut open_block: Option<usize> = None;
+    for (index, line) in lines {
+        if open_block.is_some() {
+            if line.starts_with('}') {
+                open_block = None;
+            }
+            continue;
+        }
+        let relation = line.split_once(':').map_or(line, |(relation, _)| relation);
+        let tokens = tokens(relation);
+        let is_relationship = tokens
+            .get(1)
+            .is_some_and(|cardinality| cardinality.contains("--") || cardinality.contains(".."));
+        if is_relationship && tokens.len() >= 3 {
+            entities.push(entity_name(tokens[0]));
+            entities.push(entity_name(tokens[2]));
+        } else if let Some((head, rest)) = line.split_once('{') {
+            entities.push(entity_name(head.trim()));
+            if !rest.contains('}') {
+                open_block = Some(index);
+            }
+        } else if let [entity] = tokens.as_slice() {
+            entities.push(entity_name(entity));
+        }
+    }
+
+    match open_block {
+        Some(index) => Err(format!("line {}: unterminated entity block", index + 1)),
+        None => Ok(entities),
+    }
+}
+
+/// Entities of PlantUML source declared with `entity`, `class` or `table`; the display
+/// name wins over the `as` alias.
+pub fn parse_plantuml(source: &str) -> Result<Vec<String>, String> {
+    let mut entities = Vec::new();
+    let mut open_block: Option<usize> = None;
+    for (index, line) in source.lines().map(str::trim).enumerate() {
+        if open_block.is_some() {
+            if line.starts_with('}') {
+                open_block = None;
+            }
+            continue;
+        }
+        let declaration = line.split_once('{').map_or(line, |(head, _)| head);
+        let Some((keyword, rest)) = declaration.split_once(char::is_whitespace) else {
+            continue;
+        };
+        if !matches!(keyword, "entity" | "class" | "table") {
+            continue;
+        }
+        let tokens = tokens(rest);
+        let name = tokens
+            .iter()
+            .find(|token| token.starts_with('"'))
+            .or_else(|| tokens.first());
+        let Some(name) = name else {
+            return Err(format!("line {}: {} without a name", index + 1, keyword));
+        };
+        entities.push(entity_name(name));
+        if line.contains('{') && !line.contains('}') {
+            open_block = Some(index);
+        }
+    }
+
+    match open_block {
+        Some(index) => Err(format!("line {}: unterminated entity block", index + 1)),
+        None => Ok(entities),
+    }
+}
+
+/// Key entity and schema names are compared by: lowercase alphanumerics with a plural
+/// suffix dropped, so `order_items`, `OrderItem` and `order-item` all match.
+pub fn match_key(name: &str) -> String {
+    let key: String = name
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if let Some(stem) = key.strip_suffix("ies").filter(|stem| stem.len() > 1) {
+        format!("{}y", stem)
+    } else if key.len() > 3 && key.ends_with('s') && !key.ends_with("ss") {
+        key[..key.len() - 1].to_string()
+    } else {
+        key
+    }
+}
+
+/// Whitespace-separated tokens; double-quoted tokens keep their spaces and quotes.
+fn tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut in_quote = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' => {
+                in_quote = !in_quote;
+                start.get_or_insert(i);
+            }
+            c if c.is_whitespace() && !in_quote => {
+                if let Some(s) = start.take() {
+                    tokens.push(&text[s..i]);
+                }
+            }
+            _ => {
+                start.get_or_insert(i);
+            }
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(&text[s..]);
+    }
+    tokens
+}
+
+/// `"line item"` -> `line item`, `p["Person"]` -> `p`, `Order <<table>>` -> `Order`
+fn entity_name(token: &str) -> String {
+    let token = token.split("<<").next().unwrap_or(token).trim();
+    let token = token.split('[').next().unwrap_or(token);
+    token.trim_matches('"').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mermaid_er_diagram() {
+        let content = "# Billing\n```mermaid\nerDiagram\n  \
+                       CUSTOMER ||--o{ ORDER : places\n  \
+                       ORDER ||--|{ \"line item\" : contains\n  \
+                       CUSTOMER {\n    string name\n    string email\n  }\n  \
+                       INVOICE\n  \
+                       PAYMENT { }\n```\n\
+                       ```mermaid\nflowchart LR\n  A --> B\n```\n";
+        let entities = parse_document("billing.mdx", content).expect("parsed");
+
+        assert_eq!(
+            entities,
+            vec!["CUSTOMER", "ORDER", "line item", "INVOICE", "PAYMENT"]
+        );
+    }
+
+    #[test]
+    fn test_parse_plantuml_entities() {
+        let source = "@startuml\nentity \"Customer\" as customer {\n  *id : uuid\n}\n\
+                      entity Order <<table>> {\n  total : decimal\n}\n\
+                      class InvoiceLine\ncustomer ||--o{ Order\n@enduml\n";
+        let entities = parse_document("billing.puml", source).expect("parsed");
+
+        assert_eq!(entities, vec!["Customer", "Order", "InvoiceLine"]);
+    }
+
+    #[test]
+    fn test_parse_reports_unterminated_block() {
+        let error =
+            parse_mermaid("erDiagram\n  CUSTOMER {\n    string name\n").expect_err("unterminated");
+        assert_eq!(error, "line 2: unterminated entity block");
+
+        assert!(parse_plantuml("entity Customer {\n  id : uuid\n").is_err());
+    }
+
+    #[test]
+    fn test_match_key_ignores_case_separators_and_plurals() {
+        assert_eq!(match_key("order_items"), match_key("OrderItem"));
+        assert_eq!(match_key("order-item"), match_key("ORDER_ITEMS"));
+        assert_eq!(match_key("categories"), match_key("Category"));
+        assert_eq!(match_key("Address"), "address");
+        assert_ne!(match_key("Order"), match_key("OrderLine"));
+    }
+}
@@ -1,4 +1,26 @@
-use std::{env, fs, path::Path};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+    },
+};
+
+use chardetng::{EncodingDetector, Iso2022JpDetection, Utf8Detection};
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE};
+
+/// Consecutive docs-root failures after which the backing store counts as unavailable
+const ROOT_FAILURE_THRESHOLD: u32 = 3;
+
+/// Availability of the docs root, shared by every clone of a reader.
+#[derive(Debug, Default)]
+struct RootHealth {
+    consecutive_failures: AtomicU32,
+    unavailable: AtomicBool,
+    /// Set when the root comes back after being unavailable, until taken by `take_recovery`
+    recovered: AtomicBool,
+}
 
 /// File reader that reads files relative to a specified docs root path.
 ///
@@ -14,6 +36,9 @@ use std::{env, fs, path::Path};
 #[allow(dead_code)]
 pub struct FileReader {
     docs_root: String,
+    root_health: Arc<RootHealth>,
+    /// Decode non-UTF-8 content from a detected encoding instead of failing
+    detect_encoding: bool,
 }
 
 #[allow(dead_code)]
@@ -72,9 +97,19 @@ impl FileReader {
         let resolved_docs_root = resolved_path.to_string_lossy().to_string();
         Ok(Self {
             docs_root: resolved_docs_root,
+            root_health: Arc::default(),
+            detect_encoding: false,
         })
     }
 
+    /// With `detect_encoding`, content that is not valid UTF-8 is decoded from the encoding a
+    /// byte order mark names or one detected from the bytes (e.g. UTF-16, Latin-1) rather than
+    /// failing to read.
+    pub fn with_encoding_detection(mut self, detect_encoding: bool) -> Self {
+        self.detect_encoding = detect_encoding;
+        self
+    }
+
     /// Creates a new FileReader by reading DOCS_ROOT_PATH from environment variables.
     /// Supports both environment variables and .env files.
     /// DOCS_ROOT_PATH can be either an absolute or relative path.
@@ -95,7 +130,8 @@ impl FileReader {
         Self::new(docs_root)
     }
 
-    /// Reads file content from a path relative to the docs root.
+    /// Reads file content from a path relative to the docs root. Content must be UTF-8
+    /// unless encoding detection is on.
     ///
     /// # Arguments
     /// * `relative_path` - Path relative to docs root
@@ -103,31 +139,11 @@ impl FileReader {
     /// # Returns
     /// * `Result<String, std::io::Error>` - File content as String or error
     pub fn read_file_content(&self, relative_path: &str) -> Result<String, std::io::Error> {
-        let full_path = Path::new(&self.docs_root).join(relative_path);
-
-        // Validate that the path is within the docs root to prevent directory traversal
-        let canonical_docs_root = fs::canonicalize(&self.docs_root).map_err(|e| {
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!("Cannot canonicalize DOCS_ROOT_PATH: {}", e),
-            )
-        })?;
-
-        let canonical_full_path = fs::canonicalize(&full_path).map_err(|e| {
-            std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                format!("Cannot canonicalize file path: {}", e),
-            )
-        })?;
-
-        if !canonical_full_path.starts_with(&canonical_docs_root) {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::PermissionDenied,
-                "Path traversal detected: file path is outside DOCS_ROOT_PATH",
-            ));
+        let path = self.resolve(relative_path)?;
+        if !self.detect_encoding {
+            return fs::read_to_string(path);
         }
-
-        fs::read_to_string(&canonical_full_path)
+        Ok(decode_detected(fs::read(path)?, relative_path))
     }
 
     /// Reads file content as bytes from a path relative to the docs root.
@@ -138,15 +154,40 @@ impl FileReader {
     /// # Returns
     /// * `Result<Vec<u8>, std::io::Error>` - File content as bytes or error
     pub fn read_file_bytes(&self, relative_path: &str) -> Result<Vec<u8>, std::io::Error> {
+        fs::read(self.resolve(relative_path)?)
+    }
+
+    /// Metadata (size, modification time) of a file relative to the docs root.
+    pub fn metadata(&self, relative_path: &str) -> Result<fs::Metadata, std::io::Error> {
+        fs::metadata(self.resolve(relative_path)?)
+    }
+
+    /// Checks that a file relative to the docs root can be opened for reading, without
+    /// reading it.
+    pub fn check_readable(&self, relative_path: &str) -> Result<(), std::io::Error> {
+        fs::File::open(self.resolve(relative_path)?).map(|_| ())
+    }
+
+    /// Lowercased extension of the file a path relative to the docs root resolves to, after
+    /// following symlinks.
+    pub fn resolved_extension(
+        &self,
+        relative_path: &str,
+    ) -> Result<Option<String>, std::io::Error> {
+        let resolved = self.resolve(relative_path)?;
+        Ok(resolved
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase))
+    }
+
+    /// Canonical path of a file relative to the docs root, rejecting paths that resolve
+    /// outside of it.
+    fn resolve(&self, relative_path: &str) -> Result<PathBuf, std::io::Error> {
         let full_path = Path::new(&self.docs_root).join(relative_path);
 
         // Validate that the path is within the docs root to prevent directory traversal
-        let canonical_docs_root = fs::canonicalize(&self.docs_root).map_err(|e| {
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!("Cannot canonicalize DOCS_ROOT_PATH: {}", e),
-            )
-        })?;
+        let canonical_docs_root = self.canonical_root()?;
 
         let canonical_full_path = fs::canonicalize(&full_path).map_err(|e| {
             std::io::Error::new(
@@ -162,7 +203,51 @@ impl FileReader {
             ));
         }
 
-        fs::read(&canonical_full_path)
+        Ok(canonical_full_path)
+    }
+
+    /// Canonical docs root. Failures count towards marking the backing store unavailable;
+    /// a success after that marks it recovered. Transitions are logged once.
+    fn canonical_root(&self) -> Result<PathBuf, std::io::Error> {
+        let health = &self.root_health;
+        match fs::canonicalize(&self.docs_root) {
+            Ok(root) => {
+                health.consecutive_failures.store(0, Ordering::Relaxed);
+                if health.unavailable.swap(false, Ordering::Relaxed) {
+                    health.recovered.store(true, Ordering::Relaxed);
+                    tracing::info!(docs_root = %self.docs_root, "Docs root is available again");
+                }
+                Ok(root)
+            }
+            Err(e) => {
+                let failures = health.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                if failures >= ROOT_FAILURE_THRESHOLD
+                    && !health.unavailable.swap(true, Ordering::Relaxed)
+                {
+                    tracing::warn!(docs_root = %self.docs_root, error = %e, "Docs root is unavailable, serving metadata only");
+                }
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Cannot canonicalize DOCS_ROOT_PATH: {}", e),
+                ))
+            }
+        }
+    }
+
+    /// False once reads of the docs root failed repeatedly, until it is reachable again.
+    pub fn is_available(&self) -> bool {
+        !self.root_health.unavailable.load(Ordering::Relaxed)
+    }
+
+    /// Checks the docs root (counting like a failed read) and returns whether it is available.
+    pub fn probe(&self) -> bool {
+        let _ = self.canonical_root();
+        self.is_available()
+    }
+
+    /// True once after the docs root recovered from being unavailable.
+    pub fn take_recovery(&self) -> bool {
+        self.root_health.recovered.swap(false, Ordering::Relaxed)
     }
 
     /// Gets the docs root path.
@@ -171,6 +256,132 @@ impl FileReader {
     }
 }
 
+/// Normalizes a path relative to the docs root: unifies separators, drops `.` and empty
+/// segments. Absolute paths and `..` segments are rejected.
+pub fn normalize_relative_path(path: &str) -> Result<String, &'static str> {
+    let unified = path.trim().replace('\\', "/");
+    if unified.starts_with('/') || Path::new(&unified).has_root() || unified.contains(':') {
+        return Err("Absolute paths are not allowed");
+    }
+
+    let mut segments = Vec::new();
+    for segment in unified.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => return Err("Parent directory segments ('..') are not allowed"),
+            _ => segments.push(segment),
+        }
+    }
+
+    if segments.is_empty() {
+        return Err("Path is empty");
+    }
+    Ok(segments.join("/"))
+}
+
+/// Resolves a link target against the docs-root relative file containing it. Targets starting
+/// with `/` are taken relative to the docs root. Returns `None` for external links (with a
+/// scheme) and for targets escaping the docs root.
+pub fn resolve_relative_link(base_file: &str, target: &str) -> Option<String> {
+    let unified = target.trim().replace('\\', "/");
+    if unified.contains(':') {
+        return None;
+    }
+
+    let mut segments: Vec<&str> = if unified.starts_with('/') {
+        Vec::new()
+    } else {
+        let mut base: Vec<&str> = base_file.split('/').filter(|s| !s.is_empty()).collect();
+        base.pop();
+        base
+    };
+    for segment in unified.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    (!segments.is_empty()).then(|| segments.join("/"))
+}
+
+/// Decodes `bytes` as UTF-8 when valid, else from the encoding their byte order mark names,
+/// a BOM-less UTF-16 guessed from NUL bytes, or the legacy encoding chardetng detects. Bytes
+/// no encoding decodes cleanly are read as lossy UTF-8.
+fn decode_detected(bytes: Vec<u8>, relative_path: &str) -> String {
+    let bytes = match String::from_utf8(bytes) {
+        Ok(text) => return text,
+        Err(error) => error.into_bytes(),
+    };
+    let encoding = match Encoding::for_bom(&bytes) {
+        Some((encoding, bom_length)) => {
+            return encoding
+                .decode_without_bom_handling(&bytes[bom_length..])
+                .0
+                .into_owned();
+        }
+        None => utf16_without_bom(&bytes).unwrap_or_else(|| {
+            let mut detector = EncodingDetector::new(Iso2022JpDetection::Deny);
+            detector.feed(&bytes, true);
+            detector.guess(None, Utf8Detection::Deny)
+        }),
+    };
+    let (text, had_errors) = encoding.decode_without_bom_handling(&bytes);
+    if had_errors {
+        tracing::warn!(
+            file_path = relative_path,
+            "Encoding not detected, invalid UTF-8 sequences replaced"
+        );
+        return String::from_utf8_lossy(&bytes).into_owned();
+    }
+    tracing::debug!(
+        file_path = relative_path,
+        encoding = encoding.name(),
+        "Decoded non-UTF-8 content"
+    );
+    text.into_owned()
+}
+
+/// UTF-16 byte order of text without a BOM, recognized by the NUL high bytes of ASCII
+/// characters: most even (big-endian) or odd (little-endian) bytes are NUL.
+fn utf16_without_bom(bytes: &[u8]) -> Option<&'static Encoding> {
+    let pairs = bytes.len() / 2;
+    if pairs == 0 || bytes.len() % 2 != 0 {
+        return None;
+    }
+    let nul_at = |offset: usize| {
+        bytes
+            .iter()
+            .skip(offset)
+            .step_by(2)
+            .filter(|b| **b == 0)
+            .count()
+    };
+    let (even, odd) = (nul_at(0), nul_at(1));
+    if odd * 2 > pairs && even == 0 {
+        Some(UTF_16LE)
+    } else if even * 2 > pairs && odd == 0 {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Stable content revision (FNV-1a 64, hex). Deterministic across runs and builds, unlike
+/// `DefaultHasher`, so cached revisions stay valid after a restart.
+pub fn content_revision(content: &str) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let hash = content.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    });
+    format!("{:016x}", hash)
+}
+
 #[cfg(test)]
 mod tests {
     use std::{fs, path::PathBuf};
@@ -187,6 +398,32 @@ mod tests {
         (temp_dir, docs_root)
     }
 
+    #[test]
+    fn test_removed_docs_root_is_reported_unavailable_until_it_returns() {
+        let (_temp_dir, docs_root) = setup_test_env();
+        fs::write(docs_root.join("a.md"), "# A\n").expect("write");
+        let reader = FileReader::new(docs_root.to_str().unwrap()).expect("reader");
+        let clone = reader.clone();
+
+        fs::remove_dir_all(&docs_root).expect("remove root");
+        assert!(reader.read_file_content("a.md").is_err());
+        assert!(reader.is_available());
+        assert!(reader.read_file_content("a.md").is_err());
+        assert!(reader.read_file_bytes("a.md").is_err());
+        assert!(!reader.is_available());
+        assert!(!clone.is_available());
+        assert!(!reader.probe());
+        assert!(!reader.take_recovery());
+
+        fs::create_dir_all(&docs_root).expect("remount root");
+        fs::write(docs_root.join("a.md"), "# A\n").expect("write");
+        assert!(clone.probe());
+        assert!(reader.is_available());
+        assert!(reader.take_recovery());
+        assert!(!reader.take_recovery());
+        assert_eq!(reader.read_file_content("a.md").expect("read"), "# A\n");
+    }
+
     #[test]
     fn test_file_reader_new_success() {
         let (_temp_dir, docs_root) = setup_test_env();
@@ -234,6 +471,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_read_file_content_decodes_detected_encodings() {
+        let (_temp_dir, docs_root) = setup_test_env();
+        let utf16: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain("# Überblick\n".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        fs::write(docs_root.join("utf16.md"), utf16).expect("write utf-16");
+        fs::write(
+            docs_root.join("latin1.md"),
+            b"# R\xe9sum\xe9\n\nLa caf\xe9t\xe9ria ferme \xe0 dix-huit heures, pr\xe8s de la gare.\n",
+        )
+        .expect("write latin-1");
+
+        let strict = FileReader::new(docs_root.to_str().unwrap()).expect("reader");
+        assert!(strict.read_file_content("latin1.md").is_err());
+
+        let reader = strict.with_encoding_detection(true);
+        assert_eq!(
+            reader.read_file_content("utf16.md").expect("utf-16"),
+            "# Überblick\n"
+        );
+        assert_eq!(
+            reader.read_file_content("latin1.md").expect("latin-1"),
+            "# Résumé\n\nLa cafétéria ferme à dix-huit heures, près de la gare.\n"
+        );
+        fs::write(docs_root.join("plain.md"), "déjà UTF-8").expect("write utf-8");
+        assert_eq!(
+            reader.read_file_content("plain.md").expect("utf-8"),
+            "déjà UTF-8"
+        );
+    }
+
     #[test]
     fn test_read_file_content_with_subdirectory() {
         let (_temp_dir, docs_root) = setup_test_env();
@@ -387,6 +657,44 @@ mod tests {
         assert_eq!(reader.docs_root(), docs_root.to_str().unwrap());
     }
 
+    #[test]
+    fn test_content_revision_is_stable() {
+        assert_eq!(content_revision(""), "cbf29ce484222325");
+        assert_eq!(content_revision("# ADR"), content_revision("# ADR"));
+        assert_ne!(content_revision("# ADR"), content_revision("# ADR\n"));
+    }
+
+    #[test]
+    fn test_resolve_relative_link() {
+        let base = "docs/architecture/mpa/adr/007-outbox.mdx";
+        assert_eq!(
+            resolve_relative_link(base, "../erd/customer.mdx").as_deref(),
+            Some("docs/architecture/mpa/erd/customer.mdx")
+        );
+        assert_eq!(
+            resolve_relative_link(base, "./008-saga.mdx").as_deref(),
+            Some("docs/architecture/mpa/adr/008-saga.mdx")
+        );
+        assert_eq!(
+            resolve_relative_link(base, "/docs/guides/intro.md").as_deref(),
+            Some("docs/guides/intro.md")
+        );
+        assert_eq!(resolve_relative_link(base, "../../../../../x.md"), None);
+        assert_eq!(resolve_relative_link(base, "https://example.com"), None);
+    }
+
+    #[test]
+    fn test_normalize_relative_path() {
+        assert_eq!(
+            normalize_relative_path("./content\\docs//adr/001.mdx"),
+            Ok("content/docs/adr/001.mdx".to_string())
+        );
+        assert!(normalize_relative_path("/etc/passwd").is_err());
+        assert!(normalize_relative_path("C:\\docs\\a.md").is_err());
+        assert!(normalize_relative_path("docs/../secret.md").is_err());
+        assert!(normalize_relative_path("./").is_err());
+    }
+
     #[test]
     fn test_file_reader_relative_path_nonexistent() {
         let result = FileReader::new("nonexistent_relative_path");
@@ -0,0 +1,551 @@
+use serde_json::{Map, Value};
+
+/// Markdown document split into its YAML frontmatter and body.
+#[derive(Debug, Default, PartialEq)]
+pub struct Frontmatter<'a> {
+    /// Frontmatter fields; empty when the document has no frontmatter
+    pub metadata: Map<String, Value>,
+    /// Document text after the closing `---`
+    pub body: &'a str,
+}
+
+impl Frontmatter<'_> {
+    /// Returns a non-empty string field.
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        metadata_str(&self.metadata, key)
+    }
+}
+
+/// Returns a non-empty string field of parsed frontmatter metadata.
+pub fn metadata_str<'a>(metadata: &'a Map<String, Value>, key: &str) -> Option<&'a str> {
+    metadata
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+/// Splits a leading `---` delimited block into (raw yaml, body).
+pub fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Parses YAML frontmatter. Documents without frontmatter yield empty metadata and the full body.
+pub fn parse_frontmatter(content: &str) -> Result<Frontmatter<'_>, String> {
+    let Some((raw, body)) = split_frontmatter(content) else {
+        return Ok(Frontmatter {
+            metadata: Map::new(),
+            body: content,
+        });
+    };
+
+    if raw.trim().is_empty() {
+        return Ok(Frontmatter {
+            metadata: Map::new(),
+            body,
+        });
+    }
+
+    let yaml: serde_yaml::Value =
+        serde_yaml::from_str(raw).map_err(|e| format!("Invalid frontmatter: {}", e))?;
+    match serde_json::to_value(yaml) {
+        Ok(Value::Object(metadata)) => Ok(Frontmatter { metadata, body }),
+        Ok(_) => Err("Invalid frontmatter: expected a mapping".to_string()),
+        Err(e) => Err(format!("Invalid frontmatter: {}", e)),
+    }
+}
+
+/// Counts whitespace-separated words.
+pub fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+/// Maximum length of `summary`, in characters
+pub const SUMMARY_MAX_CHARS: usize = 200;
+
+/// First paragraph of a markdown body (frontmatter already removed), skipping headings,
+/// fenced code, MDX `import`/`export` lines and JSX-only lines. Whitespace is collapsed and
+/// the text is cut at a word boundary after `SUMMARY_MAX_CHARS` characters.
+pub fn summary(body: &str) -> Option<String> {
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let text = if in_fence
+            || trimmed.starts_with("```")
+            || parse_heading(trimmed).is_some()
+            || trimmed.starts_with("import ")
+            || trimmed.starts_with("export ")
+        {
+            String::new()
+        } else {
+            strip_line_tags(trimmed)
+        };
+
+        if !text.trim().is_empty() {
+            paragraph.push(text);
+        } else if !paragraph.is_empty() {
+            break;
+        }
+    }
+
+    let text = paragraph
+        .iter()
+        .flat_map(|line| line.split_whitespace())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    if text.chars().count() <= SUMMARY_MAX_CHARS {
+        return Some(text);
+    }
+
+    let cut: String = text.chars().take(SUMMARY_MAX_CHARS).collect();
+    let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(head, _)| head);
+    Some(format!("{}…", cut.trim_end_matches([',', ';', ':', '.'])))
+}
+
+/// ATX heading (`## Title`) with the text up to the next heading.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section<'a> {
+    pub level: usize,
+    pub title: &'a str,
+    pub content: &'a str,
+}
+
+/// Splits markdown into heading sections, ignoring `#` lines inside fenced code blocks.
+/// Text before the first heading is not part of any section.
+pub fn sections(body: &str) -> Vec<Section<'_>> {
+    let mut sections: Vec<Section<'_>> = Vec::new();
+    let mut content_start = 0;
+    let mut offset = 0;
+    let mut in_fence = false;
+
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+        } else if !in_fence && let Some((level, title)) = parse_heading(trimmed) {
+            if let Some(last) = sections.last_mut() {
+                last.content = &body[content_start..offset];
+            }
+            sections.push(Section {
+                level,
+                title,
+                content: "",
+            });
+            content_start = offset + line.len();
+        }
+        offset += line.len();
+    }
+    if let Some(last) = sections.last_mut() {
+        last.content = &body[content_start.min(body.len())..];
+    }
+    sections
+}
+
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t', '\n', '\r']) {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim()))
+}
+
+/// Inline markdown link `[text](target)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownLink {
+    pub text: String,
+    pub target: String,
+}
+
+/// Extracts inline links, skipping images and fenced code blocks. Optional link titles
+/// (`[a](b "title")`) and angle brackets around the target are stripped.
+pub fn extract_links(content: &str) -> Vec<MarkdownLink> {
+    let mut links = Vec::new();
+    let mut in_fence = false;
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if !in_fence {
+            extract_line_links(line, &mut links);
+        }
+    }
+    links
+}
+
+fn extract_line_links(line: &str, links: &mut Vec<MarkdownLink>) {
+    let mut rest = line;
+    while let Some(open) = rest.find('[') {
+        let is_image = rest[..open].ends_with('!');
+        let after_open = &rest[open + 1..];
+        let Some(close) = after_open.find(']') else {
+            return;
+        };
+        let text = &after_open[..close];
+        let after_text = &after_open[close + 1..];
+        let Some(target_start) = after_text.strip_prefix('(') else {
+            rest = after_text;
+            continue;
+        };
+
+        let mut depth = 0usize;
+        let Some(target_end) = target_start.find(|c| match c {
+            '(' => {
+                depth += 1;
+                false
+            }
+            ')' if depth == 0 => true,
+            ')' => {
+                depth -= 1;
+                false
+            }
+            _ => false,
+        }) else {
+            return;
+        };
+
+        let raw_target = target_start[..target_end].trim();
+        let target = raw_target
+            .strip_prefix('<')
+            .and_then(|t| t.split_once('>'))
+            .map(|(t, _)| t)
+            .unwrap_or_else(|| raw_target.split_whitespace().next().unwrap_or(""));
+        if !is_image && !target.is_empty() {
+            links.push(MarkdownLink {
+                text: text.to_string(),
+                target: target.to_string(),
+            });
+        }
+        rest = &target_start[target_end + 1..];
+    }
+}
+
+/// Removes JSX/HTML-like tags (`<Callout type="info">`, `</Callout>`, `<Badge />`) while keeping
+/// the text between them. Fenced code blocks and inline code spans are left untouched, and
+/// lines holding nothing but tags are dropped.
+pub fn strip_jsx(content: &str) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut in_fence = false;
+    for line in content.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            output.push_str(line);
+            continue;
+        }
+        if in_fence {
+            output.push_str(line);
+            continue;
+        }
+
+        let stripped = strip_line_tags(line);
+        if stripped.trim().is_empty() && !line.trim().is_empty() {
+            continue;
+        }
+        output.push_str(&stripped);
+    }
+    output
+}
+
+fn strip_line_tags(line: &str) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(pos) = rest.find(['<', '`']) {
+        output.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        if let Some(code) = tail.strip_prefix('`') {
+            // Inline code span: copy through the closing backtick verbatim.
+            let end = code.find('`').map_or(tail.len(), |i| i + 2);
+            output.push_str(&tail[..end]);
+            rest = &tail[end..];
+            continue;
+        }
+
+        match tag_length(tail) {
+            Some(len) => rest = &tail[len..],
+            None => {
+                output.push('<');
+                rest = &tail[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Length of the tag at the start of `text`, if it starts with `<Name`, `</Name` or `<>`.
+fn tag_length(text: &str) -> Option<usize> {
+    let after = text.strip_prefix('<')?;
+    let name_start = after.strip_prefix('/').unwrap_or(after);
+    let first = name_start.chars().next()?;
+    if !(first.is_ascii_alphabetic() || first == '>') {
+        return None;
+    }
+
+    let mut quote = None;
+    let mut braces = 0usize;
+    for (i, c) in after.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '{') => braces += 1,
+            (None, '}') => braces = braces.saturating_sub(1),
+            (None, '>') if braces == 0 => return Some(i + 2),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Soft-wraps prose lines longer than `column` characters at spaces. Frontmatter, fenced and
+/// indented code, headings, tables and HTML lines are left as they are; list items and block
+/// quotes continue under their text. Words longer than `column` are not split.
+pub fn soft_wrap(content: &str, column: usize) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut in_frontmatter = content.starts_with("---");
+    let mut in_fence = false;
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let text = line.trim_end_matches(['\n', '\r']);
+        let trimmed = text.trim_start();
+        if in_frontmatter {
+            in_frontmatter = index == 0 || trimmed != "---";
+            output.push_str(line);
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        let verbatim = in_fence
+            || text.chars().count() <= column
+            || text.starts_with("    ")
+            || text.starts_with('\t')
+            || trimmed.starts_with(['#', '|', '<']);
+        if verbatim {
+            output.push_str(line);
+            continue;
+        }
+
+        let (prefix, continuation) = wrap_prefixes(text);
+        let mut width = prefix.chars().count();
+        output.push_str(prefix);
+        let mut line_empty = true;
+        for word in text[prefix.len()..]
+            .split(' ')
+            .filter(|word| !word.is_empty())
+        {
+            let word_width = word.chars().count();
+            if !line_empty && width + 1 + word_width > column {
+                output.push('\n');
+                output.push_str(&continuation);
+                width = continuation.chars().count();
+                line_empty = true;
+            }
+            if !line_empty {
+                output.push(' ');
+                width += 1;
+            }
+            output.push_str(word);
+            width += word_width;
+            line_empty = false;
+        }
+        output.push_str(&line[text.len()..]);
+    }
+    output
+}
+
+/// Leading indentation plus list or quote marker of a line, and what wrapped continuation
+/// lines start with instead.
+fn wrap_prefixes(line: &str) -> (&str, String) {
+    let indent = line.len() - line.trim_start().len();
+    let rest = &line[indent..];
+    let marker = if rest.starts_with("> ") {
+        return (&line[..indent + 2], format!("{}> ", &line[..indent]));
+    } else if rest.starts_with(['-', '*', '+']) && rest[1..].starts_with(' ') {
+        2
+    } else {
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        let numbered = digits > 0 && rest[digits..].starts_with(". ");
+        if numbered { digits + 2 } else { 0 }
+    };
+    (
+        &line[..indent + marker],
+        " ".repeat(line[..indent].chars().count() + marker),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_frontmatter_with_fields() {
+        let content = "---\ntitle: Outbox pattern\nstatus: accepted\n---\n# Heading\nBody text\n";
+        let parsed = parse_frontmatter(content).expect("valid frontmatter");
+
+        assert_eq!(parsed.get_str("title"), Some("Outbox pattern"));
+        assert_eq!(parsed.get_str("status"), Some("accepted"));
+        assert_eq!(parsed.body, "# Heading\nBody text\n");
+    }
+
+    #[test]
+    fn test_parse_frontmatter_without_block() {
+        let content = "# Just markdown\n";
+        let parsed = parse_frontmatter(content).expect("no frontmatter");
+
+        assert!(parsed.metadata.is_empty());
+        assert_eq!(parsed.body, content);
+    }
+
+    #[test]
+    fn test_parse_frontmatter_unterminated_block_is_body() {
+        let content = "---\ntitle: never closed\n";
+        let parsed = parse_frontmatter(content).expect("treated as body");
+
+        assert!(parsed.metadata.is_empty());
+        assert_eq!(parsed.body, content);
+    }
+
+    #[test]
+    fn test_parse_frontmatter_malformed_yaml() {
+        let content = "---\ntitle: [unclosed\n---\nbody\n";
+        assert!(parse_frontmatter(content).is_err());
+    }
+
+    #[test]
+    fn test_parse_frontmatter_crlf() {
+        let content = "---\r\ntitle: Windows\r\n---\r\nbody\r\n";
+        let parsed = parse_frontmatter(content).expect("valid frontmatter");
+
+        assert_eq!(parsed.get_str("title"), Some("Windows"));
+        assert_eq!(parsed.body, "body\r\n");
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let content = "See [the ERD](../erd/customer.mdx#orders) and ![diagram](./c4.png).\n\
+                       ```\n[not a link](ignored.md)\n```\n\
+                       [Spec](<./api spec.yaml> \"OpenAPI\") [wiki](https://example.com/a_(b))\n";
+        let links = extract_links(content);
+
+        let targets: Vec<&str> = links.iter().map(|l| l.target.as_str()).collect();
+        assert_eq!(
+            targets,
+            vec![
+                "../erd/customer.mdx#orders",
+                "./api spec.yaml",
+                "https://example.com/a_(b)"
+            ]
+        );
+        assert_eq!(links[0].text, "the ERD");
+    }
+
+    #[test]
+    fn test_strip_jsx_keeps_inner_text_of_nested_components() {
+        let content = "# Outbox\n\
+                       <Callout type=\"warn\" title={\"a > b\"}>\n\
+                       Use the <Tooltip tip='outbox'>outbox</Tooltip> table.<Badge />\n\
+                       </Callout>\n\
+                       Compare `a <b> c` and 1 < 2.\n\
+                       ```tsx\n<Component />\n```\n";
+
+        assert_eq!(
+            strip_jsx(content),
+            "# Outbox\n\
+             Use the outbox table.\n\
+             Compare `a <b> c` and 1 < 2.\n\
+             ```tsx\n<Component />\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_sections() {
+        let body = "intro\n# ADR-001\n## Status\nAccepted\n## Context\n```\n# not a heading\n```\n\
+                    ##Decision\n## Consequences ##\n";
+        let found = sections(body);
+
+        let titles: Vec<(usize, &str)> = found.iter().map(|s| (s.level, s.title)).collect();
+        assert_eq!(
+            titles,
+            vec![
+                (1, "ADR-001"),
+                (2, "Status"),
+                (2, "Context"),
+                (2, "Consequences")
+            ]
+        );
+        assert_eq!(found[1].content, "Accepted\n");
+        assert_eq!(found[2].content, "```\n# not a heading\n```\n##Decision\n");
+        assert_eq!(found[3].content, "");
+    }
+
+    #[test]
+    fn test_summary_skips_frontmatter_and_headings() {
+        let content = "---\ntitle: Outbox\n---\nimport Callout from './callout'\n\n# Outbox\n\n\
+                       <Callout>\n\
+                       Events are written to the *outbox* table\n  in the same transaction.\n\n\
+                       Second paragraph.\n";
+        let parsed = parse_frontmatter(content).expect("valid frontmatter");
+
+        assert_eq!(
+            summary(parsed.body).as_deref(),
+            Some("Events are written to the *outbox* table in the same transaction.")
+        );
+        assert_eq!(summary("# Only a heading\n```\ncode\n```\n"), None);
+
+        let long = format!("{}\n", "word ".repeat(100));
+        let truncated = summary(&long).expect("summary");
+        assert!(truncated.ends_with("word…"));
+        assert!(truncated.chars().count() <= SUMMARY_MAX_CHARS + 1);
+    }
+
+    #[test]
+    fn test_word_count() {
+        assert_eq!(word_count("one two\nthree\t four"), 4);
+        assert_eq!(word_count("   "), 0);
+    }
+
+    #[test]
+    fn test_soft_wrap_wraps_prose_and_keeps_structure() {
+        let content = "---\ntitle: A very long frontmatter title that stays on one line\n---\n\
+                       # A heading that is also much longer than the column\n\
+                       The outbox pattern stores events with the business change.\n\
+                       - Publish events from the outbox table in order\n\
+                       > Quoted decisions wrap under the quote marker\n\
+                       ```yaml\nkey: a code line that must not be wrapped at all\n```\n";
+        let wrapped = soft_wrap(content, 24);
+
+        assert_eq!(
+            wrapped,
+            "---\ntitle: A very long frontmatter title that stays on one line\n---\n\
+             # A heading that is also much longer than the column\n\
+             The outbox pattern\nstores events with the\nbusiness change.\n\
+             - Publish events from\n  the outbox table in\n  order\n\
+             > Quoted decisions wrap\n> under the quote marker\n\
+             ```yaml\nkey: a code line that must not be wrapped at all\n```\n"
+        );
+        assert_eq!(soft_wrap("short line\r\n", 24), "short line\r\n");
+    }
+}
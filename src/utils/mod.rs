@@ -1 +1,10 @@
+pub mod c4_graph;
+pub mod date;
+pub mod erd;
 pub mod file_reader;
+pub mod markdown;
+pub mod normalize;
+pub mod openapi;
+pub mod parse_cache;
+pub mod search;
+pub mod transclusion;
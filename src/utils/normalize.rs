@@ -0,0 +1,148 @@
+use serde_json::{Map, Value};
+
+/// How a document was normalized by [`normalize_content`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    Text,
+    Yaml,
+    Json,
+}
+
+impl Normalization {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Normalization::Text => "text",
+            Normalization::Yaml => "yaml",
+            Normalization::Json => "json",
+        }
+    }
+}
+
+/// Normalizes content for noise-free diffs. YAML and JSON (by file extension) get their keys
+/// sorted recursively; anything that fails to parse falls back to text normalization.
+pub fn normalize_content(file_path: &str, content: &str) -> (Normalization, String) {
+    let extension = file_path
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let structured = match extension.as_str() {
+        "yaml" | "yml" => serde_yaml::from_str::<Value>(content)
+            .ok()
+            .and_then(|value| serde_yaml::to_string(&sort_keys(value)).ok())
+            .map(|text| (Normalization::Yaml, text)),
+        "json" => serde_json::from_str::<Value>(content)
+            .ok()
+            .and_then(|value| serde_json::to_string_pretty(&sort_keys(value)).ok())
+            .map(|text| (Normalization::Json, text)),
+        _ => None,
+    };
+
+    let (normalization, text) = structured.unwrap_or((Normalization::Text, content.to_string()));
+    (normalization, normalize_text(&text))
+}
+
+/// Unifies line endings to `\n`, trims trailing whitespace on every line and ends the text with
+/// exactly one newline (empty input stays empty).
+pub fn normalize_text(content: &str) -> String {
+    let unified = content.replace("\r\n", "\n").replace('\r', "\n");
+    let mut output = String::with_capacity(unified.len());
+    for line in unified.lines() {
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+
+    let trimmed_len = output.trim_end_matches('\n').len();
+    output.truncate(trimmed_len);
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output
+}
+
+/// Breaks every line longer than `column` characters into `column`-wide pieces. Meant for
+/// display only: it changes the meaning of whitespace-sensitive formats.
+pub fn wrap_long_lines(content: &str, column: usize) -> String {
+    let mut output = String::with_capacity(content.len());
+    for line in content.split_inclusive('\n') {
+        let text = line.trim_end_matches(['\n', '\r']);
+        for (index, c) in text.chars().enumerate() {
+            if index > 0 && index % column == 0 {
+                output.push('\n');
+            }
+            output.push(c);
+        }
+        output.push_str(&line[text.len()..]);
+    }
+    output
+}
+
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_text_crlf_and_trailing_whitespace() {
+        let content = "# Title  \r\nline\t\r\n\r\nlast\r\n\r\n\r\n";
+        assert_eq!(normalize_text(content), "# Title\nline\n\nlast\n");
+        assert_eq!(normalize_text("old mac\rline"), "old mac\nline\n");
+        assert_eq!(normalize_text(""), "");
+    }
+
+    #[test]
+    fn test_normalize_yaml_sorts_keys() {
+        let content = "openapi: 3.0.0\r\ninfo:\r\n  version: v1\r\n  title: Billing\r\n";
+        let (normalization, normalized) = normalize_content("spec/billing.yaml", content);
+
+        assert_eq!(normalization, Normalization::Yaml);
+        assert_eq!(
+            normalized,
+            "info:\n  title: Billing\n  version: v1\nopenapi: 3.0.0\n"
+        );
+    }
+
+    #[test]
+    fn test_normalize_json_sorts_keys() {
+        let (normalization, normalized) =
+            normalize_content("a.json", r#"{"b": 1, "a": {"d": 2, "c": 3}}"#);
+
+        assert_eq!(normalization, Normalization::Json);
+        assert_eq!(
+            normalized,
+            "{\n  \"a\": {\n    \"c\": 3,\n    \"d\": 2\n  },\n  \"b\": 1\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_normalize_invalid_yaml_falls_back_to_text() {
+        let (normalization, normalized) = normalize_content("broken.yaml", "a: [b  \r\n");
+
+        assert_eq!(normalization, Normalization::Text);
+        assert_eq!(normalized, "a: [b\n");
+    }
+
+    #[test]
+    fn test_wrap_long_lines_breaks_at_the_column() {
+        assert_eq!(
+            wrap_long_lines("{\"a\":1,\"b\":2}\nok\n", 5),
+            "{\"a\":\n1,\"b\"\n:2}\nok\n"
+        );
+    }
+}
@@ -0,0 +1,545 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Path item keys holding operations, in the order the OpenAPI specification lists them.
+const HTTP_METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Operation declared under `paths`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation {
+    /// Upper-case HTTP method
+    pub method: String,
+    pub path: String,
+    pub operation_id: Option<String>,
+    pub summary: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// Parses an OpenAPI/Swagger document (YAML or JSON) into a JSON value.
+pub fn parse_spec(content: &str) -> Result<Value, String> {
+    let yaml: serde_yaml::Value =
+        serde_yaml::from_str(content).map_err(|e| format!("Invalid YAML/JSON: {}", e))?;
+    serde_json::to_value(yaml).map_err(|e| format!("Unsupported YAML value: {}", e))
+}
+
+/// How a spec file holding several `---`-separated YAML documents is parsed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MultiDocumentMode {
+    /// Use the first document declaring `openapi` (or `swagger`)
+    #[default]
+    FirstOpenapi,
+    /// Fail, reporting how many documents were found
+    Reject,
+}
+
+/// Like `parse_spec`, but also accepts multi-document YAML as `mode` directs. Empty
+/// documents (e.g. after a trailing `---`) are ignored.
+pub fn parse_spec_documents(content: &str, mode: MultiDocumentMode) -> Result<Value, String> {
+    let mut documents = serde_yaml::Deserializer::from_str(content)
+        .map(serde_yaml::Value::deserialize)
+        .filter(|document| !matches!(document, Ok(serde_yaml::Value::Null)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid YAML/JSON: {}", e))?;
+    let count = documents.len();
+    let document = match (count, mode) {
+        (0, _) => return parse_spec(content),
+        (1, _) => documents.remove(0),
+        (_, MultiDocumentMode::Reject) => {
+            return Err(format!(
+                "Found {} YAML documents; expected a single OpenAPI document",
+                count
+            ));
+        }
+        (_, MultiDocumentMode::FirstOpenapi) => documents
+            .into_iter()
+            .find(|document| document.get("openapi").is_some() || document.get("swagger").is_some())
+            .ok_or_else(|| {
+                format!(
+                    "None of the {} YAML documents declares openapi or swagger",
+                    count
+                )
+            })?,
+    };
+    serde_json::to_value(document).map_err(|e| format!("Unsupported YAML value: {}", e))
+}
+
+/// Collects the operations of `paths`, sorted by path and then method order.
+pub fn operations(spec: &Value) -> Vec<Operation> {
+    let Some(paths) = spec.get("paths").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+
+    let text = |operation: &Value, key: &str| {
+        operation
+            .get(key)
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+    };
+
+    let mut operations = Vec::new();
+    for (path, item) in paths {
+        for method in HTTP_METHODS {
+            let Some(operation) = item.get(*method) else {
+                continue;
+            };
+            let tags = operation
+                .get("tags")
+                .and_then(Value::as_array)
+                .map(|tags| {
+                    tags.iter()
+                        .filter_map(Value::as_str)
+                        .map(ToString::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+            operations.push(Operation {
+                method: method.to_ascii_uppercase(),
+                path: path.clone(),
+                operation_id: text(operation, "operationId"),
+                summary: text(operation, "summary"),
+                tags,
+            });
+        }
+    }
+    operations
+}
+
+/// Sections holding named schemas: OpenAPI 3 `components.schemas`, Swagger 2.0 `definitions`.
+const SCHEMA_SECTIONS: &[(&str, &[&str])] = &[
+    ("components.schemas", &["components", "schemas"]),
+    ("definitions", &["definitions"]),
+];
+
+fn schema_sections(
+    spec: &Value,
+) -> impl Iterator<Item = (&'static str, &serde_json::Map<String, Value>)> {
+    SCHEMA_SECTIONS.iter().filter_map(move |(name, path)| {
+        path.iter()
+            .try_fold(spec, |value, key| value.get(*key))
+            .and_then(Value::as_object)
+            .map(|schemas| (*name, schemas))
+    })
+}
+
+/// Finds a named schema, returning the section it was declared in and its definition.
+pub fn find_schema<'a>(spec: &'a Value, name: &str) -> Option<(&'static str, &'a Value)> {
+    schema_sections(spec)
+        .find_map(|(section, schemas)| schemas.get(name).map(|schema| (section, schema)))
+}
+
+/// Names of all schemas declared by the spec.
+pub fn schema_names(spec: &Value) -> Vec<&str> {
+    schema_sections(spec)
+        .flat_map(|(_, schemas)| schemas.keys().map(String::as_str))
+        .collect()
+}
+
+/// Merges the `paths` of several specs of one service version (e.g. its public and internal
+/// files) into one path item map. An operation declared by several specs keeps the first.
+pub fn merge_paths<'a>(specs: impl IntoIterator<Item = &'a Value>) -> Map<String, Value> {
+    let mut merged: Map<String, Value> = Map::new();
+    for paths in specs
+        .into_iter()
+        .filter_map(|spec| spec.get("paths").and_then(Value::as_object))
+    {
+        for (path, item) in paths {
+            let Some(item) = item.as_object() else {
+                continue;
+            };
+            let Value::Object(target) = merged
+                .entry(path.clone())
+                .or_insert_with(|| Value::Object(Map::new()))
+            else {
+                continue;
+            };
+            for (key, value) in item {
+                target.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+    }
+    merged
+}
+
+/// Structural change of one path between two versions
+#[derive(Debug, Default, PartialEq, serde::Serialize, schemars::JsonSchema)]
+pub struct PathChange {
+    /// "added", "removed" or "changed"
+    pub change: &'static str,
+    /// Upper-case methods of operations only the new version has
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_operations: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_operations: Vec<String>,
+    /// Method -> changes of an operation both versions have
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub changed_operations: BTreeMap<String, OperationChange>,
+}
+
+/// Changes of one operation present in both versions
+#[derive(Debug, Default, PartialEq, serde::Serialize, schemars::JsonSchema)]
+pub struct OperationChange {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_response_codes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_response_codes: Vec<String>,
+    /// Newly required request fields: `query:limit`, `header:X-Id`, `body:amount`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_required_fields: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_required_fields: Vec<String>,
+}
+
+impl OperationChange {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Path -> structural change between two merged path maps (see `merge_paths`); unchanged
+/// paths are left out. Compares operations, response codes and required request fields,
+/// without resolving `$ref`s.
+pub fn diff_paths(
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+) -> BTreeMap<String, PathChange> {
+    let mut changes = BTreeMap::new();
+    let paths: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for path in paths {
+        let old_operations = path_operations(old.get(path));
+        let new_operations = path_operations(new.get(path));
+        let mut change = PathChange {
+            added_operations: methods_missing_from(&new_operations, &old_operations),
+            removed_operations: methods_missing_from(&old_operations, &new_operations),
+            ..Default::default()
+        };
+        for (method, old_operation) in &old_operations {
+            let Some(new_operation) = new_operations.get(method) else {
+                continue;
+            };
+            let operation_change = diff_operation(old_operation, new_operation);
+            if !operation_change.is_empty() {
+                change
+                    .changed_operations
+                    .insert(method.to_ascii_uppercase(), operation_change);
+            }
+        }
+        change.change = match (old.contains_key(path), new.contains_key(path)) {
+            (false, _) => "added",
+            (_, false) => "removed",
+            _ if change == PathChange::default() => continue,
+            _ => "changed",
+        };
+        changes.insert(path.clone(), change);
+    }
+    changes
+}
+
+fn path_operations(item: Option<&Value>) -> BTreeMap<&'static str, &Value> {
+    HTTP_METHODS
+        .iter()
+        .filter_map(|method| Some((*method, item?.get(*method)?)))
+        .collect()
+}
+
+fn methods_missing_from(
+    operations: &BTreeMap<&'static str, &Value>,
+    other: &BTreeMap<&'static str, &Value>,
+) -> Vec<String> {
+    HTTP_METHODS
+        .iter()
+        .filter(|method| operations.contains_key(*method) && !other.contains_key(*method))
+        .map(|method| method.to_ascii_uppercase())
+        .collect()
+}
+
+fn diff_operation(old: &Value, new: &Value) -> OperationChange {
+    let response_codes = |operation: &Value| -> BTreeSet<String> {
+        operation
+            .get("responses")
+            .and_then(Value::as_object)
+            .map(|responses| responses.keys().cloned().collect())
+            .unwrap_or_default()
+    };
+    let (old_codes, new_codes) = (response_codes(old), response_codes(new));
+    let (old_fields, new_fields) = (required_fields(old), required_fields(new));
+    OperationChange {
+        added_response_codes: new_codes.difference(&old_codes).cloned().collect(),
+        removed_response_codes: old_codes.difference(&new_codes).cloned().collect(),
+        added_required_fields: new_fields.difference(&old_fields).cloned().collect(),
+        removed_required_fields: old_fields.difference(&new_fields).cloned().collect(),
+    }
+}
+
+/// Required parameters (`<in>:<name>`) and required top-level request body properties
+/// (`body:<name>`) of an operation.
+fn required_fields(operation: &Value) -> BTreeSet<String> {
+    let mut fields = BTreeSet::new();
+    for parameter in operation
+        .get("parameters")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let required = parameter.get("required").and_then(Value::as_bool) == Some(true);
+        if let (true, Some(location), Some(name)) = (
+            required,
+            parameter.get("in").and_then(Value::as_str),
+            parameter.get("name").and_then(Value::as_str),
+        ) {
+            fields.insert(format!("{}:{}", location, name));
+        }
+    }
+    let body_schemas = operation
+        .get("requestBody")
+        .and_then(|body| body.get("content"))
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|content| content.values())
+        .filter_map(|media| media.get("schema"));
+    for schema in body_schemas {
+        for name in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            fields.insert(format!("body:{}", name));
+        }
+    }
+    fields
+}
+
+/// Orders version directory names naturally: digit runs compare by value, so "v10" follows
+/// "v9" and "v2.10" follows "v2.9".
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (version_chunks(a), version_chunks(b));
+    loop {
+        let ordering = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                _ => x.cmp(y),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Splits a version into alternating runs of ASCII digits and other characters.
+fn version_chunks(version: &str) -> impl Iterator<Item = &str> {
+    let mut rest = version;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != first.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        Some(chunk)
+    })
+}
+
+/// Widest span between the lowest and highest `v<N>` version still read as a sequence; wider
+/// ones (date-style `v20240115` next to `v1`) report no gaps.
+const MAX_VERSION_SPAN: u64 = 100;
+
+/// `v<N>` versions skipped between the lowest and highest numbered version present, e.g.
+/// `v2` for `v1` and `v3`. Versions not of that form are ignored; none are reported when the
+/// two are more than `MAX_VERSION_SPAN` apart.
+pub fn missing_versions<'a>(versions: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let numbers: BTreeSet<u64> = versions
+        .into_iter()
+        .filter_map(|version| {
+            version
+                .strip_prefix(['v', 'V'])
+                .and_then(|number| number.parse().ok())
+        })
+        .collect();
+    let (Some(&lowest), Some(&highest)) = (numbers.first(), numbers.last()) else {
+        return Vec::new();
+    };
+    if highest - lowest > MAX_VERSION_SPAN {
+        return Vec::new();
+    }
+    (lowest..highest)
+        .filter(|number| !numbers.contains(number))
+        .map(|number| format!("v{}", number))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operations_with_tags() {
+        let spec = parse_spec(
+            "openapi: 3.0.0\n\
+             info: {title: Billing, version: '1'}\n\
+             paths:\n  \
+               /invoices:\n    \
+                 parameters: []\n    \
+                 get: {operationId: listInvoices, summary: List invoices, tags: [billing]}\n    \
+                 post: {operationId: createInvoice, tags: [billing, activation]}\n  \
+               /health:\n    \
+                 get: {summary: Health}\n",
+        )
+        .expect("valid spec");
+
+        let operations = operations(&spec);
+        let listed: Vec<(&str, &str, Vec<&str>)> = operations
+            .iter()
+            .map(|op| {
+                (
+                    op.method.as_str(),
+                    op.path.as_str(),
+                    op.tags.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("GET", "/health", vec![]),
+                ("GET", "/invoices", vec!["billing"]),
+                ("POST", "/invoices", vec!["billing", "activation"]),
+            ]
+        );
+        assert_eq!(operations[1].operation_id.as_deref(), Some("listInvoices"));
+        assert_eq!(operations[2].summary, None);
+    }
+
+    #[test]
+    fn test_find_schema_in_openapi_and_swagger() {
+        let openapi = parse_spec(
+            "openapi: 3.0.0\ncomponents:\n  schemas:\n    Customer:\n      type: object\n      \
+             properties:\n        address: {type: object, properties: {city: {type: string}}}\n",
+        )
+        .expect("valid");
+        let swagger =
+            parse_spec("swagger: '2.0'\ndefinitions:\n  Customer: {type: object}\n  Order: {}\n")
+                .expect("valid");
+
+        let (section, schema) = find_schema(&openapi, "Customer").expect("found");
+        assert_eq!(section, "components.schemas");
+        assert_eq!(
+            schema["properties"]["address"]["properties"]["city"]["type"],
+            "string"
+        );
+        assert_eq!(
+            find_schema(&swagger, "Customer").map(|(s, _)| s),
+            Some("definitions")
+        );
+        assert_eq!(find_schema(&swagger, "customer"), None);
+        assert_eq!(schema_names(&swagger), vec!["Customer", "Order"]);
+    }
+
+    #[test]
+    fn test_parse_spec_documents_selects_openapi_document() {
+        let content = "kind: ConfigMap\nmetadata: {name: billing}\n---\n\
+                       openapi: 3.0.0\ninfo: {title: Billing}\npaths: {}\n---\n";
+
+        let spec =
+            parse_spec_documents(content, MultiDocumentMode::FirstOpenapi).expect("second doc");
+        assert_eq!(spec["info"]["title"], "Billing");
+        assert!(spec.get("kind").is_none());
+
+        let error = parse_spec_documents(content, MultiDocumentMode::Reject).expect_err("two docs");
+        assert!(error.contains("Found 2 YAML documents"), "{}", error);
+        assert!(
+            parse_spec_documents("a: 1\n---\nb: 2\n", MultiDocumentMode::FirstOpenapi).is_err()
+        );
+        assert_eq!(
+            parse_spec_documents("openapi: 3.0.0\n", MultiDocumentMode::Reject).expect("single"),
+            parse_spec("openapi: 3.0.0\n").expect("single")
+        );
+    }
+
+    #[test]
+    fn test_parse_spec_rejects_invalid_yaml() {
+        assert!(parse_spec("paths: [unclosed\n").is_err());
+        assert!(operations(&parse_spec("openapi: 3.0.0\n").expect("valid")).is_empty());
+    }
+
+    #[test]
+    fn test_versions_sort_naturally_and_report_gaps() {
+        let mut versions = vec!["v10", "v2", "v9", "v1", "beta", "v2.10", "v2.9"];
+        versions.sort_by(|a, b| compare_versions(a, b));
+        assert_eq!(versions, ["beta", "v1", "v2", "v2.9", "v2.10", "v9", "v10"]);
+
+        assert_eq!(missing_versions(["v1", "v4", "V2", "beta"]), ["v3"]);
+        assert!(missing_versions(["v1", "v2"]).is_empty());
+        assert!(missing_versions(["v2"]).is_empty());
+        assert!(missing_versions(["v1", "v20240115"]).is_empty());
+        assert!(missing_versions(["v0", "v18446744073709551615"]).is_empty());
+        assert_eq!(missing_versions(["v1", "v101"]).len(), 99);
+    }
+
+    #[test]
+    fn test_diff_paths_reports_structural_changes() {
+        let v1 = parse_spec(
+            "paths:\n  \
+               /invoices:\n    \
+                 get:\n      \
+                   parameters: [{name: limit, in: query}]\n      \
+                   responses: {'200': {}}\n  \
+               /legacy:\n    \
+                 get: {responses: {'200': {}}}\n",
+        )
+        .expect("valid");
+        let v2_public = parse_spec(
+            "paths:\n  \
+               /invoices:\n    \
+                 get:\n      \
+                   parameters: [{name: limit, in: query, required: true}]\n      \
+                   responses: {'200': {}, '404': {}}\n",
+        )
+        .expect("valid");
+        let v2_internal = parse_spec(
+            "paths:\n  \
+               /invoices:\n    \
+                 post:\n      \
+                   requestBody: {content: {application/json: {schema: {required: [amount]}}}}\n  \
+               /refunds:\n    \
+                 post: {responses: {'201': {}}}\n",
+        )
+        .expect("valid");
+
+        let old = merge_paths([&v1]);
+        let new = merge_paths([&v2_public, &v2_internal]);
+        assert_eq!(new["/invoices"].as_object().map(Map::len), Some(2));
+
+        let changes = diff_paths(&old, &new);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes["/refunds"].change, "added");
+        assert_eq!(changes["/refunds"].added_operations, vec!["POST"]);
+        assert_eq!(changes["/legacy"].change, "removed");
+        assert_eq!(changes["/legacy"].removed_operations, vec!["GET"]);
+
+        let invoices = &changes["/invoices"];
+        assert_eq!(invoices.change, "changed");
+        assert_eq!(invoices.added_operations, vec!["POST"]);
+        assert_eq!(
+            invoices.changed_operations["GET"],
+            OperationChange {
+                added_response_codes: vec!["404".to_string()],
+                added_required_fields: vec!["query:limit".to_string()],
+                ..Default::default()
+            }
+        );
+        assert!(diff_paths(&new, &new).is_empty());
+    }
+}
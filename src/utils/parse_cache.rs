@@ -0,0 +1,137 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use super::file_reader::content_revision;
+
+/// Content revision a result was parsed from, and the result
+type Entry<T> = (String, Result<Arc<T>, String>);
+
+/// Parse results keyed by file path, reused while the file's content revision is unchanged.
+/// Failed parses are cached too, so a broken file is not re-parsed on every call.
+pub struct ParseCache<T> {
+    enabled: bool,
+    entries: Mutex<BTreeMap<String, Entry<T>>>,
+    hits: AtomicUsize,
+    parses: AtomicUsize,
+}
+
+/// Lookup counters of a parse cache since it was created
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// hits / (hits + misses); absent before the first lookup
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_rate: Option<f64>,
+}
+
+impl<T> Default for ParseCache<T> {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl<T> ParseCache<T> {
+    /// A disabled cache calls `parse` every time.
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            entries: Mutex::new(BTreeMap::new()),
+            hits: AtomicUsize::new(0),
+            parses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Cached result for `file_path` if it was parsed from the same `content`, otherwise the
+    /// result of `parse`, which replaces the stale entry.
+    pub fn get_or_parse(
+        &self,
+        file_path: &str,
+        content: &str,
+        parse: impl FnOnce(&str) -> Result<T, String>,
+    ) -> Result<Arc<T>, String> {
+        let revision = self.enabled.then(|| content_revision(content));
+        if let Some(revision) = &revision
+            && let Some((_, cached)) = self
+                .entries
+                .lock()
+                .ok()
+                .and_then(|entries| entries.get(file_path).cloned())
+                .filter(|(cached_revision, _)| cached_revision == revision)
+        {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return cached;
+        }
+
+        self.parses.fetch_add(1, Ordering::Relaxed);
+        let parsed = parse(content).map(Arc::new);
+        if let Some(revision) = revision
+            && let Ok(mut entries) = self.entries.lock()
+        {
+            entries.insert(file_path.to_string(), (revision, parsed.clone()));
+        }
+        parsed
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed) as u64;
+        let misses = self.parses.load(Ordering::Relaxed) as u64;
+        let lookups = hits + misses;
+        CacheStats {
+            hits,
+            misses,
+            hit_rate: (lookups > 0).then(|| hits as f64 / lookups as f64),
+        }
+    }
+
+    /// Number of times `parse` ran, i.e. cache misses
+    #[cfg(test)]
+    pub fn parses(&self) -> usize {
+        self.parses.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cache_reuses_results_until_content_changes() {
+        let cache: ParseCache<usize> = ParseCache::default();
+        let parse = |content: &str| Ok(content.len());
+
+        assert_eq!(*cache.get_or_parse("a.yaml", "one", parse).expect("ok"), 3);
+        assert_eq!(*cache.get_or_parse("a.yaml", "one", parse).expect("ok"), 3);
+        assert_eq!(cache.parses(), 1);
+
+        assert_eq!(
+            *cache.get_or_parse("a.yaml", "three", parse).expect("ok"),
+            5
+        );
+        assert_eq!(cache.parses(), 2);
+
+        let fail = |_: &str| Err::<usize, _>("broken".to_string());
+        assert!(cache.get_or_parse("b.yaml", "x", fail).is_err());
+        assert!(cache.get_or_parse("b.yaml", "x", parse).is_err());
+        assert_eq!(cache.parses(), 3);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (2, 3));
+        assert_eq!(stats.hit_rate, Some(0.4));
+    }
+
+    #[test]
+    fn test_disabled_parse_cache_always_parses() {
+        let cache: ParseCache<usize> = ParseCache::new(false);
+        let parse = |content: &str| Ok(content.len());
+
+        cache.get_or_parse("a.yaml", "one", parse).expect("ok");
+        cache.get_or_parse("a.yaml", "one", parse).expect("ok");
+        assert_eq!(cache.parses(), 2);
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+/// Term counts of one document.
+#[derive(Debug, Default, PartialEq)]
+pub struct TermCounts {
+    pub counts: BTreeMap<String, u32>,
+    /// Total number of tokens in the document
+    pub total: u32,
+}
+
+impl TermCounts {
+    pub fn from_text(text: &str) -> Self {
+        let mut terms = Self::default();
+        for token in tokenize(text) {
+            *terms.counts.entry(token).or_default() += 1;
+            terms.total += 1;
+        }
+        terms
+    }
+}
+
+/// Lowercased alphanumeric runs of at least two characters.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| token.chars().count() >= 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// TF-IDF with length normalization:
+/// `score(d) = Σ_t count(t, d) / sqrt(len(d)) * ln(1 + N / df(t))`,
+/// where `N` is the number of ranked documents and `df(t)` the number containing `t`.
+/// Terms missing from the document contribute nothing.
+pub fn score(
+    terms: &[String],
+    document: &TermCounts,
+    document_frequency: &BTreeMap<&str, u32>,
+    documents: u32,
+) -> f64 {
+    if document.total == 0 {
+        return 0.0;
+    }
+    let length = f64::from(document.total).sqrt();
+    terms
+        .iter()
+        .filter_map(|term| {
+            let count = *document.counts.get(term)?;
+            let df = document_frequency
+                .get(term.as_str())
+                .copied()
+                .unwrap_or(1)
+                .max(1);
+            let idf = (1.0 + f64::from(documents) / f64::from(df)).ln();
+            Some(f64::from(count) / length * idf)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("Outbox-pattern: at-least-once, v2 API!"),
+            vec!["outbox", "pattern", "at", "least", "once", "v2", "api"]
+        );
+    }
+
+    #[test]
+    fn test_score_prefers_dense_matches() {
+        let terms = tokenize("outbox events");
+        let dense = TermCounts::from_text("Outbox events go to the outbox table.");
+        let sparse = TermCounts::from_text(&format!(
+            "The outbox is mentioned once. {}",
+            "filler ".repeat(50)
+        ));
+        let unrelated = TermCounts::from_text("Nothing relevant here.");
+        let df = BTreeMap::from([("outbox", 2), ("events", 1)]);
+
+        let dense_score = score(&terms, &dense, &df, 3);
+        let sparse_score = score(&terms, &sparse, &df, 3);
+        assert!(dense_score > sparse_score);
+        assert!(sparse_score > 0.0);
+        assert_eq!(score(&terms, &unrelated, &df, 3), 0.0);
+    }
+}
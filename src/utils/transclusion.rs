@@ -0,0 +1,204 @@
+use std::fmt;
+
+use super::{file_reader::resolve_relative_link, markdown};
+
+/// Why a document could not be assembled.
+#[derive(Debug, PartialEq)]
+pub enum IncludeError {
+    /// Include chain that leads back to a file already being assembled
+    Cycle(Vec<String>),
+    /// Include target that does not resolve inside the docs root
+    OutsideDocsRoot {
+        file: String,
+        target: String,
+    },
+    Unreadable {
+        path: String,
+        error: String,
+    },
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cycle(chain) => write!(f, "include cycle: {}", chain.join(" -> ")),
+            Self::OutsideDocsRoot { file, target } => {
+                write!(f, "{} includes '{}' outside the docs root", file, target)
+            }
+            Self::Unreadable { path, error } => write!(f, "cannot read {}: {}", path, error),
+        }
+    }
+}
+
+/// Replaces include directives with the referenced partial's content, recursively:
+/// MDX imports of `.md`/`.mdx` files (`import Intro from './_intro.mdx'`, the import line is
+/// dropped and every `<Intro />` replaced) and `<!-- include: ./_intro.md -->` lines.
+/// Targets resolve against the including file; partial frontmatter is dropped. Directives
+/// inside fenced code blocks are left alone.
+pub fn inline_includes(
+    file_path: &str,
+    content: &str,
+    read: &mut dyn FnMut(&str) -> Result<String, String>,
+) -> Result<String, IncludeError> {
+    let mut chain = vec![file_path.to_string()];
+    assemble(content, &mut chain, read)
+}
+
+fn assemble(
+    content: &str,
+    chain: &mut Vec<String>,
+    read: &mut dyn FnMut(&str) -> Result<String, String>,
+) -> Result<String, IncludeError> {
+    let file_path = chain.last().cloned().unwrap_or_default();
+    let mut partial = |target: &str, chain: &mut Vec<String>| {
+        let path = resolve_relative_link(&file_path, target).ok_or_else(|| {
+            IncludeError::OutsideDocsRoot {
+                file: file_path.clone(),
+                target: target.to_string(),
+            }
+        })?;
+        if chain.contains(&path) {
+            let mut cycle = chain.clone();
+            cycle.push(path);
+            return Err(IncludeError::Cycle(cycle));
+        }
+        let content = read(&path).map_err(|error| IncludeError::Unreadable {
+            path: path.clone(),
+            error,
+        })?;
+        let body = markdown::split_frontmatter(&content).map_or(content.as_str(), |(_, body)| body);
+        chain.push(path);
+        let assembled = assemble(body, chain, read);
+        chain.pop();
+        Ok(assembled?.trim_end_matches(['\r', '\n']).to_string())
+    };
+
+    // Imported partials first: their tags may appear before or after the import line.
+    let mut imports = Vec::new();
+    let mut in_fence = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+        } else if !in_fence {
+            if let Some((name, target)) = partial_import(trimmed) {
+                imports.push((name, partial(target, chain)?));
+            }
+        }
+    }
+
+    let mut output = String::with_capacity(content.len());
+    let mut in_fence = false;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if in_fence || trimmed.starts_with("```") {
+            output.push_str(line);
+            continue;
+        }
+        if partial_import(trimmed).is_some() {
+            continue;
+        }
+        if let Some(target) = include_comment(trimmed) {
+            output.push_str(&partial(target, chain)?);
+            output.push_str(&line[line.trim_end_matches(['\r', '\n']).len()..]);
+            continue;
+        }
+        let mut line = line.to_string();
+        for (name, text) in &imports {
+            for tag in [format!("<{} />", name), format!("<{}/>", name)] {
+                line = line.replace(&tag, text);
+            }
+        }
+        output.push_str(&line);
+    }
+    Ok(output)
+}
+
+/// `import Name from './partial.mdx'` -> ("Name", "./partial.mdx"); other imports are `None`.
+fn partial_import(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix("import ")?;
+    let (name, target) = rest.split_once(" from ")?;
+    let name = name.trim();
+    let target = target
+        .trim()
+        .trim_end_matches(';')
+        .trim_matches(|c| c == '\'' || c == '"');
+    let is_partial = [".md", ".mdx"].iter().any(|ext| target.ends_with(ext));
+    let is_identifier = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    (is_partial && is_identifier).then_some((name, target))
+}
+
+/// `<!-- include: ./partial.md -->` -> "./partial.md"
+fn include_comment(line: &str) -> Option<&str> {
+    let inner = line.strip_prefix("<!--")?.strip_suffix("-->")?.trim();
+    let target = inner.strip_prefix("include:")?.trim();
+    (!target.is_empty()).then_some(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn reader(files: &[(&str, &str)]) -> impl FnMut(&str) -> Result<String, String> {
+        let files: BTreeMap<String, String> = files
+            .iter()
+            .map(|(path, content)| (path.to_string(), content.to_string()))
+            .collect();
+        move |path| {
+            files
+                .get(path)
+                .cloned()
+                .ok_or_else(|| "not found".to_string())
+        }
+    }
+
+    #[test]
+    fn test_inline_mdx_import_and_include_comment() {
+        let mut read = reader(&[
+            (
+                "guides/_partials/intro.mdx",
+                "---\ntitle: Intro\n---\nShared intro.\n",
+            ),
+            ("guides/_footer.md", "Footer text.\n"),
+        ]);
+        let content = "import Intro from './_partials/intro.mdx';\nimport Tabs from '@theme/Tabs';\n\n\
+                       # Setup\n<Intro />\n\n```mdx\n<Intro />\n```\n<!-- include: _footer.md -->\n";
+
+        let assembled = inline_includes("guides/setup.mdx", content, &mut read).expect("assembled");
+        assert_eq!(
+            assembled,
+            "import Tabs from '@theme/Tabs';\n\n# Setup\nShared intro.\n\n```mdx\n<Intro />\n```\n\
+             Footer text.\n"
+        );
+    }
+
+    #[test]
+    fn test_inline_detects_include_cycle() {
+        let mut read = reader(&[
+            ("docs/a.md", "<!-- include: b.md -->\n"),
+            ("docs/b.md", "<!-- include: ./a.md -->\n"),
+        ]);
+
+        let error =
+            inline_includes("docs/a.md", "<!-- include: b.md -->\n", &mut read).expect_err("cycle");
+        assert_eq!(
+            error,
+            IncludeError::Cycle(vec![
+                "docs/a.md".to_string(),
+                "docs/b.md".to_string(),
+                "docs/a.md".to_string()
+            ])
+        );
+        assert_eq!(
+            inline_includes("a.md", "<!-- include: ../../x.md -->\n", &mut read)
+                .expect_err("outside")
+                .to_string(),
+            "a.md includes '../../x.md' outside the docs root"
+        );
+    }
+}